@@ -0,0 +1,99 @@
+// Live reload client for `undox serve`, served at /_undox/live-reload.js.
+// Reloads the page when a rebuild changes it, and swaps in changed theme
+// stylesheets without reloading, keeping the scroll position and form state.
+// Reconnects when the server restarts or the connection drops, and reloads
+// if the site was rebuilt in the meantime.
+(function () {
+  var endpoint = new URL("live-reload?css=true", document.currentScript.src);
+  // The server sends a heartbeat every 10 seconds
+  var HEARTBEAT_TIMEOUT = 30000;
+  var MAX_RETRY_DELAY = 30000;
+  var version = null;
+  var retryDelay = 1000;
+  var source = null;
+  var watchdog = null;
+
+  function connect() {
+    source = new EventSource(endpoint);
+    alive();
+
+    source.addEventListener("hello", function (event) {
+      alive();
+      retryDelay = 1000;
+      // A different version means the server rebuilt or restarted while
+      // we weren't listening
+      if (version !== null && event.data !== version) {
+        location.reload();
+        return;
+      }
+      version = event.data;
+    });
+
+    source.addEventListener("heartbeat", alive);
+
+    // Reload events list the URL paths of the pages that changed, or "*"
+    // when every page may have, and carry the new version as their ID
+    source.addEventListener("reload", function (event) {
+      alive();
+      var paths = JSON.parse(event.data).map(normalize);
+      if (paths.indexOf("*") !== -1 || paths.indexOf(normalize(location.pathname)) !== -1) {
+        location.reload();
+        return;
+      }
+      // This page is up to date with the new build
+      if (event.lastEventId) version = event.lastEventId;
+    });
+
+    source.addEventListener("css", function (event) {
+      alive();
+      swapStylesheets(JSON.parse(event.data));
+    });
+
+    // Take over reconnecting from the browser, which gives up on some
+    // errors and doesn't back off
+    source.onerror = reconnect;
+  }
+
+  // Note that the connection is alive, and reconnect if it goes quiet
+  function alive() {
+    clearTimeout(watchdog);
+    watchdog = setTimeout(reconnect, HEARTBEAT_TIMEOUT);
+  }
+
+  function reconnect() {
+    clearTimeout(watchdog);
+    source.close();
+    setTimeout(connect, retryDelay);
+    retryDelay = Math.min(retryDelay * 2, MAX_RETRY_DELAY);
+  }
+
+  // Match a page's URL however it was requested: with or without a
+  // trailing slash, `index.html`, or `.html`
+  function normalize(path) {
+    if (path === "*") return path;
+    path = decodeURI(path)
+      .replace(/index\.html$/, "")
+      .replace(/\.html$/, "")
+      .replace(/\/+$/, "");
+    return path || "/";
+  }
+
+  function swapStylesheets(changed) {
+    var links = document.querySelectorAll('link[rel="stylesheet"]');
+    links.forEach(function (link) {
+      var url = new URL(link.href);
+      if (changed.indexOf(url.pathname) === -1) return;
+      url.searchParams.set("undox", Date.now());
+      // Load the new stylesheet before removing the old one, so the page
+      // never shows unstyled
+      var next = link.cloneNode();
+      next.href = url.href;
+      next.onload = next.onerror = function () {
+        link.remove();
+      };
+      link.after(next);
+    });
+  }
+
+  connect();
+})();
@@ -1,18 +1,42 @@
+mod aliases;
+mod assets;
 mod builder;
 mod cache;
+mod dates;
+mod diagnostics;
 mod document;
+mod feed;
+mod fingerprint;
+#[cfg(feature = "social-cards")]
+mod font;
 pub mod format;
+mod git_history;
 mod highlight;
+mod i18n;
+#[cfg(feature = "social-cards")]
+mod image;
+mod include;
+mod links;
 mod markdown;
 mod nav;
 mod paths;
 pub mod pipeline;
+mod progress;
+mod redirects;
 mod render;
 mod search;
+mod sizes;
+#[cfg(feature = "social-cards")]
+mod social_card;
 pub mod source;
+mod static_files;
+mod timing;
 mod watch;
 
-pub use builder::{BuildResult, Builder};
-pub use paths::base_path_from_config;
+pub use builder::{BuildResult, Builder, CheckReport};
+pub use cache::ChangeKind;
+pub use paths::{base_path_from_config, normalize_base_path};
+pub use progress::Progress;
 pub use search::build_search_index;
+pub use timing::{Timings, format_duration};
 pub use watch::{FileWatcher, PathClassifier, WatchEvent, WatchPaths};
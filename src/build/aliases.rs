@@ -0,0 +1,178 @@
+//! Redirects from old URLs.
+//!
+//! A document can list previous URLs in its `aliases` front matter. Each
+//! alias gets a small HTML page that redirects to the document's real URL,
+//! so links to moved pages keep working.
+
+use std::collections::{HashMap, HashSet};
+
+use super::pipeline::ProcessingDocument;
+use crate::util::html_escape;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AliasError {
+    #[error("alias {alias} of {target} collides with an existing page")]
+    CollidesWithPage { alias: String, target: String },
+
+    #[error("alias {alias} is claimed by both {first} and {second}")]
+    Duplicate {
+        alias: String,
+        first: String,
+        second: String,
+    },
+}
+
+/// A redirect from an old URL to a document.
+#[derive(Debug, PartialEq)]
+pub struct Alias {
+    /// URL path of the redirect page
+    pub url: String,
+    /// URL path of the document it redirects to
+    pub target: String,
+}
+
+/// Collect the aliases of all documents.
+///
+/// Fails if an alias is the URL of a real page or is claimed by more than
+/// one document.
+pub fn collect_aliases(docs: &[ProcessingDocument]) -> Result<Vec<Alias>, AliasError> {
+    let pages: HashSet<&str> = docs.iter().map(|doc| doc.url_path()).collect();
+    let mut claimed: HashMap<String, &str> = HashMap::new();
+    let mut aliases = Vec::new();
+
+    for doc in docs {
+        for alias in &doc.doc.front_matter.aliases {
+            let url = normalize_alias(alias);
+            let target = doc.url_path();
+
+            if pages.contains(url.as_str()) {
+                return Err(AliasError::CollidesWithPage {
+                    alias: url,
+                    target: target.to_string(),
+                });
+            }
+            if let Some(first) = claimed.get(&url) {
+                // Listing the same alias twice on one page is harmless
+                if *first == target {
+                    continue;
+                }
+                return Err(AliasError::Duplicate {
+                    alias: url,
+                    first: first.to_string(),
+                    second: target.to_string(),
+                });
+            }
+
+            claimed.insert(url.clone(), target);
+            aliases.push(Alias {
+                url,
+                target: target.to_string(),
+            });
+        }
+    }
+
+    Ok(aliases)
+}
+
+/// Normalize an alias to a URL path like the ones documents use: with a
+/// leading slash and without a trailing one.
+pub fn normalize_alias(alias: &str) -> String {
+    let trimmed = alias.trim().trim_matches('/');
+    format!("/{}", trimmed)
+}
+
+/// HTML for a page that redirects to `target`.
+///
+/// `canonical` is the URL for the canonical link (absolute when the site URL
+/// is known). The body is ignored by the search index.
+pub fn redirect_html(target: &str, canonical: &str) -> String {
+    let target = html_escape(target);
+    let canonical = html_escape(canonical);
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>Redirecting to {target}</title>\n\
+         <meta name=\"robots\" content=\"noindex\">\n\
+         <link rel=\"canonical\" href=\"{canonical}\">\n\
+         <meta http-equiv=\"refresh\" content=\"0; url={target}\">\n\
+         </head>\n\
+         <body data-pagefind-ignore=\"all\">\n\
+         <p>This page has moved to <a href=\"{target}\">{target}</a>.</p>\n\
+         </body>\n\
+         </html>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::build::document::{Document, FrontMatter};
+
+    fn doc(url_path: &str, aliases: &[&str]) -> ProcessingDocument {
+        let front_matter = FrontMatter {
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            ..Default::default()
+        };
+        let document = Document::new(
+            "docs".to_string(),
+            PathBuf::from("page.md"),
+            url_path.to_string(),
+            front_matter,
+            String::new(),
+        );
+        ProcessingDocument::new(document, PathBuf::from("/tmp/docs"))
+    }
+
+    #[test]
+    fn test_collect_aliases() {
+        let docs = [
+            doc("/guide/install", &["/old/install/", "setup", "/setup"]),
+            doc("/guide/usage", &[]),
+        ];
+        let aliases = collect_aliases(&docs).unwrap();
+        assert_eq!(
+            aliases,
+            vec![
+                Alias {
+                    url: "/old/install".to_string(),
+                    target: "/guide/install".to_string(),
+                },
+                Alias {
+                    url: "/setup".to_string(),
+                    target: "/guide/install".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alias_collisions() {
+        let docs = [doc("/a", &["/b/"]), doc("/b", &[])];
+        assert!(matches!(
+            collect_aliases(&docs),
+            Err(AliasError::CollidesWithPage { .. })
+        ));
+
+        let docs = [doc("/a", &["/old"]), doc("/b", &["/old"])];
+        assert!(matches!(
+            collect_aliases(&docs),
+            Err(AliasError::Duplicate { .. })
+        ));
+    }
+
+    #[test]
+    fn test_redirect_html() {
+        let html = redirect_html("/guide/install", "https://docs.example.com/guide/install");
+        assert!(html.contains("<meta http-equiv=\"refresh\" content=\"0; url=/guide/install\">"));
+        assert!(
+            html.contains(
+                "<link rel=\"canonical\" href=\"https://docs.example.com/guide/install\">"
+            )
+        );
+        assert!(html.contains("data-pagefind-ignore=\"all\""));
+    }
+}
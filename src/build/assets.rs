@@ -0,0 +1,161 @@
+//! Resolution of relative asset references in documents.
+//!
+//! A document at `guide/page.md` is served from `/guide/page/`, so a relative
+//! reference like `./images/shot.png` would resolve against the wrong
+//! directory in the browser. The index built here maps each static file back
+//! to its source location so references can be rewritten to the file's
+//! output URL.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::document::StaticFile;
+use crate::util::normalize_relative_path;
+
+/// Index of static files by their location on disk.
+#[derive(Debug, Default)]
+pub struct StaticFileIndex {
+    /// Source root joined with the file's relative path -> output URL
+    urls: HashMap<PathBuf, String>,
+}
+
+impl StaticFileIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a static file discovered in the source rooted at `source_root`.
+    pub fn insert(&mut self, source_root: &Path, file: &StaticFile) {
+        self.urls.insert(
+            source_root.join(&file.source_path),
+            file.output_path.clone(),
+        );
+    }
+
+    /// Assets as seen from one document.
+    pub fn for_document<'a>(
+        &'a self,
+        source_root: &'a Path,
+        document: &'a Path,
+    ) -> DocumentAssets<'a> {
+        DocumentAssets {
+            index: self,
+            source_root,
+            document,
+        }
+    }
+}
+
+/// How a reference in a document resolved.
+#[derive(Debug, PartialEq)]
+pub enum AssetRef {
+    /// Not a relative path (absolute, external, data URI); leave it alone
+    Unchanged,
+    /// A static file; use this URL instead
+    Found(String),
+    /// A relative path that doesn't match any static file
+    Missing,
+}
+
+/// Static files resolved relative to a single document.
+pub struct DocumentAssets<'a> {
+    index: &'a StaticFileIndex,
+    source_root: &'a Path,
+    /// Document path relative to the source root
+    document: &'a Path,
+}
+
+impl DocumentAssets<'_> {
+    /// Resolve a reference (e.g. an image `src`) found in the document.
+    pub fn resolve(&self, reference: &str) -> AssetRef {
+        if reference.is_empty()
+            || reference.starts_with('/')
+            || reference.starts_with('#')
+            || has_scheme(reference)
+        {
+            return AssetRef::Unchanged;
+        }
+
+        // Keep any query or fragment (e.g. `icons.svg#gear`) on the result
+        let split = reference.find(['?', '#']).unwrap_or(reference.len());
+        let (path, suffix) = reference.split_at(split);
+
+        let dir = self.document.parent().unwrap_or(Path::new(""));
+        let Some(relative) = normalize_relative_path(&dir.join(path)) else {
+            return AssetRef::Missing;
+        };
+
+        match self.index.urls.get(&self.source_root.join(relative)) {
+            Some(url) => AssetRef::Found(format!("{}{}", url, suffix)),
+            None => AssetRef::Missing,
+        }
+    }
+}
+
+/// Whether a reference starts with a URL scheme (`https:`, `data:`, ...).
+pub fn has_scheme(reference: &str) -> bool {
+    match reference.find([':', '/', '?', '#']) {
+        Some(i) => reference.as_bytes()[i] == b':',
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> StaticFileIndex {
+        let mut index = StaticFileIndex::new();
+        for (path, url) in [
+            ("images/logo.png", "/cli/images/logo.png"),
+            ("guide/images/shot.png", "/cli/guide/images/shot.png"),
+        ] {
+            index.insert(
+                Path::new("/src"),
+                &StaticFile::new("cli".to_string(), PathBuf::from(path), url.to_string()),
+            );
+        }
+        index
+    }
+
+    #[test]
+    fn test_resolve_relative() {
+        let index = index();
+        let assets = index.for_document(Path::new("/src"), Path::new("guide/page.md"));
+
+        assert_eq!(
+            assets.resolve("./images/shot.png"),
+            AssetRef::Found("/cli/guide/images/shot.png".to_string())
+        );
+        assert_eq!(
+            assets.resolve("images/shot.png?v=2"),
+            AssetRef::Found("/cli/guide/images/shot.png?v=2".to_string())
+        );
+        assert_eq!(
+            assets.resolve("../images/logo.png"),
+            AssetRef::Found("/cli/images/logo.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_missing() {
+        let index = index();
+        let assets = index.for_document(Path::new("/src"), Path::new("guide/page.md"));
+
+        assert_eq!(assets.resolve("images/nope.png"), AssetRef::Missing);
+        assert_eq!(assets.resolve("../../outside.png"), AssetRef::Missing);
+    }
+
+    #[test]
+    fn test_resolve_unchanged() {
+        let index = index();
+        let assets = index.for_document(Path::new("/src"), Path::new("page.md"));
+
+        assert_eq!(assets.resolve("/images/logo.png"), AssetRef::Unchanged);
+        assert_eq!(assets.resolve("https://x.dev/a.png"), AssetRef::Unchanged);
+        assert_eq!(
+            assets.resolve("data:image/png;base64,AA=="),
+            AssetRef::Unchanged
+        );
+    }
+}
@@ -1,23 +1,59 @@
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::config::{Location, RootConfig};
+use tracing::info;
+
+use crate::config::{
+    Location, MarkdownConfig, NavMode, OrphanSeverity, RootConfig, SourceConfig, UrlStyle,
+    git_cache_dir, is_url,
+};
 use crate::git::GitFetcher;
-use crate::util::title_case;
+use crate::theme::ThemeConfig;
+use crate::util::fnv1a;
 
+use super::aliases::normalize_alias;
+use super::assets::StaticFileIndex;
+use super::diagnostics::{Diagnostics, WarningKind};
 use super::document::ContentItem;
+use super::fingerprint::AssetManifest;
 use super::format::FormatRegistry;
-use super::highlight::SyntaxHighlighter;
+use super::git_history::GitHistory;
+use super::highlight::{HighlightError, SyntaxHighlighter};
+use super::i18n::Translations;
+use super::links::check_links;
+use super::markdown::{MarkdownError, render_inline_markdown};
 use super::nav::build_navigation_by_source;
-use super::paths::url_to_output_path;
-use super::pipeline::{Pipeline, PipelineContext, PipelineError, ProcessingDocument};
-use super::render::{RenderError, Renderer, SiteContext, SourceTab};
-use super::source::{ResolvedSource, SourceError};
+use super::paths::{
+    normalize_base_path, source_root_url, strip_base_path, url_to_output_path, with_base_path,
+};
+#[cfg(feature = "social-cards")]
+use super::pipeline::SocialCardStage;
+use super::pipeline::{
+    FeedStage, HtmlRewriteStage, LlmsStage, MANIFEST_PATH, ManifestStage, NotFoundStage, PdfStage,
+    Pipeline, PipelineContext, PipelineError, PrintStage, ProcessingDocument, RedirectStage,
+    RobotsStage,
+};
+use super::progress::Progress;
+use super::redirects::{RedirectError, collect_redirects};
+use super::render::{Announcement, RenderError, Renderer, SiteContext, SourceTab, VersionLink};
+use super::sizes::SizeReport;
+#[cfg(feature = "social-cards")]
+use super::social_card::{CardColors, SocialCard};
+use super::source::{ResolvedSource, SourceError, UrlCollision, find_url_collisions};
+use super::static_files::{Published, format_size, publish_static_file};
+use super::timing::Timings;
 
 #[derive(thiserror::Error, Debug)]
 pub enum BuildError {
     #[error("source error: {0}")]
     Source(#[from] SourceError),
 
+    #[error("source '{name}': {source}")]
+    SourceFailed { name: String, source: SourceError },
+
     #[error("render error: {0}")]
     Render(#[from] RenderError),
 
@@ -32,6 +68,51 @@ pub enum BuildError {
 
     #[error("theme error: {0}")]
     Theme(String),
+
+    #[error("found {0} broken internal link(s)")]
+    BrokenLinks(usize),
+
+    #[error("found {} URL collision(s):{}", .0.len(), list_collisions(.0))]
+    UrlCollisions(Vec<UrlCollision>),
+
+    #[error("found {0} nav entr(ies) that don't match any page")]
+    MissingNavEntries(usize),
+
+    #[error("found {0} page(s) not in the configured navigation")]
+    OrphanPages(usize),
+
+    #[error("found {0} warning(s) in strict mode")]
+    Warnings(usize),
+
+    #[error("redirect error: {0}")]
+    Redirect(#[from] RedirectError),
+
+    #[error("{0}")]
+    Highlight(#[from] HighlightError),
+
+    #[error("site.announcement: {0}")]
+    Announcement(#[from] MarkdownError),
+
+    #[error("assets.extra file '{0}' isn't part of the site")]
+    AssetNotFound(String),
+
+    #[cfg(not(feature = "social-cards"))]
+    #[error("site.social_cards needs undox to be built with the social-cards feature")]
+    SocialCardsUnavailable,
+
+    #[error("site.{setting} file not found: {path}")]
+    SiteFileNotFound {
+        setting: &'static str,
+        path: PathBuf,
+    },
+}
+
+/// One colliding URL per line, for [`BuildError::UrlCollisions`].
+fn list_collisions(collisions: &[UrlCollision]) -> String {
+    collisions
+        .iter()
+        .map(|collision| format!("\n  {}", collision))
+        .collect()
 }
 
 pub struct BuildResult {
@@ -39,8 +120,52 @@ pub struct BuildResult {
     pub theme_path: PathBuf,
     pub documents: usize,
     pub static_files: usize,
+    /// The manifest listing every page and file that was built
+    pub manifest_path: PathBuf,
+    /// PDFs written, one per source with a print view
+    pub pdfs: Vec<PathBuf>,
+    /// How long the build took
+    pub duration: Duration,
+    /// How long each step of the build took
+    pub timings: Timings,
+    /// How large each page and source's output is
+    pub sizes: SizeReport,
+    /// URL of the site's home page, for opening the site in a browser
+    pub home_url: String,
+    /// URLs of the pages whose HTML this build changed
+    pub changed_pages: Vec<String>,
+    /// Each source's local directory, by source name
+    pub source_dirs: HashMap<String, PathBuf>,
 }
 
+/// Problems found by [`Builder::check`].
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub problems: Vec<String>,
+    /// Documents whose front matter was parsed
+    pub documents: usize,
+}
+
+impl CheckReport {
+    /// Whether no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Print each problem on its own line.
+    pub fn print(&self) {
+        for problem in &self.problems {
+            eprintln!("  - {}", problem);
+        }
+    }
+}
+
+/// Templates every theme must provide.
+const REQUIRED_TEMPLATES: [&str; 2] = ["page.html", "macros.html"];
+
+/// How many sources are fetched at once.
+const MAX_CONCURRENT_FETCHES: usize = 4;
+
 pub struct Builder {
     config: RootConfig,
     /// Base path for resolving relative paths (typically the config file's directory)
@@ -52,6 +177,16 @@ pub struct Builder {
     dev_mode: bool,
     /// Whether live reload is enabled (only relevant in dev mode)
     live_reload: bool,
+    /// Whether broken internal links fail the build
+    strict_links: bool,
+    /// Whether nav entries that don't match a page fail the build
+    strict_nav: bool,
+    /// Whether draft documents are built outside dev mode
+    include_drafts: bool,
+    /// Whether any warning fails the build
+    strict: bool,
+    /// Where build progress is shown (nowhere unless set)
+    progress: Progress,
 }
 
 impl Builder {
@@ -62,6 +197,11 @@ impl Builder {
             theme_base_path: None,
             dev_mode: false,
             live_reload: false,
+            strict_links: false,
+            strict_nav: false,
+            include_drafts: false,
+            strict: false,
+            progress: Progress::hidden(),
         }
     }
 
@@ -84,6 +224,36 @@ impl Builder {
         self
     }
 
+    /// Fail the build when broken internal links are found
+    pub fn with_strict_links(mut self, strict_links: bool) -> Self {
+        self.strict_links = strict_links;
+        self
+    }
+
+    /// Fail the build when configured nav entries don't match a page
+    pub fn with_strict_nav(mut self, strict_nav: bool) -> Self {
+        self.strict_nav = strict_nav;
+        self
+    }
+
+    /// Build draft documents even outside dev mode
+    pub fn with_drafts(mut self, include_drafts: bool) -> Self {
+        self.include_drafts = include_drafts;
+        self
+    }
+
+    /// Fail the build when there are any warnings, including broken links
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Show the build's progress, with headers for each phase.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
     pub async fn build(&self) -> Result<BuildResult, BuildError> {
         // Build pipeline:
         // 1. Resolve sources -> ResolvedSource[]
@@ -92,10 +262,25 @@ impl Builder {
         // 4. Build navigation
         // 5. Render and write each document
         // 6. Copy static files
+        // 7. Check internal links
 
-        // Step 1: Resolve all sources
-        let resolved_sources = self.resolve_sources()?;
-        println!("Resolved {} source(s)", resolved_sources.len());
+        let start = Instant::now();
+        let mut timings = Timings::new();
+
+        // Check the highlight theme before doing any work
+        let highlighter = SyntaxHighlighter::with_theme(&self.config.markdown.highlight_theme)?;
+        let site_files = self.resolve_site_files()?;
+        let strict = self.strict || self.config.build.strict;
+        let diagnostics = Diagnostics::new();
+
+        // Step 1: Resolve all sources and the theme
+        self.progress.phase("Fetching sources");
+        let (resolved_sources, theme_path) = self.resolve_sources_and_theme(&mut timings)?;
+        info!("Resolved {} source(s)", resolved_sources.len());
+        let source_dirs = resolved_sources
+            .iter()
+            .map(|source| (source.config.name.clone(), source.local_path.clone()))
+            .collect();
 
         // Step 2: Create format registry (needed for content discovery)
         let format_registry = FormatRegistry::with_defaults();
@@ -103,19 +288,69 @@ impl Builder {
         // Step 3: Discover and collect content from all sources
         let mut all_items: Vec<(ContentItem, PathBuf)> = Vec::new();
         for source in &resolved_sources {
-            let content = source.discover_content(&format_registry)?;
+            let content =
+                timings.time(format!("discover source '{}'", source.config.name), || {
+                    source.discover_content(
+                        &format_registry,
+                        &self.config.markdown.front_matter,
+                        self.config.site.url_style,
+                        &diagnostics,
+                    )
+                })?;
             let display_path = source
                 .local_path
                 .canonicalize()
                 .unwrap_or(source.local_path.clone());
-            println!(
+            info!(
                 "  - {}: {} item(s) in {}",
                 source.config.name,
                 content.len(),
                 display_path.display()
             );
-            for item in content {
-                all_items.push((item, source.local_path.clone()));
+            all_items.extend(content);
+        }
+        let content_dirs: Vec<&Path> = resolved_sources
+            .iter()
+            .map(|source| source.local_path.as_path())
+            .collect();
+        let collisions = find_url_collisions(&all_items, &content_dirs, &site_files);
+        if !collisions.is_empty() {
+            return Err(BuildError::UrlCollisions(collisions));
+        }
+
+        // Serve everything under the site's base path. Only URLs change;
+        // files are still written relative to the output directory.
+        let site_base = normalize_base_path(self.config.site.base_path.as_deref());
+        let url_style = self.config.site.url_style;
+        if !site_base.is_empty() {
+            for (item, _) in &mut all_items {
+                match item {
+                    ContentItem::Document(doc) => {
+                        doc.url_path = with_base_path(&site_base, &doc.url_path);
+                        // The root page keeps its slash, like every other page
+                        if url_style == UrlStyle::TrailingSlash && doc.url_path == site_base {
+                            doc.url_path.push('/');
+                        }
+                        for alias in &mut doc.front_matter.aliases {
+                            *alias = with_base_path(&site_base, &normalize_alias(alias));
+                        }
+                    }
+                    ContentItem::Static(file) => {
+                        file.output_path = with_base_path(&site_base, &file.output_path);
+                    }
+                }
+            }
+        }
+
+        // Drop drafts before anything else sees them (dev mode always shows them)
+        if !self.dev_mode && !self.include_drafts {
+            let before = all_items.len();
+            all_items.retain(
+                |(item, _)| !matches!(item, ContentItem::Document(doc) if doc.front_matter.draft),
+            );
+            let drafts = before - all_items.len();
+            if drafts > 0 {
+                info!("Skipping {} draft(s)", drafts);
             }
         }
 
@@ -125,68 +360,140 @@ impl Builder {
             .filter(|(item, _)| matches!(item, ContentItem::Document(_)))
             .count();
         let static_count = all_items.len() - doc_count;
-        println!(
+        info!(
             "Found {} document(s) and {} static file(s)",
             doc_count, static_count
         );
 
         // Step 4: Load renderer
-        let theme_path = self.resolve_theme_path()?;
         let mut renderer = Renderer::new(&theme_path)?;
+        renderer.set_diagnostics(diagnostics.clone());
+        let theme_config =
+            ThemeConfig::load(&theme_path).map_err(|e| BuildError::Theme(e.to_string()))?;
+        let theme_settings = theme_config
+            .resolve_settings(&self.config.theme.settings)
+            .map_err(|e| BuildError::Theme(e.to_string()))?;
+        warn_undeclared_settings(&theme_config, &self.config.theme.settings, &diagnostics);
+
+        // Version pickers, for sources built from several git refs
+        let versions_by_source = version_links(&self.config.sources, &site_base, url_style);
 
-        // Step 5: Build source tabs for top-level navigation
-        let source_tabs: Vec<SourceTab> = resolved_sources
+        // Step 5: Build source tabs for top-level navigation (none when
+        // sources share a combined sidebar). Older versions of a source
+        // share the latest version's tab.
+        let nav_mode = self.config.site.nav_mode;
+        let mut tab_sources: Vec<&ResolvedSource> = resolved_sources
             .iter()
-            .map(|source| {
-                let url_prefix = source.url_prefix();
-                let is_top_level = url_prefix == "/";
-                // Use title if set, otherwise title-case the name
-                let display_name = source
-                    .config
-                    .title
-                    .clone()
-                    .unwrap_or_else(|| title_case(&source.config.name));
-                SourceTab {
-                    name: display_name,
-                    source_id: source.config.name.clone(),
-                    url: if is_top_level {
-                        "/".to_string()
-                    } else {
-                        format!("{}/", url_prefix)
-                    },
-                    is_current: false, // Will be set per-page
-                    is_top_level,
-                }
+            .filter(|source| {
+                versions_by_source
+                    .get(&source.config.name)
+                    .is_none_or(|versions| versions.iter().any(|v| v.is_current && v.is_latest))
             })
             .collect();
+        tab_sources.sort_by(|a, b| a.tab_order(b));
+
+        // The site's home page: the root when a source is served there, else
+        // the first tab's
+        let home_prefix = match tab_sources.first() {
+            Some(first) if !tab_sources.iter().any(|s| s.url_prefix() == "/") => first.url_prefix(),
+            _ => "/".to_string(),
+        };
+        let home_url = source_root_url(&site_base, &home_prefix, url_style);
+        if nav_mode != NavMode::Tabs {
+            tab_sources.clear();
+        }
+        let source_tab = |source: &ResolvedSource| {
+            let url_prefix = source.url_prefix();
+            SourceTab {
+                name: source.display_title(),
+                source_id: source.config.name.clone(),
+                group: source.config.group.clone(),
+                url: source_root_url(&site_base, &url_prefix, url_style),
+                is_current: false, // Will be set per-page
+                is_top_level: url_prefix == "/",
+                language: source.config.language.clone(),
+            }
+        };
+        let source_tabs: Vec<SourceTab> = tab_sources.into_iter().map(source_tab).collect();
 
         // Step 6: Build per-source navigation
-        let nav_by_source = build_navigation_by_source(&all_items, &resolved_sources);
+        let (nav_by_source, nav_report) =
+            build_navigation_by_source(&all_items, &resolved_sources, nav_mode, &diagnostics);
+        let orphans = self.config.nav.orphans;
+        nav_report.report(&diagnostics, orphans);
+        if self.strict_nav && !nav_report.is_ok() {
+            return Err(BuildError::MissingNavEntries(nav_report.missing.len()));
+        }
+        if orphans == OrphanSeverity::Error && !nav_report.orphans.is_empty() {
+            return Err(BuildError::OrphanPages(nav_report.orphans.len()));
+        }
 
         // Step 7: Create output directory
         let output_dir = self.output_dir();
         std::fs::create_dir_all(&output_dir)?;
 
         // Step 8: Copy theme static files to _theme/
+        let theme_output = output_dir.join("_theme");
         let theme_static = theme_path.join("static");
         if theme_static.exists() {
-            let theme_output = output_dir.join("_theme");
             copy_dir_recursive(&theme_static, &theme_output)?;
         }
 
-        // Step 9: Create syntax highlighter
-        let highlighter = SyntaxHighlighter::default();
+        // Step 9: Write the highlight theme's CSS for templates to link
+        let highlight_css = highlighter.generate_css();
+        if let Some(css) = &highlight_css {
+            std::fs::create_dir_all(&theme_output)?;
+            std::fs::write(theme_output.join("highlight.css"), css)?;
+        }
 
-        // Step 10: Build site context (shared across all pages)
+        // Step 10: Copy the favicon, logo, and social image to the output
+        // root, and build the site context (shared across all pages)
+        let mut favicon = self.config.site.favicon.clone();
+        let mut logo = self.config.site.logo.clone();
+        let mut social_image = self.config.site.social_image.clone();
+        for (setting, path) in site_files {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            std::fs::copy(&path, output_dir.join(file_name.as_ref()))?;
+            let url = with_base_path(&site_base, &format!("/{}", file_name));
+            match setting {
+                "favicon" => favicon = Some(url),
+                "logo" => logo = Some(url),
+                _ => social_image = Some(url),
+            }
+        }
+        let announcement = match &self.config.site.announcement {
+            Some(announcement) => Some(Announcement {
+                id: announcement
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| format!("{:08x}", fnv1a(announcement.text.as_bytes()))),
+                html: render_inline_markdown(
+                    &announcement.text,
+                    &highlighter,
+                    &self.config.markdown,
+                )?,
+            }),
+            None => None,
+        };
         let site_context = SiteContext {
             name: self.config.site.name.clone(),
             url: self.config.site.url.clone(),
-            favicon: self.config.site.favicon.clone(),
+            favicon,
+            description: self.config.site.description.clone(),
+            language: self.config.site.language.clone(),
+            author: self.config.site.author.clone(),
+            logo,
+            social_image,
+            base_path: site_base.clone(),
+            url_style,
+            announcement,
+            footer: self.config.site.footer.clone(),
         };
 
         // Step 11: Separate documents from static files
         let mut documents: Vec<ProcessingDocument> = Vec::new();
         let mut static_files: Vec<(&super::document::StaticFile, &PathBuf)> = Vec::new();
+        let mut static_index = StaticFileIndex::new();
 
         for (item, source_path) in &all_items {
             match item {
@@ -194,49 +501,394 @@ impl Builder {
                     documents.push(ProcessingDocument::new(doc.clone(), source_path.clone()));
                 }
                 ContentItem::Static(file) => {
+                    static_index.insert(source_path, file);
                     static_files.push((file, source_path));
                 }
             }
         }
 
+        // Date pages by their last commit and list their contributors,
+        // which means reading each repository's history
+        let site = &self.config.site;
+        if site.git_dates || site.git_contributors {
+            timings.time("git history", || {
+                let mut history = GitHistory::new();
+                if site.git_contributors {
+                    history = history.with_contributors(site.git_contributors_limit);
+                }
+                for doc in &mut documents {
+                    let file = doc.source_path.join(&doc.doc.source_path);
+                    if site.git_dates {
+                        doc.last_updated = history.last_updated(&doc.source_path, &file);
+                    }
+                    if site.git_contributors {
+                        doc.contributors = history.contributors(&doc.source_path, &file);
+                    }
+                }
+            });
+        }
+
+        // Pages without an image get a generated social card, drawn by a
+        // finalize stage once the pipeline has run
+        #[cfg(feature = "social-cards")]
+        let card_colors = if site.social_cards {
+            let colors =
+                CardColors::from_config(&theme_config.social_cards).map_err(BuildError::Theme)?;
+            for doc in &mut documents {
+                if doc.doc.front_matter.image.is_none() {
+                    let card = SocialCard {
+                        title: &doc.title(),
+                        site_name: &site.name,
+                        colors,
+                    };
+                    let url_path = strip_base_path(&site_base, doc.url_path());
+                    doc.social_card = Some(card.output_path(url_path));
+                }
+            }
+            Some(colors)
+        } else {
+            None
+        };
+        #[cfg(not(feature = "social-cards"))]
+        if site.social_cards {
+            return Err(BuildError::SocialCardsUnavailable);
+        }
+
+        // Site redirects may not replace anything the build writes
+        let mut pages: HashSet<String> = HashSet::new();
+        for doc in &documents {
+            pages.insert(doc.url_path().to_string());
+            pages.extend(
+                doc.doc
+                    .front_matter
+                    .aliases
+                    .iter()
+                    .map(|a| normalize_alias(a)),
+            );
+        }
+        pages.extend(
+            static_files
+                .iter()
+                .map(|(file, _)| file.output_path.clone()),
+        );
+        // The latest version of a source is also served without the version
+        // in its URLs, unless that would replace something
+        let mut redirect_config = self.config.site.redirects.clone();
+        for source in self
+            .config
+            .sources
+            .iter()
+            .filter(|s| !s.versions.is_empty())
+        {
+            let prefix = source.url_prefix();
+            let prefix = prefix.trim_end_matches('/');
+            let latest_prefix = format!("{}/{}", prefix, source.versions[0].name);
+            for doc in documents
+                .iter()
+                .filter(|doc| doc.source_name() == source.name)
+            {
+                let url = strip_base_path(&site_base, doc.url_path());
+                let Some(rest) = url.strip_prefix(&latest_prefix) else {
+                    continue;
+                };
+                let from = format!("{}{}", prefix, rest);
+                let from = if from.is_empty() {
+                    "/".to_string()
+                } else {
+                    from
+                };
+                if !pages.contains(&with_base_path(&site_base, &normalize_alias(&from))) {
+                    redirect_config
+                        .entry(from)
+                        .or_insert_with(|| url.to_string());
+                }
+            }
+        }
+        let redirects = collect_redirects(&redirect_config, &site_base, &pages)?;
+
+        // Sources can override the raw HTML policy
+        let markdown_by_source: HashMap<String, MarkdownConfig> = resolved_sources
+            .iter()
+            .filter_map(|source| {
+                let html_policy = source.config.html_policy?;
+                let config = MarkdownConfig {
+                    html_policy,
+                    ..self.config.markdown.clone()
+                };
+                Some((source.config.name.clone(), config))
+            })
+            .collect();
+
+        // Sidebar depth limits, per source or site-wide
+        let nav_depth_by_source: HashMap<String, usize> = resolved_sources
+            .iter()
+            .filter_map(|source| {
+                let max_depth = source
+                    .config
+                    .nav_max_depth
+                    .or(self.config.site.nav_max_depth)?;
+                Some((source.config.name.clone(), max_depth))
+            })
+            .collect();
+
+        // "Edit this page" links, for sources whose repository is known
+        let edit_url_by_source: HashMap<String, String> = resolved_sources
+            .iter()
+            .filter_map(|source| {
+                let template = source.edit_url_template(&self.config.site)?;
+                Some((source.config.name.clone(), template))
+            })
+            .collect();
+
+        // Fingerprint theme files and extra assets for `asset()`. Dev mode
+        // keeps plain names, so live reload always sees the same paths.
+        let mut manifest = AssetManifest::new();
+        if self.config.assets.fingerprint && !self.dev_mode {
+            let mut paths = Vec::new();
+            collect_files(&theme_static, "_theme", &mut paths)?;
+            if highlight_css.is_some() {
+                paths.push("_theme/highlight.css".to_string());
+            }
+            for extra in &self.config.assets.extra {
+                let path = extra.trim_start_matches('/').to_string();
+                // Source files are otherwise only copied after rendering
+                if let Some((file, source_path)) = static_files.iter().find(|(file, _)| {
+                    strip_base_path(&site_base, &file.output_path).trim_start_matches('/') == path
+                }) {
+                    let output_path = output_dir.join(&path);
+                    if let Some(parent) = output_path.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    publish_static_file(
+                        &source_path.join(&file.source_path),
+                        &output_path,
+                        self.config.static_files.link,
+                    )?;
+                }
+                if !output_dir.join(&path).is_file() {
+                    return Err(BuildError::AssetNotFound(extra.clone()));
+                }
+                paths.push(path);
+            }
+            for path in &paths {
+                manifest.add(&output_dir, path)?;
+            }
+            std::fs::write(output_dir.join("asset-manifest.json"), manifest.to_json())?;
+        }
+        renderer.set_assets(manifest, &site_base);
+
+        // Language switchers link each page to its translations
+        let translations = Translations::new(
+            &self.config.languages,
+            resolved_sources.iter().map(|source| &source.config),
+            &documents,
+        );
+
         // Step 12: Create pipeline context
         let mut ctx = PipelineContext::new(
             &output_dir,
             &site_context,
-            &self.config.theme.settings,
+            &theme_settings,
+            &self.config.extra,
             &self.config.markdown,
+            &markdown_by_source,
+            &theme_config,
             &nav_by_source,
+            &nav_depth_by_source,
+            &edit_url_by_source,
+            &versions_by_source,
+            &translations,
             &source_tabs,
             &highlighter,
             &mut renderer,
             &format_registry,
+            &static_index,
+            &diagnostics,
+            &self.progress,
             self.dev_mode,
             self.live_reload,
         );
 
         // Step 13: Run the document pipeline
-        let pipeline = Pipeline::default_pipeline();
-        pipeline.run(&mut documents, &mut ctx)?;
+        self.progress.phase("Rendering");
+        let mut pipeline = Pipeline::default_pipeline();
+        if !self.config.html.rules.is_empty() {
+            pipeline.insert_after(
+                "template",
+                HtmlRewriteStage::new(self.config.html.rules.clone()),
+            );
+        }
+        pipeline.add_finalize_stage(RedirectStage::new(
+            redirects,
+            self.config.site.redirects_format,
+        ));
+        if let Some(feed) = &self.config.feed {
+            pipeline.add_finalize_stage(FeedStage::new(feed.clone()));
+        }
+        let sitemap_url = with_base_path(&site_base, "/sitemap.xml");
+        let has_sitemap = static_files
+            .iter()
+            .any(|(file, _)| file.output_path == sitemap_url);
+        pipeline.add_finalize_stage(RobotsStage::new(
+            self.config.site.robots.clone(),
+            has_sitemap,
+        ));
+        pipeline.add_finalize_stage(NotFoundStage);
+        if self.config.site.llms {
+            let mut sources: Vec<&ResolvedSource> = resolved_sources.iter().collect();
+            sources.sort_by(|a, b| a.tab_order(b));
+            pipeline.add_finalize_stage(LlmsStage::new(
+                sources
+                    .into_iter()
+                    .map(|source| (source.config.name.clone(), source.display_title()))
+                    .collect(),
+            ));
+        }
+        // PDFs are slow to write, so dev mode leaves them out
+        let pdf = self.config.pdf.enabled && !self.dev_mode;
+        let mut print_sources: Vec<&ResolvedSource> = resolved_sources.iter().collect();
+        print_sources.sort_by(|a, b| a.tab_order(b));
+        let print_sources: Vec<SourceTab> = print_sources.into_iter().map(source_tab).collect();
+        if self.config.site.print || pdf {
+            pipeline.add_finalize_stage(PrintStage::new(print_sources.clone()));
+        }
+        let pdf_urls: Vec<(String, String)> = if pdf {
+            pipeline.add_finalize_stage(PdfStage::new(
+                self.config.pdf.clone(),
+                print_sources.clone(),
+            ));
+            print_sources
+                .iter()
+                .map(|source| (source.source_id.clone(), PdfStage::output_url(source)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        #[cfg(feature = "social-cards")]
+        if let Some(colors) = card_colors {
+            pipeline.add_finalize_stage(SocialCardStage::new(colors));
+        }
+        pipeline.add_finalize_stage(
+            ManifestStage::new(
+                static_files
+                    .iter()
+                    .map(|(file, _)| (*file).clone())
+                    .collect(),
+            )
+            .with_pdfs(pdf_urls.clone()),
+        );
+        pipeline.run(&mut documents, &mut ctx, &mut timings)?;
+        let mut sizes = std::mem::take(&mut ctx.sizes);
+        let changed_pages = std::mem::take(&mut ctx.changed_pages);
+        let pdfs: Vec<PathBuf> = pdf_urls
+            .iter()
+            .map(|(_, url)| {
+                url_to_output_path(strip_base_path(&site_base, url), &output_dir, url_style)
+            })
+            .filter(|path| path.is_file())
+            .collect();
 
         // Step 14: Copy static files
-        for (file, source_path) in static_files {
+        let copy_start = Instant::now();
+        let static_config = &self.config.static_files;
+        let (mut copied, mut linked, mut skipped) = (0, 0, 0);
+        self.progress.start("static files", static_files.len());
+        for (file, source_path) in &static_files {
+            self.progress.tick();
             let input_path = source_path.join(&file.source_path);
-            let output_path = url_to_output_path(&file.output_path, &output_dir);
+            let output_path = url_to_output_path(
+                strip_base_path(&site_base, &file.output_path),
+                &output_dir,
+                url_style,
+            );
+
+            let size = std::fs::metadata(&input_path)?.len();
+            sizes.record_static(&file.source_name, size);
+            if let Some(max_size) = static_config.max_file_size
+                && size > max_size.0
+            {
+                diagnostics.warn(
+                    WarningKind::StaticFile,
+                    Some(&input_path),
+                    format!(
+                        "{} is {}, more than static.max_file_size ({})",
+                        input_path.display(),
+                        format_size(size),
+                        format_size(max_size.0)
+                    ),
+                );
+            }
 
             if let Some(parent) = output_path.parent() {
                 std::fs::create_dir_all(parent)?;
             }
-            std::fs::copy(&input_path, &output_path)?;
+            match publish_static_file(&input_path, &output_path, static_config.link)? {
+                Published::Copied => copied += 1,
+                Published::Linked => linked += 1,
+                Published::Skipped => skipped += 1,
+            }
+        }
+        self.progress.finish();
+        timings.record("copy static files", copy_start.elapsed());
+        if !static_files.is_empty() {
+            let linked = if static_config.link {
+                format!(", {} linked", linked)
+            } else {
+                String::new()
+            };
+            info!(
+                "Static files: {} copied{}, {} up to date",
+                copied, linked, skipped
+            );
+        }
+
+        // Oversized pages are often an inlined image or a huge code dump
+        if let Some(max_kb) = self.config.budget.max_page_kb {
+            for page in sizes.pages_over(max_kb * 1024) {
+                diagnostics.warn(
+                    WarningKind::PageSize,
+                    Some(&page.file),
+                    format!(
+                        "{} is {}, more than budget.max_page_kb ({} KB)",
+                        page.url,
+                        format_size(page.bytes),
+                        max_kb
+                    ),
+                );
+            }
+        }
+
+        // Step 15: Check internal links
+        let static_paths: Vec<&str> = static_files
+            .iter()
+            .map(|(file, _)| file.output_path.as_str())
+            .collect();
+        let link_report = timings.time("check links", || check_links(&documents, &static_paths));
+        link_report.print();
+        diagnostics.print_summary();
+        if (self.strict_links || strict) && !link_report.is_ok() {
+            return Err(BuildError::BrokenLinks(link_report.broken.len()));
+        }
+        if strict && diagnostics.count() > 0 {
+            return Err(BuildError::Warnings(diagnostics.count()));
         }
 
         let display_output = output_dir.canonicalize().unwrap_or(output_dir.clone());
-        println!(
+        info!(
             "Wrote {} file(s) to {}",
             all_items.len(),
             display_output.display()
         );
 
         Ok(BuildResult {
+            manifest_path: output_dir.join(MANIFEST_PATH),
+            pdfs,
+            duration: start.elapsed(),
+            timings,
+            sizes,
+            home_url,
+            changed_pages,
+            source_dirs,
             output_dir,
             theme_path,
             documents: doc_count,
@@ -244,16 +896,177 @@ impl Builder {
         })
     }
 
-    /// Resolve all source configurations to local paths.
-    fn resolve_sources(&self) -> Result<Vec<ResolvedSource>, SourceError> {
-        let cache_dir = self.base_path.join(".undox/cache/git");
-        self.config
-            .sources
+    /// Check that the site can be built, without writing any output.
+    ///
+    /// Resolves every source, parses the front matter of every document,
+    /// checks configured nav entries against the pages found, and loads the
+    /// theme. Problems are collected rather than stopping at the first.
+    pub fn check(&self) -> CheckReport {
+        let mut report = CheckReport::default();
+        if let Err(e) = SyntaxHighlighter::with_theme(&self.config.markdown.highlight_theme) {
+            report.problems.push(format!("markdown: {}", e));
+        }
+        let cache_dir = git_cache_dir(self.config.cache_dir.as_deref(), &self.base_path);
+        let fetcher = GitFetcher::new(cache_dir);
+        let format_registry = FormatRegistry::with_defaults();
+        let diagnostics = Diagnostics::new();
+
+        let mut resolved_sources = Vec::new();
+        let mut all_items: Vec<(ContentItem, PathBuf)> = Vec::new();
+        for source_config in self.config.expanded_sources() {
+            let name = &source_config.name;
+            let source =
+                match ResolvedSource::resolve(source_config.clone(), &self.base_path, &fetcher) {
+                    Ok(source) => source,
+                    Err(e) => {
+                        report.problems.push(format!("source '{}': {}", name, e));
+                        continue;
+                    }
+                };
+            match source.discover_content(
+                &format_registry,
+                &self.config.markdown.front_matter,
+                self.config.site.url_style,
+                &diagnostics,
+            ) {
+                Ok(content) => all_items.extend(content),
+                Err(e) => report.problems.push(format!("source '{}': {}", name, e)),
+            }
+            resolved_sources.push(source);
+        }
+        let content_dirs: Vec<&Path> = resolved_sources
             .iter()
-            .map(|source_config| {
-                ResolvedSource::resolve(source_config.clone(), &self.base_path, &cache_dir)
-            })
-            .collect()
+            .map(|source| source.local_path.as_path())
+            .collect();
+        let site_files = self.resolve_site_files().unwrap_or_else(|e| {
+            report.problems.push(e.to_string());
+            Vec::new()
+        });
+        for collision in find_url_collisions(&all_items, &content_dirs, &site_files) {
+            report.problems.push(collision.to_string());
+        }
+        report.documents = all_items
+            .iter()
+            .filter(|(item, _)| matches!(item, ContentItem::Document(_)))
+            .count();
+
+        let (_, nav_report) = build_navigation_by_source(
+            &all_items,
+            &resolved_sources,
+            self.config.site.nav_mode,
+            &diagnostics,
+        );
+        for entry in nav_report.missing {
+            report.problems.push(format!(
+                "source '{}': nav entry '{}' doesn't match any page",
+                entry.source, entry.path
+            ));
+        }
+        if self.config.nav.orphans == OrphanSeverity::Error {
+            for (source, path) in nav_report.orphans {
+                report.problems.push(format!(
+                    "source '{}': page {} isn't in the configured navigation",
+                    source,
+                    path.display()
+                ));
+            }
+        }
+
+        match self.resolve_theme_path(&fetcher) {
+            Ok(theme_path) => {
+                for template in REQUIRED_TEMPLATES {
+                    if !theme_path.join("templates").join(template).is_file() {
+                        report.problems.push(format!(
+                            "theme: missing template {} in {}",
+                            template,
+                            theme_path.display()
+                        ));
+                    }
+                }
+                if let Err(e) = Renderer::new(&theme_path) {
+                    report.problems.push(format!("theme: {}", e));
+                }
+                match ThemeConfig::load(&theme_path) {
+                    Ok(theme) => {
+                        if let Err(e) = theme.resolve_settings(&self.config.theme.settings) {
+                            report.problems.push(format!("theme: {}", e));
+                        }
+                        warn_undeclared_settings(&theme, &self.config.theme.settings, &diagnostics);
+                    }
+                    Err(e) => report.problems.push(format!("theme: {}", e)),
+                }
+            }
+            Err(e) => report.problems.push(format!("theme: {}", e)),
+        }
+
+        report
+    }
+
+    /// Find the favicon, logo, and social image files to copy into the output, as
+    /// `(setting, path)` pairs. Settings that are URLs are left out.
+    fn resolve_site_files(&self) -> Result<Vec<(&'static str, PathBuf)>, BuildError> {
+        // Like the theme, these come from the parent config for child builds
+        let base = self.theme_base_path.as_ref().unwrap_or(&self.base_path);
+        let settings = [
+            ("favicon", &self.config.site.favicon),
+            ("logo", &self.config.site.logo),
+            ("social_image", &self.config.site.social_image),
+        ];
+
+        let mut files = Vec::new();
+        for (setting, value) in settings {
+            let Some(value) = value.as_deref().filter(|value| !is_url(value)) else {
+                continue;
+            };
+            let path = base.join(value);
+            if !path.is_file() {
+                return Err(BuildError::SiteFileNotFound { setting, path });
+            }
+            files.push((setting, path));
+        }
+        Ok(files)
+    }
+
+    /// Resolve every source and the theme, fetching git repositories
+    /// concurrently: up to [`MAX_CONCURRENT_FETCHES`] sources at once,
+    /// alongside the theme. A failure is reported for the first failing
+    /// source, in config order.
+    fn resolve_sources_and_theme(
+        &self,
+        timings: &mut Timings,
+    ) -> Result<(Vec<ResolvedSource>, PathBuf), BuildError> {
+        let cache_dir = git_cache_dir(self.config.cache_dir.as_deref(), &self.base_path);
+        let fetcher = GitFetcher::new(cache_dir);
+
+        let source_configs = self.config.expanded_sources();
+        let (sources, theme) = std::thread::scope(|scope| {
+            let theme = scope.spawn(|| {
+                let start = Instant::now();
+                (self.resolve_theme_path(&fetcher), start.elapsed())
+            });
+            let sources = run_concurrently(&source_configs, MAX_CONCURRENT_FETCHES, |config| {
+                let start = Instant::now();
+                let result = ResolvedSource::resolve(config.clone(), &self.base_path, &fetcher)
+                    .map_err(|source| BuildError::SourceFailed {
+                        name: config.name.clone(),
+                        source,
+                    });
+                (result, start.elapsed())
+            });
+            let theme = theme
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            (sources, theme)
+        });
+
+        let mut resolved = Vec::with_capacity(sources.len());
+        for (config, (result, duration)) in source_configs.iter().zip(sources) {
+            timings.record(format!("resolve source '{}'", config.name), duration);
+            resolved.push(result?);
+        }
+        let (theme_path, duration) = theme;
+        timings.record("resolve theme", duration);
+        Ok((resolved, theme_path?))
     }
 
     /// Get the output directory path, resolved against base_path.
@@ -267,8 +1080,7 @@ impl Builder {
     }
 
     /// Resolve the theme location to a local path.
-    fn resolve_theme_path(&self) -> Result<PathBuf, BuildError> {
-        let cache_dir = self.base_path.join(".undox/cache/git");
+    fn resolve_theme_path(&self, fetcher: &GitFetcher) -> Result<PathBuf, BuildError> {
         // Use theme_base_path if set (for child configs), otherwise base_path
         let theme_base = self.theme_base_path.as_ref().unwrap_or(&self.base_path);
 
@@ -293,8 +1105,7 @@ impl Builder {
             Location::Git { git } => {
                 // Fetch theme from git
                 let git_loc = git.to_location();
-                eprintln!("Fetching theme from {}...", git_loc.url);
-                let fetcher = GitFetcher::new(cache_dir);
+                info!("Fetching theme from {}...", git_loc.url);
                 let repo_path = fetcher.fetch_location(&git_loc)?;
 
                 // Apply path if specified
@@ -317,6 +1128,106 @@ impl Builder {
     }
 }
 
+/// Run `f` on each item on up to `limit` threads, returning the results in
+/// the order of `items`.
+fn run_concurrently<T: Sync, R: Send>(
+    items: &[T],
+    limit: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..limit.min(items.len()) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(i) else {
+                        break;
+                    };
+                    let result = f(item);
+                    results.lock().unwrap()[i] = Some(result);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item is run"))
+        .collect()
+}
+
+/// Links to every version of each source with `versions`, keyed by the name
+/// of the source each version is built as, with that version marked
+/// current.
+fn version_links(
+    sources: &[SourceConfig],
+    site_base: &str,
+    url_style: UrlStyle,
+) -> HashMap<String, Vec<VersionLink>> {
+    let mut by_source = HashMap::new();
+    for source in sources.iter().filter(|s| !s.versions.is_empty()) {
+        let expanded = source.expand_versions();
+        let links: Vec<VersionLink> = source
+            .versions
+            .iter()
+            .zip(&expanded)
+            .enumerate()
+            .map(|(i, (version, config))| VersionLink {
+                name: version.name.clone(),
+                url: source_root_url(site_base, &config.url_prefix(), url_style),
+                is_latest: i == 0,
+                is_current: false,
+                source_id: config.name.clone(),
+            })
+            .collect();
+        for config in &expanded {
+            let mut links = links.clone();
+            for link in &mut links {
+                link.is_current = link.source_id == config.name;
+            }
+            by_source.insert(config.name.clone(), links);
+        }
+    }
+    by_source
+}
+
+/// Warn about each theme setting the theme doesn't declare.
+fn warn_undeclared_settings(
+    theme: &ThemeConfig,
+    settings: &serde_json::Value,
+    diagnostics: &Diagnostics,
+) {
+    for key in theme.undeclared_settings(settings) {
+        diagnostics.warn(
+            WarningKind::ThemeSettings,
+            None,
+            format!("theme doesn't declare a setting named '{}'", key),
+        );
+    }
+}
+
+/// List the files under `dir`, as `prefix/relative/path` strings.
+fn collect_files(dir: &Path, prefix: &str, files: &mut Vec<String>) -> std::io::Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if entry.path().is_dir() {
+            collect_files(&entry.path(), &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
 /// Recursively copy a directory to a destination.
 fn copy_dir_recursive(src: &Path, dst: &Path) -> std::io::Result<()> {
     if !src.exists() {
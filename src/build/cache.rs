@@ -34,12 +34,38 @@ pub enum ChangeKind {
     },
     /// A template file changed.
     Template { path: PathBuf },
+    /// A file in the theme's `static/` directory was added, modified, or
+    /// deleted.
+    ThemeStatic { path: PathBuf, deleted: bool },
     /// The main config file changed.
     Config,
     /// The theme config changed.
     ThemeConfig,
 }
 
+/// Extensions of static files that are copied as they are, and can't be
+/// included into pages as snippets.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "avif", "ico", "bmp", "woff", "woff2", "ttf", "otf",
+    "eot", "mp3", "mp4", "ogg", "wav", "webm", "pdf", "zip", "gz",
+];
+
+impl ChangeKind {
+    /// Whether the change can alter the text of pages, and so the search
+    /// index. Theme static files and binary files can't; other static files
+    /// may be included into pages.
+    pub fn affects_page_text(&self) -> bool {
+        match self {
+            ChangeKind::ThemeStatic { .. } => false,
+            ChangeKind::StaticFile { path, .. } => !path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| BINARY_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str())),
+            _ => true,
+        }
+    }
+}
+
 /// What scope of rebuild is needed based on the changes.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InvalidationScope {
@@ -142,6 +168,9 @@ impl BuildCache {
                 ChangeKind::StaticFile { path, .. } => {
                     files_to_rebuild.push(path.clone());
                 }
+                ChangeKind::ThemeStatic { .. } => {
+                    // Copied to `_theme/` as they are; no document uses them
+                }
             }
         }
 
@@ -290,3 +319,32 @@ impl BuildCache {
         self.static_files.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affects_page_text() {
+        let static_file = |path: &str| ChangeKind::StaticFile {
+            source_name: "docs".to_string(),
+            path: PathBuf::from(path),
+            deleted: false,
+        };
+        assert!(!static_file("docs/images/logo.PNG").affects_page_text());
+        assert!(static_file("docs/examples/main.rs").affects_page_text());
+        assert!(
+            !ChangeKind::ThemeStatic {
+                path: PathBuf::from("theme/static/style.css"),
+                deleted: false,
+            }
+            .affects_page_text()
+        );
+        assert!(
+            ChangeKind::Template {
+                path: PathBuf::from("theme/templates/page.html"),
+            }
+            .affects_page_text()
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Dates in front matter.
+//!
+//! Pages can set `date` and `updated` as a plain date (`2024-01-05`) or an
+//! RFC 3339 timestamp (`2024-01-05T09:30:00Z`). These are the formats Tera's
+//! built-in `date` filter reads, so templates can format the values as they
+//! like: `{{ page.date | date(format="%B %d, %Y") }}`.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// Format of the pre-formatted date strings, e.g. "January 5, 2024".
+const DISPLAY_FORMAT: &str = "%B %-d, %Y";
+
+/// Parse a front matter date, returning the calendar date it falls on.
+///
+/// Timestamps without an offset (`2024-01-05T09:30:00`) are accepted too.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    parse_timestamp(value).map(|timestamp| timestamp.date_naive())
+}
+
+/// Parse a front matter date as a point in time, for feeds.
+///
+/// Plain dates are midnight UTC, and timestamps without an offset are taken
+/// to be UTC.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::<FixedOffset>::parse_from_rfc3339(value) {
+        return Some(datetime);
+    }
+    let naive = match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => date.and_time(NaiveTime::MIN),
+        Err(_) => NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").ok()?,
+    };
+    Some(naive.and_utc().fixed_offset())
+}
+
+/// Format a front matter date for display, or `None` if it isn't valid.
+pub fn format_date(value: &str) -> Option<String> {
+    parse_date(value).map(|date| date.format(DISPLAY_FORMAT).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date() {
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 5);
+        assert_eq!(parse_date("2024-01-05"), expected);
+        assert_eq!(parse_date("2024-01-05T09:30:00Z"), expected);
+        assert_eq!(parse_date("2024-01-05T23:30:00-05:00"), expected);
+        assert_eq!(parse_date("2024-01-05T09:30:00"), expected);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("January 5, 2024"), None);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let timestamp = |value| parse_timestamp(value).map(|t| t.to_rfc3339());
+        assert_eq!(
+            timestamp("2024-01-05").as_deref(),
+            Some("2024-01-05T00:00:00+00:00")
+        );
+        assert_eq!(
+            timestamp("2024-01-05T09:30:00").as_deref(),
+            Some("2024-01-05T09:30:00+00:00")
+        );
+        assert_eq!(
+            timestamp("2024-01-05T23:30:00-05:00").as_deref(),
+            Some("2024-01-05T23:30:00-05:00")
+        );
+        assert_eq!(timestamp("tomorrow"), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(
+            format_date("2024-01-05").as_deref(),
+            Some("January 5, 2024")
+        );
+        assert_eq!(format_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_tera_date_filter_reads_front_matter_dates() {
+        // Templates format dates with Tera's built-in filter
+        for value in ["2024-01-05", "2024-01-05T09:30:00Z", "2024-01-05T09:30:00"] {
+            let mut context = tera::Context::new();
+            context.insert("date", value);
+            let rendered =
+                tera::Tera::one_off("{{ date | date(format=\"%B %d, %Y\") }}", &context, false)
+                    .unwrap();
+            assert_eq!(rendered, "January 05, 2024");
+        }
+    }
+}
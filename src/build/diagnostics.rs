@@ -0,0 +1,179 @@
+//! Build warnings.
+//!
+//! Problems that don't stop a build, such as a page whose front matter can't
+//! be parsed or an image that doesn't exist, are printed as they're found and
+//! recorded here, so the build can sum them up at the end and `--strict` can
+//! fail on them. Errors that fail the build once they've all been found, such
+//! as pages left out of the navigation with `nav.orphans: error`, are
+//! recorded the same way.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// What a warning is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningKind {
+    /// A file that couldn't be read
+    Read,
+    /// Front matter or front matter defaults that couldn't be used
+    FrontMatter,
+    /// Navigation entries and directory metadata
+    Nav,
+    /// Images a page refers to that don't exist
+    Image,
+    /// Icons a template asks for that don't exist
+    Icon,
+    /// Theme settings the theme doesn't declare
+    ThemeSettings,
+    /// Pages left out of the feed, or a feed that can't be written
+    Feed,
+    /// Static files larger than `static.max_file_size`
+    StaticFile,
+    /// Symbolic links that are skipped or lead out of their source
+    Symlink,
+    /// Pages larger than `budget.max_page_kb`
+    PageSize,
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            WarningKind::Read => "read",
+            WarningKind::FrontMatter => "front matter",
+            WarningKind::Nav => "nav",
+            WarningKind::Image => "image",
+            WarningKind::Icon => "icon",
+            WarningKind::ThemeSettings => "theme settings",
+            WarningKind::Feed => "feed",
+            WarningKind::StaticFile => "static file",
+            WarningKind::Symlink => "symlink",
+            WarningKind::PageSize => "page size",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A recorded warning or error.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub kind: WarningKind,
+    /// Whether this is an error, which fails the build
+    pub error: bool,
+    /// The file the warning is about, if any
+    pub file: Option<PathBuf>,
+    pub message: String,
+}
+
+/// Collects the warnings of one build.
+///
+/// Clones share the same list, so stages and template functions can each
+/// hold one.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    warnings: Arc<Mutex<Vec<Warning>>>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Print a warning and record it.
+    pub fn warn(&self, kind: WarningKind, file: Option<&Path>, message: impl Into<String>) {
+        self.record(kind, false, file, message.into());
+    }
+
+    /// Print an error and record it. The caller fails the build once it has
+    /// reported every error it found.
+    pub fn error(&self, kind: WarningKind, file: Option<&Path>, message: impl Into<String>) {
+        self.record(kind, true, file, message.into());
+    }
+
+    fn record(&self, kind: WarningKind, error: bool, file: Option<&Path>, message: String) {
+        eprintln!("{}: {}", if error { "Error" } else { "Warning" }, message);
+        self.lock().push(Warning {
+            kind,
+            error,
+            file: file.map(Path::to_path_buf),
+            message,
+        });
+    }
+
+    /// Number of warnings recorded, not counting errors.
+    pub fn count(&self) -> usize {
+        self.lock().iter().filter(|warning| !warning.error).count()
+    }
+
+    /// All warnings and errors recorded so far, in the order they were found.
+    #[cfg(test)]
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.lock().clone()
+    }
+
+    /// Print how many warnings there were of each kind, if there were any,
+    /// and list them again grouped by file, so they aren't lost among the
+    /// rest of the build's output.
+    pub fn print_summary(&self) {
+        let mut warnings: Vec<Warning> = self
+            .lock()
+            .iter()
+            .filter(|warning| !warning.error)
+            .cloned()
+            .collect();
+        if warnings.is_empty() {
+            return;
+        }
+
+        let mut by_kind: BTreeMap<WarningKind, usize> = BTreeMap::new();
+        for warning in &warnings {
+            *by_kind.entry(warning.kind).or_default() += 1;
+        }
+        let counts: Vec<String> = by_kind
+            .iter()
+            .map(|(kind, count)| format!("{} {}", count, kind))
+            .collect();
+        eprintln!("{} warning(s): {}", warnings.len(), counts.join(", "));
+
+        // Warnings about no file in particular come first; the sort is
+        // stable, so each file's keep the order they were found in
+        warnings.sort_by(|a, b| a.file.cmp(&b.file));
+        for warning in &warnings {
+            eprintln!("  {}", warning.message);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Warning>> {
+        // A panic while holding the lock can't leave the list half-written
+        self.warnings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_warnings() {
+        let diagnostics = Diagnostics::new();
+        let stage = diagnostics.clone();
+        stage.warn(WarningKind::Image, Some(Path::new("a.md")), "missing");
+        diagnostics.warn(WarningKind::Read, None, "unreadable");
+
+        assert_eq!(diagnostics.count(), 2);
+        let warnings = diagnostics.warnings();
+        assert_eq!(warnings[0].kind, WarningKind::Image);
+        assert_eq!(warnings[0].file.as_deref(), Some(Path::new("a.md")));
+        assert_eq!(warnings[1].message, "unreadable");
+    }
+
+    #[test]
+    fn test_errors_not_counted_as_warnings() {
+        let diagnostics = Diagnostics::new();
+        diagnostics.error(WarningKind::Nav, Some(Path::new("a.md")), "not in nav");
+
+        assert_eq!(diagnostics.count(), 0);
+        assert!(diagnostics.warnings()[0].error);
+    }
+}
@@ -1,8 +1,10 @@
 use std::path::PathBuf;
 
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
 use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
 
-use crate::util::title_case;
+use crate::util::{split_numeric_prefix, title_case};
 
 // =============================================================================
 // Content items (documents and static files)
@@ -10,6 +12,8 @@ use crate::util::title_case;
 
 /// A content item discovered in a source.
 /// Can be either a document (markdown) or a static file (images, etc.).
+// Most items are documents, so boxing them wouldn't save anything
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum ContentItem {
     /// A markdown document that will be rendered to HTML
@@ -25,6 +29,8 @@ pub enum ContentItem {
 /// A static file (image, CSS, JS, etc.) that gets copied to output.
 #[derive(Debug, Clone)]
 pub struct StaticFile {
+    /// Which source this file belongs to
+    pub source_name: String,
     /// Path relative to the source root (e.g., "images/screenshot.png")
     pub source_path: PathBuf,
     /// The output path this file will be written to (e.g., "/cli/images/screenshot.png")
@@ -33,8 +39,9 @@ pub struct StaticFile {
 
 impl StaticFile {
     /// Create a new static file.
-    pub fn new(_source_name: String, source_path: PathBuf, output_path: String) -> Self {
+    pub fn new(source_name: String, source_path: PathBuf, output_path: String) -> Self {
         Self {
+            source_name,
             source_path,
             output_path,
         }
@@ -63,6 +70,11 @@ pub struct Document {
     pub front_matter: FrontMatter,
     /// Raw markdown content (front matter already stripped)
     pub raw_content: String,
+    /// Text of the first `# Heading` in the content, if any
+    pub heading_title: Option<String>,
+    /// Ordering prefix of the filename (`01-intro.md`), set when the source
+    /// strips numeric prefixes
+    pub numeric_prefix: Option<u64>,
 }
 
 /// Front matter metadata parsed from the document.
@@ -75,8 +87,27 @@ pub struct FrontMatter {
     /// Hide from navigation
     #[serde(default)]
     pub hidden: bool,
+    /// Exclude from production builds (still shown by `undox serve`)
+    #[serde(default)]
+    pub draft: bool,
+    /// Publication date (`YYYY-MM-DD` or RFC 3339)
+    pub date: Option<String>,
+    /// Date of the last significant update (`YYYY-MM-DD` or RFC 3339)
+    pub updated: Option<String>,
+    /// Canonical URL, for pages whose original lives elsewhere
+    pub canonical_url: Option<String>,
+    /// Image shown when the page is shared on social media
+    pub image: Option<String>,
     /// Custom slug override
     pub slug: Option<String>,
+    /// Old URLs that redirect to this page
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Position in auto-generated navigation (lower comes first)
+    #[serde(alias = "nav_order")]
+    pub weight: Option<i64>,
+    /// Set to `false` to hide the table of contents on this page
+    pub toc: Option<bool>,
     /// Additional arbitrary metadata (available in templates at top level, e.g., `page.author`)
     #[serde(flatten)]
     pub extra: std::collections::HashMap<String, serde_yaml::Value>,
@@ -89,6 +120,37 @@ pub struct ParsedContent {
     pub front_matter: FrontMatter,
     /// The markdown content without the front matter block
     pub content: String,
+    /// Why the front matter block couldn't be parsed, in which case
+    /// `front_matter` is empty
+    pub error: Option<FrontMatterError>,
+}
+
+/// A front matter block that isn't valid YAML for [`FrontMatter`].
+#[derive(thiserror::Error, Debug)]
+#[error("{message}{}", .line.map(|(line, column)| format!(" at line {} column {}", line, column)).unwrap_or_default())]
+pub struct FrontMatterError {
+    /// The YAML error, without its location
+    pub message: String,
+    /// Line and column in the file (1-based), if known
+    pub line: Option<(usize, usize)>,
+}
+
+impl FrontMatterError {
+    /// Convert a YAML error, shifting its line by `line_offset` (the number
+    /// of file lines before the YAML starts).
+    pub fn from_yaml(error: &serde_yaml::Error, line_offset: usize) -> Self {
+        let line = error
+            .location()
+            .map(|loc| (loc.line() + line_offset, loc.column()));
+        // The message ends with the location within the YAML, which is
+        // replaced by the location within the file
+        let message = error.to_string();
+        let message = match message.rsplit_once(" at line ") {
+            Some((message, _)) if line.is_some() => message.to_string(),
+            _ => message,
+        };
+        Self { message, line }
+    }
 }
 
 /// Parse front matter from markdown content.
@@ -105,15 +167,27 @@ pub struct ParsedContent {
 /// # Content starts here
 /// ```
 ///
-/// Returns the parsed front matter and the remaining content.
-pub fn parse_front_matter(content: &str) -> ParsedContent {
-    let content = content.trim_start();
+/// Returns the parsed front matter and the remaining content. If the YAML
+/// is invalid, the front matter is left empty and the error is returned
+/// alongside so the caller can decide whether to fail.
+///
+/// Fields the page doesn't set are filled in from `defaults` (the merged
+/// `_defaults.yaml` files of its directories). Merging is shallow: a field
+/// set by the page replaces the default entirely.
+pub fn parse_front_matter_with_defaults(content: &str, defaults: &Mapping) -> ParsedContent {
+    let trimmed = content.trim_start();
+    // Lines before the opening delimiter, for error locations
+    let leading_lines = content[..content.len() - trimmed.len()]
+        .matches('\n')
+        .count();
+    let content = trimmed;
 
     // Check if content starts with front matter delimiter
     if !content.starts_with("---") {
         return ParsedContent {
-            front_matter: FrontMatter::default(),
+            front_matter: with_defaults(Mapping::new(), defaults),
             content: content.to_string(),
+            error: None,
         };
     }
 
@@ -124,13 +198,17 @@ pub fn parse_front_matter(content: &str) -> ParsedContent {
     let Some(closing_pos) = closing_pos else {
         // No closing delimiter found, treat entire content as markdown
         return ParsedContent {
-            front_matter: FrontMatter::default(),
+            front_matter: with_defaults(Mapping::new(), defaults),
             content: content.to_string(),
+            error: None,
         };
     };
 
     // Extract the YAML content (skip the opening newline if present)
-    let yaml_content = after_opening[..closing_pos].trim_start_matches('\n');
+    let yaml_block = &after_opening[..closing_pos];
+    let yaml_content = yaml_block.trim_start_matches('\n');
+    // File line of the first YAML line, less one
+    let yaml_offset = leading_lines + 1 + (yaml_block.len() - yaml_content.len()).saturating_sub(1);
 
     // Extract the markdown content (skip the closing delimiter and newline)
     let markdown_start = 3 + closing_pos + 4; // "---" + yaml + "\n---"
@@ -142,22 +220,77 @@ pub fn parse_front_matter(content: &str) -> ParsedContent {
         String::new()
     };
 
-    // Parse the YAML
-    let front_matter = match serde_yaml::from_str(yaml_content) {
-        Ok(fm) => fm,
-        Err(e) => {
-            // Log warning but continue with default front matter
-            eprintln!("Warning: Failed to parse front matter: {}", e);
-            FrontMatter::default()
+    // Parse the YAML on its own first, so errors point into the file
+    let parsed = if yaml_content.trim().is_empty() {
+        Ok(FrontMatter::default())
+    } else {
+        serde_yaml::from_str::<FrontMatter>(yaml_content)
+    };
+    let (front_matter, error) = match parsed {
+        Ok(fm) if defaults.is_empty() => (fm, None),
+        Ok(_) => {
+            let page = match serde_yaml::from_str::<Value>(yaml_content) {
+                Ok(Value::Mapping(page)) => page,
+                _ => Mapping::new(),
+            };
+            (with_defaults(page, defaults), None)
         }
+        Err(e) => (
+            FrontMatter::default(),
+            Some(FrontMatterError::from_yaml(&e, yaml_offset)),
+        ),
     };
 
     ParsedContent {
         front_matter,
         content: markdown_content,
+        error,
     }
 }
 
+/// Lay page front matter over defaults.
+///
+/// Both sides are already known to be valid front matter, and merging top
+/// level keys keeps it that way.
+fn with_defaults(page: Mapping, defaults: &Mapping) -> FrontMatter {
+    let mut merged = defaults.clone();
+    merged.extend(page);
+    serde_yaml::from_value(Value::Mapping(merged)).unwrap_or_default()
+}
+
+/// Find the text of the first level-1 heading, with inline formatting
+/// (emphasis, code, links) stripped.
+fn first_heading(markdown: &str) -> Option<String> {
+    let mut title: Option<String> = None;
+    for event in Parser::new_ext(markdown, Options::ENABLE_HEADING_ATTRIBUTES) {
+        match event {
+            Event::Start(Tag::Heading {
+                level: HeadingLevel::H1,
+                ..
+            }) => title = Some(String::new()),
+            Event::End(TagEnd::Heading(HeadingLevel::H1)) => {
+                let text = title.take().unwrap_or_default();
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !text.is_empty() {
+                    return Some(text);
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(title) = &mut title {
+                    title.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(title) = &mut title {
+                    title.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 impl Document {
     /// Create a new document with all fields.
     pub fn new(
@@ -167,24 +300,45 @@ impl Document {
         front_matter: FrontMatter,
         raw_content: String,
     ) -> Self {
+        let heading_title = first_heading(&raw_content);
         Self {
             source_name,
             source_path,
             url_path,
             front_matter,
             raw_content,
+            heading_title,
+            numeric_prefix: None,
         }
     }
 
-    /// Get the document title, falling back to filename if not in front matter.
+    /// Get the document title.
+    ///
+    /// Uses the front matter title, then the first `# Heading` in the
+    /// content, then the filename.
     pub fn title(&self) -> String {
-        self.front_matter.title.clone().unwrap_or_else(|| {
-            self.source_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .map(title_case)
-                .unwrap_or_else(|| "Untitled".to_string())
-        })
+        self.front_matter
+            .title
+            .clone()
+            .or_else(|| self.heading_title.clone())
+            .unwrap_or_else(|| {
+                self.source_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(|stem| match self.numeric_prefix {
+                        Some(_) => title_case(split_numeric_prefix(stem).1),
+                        None => title_case(stem),
+                    })
+                    .unwrap_or_else(|| "Untitled".to_string())
+            })
+    }
+
+    /// Get the navigation weight: from front matter, else the filename's
+    /// numeric prefix.
+    pub fn weight(&self) -> Option<i64> {
+        self.front_matter
+            .weight
+            .or_else(|| self.numeric_prefix.map(|prefix| prefix as i64))
     }
 }
 
@@ -204,6 +358,36 @@ mod tests {
         assert_eq!(doc.title(), "Installation");
     }
 
+    #[test]
+    fn test_document_title_from_heading() {
+        let doc = Document::new(
+            "docs".to_string(),
+            PathBuf::from("api.md"),
+            "/api".to_string(),
+            FrontMatter::default(),
+            "```sh\n# not a heading\n```\n\n## Intro\n\n# The `undox` *HTTP* [API](/api) {#api}\n"
+                .to_string(),
+        );
+        assert_eq!(doc.title(), "The undox HTTP API");
+    }
+
+    #[test]
+    fn test_document_title_without_numeric_prefix() {
+        let mut doc = Document::new(
+            "docs".to_string(),
+            PathBuf::from("10-guides/01-getting-started.md"),
+            "/guides/getting-started".to_string(),
+            FrontMatter::default(),
+            String::new(),
+        );
+        assert_eq!(doc.title(), "01 Getting Started");
+        assert_eq!(doc.weight(), None);
+
+        doc.numeric_prefix = Some(1);
+        assert_eq!(doc.title(), "Getting Started");
+        assert_eq!(doc.weight(), Some(1));
+    }
+
     #[test]
     fn test_document_title_from_front_matter() {
         let doc = Document::new(
@@ -228,7 +412,7 @@ description: A test page
 
 # Hello World
 "#;
-        let parsed = parse_front_matter(content);
+        let parsed = parse_front_matter_with_defaults(content, &Mapping::new());
         assert_eq!(parsed.front_matter.title, Some("My Page".to_string()));
         assert_eq!(
             parsed.front_matter.description,
@@ -237,6 +421,62 @@ description: A test page
         assert_eq!(parsed.content.trim(), "# Hello World");
     }
 
+    #[test]
+    fn test_parse_front_matter_draft() {
+        let parsed = parse_front_matter_with_defaults(
+            "---\ntitle: WIP\ndraft: true\n---\n\nSoon",
+            &Mapping::new(),
+        );
+        assert!(parsed.front_matter.draft);
+        assert!(!parsed.front_matter.extra.contains_key("draft"));
+
+        let parsed = parse_front_matter_with_defaults("---\ntitle: Done\n---\n", &Mapping::new());
+        assert!(!parsed.front_matter.draft);
+    }
+
+    #[test]
+    fn test_parse_front_matter_weight() {
+        let parsed = parse_front_matter_with_defaults("---\nweight: 10\n---\n", &Mapping::new());
+        assert_eq!(parsed.front_matter.weight, Some(10));
+
+        let parsed = parse_front_matter_with_defaults("---\nnav_order: -1\n---\n", &Mapping::new());
+        assert_eq!(parsed.front_matter.weight, Some(-1));
+    }
+
+    #[test]
+    fn test_parse_front_matter_invalid_yaml() {
+        let content = "\n---\ntitle: Broken\ntags: [unclosed\n---\n\nStill rendered";
+        let parsed = parse_front_matter_with_defaults(content, &Mapping::new());
+        assert_eq!(parsed.front_matter.title, None);
+        assert_eq!(parsed.content, "Still rendered");
+
+        let error = parsed.error.expect("expected a YAML error");
+        assert_eq!(error.line.map(|(line, _)| line), Some(5));
+        assert!(error.to_string().ends_with(" at line 5 column 1"));
+    }
+
+    #[test]
+    fn test_parse_front_matter_with_defaults() {
+        let defaults: Mapping =
+            serde_yaml::from_str("hidden: true\nauthor: Team\ntitle: Default").unwrap();
+
+        let parsed = parse_front_matter_with_defaults(
+            "---\ntitle: Mine\nhidden: false\n---\nBody",
+            &defaults,
+        );
+        assert_eq!(parsed.front_matter.title, Some("Mine".to_string()));
+        assert!(!parsed.front_matter.hidden);
+        assert_eq!(
+            parsed.front_matter.extra.get("author"),
+            Some(&Value::String("Team".to_string()))
+        );
+
+        // Pages without front matter still get the defaults
+        let parsed = parse_front_matter_with_defaults("# Just Markdown", &defaults);
+        assert!(parsed.front_matter.hidden);
+        assert_eq!(parsed.content, "# Just Markdown");
+    }
+
     #[test]
     fn test_parse_front_matter_with_custom_fields() {
         let content = r#"---
@@ -249,7 +489,7 @@ tags:
 
 Content here
 "#;
-        let parsed = parse_front_matter(content);
+        let parsed = parse_front_matter_with_defaults(content, &Mapping::new());
         assert_eq!(parsed.front_matter.title, Some("Custom Page".to_string()));
         assert!(parsed.front_matter.extra.contains_key("author"));
         assert!(parsed.front_matter.extra.contains_key("tags"));
@@ -258,7 +498,7 @@ Content here
     #[test]
     fn test_parse_front_matter_no_front_matter() {
         let content = "# Just Markdown\n\nNo front matter here.";
-        let parsed = parse_front_matter(content);
+        let parsed = parse_front_matter_with_defaults(content, &Mapping::new());
         assert_eq!(parsed.front_matter.title, None);
         assert!(parsed.content.starts_with("# Just Markdown"));
     }
@@ -266,7 +506,7 @@ Content here
     #[test]
     fn test_parse_front_matter_empty_front_matter() {
         let content = "---\n---\n\n# Content";
-        let parsed = parse_front_matter(content);
+        let parsed = parse_front_matter_with_defaults(content, &Mapping::new());
         assert_eq!(parsed.front_matter.title, None);
         assert!(parsed.content.starts_with("# Content"));
     }
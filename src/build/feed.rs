@@ -0,0 +1,171 @@
+//! Atom feeds.
+//!
+//! The `feed` config lists one source's pages, such as a changelog or blog,
+//! in an Atom feed, newest first by their `date` front matter.
+
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+
+use crate::util::html_escape;
+
+/// An Atom feed, ready to write.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    /// Name given as the feed's author
+    pub author: String,
+    /// Absolute URL of the site
+    pub site_url: String,
+    /// Absolute URL of the feed itself, also used as its ID
+    pub feed_url: String,
+    /// Entries, newest first
+    pub entries: Vec<FeedEntry>,
+}
+
+/// A page listed in a feed.
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub title: String,
+    /// Absolute URL of the page, also used as its ID
+    pub url: String,
+    pub published: DateTime<FixedOffset>,
+    pub updated: DateTime<FixedOffset>,
+    pub content: Option<FeedContent>,
+}
+
+/// What a feed entry carries besides its title.
+#[derive(Debug, Clone)]
+pub enum FeedContent {
+    /// Plain text summary
+    Summary(String),
+    /// The page's rendered HTML
+    Html(String),
+}
+
+impl Feed {
+    /// Render the feed as an Atom document.
+    ///
+    /// The feed's `updated` time is that of its newest entry, or the Unix
+    /// epoch when it has none, so the same pages always give the same feed.
+    pub fn to_atom(&self) -> String {
+        let updated = self
+            .entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or_else(|| DateTime::UNIX_EPOCH.fixed_offset());
+
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        out.push_str(&format!("  <title>{}</title>\n", html_escape(&self.title)));
+        out.push_str(&format!(
+            "  <link href=\"{}\"/>\n",
+            html_escape(&self.site_url)
+        ));
+        out.push_str(&format!(
+            "  <link rel=\"self\" href=\"{}\"/>\n",
+            html_escape(&self.feed_url)
+        ));
+        out.push_str(&format!("  <id>{}</id>\n", html_escape(&self.feed_url)));
+        out.push_str(&format!("  <updated>{}</updated>\n", timestamp(&updated)));
+        out.push_str(&format!(
+            "  <author><name>{}</name></author>\n",
+            html_escape(&self.author)
+        ));
+
+        for entry in &self.entries {
+            let url = html_escape(&entry.url);
+            out.push_str("  <entry>\n");
+            out.push_str(&format!(
+                "    <title>{}</title>\n",
+                html_escape(&entry.title)
+            ));
+            out.push_str(&format!("    <link href=\"{}\"/>\n", url));
+            out.push_str(&format!("    <id>{}</id>\n", url));
+            out.push_str(&format!(
+                "    <published>{}</published>\n",
+                timestamp(&entry.published)
+            ));
+            out.push_str(&format!(
+                "    <updated>{}</updated>\n",
+                timestamp(&entry.updated)
+            ));
+            match &entry.content {
+                Some(FeedContent::Summary(text)) => {
+                    out.push_str(&format!("    <summary>{}</summary>\n", html_escape(text)));
+                }
+                // Relative links in the HTML resolve against the page
+                Some(FeedContent::Html(html)) => out.push_str(&format!(
+                    "    <content type=\"html\" xml:base=\"{}\">{}</content>\n",
+                    url,
+                    html_escape(html)
+                )),
+                None => {}
+            }
+            out.push_str("  </entry>\n");
+        }
+
+        out.push_str("</feed>\n");
+        out
+    }
+}
+
+/// Format a timestamp the way Atom wants it.
+fn timestamp(time: &DateTime<FixedOffset>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::dates::parse_timestamp;
+
+    #[test]
+    fn test_to_atom() {
+        let entry = |title: &str, date: &str, content| FeedEntry {
+            title: title.to_string(),
+            url: format!("https://example.com/blog/{}", title.to_lowercase()),
+            published: parse_timestamp(date).unwrap(),
+            updated: parse_timestamp(date).unwrap(),
+            content,
+        };
+        let feed = Feed {
+            title: "Docs & Blog".to_string(),
+            author: "Example".to_string(),
+            site_url: "https://example.com/".to_string(),
+            feed_url: "https://example.com/atom.xml".to_string(),
+            entries: vec![
+                entry(
+                    "Release",
+                    "2024-03-01T12:00:00+01:00",
+                    Some(FeedContent::Html("<p>New <em>things</em></p>".to_string())),
+                ),
+                entry(
+                    "Hello",
+                    "2024-01-05",
+                    Some(FeedContent::Summary("First post".to_string())),
+                ),
+            ],
+        };
+
+        let atom = feed.to_atom();
+        assert!(atom.contains("<title>Docs &amp; Blog</title>"));
+        assert!(atom.contains("<id>https://example.com/atom.xml</id>"));
+        assert!(atom.contains("<updated>2024-03-01T12:00:00+01:00</updated>\n  <author>"));
+        assert!(atom.contains(
+            "<content type=\"html\" xml:base=\"https://example.com/blog/release\">\
+             &lt;p&gt;New &lt;em&gt;things&lt;/em&gt;&lt;/p&gt;</content>"
+        ));
+        assert!(atom.contains("<published>2024-01-05T00:00:00Z</published>"));
+        assert!(atom.contains("<summary>First post</summary>"));
+
+        let empty = Feed {
+            entries: Vec::new(),
+            ..feed
+        };
+        assert!(
+            empty
+                .to_atom()
+                .contains("<updated>1970-01-01T00:00:00Z</updated>")
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Asset fingerprinting.
+//!
+//! Theme files keep the same name from one deploy to the next, so browsers
+//! can hold on to stale copies. With `assets.fingerprint`, each file is also
+//! written under a name containing a hash of its content (`style.css` gets a
+//! `style.1a2b3c4d.css` copy), and templates link to it with
+//! `asset(path="_theme/style.css")`. The original names stay, so relative
+//! `url()` references inside stylesheets keep working.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::util::fnv1a;
+
+/// Map of output paths to their fingerprinted copies, both relative to the
+/// output directory (`_theme/style.css` -> `_theme/style.1a2b3c4d.css`).
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct AssetManifest {
+    files: BTreeMap<String, String>,
+}
+
+impl AssetManifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write a fingerprinted copy of the file at `path` in `output_dir`, and
+    /// record it.
+    pub fn add(&mut self, output_dir: &Path, path: &str) -> std::io::Result<()> {
+        let content = std::fs::read(output_dir.join(path))?;
+        let fingerprinted = fingerprinted_path(path, &content);
+        std::fs::write(output_dir.join(&fingerprinted), content)?;
+        self.files.insert(path.to_string(), fingerprinted);
+        Ok(())
+    }
+
+    /// Get the fingerprinted copy of a path, if there is one.
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.files.get(path).map(String::as_str)
+    }
+
+    /// The manifest as JSON, for debugging.
+    pub fn to_json(&self) -> String {
+        let mut out = serde_json::to_string_pretty(self).expect("manifest serializes to JSON");
+        out.push('\n');
+        out
+    }
+}
+
+/// Insert a hash of `content` before the extension of `path`.
+///
+/// # Examples
+/// ```ignore
+/// fingerprinted_path("_theme/css/style.css", b"...") => "_theme/css/style.1a2b3c4d.css"
+/// fingerprinted_path("_theme/LICENSE", b"...") => "_theme/LICENSE.1a2b3c4d"
+/// ```
+pub fn fingerprinted_path(path: &str, content: &[u8]) -> String {
+    let hash = format!("{:08x}", fnv1a(content));
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), path),
+    };
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{}{}.{}.{}", dir, stem, hash, extension)
+        }
+        _ => format!("{}{}.{}", dir, name, hash),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprinted_path() {
+        let hash = format!("{:08x}", fnv1a(b"body {}"));
+        assert_eq!(
+            fingerprinted_path("_theme/css/style.css", b"body {}"),
+            format!("_theme/css/style.{}.css", hash)
+        );
+        assert_eq!(
+            fingerprinted_path("_theme/app.min.js", b"body {}"),
+            format!("_theme/app.min.{}.js", hash)
+        );
+        assert_eq!(
+            fingerprinted_path("_theme/.nojekyll", b"body {}"),
+            format!("_theme/.nojekyll.{}", hash)
+        );
+        // The hash follows the content
+        assert_ne!(
+            fingerprinted_path("style.css", b"a"),
+            fingerprinted_path("style.css", b"b")
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Text drawing for social cards, with the bundled font.
+//!
+//! There's no kerning, which large text on a card doesn't need.
+
+use std::sync::OnceLock;
+
+use ab_glyph::{Font as _, FontRef, PxScale, ScaleFont, point};
+
+use super::image::Image;
+
+/// The bundled font, DejaVu Sans Bold.
+const FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// A font to draw text with.
+pub struct Font {
+    font: FontRef<'static>,
+}
+
+impl Font {
+    /// The bundled font, parsed the first time it's needed.
+    pub fn bundled() -> &'static Font {
+        static FONT: OnceLock<Font> = OnceLock::new();
+        FONT.get_or_init(|| Font {
+            font: FontRef::try_from_slice(FONT_DATA).expect("the bundled font is valid"),
+        })
+    }
+
+    /// The scale that draws text at `size` pixels per em.
+    fn scale(&self, size: f32) -> PxScale {
+        let units_per_em = self.font.units_per_em().unwrap_or(2048.0);
+        PxScale::from(size * self.font.height_unscaled() / units_per_em)
+    }
+
+    /// Width of `text` drawn at `size` pixels.
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let font = self.font.as_scaled(self.scale(size));
+        text.chars().map(|c| font.h_advance(font.glyph_id(c))).sum()
+    }
+
+    /// Draw `text` at `size` pixels onto `image`, starting at `x` with its
+    /// baseline at `baseline`.
+    pub fn draw_text(
+        &self,
+        image: &mut Image,
+        text: &str,
+        x: f32,
+        baseline: f32,
+        size: f32,
+        color: [u8; 3],
+    ) {
+        let font = self.font.as_scaled(self.scale(size));
+        let mut pen = x;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            let glyph = id.with_scale_and_position(font.scale(), point(pen, baseline));
+            if let Some(outline) = font.outline_glyph(glyph) {
+                let bounds = outline.px_bounds();
+                outline.draw(|x, y, coverage| {
+                    image.blend(
+                        bounds.min.x as i64 + x as i64,
+                        bounds.min.y as i64 + y as i64,
+                        color,
+                        coverage,
+                    )
+                });
+            }
+            pen += font.h_advance(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_text() {
+        let font = Font::bundled();
+        assert_ne!(font.font.glyph_id('A').0, 0);
+        assert_ne!(font.font.glyph_id('é').0, 0);
+        assert!(font.text_width("Wide", 40.0) > font.text_width("ii", 40.0));
+
+        let mut image = Image::new(60, 40, [255, 255, 255]);
+        font.draw_text(&mut image, "H", 10.0, 30.0, 32.0, [0, 0, 0]);
+        // Inside the left stem of the H, and between its stems above the bar
+        assert_eq!(image.pixel(14, 20), [0, 0, 0]);
+        assert_eq!(image.pixel(20, 12), [255, 255, 255]);
+        // Nothing is drawn past the glyph
+        assert_eq!(image.pixel(50, 20), [255, 255, 255]);
+    }
+}
@@ -22,6 +22,7 @@
 
 use std::path::Path;
 
+use crate::build::assets::DocumentAssets;
 use crate::build::highlight::SyntaxHighlighter;
 use crate::build::markdown::render_markdown;
 use crate::build::render::TocEntry;
@@ -34,6 +35,10 @@ pub struct FormatOutput {
     pub html: String,
     /// Table of contents extracted from headings.
     pub toc: Vec<TocEntry>,
+    /// Whether the content contains math expressions.
+    pub has_math: bool,
+    /// Relative image references that didn't match a static file.
+    pub missing_images: Vec<String>,
 }
 
 /// Context available during format rendering.
@@ -42,6 +47,8 @@ pub struct FormatContext<'a> {
     pub highlighter: &'a SyntaxHighlighter,
     /// Markdown-specific configuration (also used by other formats for consistency).
     pub markdown_config: &'a MarkdownConfig,
+    /// Static files visible from the document being rendered.
+    pub assets: Option<DocumentAssets<'a>>,
 }
 
 /// Error during format rendering.
@@ -91,10 +98,17 @@ impl ContentFormat for MarkdownFormat {
     }
 
     fn render(&self, content: &str, ctx: &FormatContext) -> Result<FormatOutput, FormatError> {
-        let output = render_markdown(content, ctx.highlighter, ctx.markdown_config)?;
+        let output = render_markdown(
+            content,
+            ctx.highlighter,
+            ctx.markdown_config,
+            ctx.assets.as_ref(),
+        )?;
         Ok(FormatOutput {
             html: output.html,
             toc: output.toc,
+            has_math: output.has_math,
+            missing_images: output.missing_images,
         })
     }
 }
@@ -219,6 +233,8 @@ mod tests {
             Ok(FormatOutput {
                 html: "<p>mock</p>".to_string(),
                 toc: vec![],
+                has_math: false,
+                missing_images: vec![],
             })
         }
     }
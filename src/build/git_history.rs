@@ -0,0 +1,280 @@
+//! Page metadata from git history.
+//!
+//! With `site.git_dates`, each page's `last_updated` is the time of the most
+//! recent commit that touched its file, and with `site.git_contributors`,
+//! `contributors` lists the people who committed to it. A repository's
+//! history is walked once for both and cached, so the cost grows with the
+//! length of the history rather than with pages times history. Files git
+//! doesn't know about use their modification time instead.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset, TimeZone, Timelike, Utc};
+use git2::{Repository, Sort};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Someone who committed changes to a page.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Contributor {
+    pub name: String,
+    /// SHA-256 of the trimmed, lowercased email, as Gravatar expects
+    pub email_hash: String,
+}
+
+impl Contributor {
+    fn new(name: &str, email: &str) -> Self {
+        let email_hash = Sha256::digest(email.trim().to_lowercase().as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Self {
+            name: name.to_string(),
+            email_hash,
+        }
+    }
+}
+
+/// What the history says about one file.
+#[derive(Debug, Clone, Default)]
+struct FileHistory {
+    last_commit: Option<DateTime<FixedOffset>>,
+    /// Most recent first, one per email address
+    contributors: Vec<Contributor>,
+}
+
+/// File histories, cached per repository.
+#[derive(Default)]
+pub struct GitHistory {
+    /// How many contributors to keep per file (none unless enabled)
+    contributor_limit: usize,
+    /// Working directory of the repository each source root is in, if any
+    workdirs: HashMap<PathBuf, Option<PathBuf>>,
+    /// History of each file, by working directory and path relative to it
+    histories: HashMap<PathBuf, HashMap<PathBuf, FileHistory>>,
+}
+
+impl GitHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect up to `limit` contributors per file while walking history.
+    pub fn with_contributors(mut self, limit: usize) -> Self {
+        self.contributor_limit = limit;
+        self
+    }
+
+    /// When `file`, in the source rooted at `source_root`, was last changed:
+    /// its last commit, else its modification time.
+    pub fn last_updated(
+        &mut self,
+        source_root: &Path,
+        file: &Path,
+    ) -> Option<DateTime<FixedOffset>> {
+        self.file_history(source_root, file)
+            .and_then(|history| history.last_commit)
+            .or_else(|| modified_time(file))
+    }
+
+    /// The people who committed to `file`, most recent first.
+    pub fn contributors(&mut self, source_root: &Path, file: &Path) -> Vec<Contributor> {
+        self.file_history(source_root, file)
+            .map(|history| history.contributors.clone())
+            .unwrap_or_default()
+    }
+
+    fn file_history(&mut self, source_root: &Path, file: &Path) -> Option<&FileHistory> {
+        let workdir = self
+            .workdirs
+            .entry(source_root.to_path_buf())
+            .or_insert_with(|| find_workdir(source_root))
+            .clone()?;
+        let limit = self.contributor_limit;
+        let histories = self.histories.entry(workdir.clone()).or_insert_with(|| {
+            match Repository::open(&workdir) {
+                Ok(repo) => file_histories(&repo, limit).unwrap_or_default(),
+                Err(_) => HashMap::new(),
+            }
+        });
+
+        let file = file.canonicalize().ok()?;
+        histories.get(file.strip_prefix(&workdir).ok()?)
+    }
+}
+
+/// The canonical working directory of the repository `path` is in.
+fn find_workdir(path: &Path) -> Option<PathBuf> {
+    let repo = Repository::discover(path).ok()?;
+    repo.workdir()?.canonicalize().ok()
+}
+
+/// Walk the history from HEAD, newest first, recording the first (most
+/// recent) commit that changes each file and up to `contributor_limit` of
+/// its authors, as named by the repository's `.mailmap`.
+fn file_histories(
+    repo: &Repository,
+    contributor_limit: usize,
+) -> Result<HashMap<PathBuf, FileHistory>, git2::Error> {
+    let mailmap = repo.mailmap().ok();
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TIME)?;
+
+    let mut histories: HashMap<PathBuf, FileHistory> = HashMap::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let time = commit_time(&commit);
+        let author = if contributor_limit > 0 {
+            let author = match &mailmap {
+                Some(mailmap) => commit.author_with_mailmap(mailmap)?,
+                None => commit.author(),
+            };
+            Some(Contributor::new(
+                author.name().unwrap_or_default(),
+                author.email().unwrap_or_default(),
+            ))
+        } else {
+            None
+        };
+
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path() else {
+                continue;
+            };
+            let history = histories.entry(path.to_path_buf()).or_default();
+            if history.last_commit.is_none() {
+                history.last_commit = time;
+            }
+            if let Some(author) = &author
+                && history.contributors.len() < contributor_limit
+                && !history
+                    .contributors
+                    .iter()
+                    .any(|contributor| contributor.email_hash == author.email_hash)
+            {
+                history.contributors.push(author.clone());
+            }
+        }
+    }
+    Ok(histories)
+}
+
+fn commit_time(commit: &git2::Commit) -> Option<DateTime<FixedOffset>> {
+    let time = commit.time();
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)?;
+    offset.timestamp_opt(time.seconds(), 0).single()
+}
+
+fn modified_time(file: &Path) -> Option<DateTime<FixedOffset>> {
+    let modified = std::fs::metadata(file).ok()?.modified().ok()?;
+    // Whole seconds, like commit times
+    DateTime::<Utc>::from(modified)
+        .with_nanosecond(0)
+        .map(|time| time.fixed_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_history() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let content = dir.join("content");
+        std::fs::create_dir_all(&content).unwrap();
+        let repo = Repository::init(dir).unwrap();
+        std::fs::write(
+            dir.join(".mailmap"),
+            "Ada Lovelace <ada@example.com>\n\
+             Ada Lovelace <ada@example.com> <ada@old.example.com>\n",
+        )
+        .unwrap();
+
+        // Three commits; only the first touches intro.md
+        let commit = |files: &[&str], seconds: i64, name: &str, email: &str| {
+            let mut index = repo.index().unwrap();
+            for file in files {
+                std::fs::write(content.join(file), seconds.to_string()).unwrap();
+                index.add_path(&Path::new("content").join(file)).unwrap();
+            }
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let signature =
+                git2::Signature::new(name, email, &git2::Time::new(seconds, 60)).unwrap();
+            let parents: Vec<git2::Commit> = repo
+                .head()
+                .ok()
+                .and_then(|head| head.peel_to_commit().ok())
+                .into_iter()
+                .collect();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "edit",
+                &tree,
+                &parents,
+            )
+            .unwrap();
+        };
+        commit(
+            &["intro.md", "install.md"],
+            1_700_000_000,
+            "Ada",
+            "ada@old.example.com",
+        );
+        commit(&["install.md"], 1_700_043_200, "Bob", "bob@example.com");
+        commit(&["install.md"], 1_700_086_400, "Ada", "ada@example.com");
+        std::fs::write(content.join("draft.md"), "").unwrap();
+
+        let mut history = GitHistory::new().with_contributors(10);
+        let date = |history: &mut GitHistory, file| {
+            history
+                .last_updated(&content, &content.join(file))
+                .map(|date| date.to_rfc3339())
+        };
+        assert_eq!(
+            date(&mut history, "intro.md").as_deref(),
+            Some("2023-11-14T23:13:20+01:00")
+        );
+        assert_eq!(
+            date(&mut history, "install.md").as_deref(),
+            Some("2023-11-15T23:13:20+01:00")
+        );
+        // Untracked files fall back to their modification time
+        assert!(date(&mut history, "draft.md").is_some());
+
+        // The mailmap merges Ada's old address into her current one
+        let names = |history: &mut GitHistory, file| {
+            history
+                .contributors(&content, &content.join(file))
+                .into_iter()
+                .map(|contributor| contributor.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&mut history, "install.md"), ["Ada Lovelace", "Bob"]);
+        assert_eq!(names(&mut history, "intro.md"), ["Ada Lovelace"]);
+        assert!(names(&mut history, "draft.md").is_empty());
+    }
+
+    #[test]
+    fn test_contributor_email_hash() {
+        assert_eq!(
+            Contributor::new("Ada", " Ada@Example.com ").email_hash,
+            Contributor::new("Ada", "ada@example.com").email_hash
+        );
+        assert_eq!(
+            Contributor::new("Ada", "ada@example.com").email_hash.len(),
+            64
+        );
+    }
+}
@@ -1,9 +1,21 @@
+mod ansi;
+
 use autumnus::{HtmlLinkedBuilder, formatter::Formatter, languages::Language, themes};
 
+use crate::util::html_escape;
+
+#[derive(thiserror::Error, Debug)]
+pub enum HighlightError {
+    #[error("unknown highlight theme '{name}'; available themes: {}", available.join(", "))]
+    UnknownTheme {
+        name: String,
+        available: Vec<String>,
+    },
+}
+
 /// A syntax highlighter using autumnus (tree-sitter based).
 pub struct SyntaxHighlighter {
     /// Theme name for CSS generation (used by generate_css).
-    #[allow(dead_code)]
     theme_name: String,
 }
 
@@ -15,9 +27,37 @@ impl SyntaxHighlighter {
         }
     }
 
+    /// Create a syntax highlighter with the given theme, checking that
+    /// autumnus has it.
+    pub fn with_theme(theme_name: &str) -> Result<Self, HighlightError> {
+        if themes::get(theme_name).is_err() {
+            let mut available: Vec<String> = themes::ALL_THEMES
+                .iter()
+                .map(|theme| theme.name.clone())
+                .collect();
+            available.sort();
+            return Err(HighlightError::UnknownTheme {
+                name: theme_name.to_string(),
+                available,
+            });
+        }
+        Ok(Self::new(theme_name))
+    }
+
     /// Highlight code and return HTML with CSS classes.
     /// Returns the original code wrapped in a plain `<code>` if the language is not supported.
+    ///
+    /// `diff` and `console` blocks get line-level markup of their own instead
+    /// of going through the grammar; see `diff_block` and `console_block`.
+    /// `ansi` blocks have their color escape codes turned into spans.
     pub fn highlight(&self, code: &str, language: &str) -> String {
+        match language {
+            "ansi" => return Self::ansi_block(code, language),
+            "diff" | "patch" => return Self::diff_block(code, language),
+            "console" | "shell-session" => return Self::console_block(code, language),
+            _ => {}
+        }
+
         // Use Language::guess which handles language detection from name or extension
         let lang = Language::guess(language, code);
 
@@ -47,13 +87,113 @@ impl SyntaxHighlighter {
         }
     }
 
+    /// Highlight an inline code span.
+    ///
+    /// Returns just the `<code>` element, without the `<pre>` wrapper, with
+    /// per-line block wrappers turned into spans so the result can sit inside
+    /// a paragraph.
+    pub fn highlight_inline(&self, code: &str, language: &str) -> String {
+        let block = self.highlight(code, language);
+        match (block.find("<code"), block.rfind("</code>")) {
+            (Some(start), Some(end)) if start < end => block[start..end + "</code>".len()]
+                .replace("<div", "<span")
+                .replace("</div>", "</span>"),
+            _ => format!("<code>{}</code>", html_escape(code)),
+        }
+    }
+
     /// Generate CSS for the current theme.
-    #[allow(dead_code)]
     pub fn generate_css(&self) -> Option<String> {
         let theme = themes::get(&self.theme_name).ok()?;
         Some(theme.css(false)) // false = don't enable italic
     }
 
+    /// Render a diff with one span per line, classed by line type:
+    /// `diff-add`, `diff-del`, `diff-hunk` (`@@` lines), `diff-header`
+    /// (`+++`/`---` file lines), or no extra class for context lines.
+    fn diff_block(code: &str, language: &str) -> String {
+        let lines = code.lines().map(|line| {
+            let class = if line.starts_with("+++") || line.starts_with("---") {
+                Some("diff-header")
+            } else if line.starts_with('+') {
+                Some("diff-add")
+            } else if line.starts_with('-') {
+                Some("diff-del")
+            } else if line.starts_with("@@") {
+                Some("diff-hunk")
+            } else {
+                None
+            };
+            match class {
+                Some(class) => format!(
+                    "<span class=\"line {}\">{}</span>",
+                    class,
+                    html_escape(line)
+                ),
+                None => format!("<span class=\"line\">{}</span>", html_escape(line)),
+            }
+        });
+        Self::line_block(lines, language)
+    }
+
+    /// Render a console session, separating commands from their output.
+    ///
+    /// Lines starting with a `$` prompt (and lines continued from them with a
+    /// trailing `\`) become `console-command` spans, with the prompt in its
+    /// own `console-prompt` span so themes can make it unselectable and copy
+    /// buttons can copy just the commands. Everything else is `console-output`.
+    fn console_block(code: &str, language: &str) -> String {
+        let mut continued = false;
+        let lines = code.lines().map(|line| {
+            let prompt = if line == "$" {
+                Some("")
+            } else {
+                line.strip_prefix("$ ")
+            };
+
+            let html = match prompt {
+                _ if continued => format!(
+                    "<span class=\"line console-command\">{}</span>",
+                    html_escape(line)
+                ),
+                Some(command) => format!(
+                    "<span class=\"line console-command\"><span class=\"console-prompt\">$ </span>{}</span>",
+                    html_escape(command)
+                ),
+                None => format!(
+                    "<span class=\"line console-output\">{}</span>",
+                    html_escape(line)
+                ),
+            };
+
+            continued = (continued || prompt.is_some()) && line.ends_with('\\');
+            html
+        });
+        Self::line_block(lines, language)
+    }
+
+    /// Render terminal output, converting ANSI color codes to `ansi-*` spans.
+    ///
+    /// Without any escape codes this is the same as a plain code block.
+    fn ansi_block(code: &str, language: &str) -> String {
+        format!(
+            "<pre><code class=\"language-{}\">{}</code></pre>",
+            language,
+            ansi::to_html(code)
+        )
+    }
+
+    /// Wrap pre-rendered lines in a `<pre>` block.
+    fn line_block(lines: impl Iterator<Item = String>, language: &str) -> String {
+        let body: Vec<String> = lines.collect();
+        format!(
+            "<pre class=\"{}\"><code class=\"language-{}\">{}\n</code></pre>",
+            language,
+            language,
+            body.join("\n")
+        )
+    }
+
     /// Create a plain code block without highlighting.
     fn plain_code_block(code: &str, language: &str) -> String {
         let escaped = html_escape(code);
@@ -75,18 +215,22 @@ impl Default for SyntaxHighlighter {
     }
 }
 
-/// Escape HTML special characters.
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_with_theme() {
+        assert!(SyntaxHighlighter::with_theme("github-light").is_ok());
+
+        let err = SyntaxHighlighter::with_theme("github-darkest")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.starts_with("unknown highlight theme 'github-darkest'; available themes: "));
+        assert!(err.contains("github-dark"));
+    }
+
     #[test]
     fn test_highlight_rust() {
         let highlighter = SyntaxHighlighter::default();
@@ -107,8 +251,83 @@ mod tests {
     }
 
     #[test]
-    fn test_html_escape() {
-        assert_eq!(html_escape("<div>&</div>"), "&lt;div&gt;&amp;&lt;/div&gt;");
+    fn test_highlight_inline() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let result = highlighter.highlight_inline("a < b", "unknown_lang_xyz");
+        assert_eq!(
+            result,
+            "<code class=\"language-unknown_lang_xyz\">a &lt; b</code>"
+        );
+
+        let result = highlighter.highlight_inline("let x = 1;", "rust");
+        assert!(result.starts_with("<code"));
+        assert!(result.ends_with("</code>"));
+        assert!(!result.contains("<pre"));
+        assert!(!result.contains("<div"));
+    }
+
+    #[test]
+    fn test_highlight_diff() {
+        let highlighter = SyntaxHighlighter::default();
+        let code = "--- a/undox.yaml\n+++ b/undox.yaml\n@@ -1,2 +1,2 @@\n site:\n-  name: Old\n+  name: <New>\n";
+        let result = highlighter.highlight(code, "diff");
+
+        assert!(result.starts_with("<pre class=\"diff\"><code class=\"language-diff\">"));
+        assert!(result.contains("<span class=\"line diff-header\">--- a/undox.yaml</span>"));
+        assert!(result.contains("<span class=\"line diff-header\">+++ b/undox.yaml</span>"));
+        assert!(result.contains("<span class=\"line diff-hunk\">@@ -1,2 +1,2 @@</span>"));
+        assert!(result.contains("<span class=\"line\"> site:</span>"));
+        assert!(result.contains("<span class=\"line diff-del\">-  name: Old</span>"));
+        assert!(result.contains("<span class=\"line diff-add\">+  name: &lt;New&gt;</span>"));
+    }
+
+    #[test]
+    fn test_highlight_console() {
+        let highlighter = SyntaxHighlighter::default();
+        let code =
+            "$ undox build \\\n    --strict-links\nBuilt site to _site\n$\n$ echo done\ndone\n";
+        let result = highlighter.highlight(code, "console");
+
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "<pre class=\"console\"><code class=\"language-console\"><span class=\"line console-command\"><span class=\"console-prompt\">$ </span>undox build \\</span>",
+                "<span class=\"line console-command\">    --strict-links</span>",
+                "<span class=\"line console-output\">Built site to _site</span>",
+                "<span class=\"line console-command\"><span class=\"console-prompt\">$ </span></span>",
+                "<span class=\"line console-command\"><span class=\"console-prompt\">$ </span>echo done</span>",
+                "<span class=\"line console-output\">done</span>",
+                "</code></pre>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_shell_session_alias() {
+        let highlighter = SyntaxHighlighter::default();
+        let result = highlighter.highlight("$ ls\nfile.txt", "shell-session");
+
+        assert!(result.contains("<code class=\"language-shell-session\">"));
+        assert!(result.contains("console-command"));
+        assert!(result.contains("<span class=\"line console-output\">file.txt</span>"));
+    }
+
+    #[test]
+    fn test_highlight_ansi() {
+        let highlighter = SyntaxHighlighter::default();
+        let result = highlighter.highlight("\x1b[32mPASS\x1b[0m <test>", "ansi");
+        assert_eq!(
+            result,
+            "<pre><code class=\"language-ansi\"><span class=\"ansi-green\">PASS</span> &lt;test&gt;</code></pre>"
+        );
+
+        // Without escapes it matches a plain block
+        assert_eq!(
+            highlighter.highlight("a < b", "ansi"),
+            SyntaxHighlighter::plain_code_block("a < b", "ansi")
+        );
     }
 
     #[test]
@@ -0,0 +1,208 @@
+//! ANSI escape sequences in terminal output.
+//!
+//! SGR sequences (`ESC [ ... m`) become `<span>`s with classes such as
+//! `ansi-bold`, `ansi-red`, `ansi-bright-green`, or `ansi-bg-blue`. All other
+//! escape sequences (cursor movement, hyperlinks, 256-color and true-color
+//! codes, ...) are stripped.
+
+use crate::util::html_escape;
+
+const COLORS: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Text style accumulated from SGR codes.
+#[derive(Clone, Default, PartialEq)]
+struct Style {
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    fg: Option<String>,
+    bg: Option<String>,
+}
+
+impl Style {
+    fn classes(&self) -> Vec<String> {
+        let mut classes = Vec::new();
+        for (on, class) in [
+            (self.bold, "ansi-bold"),
+            (self.dim, "ansi-dim"),
+            (self.italic, "ansi-italic"),
+            (self.underline, "ansi-underline"),
+        ] {
+            if on {
+                classes.push(class.to_string());
+            }
+        }
+        if let Some(fg) = &self.fg {
+            classes.push(format!("ansi-{}", fg));
+        }
+        if let Some(bg) = &self.bg {
+            classes.push(format!("ansi-bg-{}", bg));
+        }
+        classes
+    }
+
+    /// Apply the parameters of one SGR sequence.
+    fn apply(&mut self, params: &str) {
+        let mut codes = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.fg = Some(COLORS[(code - 30) as usize].to_string()),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(COLORS[(code - 40) as usize].to_string()),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(format!("bright-{}", COLORS[(code - 90) as usize])),
+                100..=107 => self.bg = Some(format!("bright-{}", COLORS[(code - 100) as usize])),
+                // Extended colors aren't supported; skip their arguments
+                38 | 48 => match codes.next() {
+                    Some(5) => {
+                        codes.next();
+                    }
+                    Some(2) => {
+                        codes.nth(2);
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Convert text containing ANSI escapes to HTML, escaping the text itself.
+pub fn to_html(code: &str) -> String {
+    let mut out = String::with_capacity(code.len());
+    let mut style = Style::default();
+    let mut open_span = false;
+    let mut text = String::new();
+    let mut chars = code.chars().peekable();
+
+    // Write pending text, opening a span for the current style
+    let flush = |out: &mut String, text: &mut String, style: &Style, open_span: &mut bool| {
+        if text.is_empty() {
+            return;
+        }
+        if !*open_span {
+            let classes = style.classes();
+            if !classes.is_empty() {
+                out.push_str(&format!("<span class=\"{}\">", classes.join(" ")));
+                *open_span = true;
+            }
+        }
+        out.push_str(&html_escape(text));
+        text.clear();
+    };
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            text.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: parameters, then a final byte in `@`..=`~`
+            Some('[') => {
+                let mut params = String::new();
+                let mut last = None;
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if last == Some('m') {
+                    let mut next = style.clone();
+                    next.apply(&params);
+                    if next != style {
+                        flush(&mut out, &mut text, &style, &mut open_span);
+                        if open_span {
+                            out.push_str("</span>");
+                            open_span = false;
+                        }
+                        style = next;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or `ESC \`
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Any other escape is a single character
+            _ => {}
+        }
+    }
+
+    flush(&mut out, &mut text, &style, &mut open_span);
+    if open_span {
+        out.push_str("</span>");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors_and_reset() {
+        assert_eq!(
+            to_html("\x1b[1;31merror\x1b[0m: failed"),
+            "<span class=\"ansi-bold ansi-red\">error</span>: failed"
+        );
+        assert_eq!(
+            to_html("\x1b[92mok\x1b[39m \x1b[44mbg\x1b[m"),
+            "<span class=\"ansi-bright-green\">ok</span> <span class=\"ansi-bg-blue\">bg</span>"
+        );
+    }
+
+    #[test]
+    fn test_text_is_escaped() {
+        assert_eq!(
+            to_html("\x1b[33m<Vec<T>>\x1b[0m & more"),
+            "<span class=\"ansi-yellow\">&lt;Vec&lt;T&gt;&gt;</span> &amp; more"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_sequences_stripped() {
+        // Cursor movement, 256-color, and OSC hyperlinks
+        assert_eq!(to_html("a\x1b[2Kb"), "ab");
+        assert_eq!(to_html("\x1b[38;5;208mx\x1b[0m"), "x");
+        assert_eq!(
+            to_html("\x1b]8;;https://x.dev\x1b\\link\x1b]8;;\x07"),
+            "link"
+        );
+    }
+
+    #[test]
+    fn test_style_changes_mid_text() {
+        assert_eq!(
+            to_html("\x1b[1mbold \x1b[4mboth\x1b[22m under\x1b[0m"),
+            "<span class=\"ansi-bold\">bold </span>\
+             <span class=\"ansi-bold ansi-underline\">both</span>\
+             <span class=\"ansi-underline\"> under</span>"
+        );
+    }
+}
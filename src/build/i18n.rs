@@ -0,0 +1,119 @@
+//! Multilingual sites.
+//!
+//! Each source can be written in one of the site's `languages`. A page's
+//! translations are the pages with the same path in the sources of the other
+//! languages that sit at the same place in the site: `/en/guide` and
+//! `/de/guide`, or `/en` and `/de`.
+
+use std::collections::HashMap;
+
+use crate::build::pipeline::ProcessingDocument;
+use crate::build::render::Translation;
+use crate::config::{Languages, SourceConfig};
+
+/// The sources' languages and the pages in each language, for language
+/// switchers.
+#[derive(Debug, Default)]
+pub struct Translations {
+    languages: Languages,
+    /// Language of each source that has one, with its URL prefix less the
+    /// language
+    sources: HashMap<String, (String, String)>,
+    /// URL of each page, by language and its prefix and path in the source
+    pages: HashMap<(String, String), String>,
+}
+
+impl Translations {
+    pub fn new<'a>(
+        languages: &Languages,
+        sources: impl IntoIterator<Item = &'a SourceConfig>,
+        documents: &[ProcessingDocument],
+    ) -> Self {
+        let sources: HashMap<String, (String, String)> = sources
+            .into_iter()
+            .filter_map(|source| {
+                let language = source.language.clone()?;
+                let prefix = prefix_without_language(&source.url_prefix(), &language);
+                Some((source.name.clone(), (language, prefix)))
+            })
+            .collect();
+        let mut translations = Self {
+            languages: languages.clone(),
+            sources,
+            pages: HashMap::new(),
+        };
+        for doc in documents {
+            if let Some(key) = translations.key(doc) {
+                translations.pages.insert(key, doc.url_path().to_string());
+            }
+        }
+        translations
+    }
+
+    /// Get the language a source is written in, if it has one.
+    pub fn language_of(&self, source_name: &str) -> Option<&str> {
+        self.sources
+            .get(source_name)
+            .map(|(language, _)| language.as_str())
+    }
+
+    /// Get a page in each of the site's languages, with `url` unset for
+    /// languages it hasn't been translated into. Empty for pages of sources
+    /// without a language.
+    pub fn for_page(&self, doc: &ProcessingDocument) -> Vec<Translation> {
+        let Some((current, path)) = self.key(doc) else {
+            return Vec::new();
+        };
+        self.languages
+            .0
+            .iter()
+            .map(|language| Translation {
+                language: language.code.clone(),
+                name: language.name.clone(),
+                url: self
+                    .pages
+                    .get(&(language.code.clone(), path.clone()))
+                    .cloned(),
+                is_current: language.code == current,
+            })
+            .collect()
+    }
+
+    /// A page's language, and its source's prefix less the language followed
+    /// by its path in the source, which its translations share.
+    fn key(&self, doc: &ProcessingDocument) -> Option<(String, String)> {
+        let (language, prefix) = self.sources.get(doc.source_name())?;
+        let path = doc.doc.source_path.with_extension("");
+        let path: Vec<String> = path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        Some((language.clone(), format!("{}/{}", prefix, path.join("/"))))
+    }
+}
+
+/// A URL prefix without a leading segment naming `language`, so `/de/guide`
+/// becomes `/guide` and `/de` becomes `/`.
+fn prefix_without_language(prefix: &str, language: &str) -> String {
+    let rest = prefix.trim_matches('/');
+    let rest = match rest.split_once('/') {
+        Some((first, rest)) if first.eq_ignore_ascii_case(language) => rest,
+        None if rest.eq_ignore_ascii_case(language) => "",
+        _ => rest,
+    };
+    format!("/{}", rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_without_language() {
+        assert_eq!(prefix_without_language("/de/guide", "de"), "/guide");
+        assert_eq!(prefix_without_language("/pt-BR", "pt-br"), "/");
+        assert_eq!(prefix_without_language("/", "en"), "/");
+        assert_eq!(prefix_without_language("/guide", "en"), "/guide");
+        assert_eq!(prefix_without_language("/dev/guide", "de"), "/dev/guide");
+    }
+}
@@ -0,0 +1,90 @@
+//! RGB images for social cards.
+
+/// An 8-bit RGB image.
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// An image filled with `background`.
+    pub fn new(width: usize, height: usize, background: [u8; 3]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: background.repeat(width * height),
+        }
+    }
+
+    /// The color of a pixel.
+    #[cfg(test)]
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        let at = 3 * (y * self.width + x);
+        [self.pixels[at], self.pixels[at + 1], self.pixels[at + 2]]
+    }
+
+    /// Fill a rectangle, clipped to the image.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 3]) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                let at = 3 * (row * self.width + column);
+                self.pixels[at..at + 3].copy_from_slice(&color);
+            }
+        }
+    }
+
+    /// Mix `color` into a pixel by `coverage`, from 0 to 1. Pixels outside
+    /// the image are ignored.
+    pub fn blend(&mut self, x: i64, y: i64, color: [u8; 3], coverage: f32) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let at = 3 * (y as usize * self.width + x as usize);
+        for (channel, value) in self.pixels[at..at + 3].iter_mut().zip(color) {
+            let mixed = *channel as f32 + (value as f32 - *channel as f32) * coverage;
+            *channel = mixed.round() as u8;
+        }
+    }
+
+    /// Encode the image as a PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .expect("writing to a Vec can't fail");
+        png
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_png() {
+        let mut image = Image::new(3, 2, [255, 255, 255]);
+        image.fill_rect(1, 0, 5, 1, [255, 0, 0]);
+        image.blend(0, 1, [0, 0, 0], 0.5);
+        let png = image.to_png();
+
+        let mut reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(
+            pixels,
+            [
+                255, 255, 255, 255, 0, 0, 255, 0, 0, //
+                128, 128, 128, 255, 255, 255, 255, 255, 255,
+            ]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Snippet includes for markdown content.
+//!
+//! A line consisting of an include directive is replaced with the contents
+//! of another file, resolved relative to the source root:
+//!
+//! ```markdown
+//! --8<-- "partials/warning.md"
+//! --8<-- "examples/main.rs:10:20"
+//! --8<-- "examples/main.rs:setup"
+//! ```
+//!
+//! The optional suffix selects a 1-based, inclusive line range (either end
+//! may be omitted) or a named region delimited by `--8<-- [start:name]` and
+//! `--8<-- [end:name]` marker lines. Marker lines are never included in the
+//! output. Included files may include other files. Prefix a directive with
+//! `;` to render it literally.
+
+use std::path::{Path, PathBuf};
+
+use crate::util::normalize_relative_path;
+
+const DIRECTIVE: &str = "--8<--";
+
+#[derive(thiserror::Error, Debug)]
+pub enum IncludeError {
+    #[error("included file not found: {0}")]
+    NotFound(String),
+
+    #[error("include path escapes the source root: {0}")]
+    OutsideRoot(String),
+
+    #[error("recursive include of {0}")]
+    Recursive(String),
+
+    #[error("region '{region}' not found in {path}")]
+    RegionNotFound { path: String, region: String },
+
+    #[error("invalid line range in include: {0}")]
+    InvalidRange(String),
+
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Which part of the included file to use.
+#[derive(Debug, PartialEq)]
+enum Selection {
+    All,
+    Lines(Option<usize>, Option<usize>),
+    Region(String),
+}
+
+/// Expand all include directives in `content`.
+///
+/// `source_root` is the directory paths are resolved against.
+pub fn expand_includes(content: &str, source_root: &Path) -> Result<String, IncludeError> {
+    let load = |path: &Path| std::fs::read_to_string(source_root.join(path));
+    expand(content, &load, &mut Vec::new())
+}
+
+/// Expand directives, loading files (by root-relative path) with `load`.
+///
+/// `stack` holds the files currently being included, to detect cycles.
+fn expand(
+    content: &str,
+    load: &dyn Fn(&Path) -> std::io::Result<String>,
+    stack: &mut Vec<PathBuf>,
+) -> Result<String, IncludeError> {
+    if !content.contains(DIRECTIVE) {
+        return Ok(content.to_string());
+    }
+
+    let mut out = String::with_capacity(content.len());
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        // `;--8<-- ...` escapes the directive
+        if let Some(escaped) = trimmed.strip_prefix(';')
+            && escaped.starts_with(DIRECTIVE)
+        {
+            out.push_str(indent);
+            out.push_str(escaped);
+            out.push('\n');
+            continue;
+        }
+
+        let Some(spec) = parse_directive(trimmed) else {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        };
+
+        let (path, selection) = parse_spec(spec)?;
+        let relative = normalize_relative_path(Path::new(path))
+            .ok_or_else(|| IncludeError::OutsideRoot(path.to_string()))?;
+        if stack.contains(&relative) {
+            return Err(IncludeError::Recursive(path.to_string()));
+        }
+        let included = load(&relative).map_err(|source| match source.kind() {
+            std::io::ErrorKind::NotFound => IncludeError::NotFound(path.to_string()),
+            _ => IncludeError::Io {
+                path: path.to_string(),
+                source,
+            },
+        })?;
+
+        let selected = select(&included, &selection, path)?;
+
+        stack.push(relative);
+        let expanded = expand(&selected, load, stack)?;
+        stack.pop();
+
+        for included_line in expanded.lines() {
+            if !included_line.is_empty() {
+                out.push_str(indent);
+            }
+            out.push_str(included_line);
+            out.push('\n');
+        }
+    }
+
+    Ok(out)
+}
+
+/// Parse `--8<-- "spec"`, returning the quoted spec.
+fn parse_directive(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix(DIRECTIVE)?.trim();
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    rest[1..].strip_suffix(quote)
+}
+
+/// Split `path[:start[:end]]` or `path:region` into its parts.
+fn parse_spec(spec: &str) -> Result<(&str, Selection), IncludeError> {
+    let mut parts = spec.split(':');
+    let path = parts.next().unwrap_or_default();
+    let rest: Vec<&str> = parts.collect();
+
+    let parse_line = |s: &str| -> Result<Option<usize>, IncludeError> {
+        if s.is_empty() {
+            return Ok(None);
+        }
+        match s.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(Some(n)),
+            _ => Err(IncludeError::InvalidRange(spec.to_string())),
+        }
+    };
+
+    let selection = match rest.as_slice() {
+        [] => Selection::All,
+        [one] if one.chars().all(|c| c.is_ascii_digit()) => {
+            Selection::Lines(parse_line(one)?, None)
+        }
+        [region] => Selection::Region(region.to_string()),
+        [start, end] => Selection::Lines(parse_line(start)?, parse_line(end)?),
+        _ => return Err(IncludeError::InvalidRange(spec.to_string())),
+    };
+
+    Ok((path, selection))
+}
+
+/// Apply a selection to a file's contents, dropping region marker lines.
+fn select(content: &str, selection: &Selection, path: &str) -> Result<String, IncludeError> {
+    let lines: Vec<&str> = content.lines().collect();
+
+    let selected: Vec<&str> = match selection {
+        Selection::All => lines,
+        Selection::Lines(start, end) => {
+            let start = start.unwrap_or(1) - 1;
+            let end = end.unwrap_or(lines.len()).min(lines.len());
+            lines.get(start..end).unwrap_or_default().to_vec()
+        }
+        Selection::Region(region) => {
+            let start_marker = format!("{} [start:{}]", DIRECTIVE, region);
+            let end_marker = format!("{} [end:{}]", DIRECTIVE, region);
+            let start = lines
+                .iter()
+                .position(|l| l.contains(&start_marker))
+                .ok_or_else(|| IncludeError::RegionNotFound {
+                    path: path.to_string(),
+                    region: region.clone(),
+                })?;
+            let end = lines[start..]
+                .iter()
+                .position(|l| l.contains(&end_marker))
+                .map(|i| start + i)
+                .unwrap_or(lines.len());
+            lines[start + 1..end].to_vec()
+        }
+    };
+
+    let mut out = String::new();
+    for line in selected {
+        if is_region_marker(line) {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Whether a line is a `--8<-- [start:name]` / `--8<-- [end:name]` marker.
+fn is_region_marker(line: &str) -> bool {
+    line.find(DIRECTIVE).is_some_and(|i| {
+        let rest = line[i + DIRECTIVE.len()..].trim_start();
+        rest.starts_with("[start:") || rest.starts_with("[end:")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Expand against an in-memory set of files.
+    fn expand_with(content: &str, files: &[(&str, &str)]) -> Result<String, IncludeError> {
+        let files: HashMap<PathBuf, String> = files
+            .iter()
+            .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+            .collect();
+        let load = |path: &Path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
+        };
+        expand(content, &load, &mut Vec::new())
+    }
+
+    const FILES: &[(&str, &str)] = &[
+        ("partials/warning.md", "> **Warning:** be careful\n"),
+        (
+            "example.rs",
+            "use std::io;\n// --8<-- [start:main]\nfn main() {}\n// --8<-- [end:main]\n",
+        ),
+    ];
+
+    #[test]
+    fn test_include_whole_file() {
+        let out = expand_with("Intro\n\n--8<-- \"partials/warning.md\"\n", FILES).unwrap();
+        assert_eq!(out, "Intro\n\n> **Warning:** be careful\n");
+    }
+
+    #[test]
+    fn test_include_preserves_indent() {
+        let out = expand_with("- item\n\n    --8<-- 'partials/warning.md'\n", FILES).unwrap();
+        assert_eq!(out, "- item\n\n    > **Warning:** be careful\n");
+    }
+
+    #[test]
+    fn test_include_line_range() {
+        let out = expand_with("--8<-- \"example.rs:1:1\"", FILES).unwrap();
+        assert_eq!(out, "use std::io;\n");
+
+        let out = expand_with("--8<-- \"example.rs:3\"", FILES).unwrap();
+        assert_eq!(out, "fn main() {}\n");
+
+        let err = expand_with("--8<-- \"example.rs:0:2\"", FILES).unwrap_err();
+        assert!(matches!(err, IncludeError::InvalidRange(_)));
+    }
+
+    #[test]
+    fn test_include_region() {
+        let out = expand_with("--8<-- \"example.rs:main\"", FILES).unwrap();
+        assert_eq!(out, "fn main() {}\n");
+
+        let err = expand_with("--8<-- \"example.rs:nope\"", FILES).unwrap_err();
+        assert!(matches!(err, IncludeError::RegionNotFound { .. }));
+    }
+
+    #[test]
+    fn test_include_whole_file_strips_markers() {
+        let out = expand_with("--8<-- \"example.rs\"", FILES).unwrap();
+        assert_eq!(out, "use std::io;\nfn main() {}\n");
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let err = expand_with("--8<-- \"missing.md\"", FILES).unwrap_err();
+        assert!(matches!(err, IncludeError::NotFound(_)));
+
+        let err = expand_with("--8<-- \"../secret.md\"", FILES).unwrap_err();
+        assert!(matches!(err, IncludeError::OutsideRoot(_)));
+    }
+
+    #[test]
+    fn test_include_nested_and_recursive() {
+        let files = &[
+            ("outer.md", "Before\n--8<-- \"partials/warning.md\"\n"),
+            ("partials/warning.md", "Careful\n"),
+            ("a.md", "--8<-- \"b.md\"\n"),
+            ("b.md", "--8<-- \"./a.md\"\n"),
+        ];
+
+        let out = expand_with("--8<-- \"outer.md\"", files).unwrap();
+        assert_eq!(out, "Before\nCareful\n");
+
+        let err = expand_with("--8<-- \"a.md\"", files).unwrap_err();
+        assert!(matches!(err, IncludeError::Recursive(_)));
+    }
+
+    #[test]
+    fn test_include_escaped() {
+        let out = expand_with(";--8<-- \"missing.md\"\n", FILES).unwrap();
+        assert_eq!(out, "--8<-- \"missing.md\"\n");
+    }
+}
@@ -0,0 +1,294 @@
+//! Internal link checking.
+//!
+//! After documents are rendered, every `href` in their HTML content is
+//! resolved against the set of generated pages and static files. Links that
+//! don't resolve are reported as broken.
+//!
+//! Only the rendered content is checked, not the page template, so theme
+//! navigation links never show up in the report.
+
+use std::collections::HashSet;
+
+use super::aliases::normalize_alias;
+use super::assets::has_scheme;
+use super::pipeline::ProcessingDocument;
+use crate::util::decode_entities;
+
+/// A link that points to a page or anchor that doesn't exist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokenLink {
+    /// URL path of the document containing the link
+    pub source: String,
+    /// The link target as written
+    pub href: String,
+}
+
+/// Result of checking all internal links in a build.
+#[derive(Debug, Default)]
+pub struct LinkReport {
+    /// Number of internal links checked
+    pub checked: usize,
+    /// Links that didn't resolve
+    pub broken: Vec<BrokenLink>,
+}
+
+impl LinkReport {
+    /// Whether every internal link resolved.
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty()
+    }
+
+    /// Print the broken links, grouped by source document.
+    pub fn print(&self) {
+        if self.is_ok() {
+            return;
+        }
+
+        eprintln!("Found {} broken internal link(s):", self.broken.len());
+        let mut current: Option<&str> = None;
+        for link in &self.broken {
+            if current != Some(link.source.as_str()) {
+                eprintln!("  {}", link.source);
+                current = Some(link.source.as_str());
+            }
+            eprintln!("    -> {}", link.href);
+        }
+    }
+}
+
+/// Check internal links in all rendered documents.
+///
+/// `static_paths` are the output URL paths of copied static files. Links are
+/// resolved relative to the page's directory: the page's own URL, since most
+/// pages are written to `{url_path}/index.html`, or its parent for pages
+/// written as `.html` files. Anchor-only links (`#section`) are checked
+/// against the ids on the page itself: its TOC entries plus any other `id`
+/// attributes in the content (footnotes, raw HTML).
+pub fn check_links(docs: &[ProcessingDocument], static_paths: &[&str]) -> LinkReport {
+    let aliases: Vec<String> = docs
+        .iter()
+        .flat_map(|d| d.doc.front_matter.aliases.iter())
+        .map(|alias| normalize_alias(alias))
+        .collect();
+    // Links with and without a trailing slash reach the same page
+    let mut targets: HashSet<&str> = docs
+        .iter()
+        .map(|d| match d.url_path().trim_end_matches('/') {
+            "" => "/",
+            url => url,
+        })
+        .collect();
+    targets.extend(static_paths.iter().copied());
+    targets.extend(aliases.iter().map(String::as_str));
+
+    let mut report = LinkReport::default();
+
+    for doc in docs {
+        let mut ids: HashSet<String> = doc.toc.iter().map(|e| e.id.clone()).collect();
+        ids.extend(attribute_values(&doc.content, "id"));
+
+        for href in attribute_values(&doc.content, "href") {
+            if is_external(&href) {
+                continue;
+            }
+            report.checked += 1;
+
+            let (path, fragment) = match href.split_once('#') {
+                Some((path, fragment)) => (path, Some(fragment)),
+                None => (href.as_str(), None),
+            };
+            let path = path.split('?').next().unwrap_or_default();
+
+            let ok = if path.is_empty() {
+                fragment.is_none_or(|f| f.is_empty() || ids.contains(f))
+            } else {
+                let resolved = resolve(doc.url_path(), path);
+                targets.contains(resolved.as_str())
+                    || resolved
+                        .strip_suffix("/index.html")
+                        .map(|p| if p.is_empty() { "/" } else { p })
+                        .is_some_and(|p| targets.contains(p))
+            };
+
+            if !ok {
+                report.broken.push(BrokenLink {
+                    source: doc.url_path().to_string(),
+                    href,
+                });
+            }
+        }
+    }
+
+    report
+}
+
+/// Whether a link points outside the site (has a scheme or is protocol-relative).
+pub fn is_external(href: &str) -> bool {
+    href.starts_with("//") || has_scheme(href)
+}
+
+/// Resolve a link path against the URL path of the page containing it.
+pub fn resolve(page: &str, path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    if !path.starts_with('/') {
+        segments.extend(page.split('/').filter(|s| !s.is_empty()));
+        // A page written as a file is in its parent directory
+        if page.ends_with(".html") {
+            segments.pop();
+        }
+    }
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    format!("/{}", segments.join("/"))
+}
+
+/// Collect the values of an attribute from every tag in an HTML fragment.
+fn attribute_values(html: &str, name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let pattern = format!("{}=", name);
+    let mut rest = html;
+
+    while let Some(pos) = rest.find(&pattern) {
+        // Must be a standalone attribute name, e.g. not `data-id=`
+        let preceded_by_space = rest[..pos]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_whitespace());
+        rest = &rest[pos + pattern.len()..];
+        if !preceded_by_space {
+            continue;
+        }
+
+        let Some(quote @ ('"' | '\'')) = rest.chars().next() else {
+            continue;
+        };
+        let Some(end) = rest[1..].find(quote) else {
+            break;
+        };
+        values.push(decode_entities(&rest[1..end + 1]));
+        rest = &rest[end + 2..];
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::build::document::{Document, FrontMatter};
+    use crate::build::render::TocEntry;
+
+    fn doc(url_path: &str, html: &str) -> ProcessingDocument {
+        let mut doc = ProcessingDocument::new(
+            Document {
+                source_name: "docs".to_string(),
+                source_path: PathBuf::from("page.md"),
+                url_path: url_path.to_string(),
+                front_matter: FrontMatter::default(),
+                raw_content: String::new(),
+                heading_title: None,
+                numeric_prefix: None,
+            },
+            PathBuf::new(),
+        );
+        doc.content = html.to_string();
+        doc
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve("/guide/content", "/guide/config"), "/guide/config");
+        assert_eq!(resolve("/guide", "setup"), "/guide/setup");
+        assert_eq!(resolve("/guide/content", "../config"), "/guide/config");
+        assert_eq!(resolve("/guide", "./img/a.png"), "/guide/img/a.png");
+        assert_eq!(resolve("/", ".."), "/");
+        assert_eq!(
+            resolve("/guide/content.html", "config.html"),
+            "/guide/config.html"
+        );
+        assert_eq!(resolve("/guide/content/", "../config/"), "/guide/config");
+    }
+
+    #[test]
+    fn test_is_external() {
+        assert!(is_external("https://example.com"));
+        assert!(is_external("mailto:me@example.com"));
+        assert!(is_external("//cdn.example.com/x.js"));
+        assert!(!is_external("/guide"));
+        assert!(!is_external("../a:b"));
+        assert!(!is_external("#section"));
+    }
+
+    #[test]
+    fn test_attribute_values() {
+        let html = r#"<a href="/a?x=1&amp;y=2">A</a> <img data-href="no"> <a href='/b'>B</a>"#;
+        assert_eq!(attribute_values(html, "href"), vec!["/a?x=1&y=2", "/b"]);
+    }
+
+    #[test]
+    fn test_check_links() {
+        let mut page = doc(
+            "/guide",
+            r##"<a href="/guide/setup">ok</a>
+<a href="setup">ok</a>
+<a href="img/logo.png">ok</a>
+<a href="/guide/missing">broken</a>
+<a href="#install">ok</a>
+<a href="#fn1">ok</a>
+<a href="#nope">broken</a>
+<a href="https://example.com">external</a>
+<div id="fn1"></div>"##,
+        );
+        page.toc.push(TocEntry {
+            text: "Install".to_string(),
+            id: "install".to_string(),
+            level: 2,
+        });
+        let docs = vec![page, doc("/guide/setup", "<a href=\"/guide/\">ok</a>")];
+
+        let report = check_links(&docs, &["/guide/img/logo.png"]);
+        assert_eq!(report.checked, 8);
+
+        assert_eq!(
+            report.broken,
+            vec![
+                BrokenLink {
+                    source: "/guide".to_string(),
+                    href: "/guide/missing".to_string(),
+                },
+                BrokenLink {
+                    source: "/guide".to_string(),
+                    href: "#nope".to_string(),
+                },
+            ]
+        );
+
+        // With `url_style: trailing_slash` or `html`
+        let docs = vec![
+            doc(
+                "/guide/",
+                r#"<a href="setup/">ok</a> <a href="/guide">ok</a>"#,
+            ),
+            doc("/guide/setup/", ""),
+            doc("/api/index.html", r#"<a href="client.html">ok</a>"#),
+            doc(
+                "/api/client.html",
+                r#"<a href="index.html">ok</a> <a href="/api/">broken</a>"#,
+            ),
+        ];
+        let report = check_links(&docs, &[]);
+        let broken: Vec<&str> = report.broken.iter().map(|l| l.href.as_str()).collect();
+        assert_eq!(broken, ["/api/"]);
+    }
+}
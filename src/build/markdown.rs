@@ -1,10 +1,24 @@
 //! Markdown rendering with syntax highlighting and TOC extraction.
 
-use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd, html};
+mod admonition;
+mod emoji;
+mod fence;
+mod html_policy;
+mod inline_code;
+mod tabs;
 
+use std::borrow::Cow;
+
+use pulldown_cmark::{
+    CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd, TextMergeStream, html,
+};
+
+use self::fence::FenceInfo;
+use super::assets::{AssetRef, DocumentAssets};
 use super::highlight::SyntaxHighlighter;
 use super::render::TocEntry;
-use crate::config::MarkdownConfig;
+use crate::config::{AnchorPosition, HeadingsConfig, MarkdownConfig};
+use crate::util::{fnv1a, html_escape, title_case};
 
 #[derive(thiserror::Error, Debug)]
 pub enum MarkdownError {
@@ -16,58 +30,140 @@ pub enum MarkdownError {
 pub struct MarkdownOutput {
     pub html: String,
     pub toc: Vec<TocEntry>,
+    /// Whether the document contains math (so themes can load KaTeX)
+    pub has_math: bool,
+    /// Relative image paths that didn't match any static file
+    pub missing_images: Vec<String>,
 }
 
 /// Render markdown to HTML using pulldown-cmark with syntax highlighting.
+///
+/// When `assets` is given, relative image paths are resolved against the
+/// document's location and rewritten to the static file's output URL.
 pub fn render_markdown(
     markdown: &str,
     highlighter: &SyntaxHighlighter,
     markdown_config: &MarkdownConfig,
+    assets: Option<&DocumentAssets>,
 ) -> Result<MarkdownOutput, MarkdownError> {
     let mut options = Options::empty();
+    let mut admonitions = false;
+    let mut emoji = false;
+    let mut tabs = false;
+    let mut inline_highlight = false;
     for extension in &markdown_config.extensions {
         match extension.as_str() {
+            "admonitions" => {
+                // GFM alerts (`> [!NOTE]`) are only parsed with ENABLE_GFM
+                options.insert(Options::ENABLE_GFM);
+                admonitions = true;
+            }
             "definition_lists" => options.insert(Options::ENABLE_DEFINITION_LIST),
+            "emoji" => emoji = true,
             "footnotes" => options.insert(Options::ENABLE_FOOTNOTES),
             "gfm" => options.insert(Options::ENABLE_GFM),
             "heading_attributes" => options.insert(Options::ENABLE_HEADING_ATTRIBUTES),
+            "inline_highlight" => inline_highlight = true,
+            "math" => options.insert(Options::ENABLE_MATH),
             "strikethrough" => options.insert(Options::ENABLE_STRIKETHROUGH),
             "tables" => options.insert(Options::ENABLE_TABLES),
+            "tabs" => tabs = true,
             "tasklists" => options.insert(Options::ENABLE_TASKLISTS),
             other => return Err(MarkdownError::InvalidExtension(other.to_string())),
         }
     }
 
-    let parser = Parser::new_ext(markdown, options);
+    // Rewrite `!!!` admonition blocks and `:::` tab containers into markers
+    // before parsing
+    let mut markdown: Cow<str> = Cow::Borrowed(markdown);
+    if admonitions {
+        markdown = Cow::Owned(admonition::expand(&markdown));
+    }
+    if tabs {
+        markdown = Cow::Owned(tabs::expand(&markdown));
+    }
+
+    // Merge adjacent text events so shortcodes like `:white_check_mark:`
+    // aren't split at the underscores
+    let parser = TextMergeStream::new(Parser::new_ext(&markdown, options));
+
+    // Apply the raw HTML policy, leaving admonition and tab markers alone
+    let mut html_filter = html_policy::HtmlFilter::new(markdown_config.html_policy);
+    let parser = parser.filter_map(|event| match &event {
+        Event::Html(html)
+            if (admonitions && admonition::parse_marker(html).is_some())
+                || (tabs && tabs::parse_marker(html).is_some()) =>
+        {
+            Some(event)
+        }
+        _ => html_filter.apply(event),
+    });
+
+    let parser: Vec<Event> = if inline_highlight {
+        inline_code::mark_languages(parser)
+    } else {
+        parser.collect()
+    };
 
     // Process events, intercepting code blocks for syntax highlighting
     let mut in_code_block = false;
-    let mut code_language = String::new();
+    let mut code_fence = FenceInfo::default();
     let mut code_content = String::new();
 
     // Intercept headings to add id attributes for permalinks
     struct HeadingState {
-        level: pulldown_cmark::HeadingLevel,
+        level: HeadingLevel,
         classes: Vec<String>,
         attrs: Vec<(String, Option<String>)>,
     }
     let mut in_heading: Option<HeadingState> = None;
     let mut used_heading_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
+    // Plain text of the heading (for the TOC and slug) and its rendered HTML
     let mut heading_text = String::new();
+    let mut heading_html = String::new();
     let mut toc_entries: Vec<TocEntry> = Vec::new();
 
+    // Headings inside admonitions and tabs are not added to the table of contents
+    let mut container_depth = 0usize;
+    // Tab groups seen so far (for unique input ids), and the open groups
+    // with the number of tabs in each
+    let mut tab_groups = 0usize;
+    let mut open_tab_groups: Vec<(usize, usize)> = Vec::new();
+
+    let mut has_math = false;
+
+    // Intercept images to rewrite relative paths and add loading attributes
+    struct ImageState {
+        src: String,
+        title: String,
+    }
+    let mut in_image: Option<ImageState> = None;
+    let mut image_alt = String::new();
+    let mut missing_images: Vec<String> = Vec::new();
+
+    // Language for the next inline code span, from a `{lang}` hint
+    let mut inline_code_lang: Option<String> = None;
+
     let events: Vec<Event> = parser
+        .into_iter()
         .flat_map(|event| match event {
             Event::Start(Tag::Heading {
                 level,
-                ref id,
-                ref classes,
-                ref attrs,
+                id,
+                classes,
+                attrs,
             }) => {
+                let level = offset_heading_level(level, markdown_config.headings.offset);
+
                 // If heading already has an id, just pass it through
                 if let Some(existing_id) = id {
                     used_heading_ids.insert(existing_id.to_string());
-                    return vec![event];
+                    return vec![Event::Start(Tag::Heading {
+                        level,
+                        id: Some(existing_id),
+                        classes,
+                        attrs,
+                    })];
                 }
                 // Otherwise, capture the heading to generate an id
                 in_heading = Some(HeadingState {
@@ -79,13 +175,15 @@ pub fn render_markdown(
                         .collect(),
                 });
                 heading_text.clear();
+                heading_html.clear();
                 vec![]
             }
             Event::End(TagEnd::Heading(_)) if in_heading.is_some() => {
                 let state = in_heading.take().unwrap();
 
                 // Generate a unique id from the heading text
-                let base_id = slugify(&heading_text);
+                let base_id =
+                    slugify(&heading_text, markdown_config.headings.transliterate);
                 let mut id = base_id.clone();
                 let mut suffix = 1;
                 while used_heading_ids.contains(&id) {
@@ -95,11 +193,13 @@ pub fn render_markdown(
                 used_heading_ids.insert(id.clone());
 
                 // Add to table of contents
-                toc_entries.push(TocEntry {
-                    text: heading_text.clone(),
-                    id: id.clone(),
-                    level: state.level as u8,
-                });
+                if container_depth == 0 {
+                    toc_entries.push(TocEntry {
+                        text: heading_text.clone(),
+                        id: id.clone(),
+                        level: state.level as u8,
+                    });
+                }
 
                 // Build class attribute if there are classes
                 let class_attr = if state.classes.is_empty() {
@@ -118,30 +218,87 @@ pub fn render_markdown(
                     })
                     .collect::<String>();
 
-                // Emit the heading with id and permalink
-                let permalink = format!(
-                    "<a class=\"header-anchor\" href=\"#{}\" aria-label=\"Link to this heading\">#</a>",
-                    id
-                );
+                // Emit the heading with id and (optionally) a permalink
+                let level = state.level as usize;
+                let inner = match heading_anchor(&id, level as u8, &markdown_config.headings) {
+                    Some(anchor) => match markdown_config.headings.position {
+                        AnchorPosition::Before => format!("{} {}", anchor, heading_html),
+                        AnchorPosition::After => format!("{} {}", heading_html, anchor),
+                    },
+                    None => heading_html.clone(),
+                };
                 vec![Event::Html(
                     format!(
-                        "<h{} id=\"{}\"{}{}>{} {}</h{}>",
-                        state.level as usize,
-                        id,
-                        class_attr,
-                        extra_attrs,
-                        heading_text,
-                        permalink,
-                        state.level as usize,
+                        "<h{} id=\"{}\"{}{}>{}</h{}>",
+                        level, id, class_attr, extra_attrs, inner, level,
                     )
                     .into(),
                 )]
             }
+            Event::End(TagEnd::Heading(level)) => vec![Event::End(TagEnd::Heading(
+                offset_heading_level(level, markdown_config.headings.offset),
+            ))],
+            Event::Start(Tag::BlockQuote(Some(kind))) if admonitions => {
+                container_depth += 1;
+                let kind = admonition::blockquote_kind(kind);
+                vec![Event::Html(
+                    admonition::open_html(kind, &title_case(kind)).into(),
+                )]
+            }
+            Event::End(TagEnd::BlockQuote(Some(_))) if admonitions => {
+                container_depth = container_depth.saturating_sub(1);
+                vec![Event::Html(admonition::close_html().into())]
+            }
+            Event::Html(ref html) if admonitions || tabs => {
+                if admonitions && let Some(marker) = admonition::parse_marker(html) {
+                    let html = match marker {
+                        admonition::Marker::Open { kind, title } => {
+                            container_depth += 1;
+                            admonition::open_html(&kind, &title)
+                        }
+                        admonition::Marker::Close => {
+                            container_depth = container_depth.saturating_sub(1);
+                            admonition::close_html().to_string()
+                        }
+                    };
+                    vec![Event::Html(html.into())]
+                } else if tabs && let Some(marker) = tabs::parse_marker(html) {
+                    let html = match marker {
+                        tabs::Marker::OpenTabs => {
+                            container_depth += 1;
+                            tab_groups += 1;
+                            open_tab_groups.push((tab_groups, 0));
+                            tabs::open_tabs_html().to_string()
+                        }
+                        tabs::Marker::OpenTab(title) => {
+                            // A hand-written marker outside a group is left as is
+                            let Some((group, count)) = open_tab_groups.last_mut() else {
+                                return vec![event];
+                            };
+                            container_depth += 1;
+                            *count += 1;
+                            tabs::open_tab_html(*group, *count, &title)
+                        }
+                        tabs::Marker::CloseTab => {
+                            container_depth = container_depth.saturating_sub(1);
+                            tabs::close_tab_html().to_string()
+                        }
+                        tabs::Marker::CloseTabs => {
+                            container_depth = container_depth.saturating_sub(1);
+                            open_tab_groups.pop();
+                            tabs::close_tabs_html().to_string()
+                        }
+                    };
+                    vec![Event::Html(html.into())]
+                } else {
+                    vec![event]
+                }
+            }
             Event::Start(Tag::CodeBlock(kind)) => {
                 in_code_block = true;
-                code_language = match kind {
-                    CodeBlockKind::Fenced(lang) => lang.to_string(),
-                    CodeBlockKind::Indented => String::new(),
+                code_fence = match kind {
+                    CodeBlockKind::Fenced(info) => FenceInfo::parse(&info),
+                    CodeBlockKind::Indented => FenceInfo::default(),
                 };
                 code_content.clear();
                 vec![] // Don't emit the start tag yet
@@ -149,15 +306,131 @@ pub fn render_markdown(
             Event::End(TagEnd::CodeBlock) => {
                 in_code_block = false;
                 // Apply syntax highlighting and emit as raw HTML
-                let highlighted = highlighter.highlight(&code_content, &code_language);
-                vec![Event::Html(highlighted.into())]
+                let highlighted = highlighter.highlight(&code_content, &code_fence.language);
+                let html = match &code_fence.title {
+                    // Wrap titled blocks in a figure so themes can render a header
+                    Some(title) => format!(
+                        "<figure class=\"code-block\"><figcaption class=\"code-title\">{}</figcaption>{}</figure>",
+                        html_escape(title),
+                        highlighted
+                    ),
+                    None => highlighted,
+                };
+                vec![Event::Html(html.into())]
             }
             Event::Text(text) if in_code_block => {
                 code_content.push_str(&text);
                 vec![]
             }
+            Event::Start(Tag::Image {
+                dest_url, title, ..
+            }) => {
+                let src = match assets.map(|assets| assets.resolve(&dest_url)) {
+                    Some(AssetRef::Found(url)) => url,
+                    Some(AssetRef::Missing) => {
+                        missing_images.push(dest_url.to_string());
+                        dest_url.to_string()
+                    }
+                    Some(AssetRef::Unchanged) | None => dest_url.to_string(),
+                };
+                in_image = Some(ImageState {
+                    src,
+                    title: title.to_string(),
+                });
+                image_alt.clear();
+                vec![]
+            }
+            Event::End(TagEnd::Image) if in_image.is_some() => {
+                let state = in_image.take().unwrap();
+                let mut html = format!(
+                    "<img src=\"{}\" alt=\"{}\"",
+                    html_escape(&state.src),
+                    html_escape(&image_alt)
+                );
+                if !state.title.is_empty() {
+                    html.push_str(&format!(" title=\"{}\"", html_escape(&state.title)));
+                }
+                if markdown_config.images.lazy {
+                    html.push_str(" loading=\"lazy\" decoding=\"async\"");
+                }
+                html.push_str(" />");
+
+                if in_heading.is_some() {
+                    heading_html.push_str(&html);
+                    vec![]
+                } else {
+                    vec![Event::InlineHtml(html.into())]
+                }
+            }
+            // Alt text is plain text; formatting inside it is dropped
+            Event::Text(text) | Event::Code(text) if in_image.is_some() => {
+                inline_code_lang = None;
+                image_alt.push_str(&text);
+                vec![]
+            }
+            _ if in_image.is_some() => vec![],
             Event::Text(text) if in_heading.is_some() => {
+                let text = if emoji {
+                    emoji::expand(&text)
+                } else {
+                    text.to_string()
+                };
                 heading_text.push_str(&text);
+                heading_html.push_str(&html_escape(&text));
+                vec![]
+            }
+            Event::Text(text) if emoji => vec![Event::Text(emoji::expand(&text).into())],
+            Event::InlineMath(math) => {
+                has_math = true;
+                let html = format!("<span class=\"math-inline\">{}</span>", html_escape(&math));
+                if in_heading.is_some() {
+                    // The TOC and slug use the TeX source as plain text
+                    heading_text.push_str(&math);
+                    heading_html.push_str(&html);
+                    vec![]
+                } else {
+                    vec![Event::InlineHtml(html.into())]
+                }
+            }
+            Event::DisplayMath(math) => {
+                has_math = true;
+                let html = format!(
+                    "<span class=\"math-display\">{}</span>",
+                    html_escape(&math)
+                );
+                if in_heading.is_some() {
+                    heading_text.push_str(&math);
+                    heading_html.push_str(&html);
+                    vec![]
+                } else {
+                    vec![Event::InlineHtml(html.into())]
+                }
+            }
+            Event::InlineHtml(ref html)
+                if inline_highlight && inline_code::parse_marker(html).is_some() =>
+            {
+                inline_code_lang = inline_code::parse_marker(html).map(str::to_string);
+                vec![]
+            }
+            Event::Code(code) if in_heading.is_some() || inline_code_lang.is_some() => {
+                let html = match inline_code_lang.take() {
+                    Some(lang) => highlighter.highlight_inline(&code, &lang),
+                    None => format!("<code>{}</code>", html_escape(&code)),
+                };
+                if in_heading.is_some() {
+                    heading_text.push_str(&code);
+                    heading_html.push_str(&html);
+                    vec![]
+                } else {
+                    vec![Event::InlineHtml(html.into())]
+                }
+            }
+            _ if in_heading.is_some() => {
+                // Keep other inline markup (emphasis, links, ...) in the heading HTML
+                if matches!(event, Event::SoftBreak | Event::HardBreak) {
+                    heading_text.push(' ');
+                }
+                html::push_html(&mut heading_html, std::iter::once(event));
                 vec![]
             }
             _ => vec![event],
@@ -170,25 +443,246 @@ pub fn render_markdown(
     Ok(MarkdownOutput {
         html: html_output,
         toc: toc_entries,
+        has_math,
+        missing_images,
     })
 }
 
+/// Render a short piece of markdown, like a banner, to inline HTML: a single
+/// paragraph isn't wrapped in `<p>`.
+pub fn render_inline_markdown(
+    markdown: &str,
+    highlighter: &SyntaxHighlighter,
+    markdown_config: &MarkdownConfig,
+) -> Result<String, MarkdownError> {
+    let output = render_markdown(markdown, highlighter, markdown_config, None)?;
+    let html = output.html.trim_end();
+    let inner = html
+        .strip_prefix("<p>")
+        .and_then(|html| html.strip_suffix("</p>"))
+        .filter(|inner| !inner.contains("<p>"));
+    Ok(inner.unwrap_or(html).to_string())
+}
+
+/// Shift a heading level down by `offset`, capped at h6.
+fn offset_heading_level(level: HeadingLevel, offset: u8) -> HeadingLevel {
+    let shifted = (level as usize + offset as usize).min(6);
+    HeadingLevel::try_from(shifted).unwrap_or(HeadingLevel::H6)
+}
+
+/// Build the permalink anchor for a heading, if anchors apply to its level.
+fn heading_anchor(id: &str, level: u8, config: &HeadingsConfig) -> Option<String> {
+    if !config.anchors || !config.levels.contains(&level) {
+        return None;
+    }
+
+    let class = match &config.class {
+        Some(extra) => format!("header-anchor {}", extra),
+        None => "header-anchor".to_string(),
+    };
+    Some(format!(
+        "<a class=\"{}\" href=\"#{}\" aria-label=\"Link to this heading\">{}</a>",
+        class, id, config.symbol
+    ))
+}
+
 /// Convert a string to a slug suitable for use as an HTML id.
-fn slugify(s: &str) -> String {
-    s.to_lowercase()
+///
+/// Letters and digits from any script are kept, so "Überblick" becomes
+/// "überblick". With `transliterate`, the text is converted to ASCII first
+/// ("uberblick"). If nothing survives (e.g. a heading that is only
+/// punctuation or emoji), a stable id derived from a hash of the text is
+/// used instead of an empty string.
+fn slugify(s: &str, transliterate: bool) -> String {
+    let text: Cow<str> = if transliterate {
+        Cow::Owned(deunicode::deunicode(s))
+    } else {
+        Cow::Borrowed(s)
+    };
+
+    let slug = text
+        .to_lowercase()
         .replace(' ', "-")
-        .replace(|c: char| !c.is_alphanumeric() && c != '-', "")
+        .replace(|c: char| !c.is_alphanumeric() && c != '-', "");
+
+    if slug.is_empty() {
+        format!("heading-{:08x}", fnv1a(s.as_bytes()))
+    } else {
+        slug
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{HtmlPolicy, ImagesConfig};
 
     #[test]
     fn test_slugify() {
-        assert_eq!(slugify("Hello World"), "hello-world");
-        assert_eq!(slugify("What's New?"), "whats-new");
-        assert_eq!(slugify("API Reference"), "api-reference");
+        assert_eq!(slugify("Hello World", false), "hello-world");
+        assert_eq!(slugify("What's New?", false), "whats-new");
+        assert_eq!(slugify("API Reference", false), "api-reference");
+        // Existing ids keep every dash, so deep links don't break
+        assert_eq!(slugify("Install - Linux", false), "install---linux");
+        assert_eq!(slugify("A & B", false), "a--b");
+    }
+
+    #[test]
+    fn test_render_inline_markdown() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+        let inline = |markdown: &str| render_inline_markdown(markdown, &highlighter, &config);
+
+        assert_eq!(
+            inline("**v2** is out! [Read more](/blog/v2)\n").unwrap(),
+            "<strong>v2</strong> is out! <a href=\"/blog/v2\">Read more</a>"
+        );
+        assert_eq!(inline("One\n\nTwo").unwrap(), "<p>One</p>\n<p>Two</p>");
+    }
+
+    #[test]
+    fn test_heading_inline_formatting() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown(
+            "## Using `undox <build>` **fast** and *well*",
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert!(output.html.starts_with(
+            "<h2 id=\"using-undox-build-fast-and-well\">Using <code>undox &lt;build&gt;</code> <strong>fast</strong> and <em>well</em> <a"
+        ));
+        assert_eq!(output.toc[0].text, "Using undox <build> fast and well");
+        assert_eq!(output.toc[0].id, "using-undox-build-fast-and-well");
+    }
+
+    #[test]
+    fn test_heading_with_link() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output =
+            render_markdown("## See [the guide](/guide)", &highlighter, &config, None).unwrap();
+
+        assert!(output.html.contains(
+            "<h2 id=\"see-the-guide\">See <a href=\"/guide\">the guide</a> <a class=\"header-anchor\""
+        ));
+        assert_eq!(output.toc[0].text, "See the guide");
+    }
+
+    #[test]
+    fn test_inline_code_highlighting() {
+        let highlighter = SyntaxHighlighter::default();
+        let mut config = MarkdownConfig::default();
+        config.extensions.push("inline_highlight".to_string());
+
+        let output = render_markdown(
+            "Use `a < b`{unknown_lang_xyz} or `{x}`{unknown_lang_xyz}, not `plain` {rust}.",
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.html,
+            "<p>Use <code class=\"language-unknown_lang_xyz\">a &lt; b</code> or <code class=\"language-unknown_lang_xyz\">{x}</code>, not <code>plain</code> {rust}.</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_code_hint_needs_extension() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown("`x`{rust}", &highlighter, &config, None).unwrap();
+
+        assert_eq!(output.html, "<p><code>x</code>{rust}</p>\n");
+    }
+
+    #[test]
+    fn test_inline_code_highlighting_in_heading() {
+        let highlighter = SyntaxHighlighter::default();
+        let mut config = MarkdownConfig::default();
+        config.extensions.push("inline_highlight".to_string());
+
+        let output = render_markdown(
+            "## Run `a<b`{unknown_lang_xyz} first",
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert!(
+            output
+                .html
+                .contains("Run <code class=\"language-unknown_lang_xyz\">a&lt;b</code>")
+        );
+        assert_eq!(output.toc[0].text, "Run a<b first");
+    }
+
+    #[test]
+    fn test_heading_offset() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig {
+            headings: HeadingsConfig {
+                anchors: false,
+                offset: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let output = render_markdown(
+            "# Title\n\n## Section\n\n###### Deep\n\n## Custom {#custom}",
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert!(output.html.contains("<h2 id=\"title\">Title</h2>"));
+        assert!(output.html.contains("<h3 id=\"section\">Section</h3>"));
+        assert!(output.html.contains("<h6 id=\"deep\">Deep</h6>"));
+        assert!(output.html.contains("<h3 id=\"custom\">Custom</h3>"));
+        let levels: Vec<u8> = output.toc.iter().map(|e| e.level).collect();
+        assert_eq!(levels, vec![2, 3, 6]);
+    }
+
+    #[test]
+    fn test_slugify_unicode() {
+        assert_eq!(slugify("Überblick", false), "überblick");
+        assert_eq!(slugify("快速开始", false), "快速开始");
+        assert_eq!(slugify("Überblick", true), "uberblick");
+        // ASCII slugs are the same either way
+        assert_eq!(slugify("What's New?", true), "whats-new");
+    }
+
+    #[test]
+    fn test_slugify_fallback() {
+        let id = slugify("???", false);
+        assert!(id.starts_with("heading-"));
+        assert_eq!(id, slugify("???", false));
+        assert_ne!(id, slugify("!!!", false));
+    }
+
+    #[test]
+    fn test_heading_ids_unique_for_fallback() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output =
+            render_markdown("## ???\n\n## ???\n\n## ¿?", &highlighter, &config, None).unwrap();
+
+        let ids: Vec<&str> = output.toc.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids[1], format!("{}-1", ids[0]));
+        assert_ne!(ids[2], ids[0]);
+        assert!(ids.iter().all(|id| !id.is_empty()));
     }
 
     #[test]
@@ -196,7 +690,7 @@ mod tests {
         let highlighter = SyntaxHighlighter::default();
         let config = MarkdownConfig::default();
 
-        let output = render_markdown("# Hello\n\nWorld", &highlighter, &config).unwrap();
+        let output = render_markdown("# Hello\n\nWorld", &highlighter, &config, None).unwrap();
 
         assert!(output.html.contains("Hello"));
         assert!(output.html.contains("<p>World</p>"));
@@ -210,20 +704,513 @@ mod tests {
         let highlighter = SyntaxHighlighter::default();
         let config = MarkdownConfig::default();
 
-        let output = render_markdown("```rust\nlet x = 1;\n```", &highlighter, &config).unwrap();
+        let output =
+            render_markdown("```rust\nlet x = 1;\n```", &highlighter, &config, None).unwrap();
 
         assert!(output.html.contains("let"));
         assert!(output.html.contains("<pre"));
     }
 
+    #[test]
+    fn test_render_code_block_with_title() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown(
+            "```rust title=\"src/<main>.rs\"\nlet x = 1;\n```",
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert!(output.html.starts_with(
+            "<figure class=\"code-block\"><figcaption class=\"code-title\">src/&lt;main&gt;.rs</figcaption><pre"
+        ));
+        assert!(output.html.contains("</pre></figure>"));
+    }
+
+    #[test]
+    fn test_render_code_block_without_title_unchanged() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+        let code = "let x = 1;\n";
+
+        let output = render_markdown(
+            &format!("```rust\n{}```", code),
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(output.html, highlighter.highlight(code, "rust"));
+    }
+
+    #[test]
+    fn test_heading_anchor_default() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown("## Install", &highlighter, &config, None).unwrap();
+
+        assert_eq!(
+            output.html,
+            "<h2 id=\"install\">Install <a class=\"header-anchor\" href=\"#install\" aria-label=\"Link to this heading\">#</a></h2>"
+        );
+    }
+
+    #[test]
+    fn test_heading_anchor_configured() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig {
+            headings: HeadingsConfig {
+                symbol: "¶".to_string(),
+                class: Some("icon-link".to_string()),
+                position: AnchorPosition::Before,
+                levels: vec![2, 3],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let output = render_markdown("# Title\n\n## Install", &highlighter, &config, None).unwrap();
+
+        assert!(output.html.contains("<h1 id=\"title\">Title</h1>"));
+        assert!(output.html.contains(
+            "<h2 id=\"install\"><a class=\"header-anchor icon-link\" href=\"#install\" aria-label=\"Link to this heading\">¶</a> Install</h2>"
+        ));
+    }
+
+    #[test]
+    fn test_heading_anchors_disabled() {
+        let highlighter = SyntaxHighlighter::default();
+        let mut config = MarkdownConfig::default();
+        config.headings.anchors = false;
+
+        let output = render_markdown("## Install", &highlighter, &config, None).unwrap();
+
+        assert_eq!(output.html, "<h2 id=\"install\">Install</h2>");
+        assert_eq!(output.toc.len(), 1);
+    }
+
+    fn admonition_config() -> MarkdownConfig {
+        let mut config = MarkdownConfig::default();
+        config.extensions.push("admonitions".to_string());
+        config
+    }
+
+    #[test]
+    fn test_admonition_block() {
+        let highlighter = SyntaxHighlighter::default();
+        let markdown = "!!! warning \"Heads up\"\n    Some **bold** text.\n\n    ```\n    code here\n    ```\n\nAfter.";
+
+        let output = render_markdown(markdown, &highlighter, &admonition_config(), None).unwrap();
+
+        assert!(output.html.contains("<div class=\"admonition warning\">"));
+        assert!(
+            output
+                .html
+                .contains("<p class=\"admonition-title\">Heads up</p>")
+        );
+        assert!(output.html.contains("<strong>bold</strong>"));
+        assert!(output.html.contains("<pre"));
+        assert!(output.html.contains("code here"));
+        assert!(output.html.contains("</div>"));
+        assert!(output.html.contains("<p>After.</p>"));
+        assert!(!output.html.contains("undox:admonition"));
+    }
+
+    #[test]
+    fn test_admonition_gfm_alert() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let output = render_markdown(
+            "> [!TIP]\n> Use the cache.",
+            &highlighter,
+            &admonition_config(),
+            None,
+        )
+        .unwrap();
+
+        assert!(output.html.contains("<div class=\"admonition tip\">"));
+        assert!(
+            output
+                .html
+                .contains("<p class=\"admonition-title\">Tip</p>")
+        );
+        assert!(output.html.contains("Use the cache."));
+        assert!(!output.html.contains("<blockquote"));
+    }
+
+    #[test]
+    fn test_admonition_headings_excluded_from_toc() {
+        let highlighter = SyntaxHighlighter::default();
+        let markdown = "# Title\n\n!!! note\n    ## Inside\n\n## Outside";
+
+        let output = render_markdown(markdown, &highlighter, &admonition_config(), None).unwrap();
+
+        let toc: Vec<&str> = output.toc.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(toc, vec!["Title", "Outside"]);
+        // The heading still gets an id for direct linking
+        assert!(output.html.contains("id=\"inside\""));
+    }
+
+    #[test]
+    fn test_admonitions_disabled_by_default() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown("!!! note\n    Body", &highlighter, &config, None).unwrap();
+
+        assert!(!output.html.contains("admonition"));
+    }
+
+    fn emoji_config() -> MarkdownConfig {
+        let mut config = MarkdownConfig::default();
+        config.extensions.push("emoji".to_string());
+        config
+    }
+
+    #[test]
+    fn test_emoji_shortcodes() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let output = render_markdown(
+            "Ship it :rocket: :white_check_mark: :nope:",
+            &highlighter,
+            &emoji_config(),
+            None,
+        )
+        .unwrap();
+
+        assert!(output.html.contains("<p>Ship it 🚀 ✅ :nope:</p>"));
+    }
+
+    #[test]
+    fn test_emoji_not_expanded_in_code() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let output = render_markdown(
+            "Use `:rocket:` literally\n\n```\n:tada:\n```",
+            &highlighter,
+            &emoji_config(),
+            None,
+        )
+        .unwrap();
+
+        assert!(output.html.contains("<code>:rocket:</code>"));
+        assert!(output.html.contains(":tada:"));
+        assert!(!output.html.contains("🎉"));
+    }
+
+    #[test]
+    fn test_emoji_in_heading() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let output = render_markdown(
+            "## :sparkles: New Features",
+            &highlighter,
+            &emoji_config(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(output.toc[0].text, "✨ New Features");
+        assert_eq!(output.toc[0].id, "-new-features");
+        assert!(output.html.contains("✨ New Features"));
+    }
+
+    #[test]
+    fn test_emoji_disabled_by_default() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown(":rocket:", &highlighter, &config, None).unwrap();
+
+        assert!(output.html.contains(":rocket:"));
+    }
+
+    fn math_config() -> MarkdownConfig {
+        let mut config = MarkdownConfig::default();
+        config.extensions.push("math".to_string());
+        config
+    }
+
+    #[test]
+    fn test_math_inline_and_display() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let output = render_markdown(
+            "Euler: $e^{i\\pi} + 1 = 0$\n\n$$a < b$$",
+            &highlighter,
+            &math_config(),
+            None,
+        )
+        .unwrap();
+
+        assert!(output.has_math);
+        assert!(
+            output
+                .html
+                .contains("<span class=\"math-inline\">e^{i\\pi} + 1 = 0</span>")
+        );
+        assert!(
+            output
+                .html
+                .contains("<span class=\"math-display\">a &lt; b</span>")
+        );
+    }
+
+    #[test]
+    fn test_math_in_heading() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let output =
+            render_markdown("## Solving $x^2$ fast", &highlighter, &math_config(), None).unwrap();
+
+        assert_eq!(output.toc.len(), 1);
+        assert_eq!(output.toc[0].text, "Solving x^2 fast");
+        assert_eq!(output.toc[0].id, "solving-x2-fast");
+        assert!(
+            output
+                .html
+                .contains("<span class=\"math-inline\">x^2</span>")
+        );
+    }
+
+    #[test]
+    fn test_math_in_code_untouched() {
+        let highlighter = SyntaxHighlighter::default();
+
+        let output = render_markdown(
+            "Cost is `$5` today\n\n```\n$x$\n```",
+            &highlighter,
+            &math_config(),
+            None,
+        )
+        .unwrap();
+
+        assert!(!output.has_math);
+        assert!(!output.html.contains("math-inline"));
+        assert!(output.html.contains("<code>$5</code>"));
+    }
+
+    #[test]
+    fn test_math_disabled_by_default() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown("Price: $x$", &highlighter, &config, None).unwrap();
+
+        assert!(!output.has_math);
+        assert!(output.html.contains("$x$"));
+    }
+
+    fn tabs_config() -> MarkdownConfig {
+        let mut config = MarkdownConfig::default();
+        config.extensions.push("tabs".to_string());
+        config
+    }
+
+    #[test]
+    fn test_render_tabs() {
+        let highlighter = SyntaxHighlighter::default();
+        let markdown = "## Install\n\n::: tabs\n::: tab \"macOS\"\n### Homebrew\n\n```sh\nbrew install undox\n```\n:::\n::: tab \"Linux\"\nUse *apt*.\n:::\n:::\n\n::: tabs\n::: tab \"A\"\nA\n:::\n:::\n";
+        let output = render_markdown(markdown, &highlighter, &tabs_config(), None).unwrap();
+
+        assert!(output.html.contains("<div class=\"tabs\">"));
+        assert!(output.html.contains(
+            "<input type=\"radio\" class=\"tab-input\" name=\"tabs-1\" id=\"tabs-1-1\" checked>"
+        ));
+        assert!(
+            output
+                .html
+                .contains("<label class=\"tab-label\" for=\"tabs-1-2\">Linux</label>")
+        );
+        assert!(
+            output
+                .html
+                .contains("<div class=\"tab-panel\" data-tab=\"macOS\">")
+        );
+        assert!(output.html.contains("<em>apt</em>"));
+        // Code blocks inside tabs are still highlighted
+        assert!(output.html.contains("<pre"));
+        assert!(output.html.contains("brew install undox"));
+        // Each group on the page gets its own ids
+        assert!(output.html.contains("id=\"tabs-2-1\""));
+
+        // Headings inside tabs stay out of the TOC but keep their ids
+        assert!(output.html.contains("<h3 id=\"homebrew\""));
+        assert_eq!(output.toc.len(), 1);
+        assert_eq!(output.toc[0].id, "install");
+    }
+
+    #[test]
+    fn test_tabs_disabled_by_default() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+
+        let output = render_markdown(
+            "::: tabs\n::: tab \"A\"\nA\n:::\n:::",
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert!(!output.html.contains("class=\"tabs\""));
+        assert!(output.html.contains("::: tabs"));
+    }
+
+    #[test]
+    fn test_image_relative_src_rewritten() {
+        use std::path::{Path, PathBuf};
+
+        use crate::build::assets::StaticFileIndex;
+        use crate::build::document::StaticFile;
+
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig::default();
+        let mut index = StaticFileIndex::new();
+        index.insert(
+            Path::new("/src"),
+            &StaticFile::new(
+                "cli".to_string(),
+                PathBuf::from("guide/images/shot.png"),
+                "/cli/guide/images/shot.png".to_string(),
+            ),
+        );
+        let assets = index.for_document(Path::new("/src"), Path::new("guide/page.md"));
+
+        let output = render_markdown(
+            "![A *shot*](./images/shot.png \"Title\") ![gone](missing.png)",
+            &highlighter,
+            &config,
+            Some(&assets),
+        )
+        .unwrap();
+
+        assert!(
+            output.html.contains(
+                "<img src=\"/cli/guide/images/shot.png\" alt=\"A shot\" title=\"Title\" />"
+            )
+        );
+        assert!(
+            output
+                .html
+                .contains("<img src=\"missing.png\" alt=\"gone\" />")
+        );
+        assert_eq!(output.missing_images, vec!["missing.png"]);
+    }
+
+    #[test]
+    fn test_image_lazy_loading() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig {
+            images: ImagesConfig { lazy: true },
+            ..Default::default()
+        };
+
+        let output = render_markdown(
+            "![Logo](https://x.dev/logo.png)",
+            &highlighter,
+            &config,
+            None,
+        )
+        .unwrap();
+
+        assert!(output.html.contains(
+            "<img src=\"https://x.dev/logo.png\" alt=\"Logo\" loading=\"lazy\" decoding=\"async\" />"
+        ));
+        assert!(output.missing_images.is_empty());
+    }
+
+    const RAW_HTML: &str = "Hi <b onclick=\"x()\">there</b><script>alert(1)</script>\n\n\
+        <div class=\"box\" style=\"color: red\">\n<style>p {}</style>\n</div>\n\n\
+        `<i>inline</i>`\n\n```text\n<script>kept()</script>\n```\n";
+
+    fn render_with_policy(html_policy: HtmlPolicy) -> String {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig {
+            html_policy,
+            ..Default::default()
+        };
+        render_markdown(RAW_HTML, &highlighter, &config, None)
+            .unwrap()
+            .html
+    }
+
+    #[test]
+    fn test_html_policy_allow() {
+        let html = render_with_policy(HtmlPolicy::Allow);
+        assert!(html.contains("<b onclick=\"x()\">there</b><script>alert(1)</script>"));
+        assert!(html.contains("<div class=\"box\" style=\"color: red\">"));
+        assert!(html.contains("<code>&lt;i&gt;inline&lt;/i&gt;</code>"));
+        assert!(html.contains("&lt;script&gt;kept()&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn test_html_policy_escape() {
+        let html = render_with_policy(HtmlPolicy::Escape);
+        assert!(html.contains("Hi &lt;b onclick=\"x()\"&gt;there&lt;/b&gt;&lt;script&gt;"));
+        assert!(html.contains("&lt;div class=\"box\""));
+        assert!(!html.contains("<script>") && !html.contains("<div"));
+        assert!(html.contains("<code>&lt;i&gt;inline&lt;/i&gt;</code>"));
+        assert!(html.contains("&lt;script&gt;kept()&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn test_html_policy_sanitize() {
+        let html = render_with_policy(HtmlPolicy::Sanitize);
+        assert!(html.contains("<p>Hi <b>there</b></p>"));
+        assert!(html.contains("<div class=\"box\">\n\n</div>"));
+        assert!(!html.contains("alert") && !html.contains("<style"));
+        assert!(html.contains("<code>&lt;i&gt;inline&lt;/i&gt;</code>"));
+        assert!(html.contains("&lt;script&gt;kept()&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn test_html_policy_sanitize_markdown_links() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig {
+            html_policy: HtmlPolicy::Sanitize,
+            ..Default::default()
+        };
+        let markdown = "[x](javascript:alert(1)) <javascript:alert(2)> \
+            ![i](javascript:alert(3)) [ok](https://example.com/) <https://example.com/auto>";
+        let html = render_markdown(markdown, &highlighter, &config, None)
+            .unwrap()
+            .html;
+        assert!(!html.contains("=\"javascript"), "{}", html);
+        assert!(html.contains("<a href=\"\">x</a>"));
+        assert!(html.contains("<a href=\"\">javascript:alert(2)</a>"));
+        assert!(html.contains("<img src=\"\""));
+        assert!(html.contains("<a href=\"https://example.com/\">ok</a>"));
+        assert!(html.contains("<a href=\"https://example.com/auto\">"));
+    }
+
+    #[test]
+    fn test_html_policy_keeps_markers() {
+        let highlighter = SyntaxHighlighter::default();
+        let config = MarkdownConfig {
+            extensions: vec!["admonitions".to_string()],
+            html_policy: HtmlPolicy::Sanitize,
+            ..Default::default()
+        };
+        let output = render_markdown("!!! note\n    Body", &highlighter, &config, None).unwrap();
+        assert!(output.html.contains("<div class=\"admonition note\">"));
+    }
+
     #[test]
     fn test_invalid_extension() {
         let highlighter = SyntaxHighlighter::default();
         let config = MarkdownConfig {
             extensions: vec!["not_a_real_extension".to_string()],
+            ..Default::default()
         };
 
-        let result = render_markdown("# Test", &highlighter, &config);
+        let result = render_markdown("# Test", &highlighter, &config, None);
         assert!(result.is_err());
     }
 }
@@ -0,0 +1,212 @@
+//! Admonition (callout) blocks.
+//!
+//! Two syntaxes are supported when the `admonitions` extension is enabled:
+//!
+//! ```markdown
+//! !!! warning "Read this first"
+//!     Indented content, parsed as regular markdown.
+//!
+//! > [!NOTE]
+//! > GitHub-style alert.
+//! ```
+//!
+//! Both render as `<div class="admonition {kind}">` with a
+//! `<p class="admonition-title">` element. The `!!!` form is rewritten into
+//! marker comments before parsing; the render loop turns those markers (and
+//! GFM alert blockquotes) into the final HTML.
+
+use pulldown_cmark::BlockQuoteKind;
+
+use super::fence::FenceTracker;
+use crate::util::{html_escape, title_case};
+
+const OPEN_MARKER: &str = "<!-- undox:admonition ";
+const CLOSE_MARKER: &str = "<!-- undox:/admonition -->";
+
+/// A marker found in an HTML event.
+#[derive(Debug, PartialEq)]
+pub enum Marker {
+    /// Start of an admonition with its kind and (already escaped) title
+    Open { kind: String, title: String },
+    /// End of the innermost open admonition
+    Close,
+}
+
+/// Rewrite `!!! kind "Title"` blocks into marker comments.
+///
+/// The indented body is dedented and processed recursively, so nested
+/// admonitions work. Lines inside fenced code blocks are never touched.
+pub fn expand(markdown: &str) -> String {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out = String::with_capacity(markdown.len());
+    let mut fence = FenceTracker::default();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if !fence.in_code(line)
+            && let Some((kind, title)) = parse_opener(line)
+        {
+            i += 1;
+            let mut body: Vec<&str> = Vec::new();
+            while i < lines.len() {
+                let body_line = lines[i];
+                if body_line.trim().is_empty() {
+                    body.push("");
+                } else if let Some(rest) = dedent(body_line) {
+                    body.push(rest);
+                } else {
+                    break;
+                }
+                i += 1;
+            }
+
+            out.push('\n');
+            out.push_str(&format!("{}{} \"{}\" -->\n\n", OPEN_MARKER, kind, title));
+            out.push_str(&expand(&body.join("\n")));
+            out.push_str(&format!("\n\n{}\n\n", CLOSE_MARKER));
+            continue;
+        }
+
+        out.push_str(line);
+        out.push('\n');
+        i += 1;
+    }
+
+    out
+}
+
+/// Parse an admonition marker out of an HTML event, if it is one.
+///
+/// Markers can also be written by hand, so anything that [`expand`] could
+/// not have produced is rejected rather than copied into the output.
+pub fn parse_marker(html: &str) -> Option<Marker> {
+    let html = html.trim();
+    if html == CLOSE_MARKER {
+        return Some(Marker::Close);
+    }
+
+    let rest = html.strip_prefix(OPEN_MARKER)?.strip_suffix("-->")?.trim();
+    let (kind, title) = rest.split_once(' ')?;
+    let title = title.trim().strip_prefix('"')?.strip_suffix('"')?;
+    if !valid_kind(kind) || title.contains(['<', '>', '"']) {
+        return None;
+    }
+    Some(Marker::Open {
+        kind: kind.to_string(),
+        title: title.to_string(),
+    })
+}
+
+/// Opening HTML for an admonition. `title` must already be escaped.
+pub fn open_html(kind: &str, title: &str) -> String {
+    format!(
+        "<div class=\"admonition {}\">\n<p class=\"admonition-title\">{}</p>\n",
+        kind, title
+    )
+}
+
+/// Closing HTML for an admonition.
+pub fn close_html() -> &'static str {
+    "</div>\n"
+}
+
+/// The admonition kind for a GFM alert blockquote.
+pub fn blockquote_kind(kind: BlockQuoteKind) -> &'static str {
+    match kind {
+        BlockQuoteKind::Note => "note",
+        BlockQuoteKind::Tip => "tip",
+        BlockQuoteKind::Important => "important",
+        BlockQuoteKind::Warning => "warning",
+        BlockQuoteKind::Caution => "caution",
+    }
+}
+
+/// Parse a `!!! kind "Optional title"` line.
+///
+/// Returns the kind and the escaped title (defaulting to the title-cased kind).
+fn parse_opener(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix("!!!")?;
+    if !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let rest = rest.trim();
+    let (kind, title) = match rest.split_once(char::is_whitespace) {
+        Some((kind, title)) => (kind, Some(title.trim())),
+        None => (rest, None),
+    };
+
+    if !valid_kind(kind) {
+        return None;
+    }
+    let kind = kind.to_ascii_lowercase();
+
+    let title = match title {
+        Some(title) => title.strip_prefix('"')?.strip_suffix('"')?.to_string(),
+        None => title_case(&kind),
+    };
+
+    Some((kind, html_escape(&title)))
+}
+
+fn valid_kind(kind: &str) -> bool {
+    !kind.is_empty()
+        && kind
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Strip one level of indentation (four spaces or a tab).
+fn dedent(line: &str) -> Option<&str> {
+    line.strip_prefix("    ")
+        .or_else(|| line.strip_prefix('\t'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_with_title() {
+        let expanded = expand("!!! warning \"Careful\"\n    Body text\n\nAfter");
+        assert!(expanded.contains("<!-- undox:admonition warning \"Careful\" -->"));
+        assert!(expanded.contains("\nBody text\n"));
+        assert!(expanded.contains(CLOSE_MARKER));
+        assert!(expanded.trim_end().ends_with("After"));
+    }
+
+    #[test]
+    fn test_expand_default_title() {
+        let expanded = expand("!!! tip\n    Body");
+        assert!(expanded.contains("<!-- undox:admonition tip \"Tip\" -->"));
+    }
+
+    #[test]
+    fn test_expand_ignores_fenced_code() {
+        let markdown = "```\n!!! note\n    not an admonition\n```\n";
+        assert_eq!(expand(markdown), markdown);
+    }
+
+    #[test]
+    fn test_parse_marker_round_trip() {
+        let expanded = expand("!!! danger \"<Danger>\"\n    Body");
+        let open_line = expanded
+            .lines()
+            .find(|l| l.starts_with(OPEN_MARKER))
+            .unwrap();
+        assert_eq!(
+            parse_marker(open_line),
+            Some(Marker::Open {
+                kind: "danger".to_string(),
+                title: "&lt;Danger&gt;".to_string(),
+            })
+        );
+        assert_eq!(parse_marker(CLOSE_MARKER), Some(Marker::Close));
+        assert_eq!(parse_marker("<div>"), None);
+        assert_eq!(
+            parse_marker("<!-- undox:admonition x\"onclick=\"alert(1) \"T\" -->"),
+            None
+        );
+    }
+}
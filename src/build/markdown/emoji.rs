@@ -0,0 +1,410 @@
+//! Emoji shortcode expansion (`:rocket:` -> 🚀).
+//!
+//! Uses a bundled subset of the gemoji names used by GitHub. Unknown
+//! shortcodes are left untouched.
+
+/// Replace every known `:shortcode:` in `text` with its emoji.
+pub fn expand(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(':') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let replaced = after.find(':').and_then(|end| {
+            let name = &after[..end];
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_+-".contains(c));
+            valid
+                .then(|| lookup(name))
+                .flatten()
+                .map(|emoji| (emoji, end))
+        });
+
+        match replaced {
+            Some((emoji, end)) => {
+                out.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            None => {
+                // Not a shortcode; the next colon may still start one
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Look up an emoji by its gemoji name.
+fn lookup(name: &str) -> Option<&'static str> {
+    let emoji = match name {
+        // Smileys
+        "smile" => "😄",
+        "smiley" => "😃",
+        "grin" => "😁",
+        "grinning" => "😀",
+        "laughing" | "satisfied" => "😆",
+        "joy" => "😂",
+        "sweat_smile" => "😅",
+        "wink" => "😉",
+        "blush" => "😊",
+        "innocent" => "😇",
+        "heart_eyes" => "😍",
+        "sunglasses" => "😎",
+        "thinking" => "🤔",
+        "neutral_face" => "😐",
+        "expressionless" => "😑",
+        "unamused" => "😒",
+        "roll_eyes" => "🙄",
+        "grimacing" => "😬",
+        "relieved" => "😌",
+        "pensive" => "😔",
+        "confused" => "😕",
+        "slightly_smiling_face" => "🙂",
+        "upside_down_face" => "🙃",
+        "hushed" => "😯",
+        "open_mouth" => "😮",
+        "astonished" => "😲",
+        "flushed" => "😳",
+        "scream" => "😱",
+        "cry" => "😢",
+        "sob" => "😭",
+        "angry" => "😠",
+        "rage" => "😡",
+        "sleeping" => "😴",
+        "mask" => "😷",
+        "nerd_face" => "🤓",
+        "partying_face" => "🥳",
+        "exploding_head" => "🤯",
+        "skull" => "💀",
+        "ghost" => "👻",
+        "robot" => "🤖",
+        "poop" | "hankey" => "💩",
+        // Gestures
+        "+1" | "thumbsup" => "👍",
+        "-1" | "thumbsdown" => "👎",
+        "ok_hand" => "👌",
+        "wave" => "👋",
+        "clap" => "👏",
+        "raised_hands" => "🙌",
+        "pray" => "🙏",
+        "muscle" => "💪",
+        "point_right" => "👉",
+        "point_left" => "👈",
+        "point_up" => "☝️",
+        "point_down" => "👇",
+        "v" => "✌️",
+        "handshake" => "🤝",
+        "eyes" => "👀",
+        "brain" => "🧠",
+        // Hearts and symbols
+        "heart" => "❤️",
+        "broken_heart" => "💔",
+        "sparkling_heart" => "💖",
+        "yellow_heart" => "💛",
+        "green_heart" => "💚",
+        "blue_heart" => "💙",
+        "purple_heart" => "💜",
+        "100" => "💯",
+        "boom" | "collision" => "💥",
+        "sparkles" => "✨",
+        "star" => "⭐",
+        "star2" => "🌟",
+        "dizzy" => "💫",
+        "zap" => "⚡",
+        "fire" => "🔥",
+        "snowflake" => "❄️",
+        "sunny" => "☀️",
+        "cloud" => "☁️",
+        "rainbow" => "🌈",
+        "umbrella" => "☔",
+        "droplet" => "💧",
+        "ocean" => "🌊",
+        "earth_americas" => "🌎",
+        "globe_with_meridians" => "🌐",
+        "crescent_moon" => "🌙",
+        // Status
+        "white_check_mark" => "✅",
+        "heavy_check_mark" => "✔️",
+        "ballot_box_with_check" => "☑️",
+        "x" => "❌",
+        "negative_squared_cross_mark" => "❎",
+        "heavy_multiplication_x" => "✖️",
+        "warning" => "⚠️",
+        "no_entry" => "⛔",
+        "no_entry_sign" => "🚫",
+        "stop_sign" => "🛑",
+        "construction" => "🚧",
+        "question" => "❓",
+        "grey_question" => "❔",
+        "exclamation" | "heavy_exclamation_mark" => "❗",
+        "grey_exclamation" => "❕",
+        "bangbang" => "‼️",
+        "information_source" => "ℹ️",
+        "red_circle" => "🔴",
+        "orange_circle" => "🟠",
+        "yellow_circle" => "🟡",
+        "green_circle" => "🟢",
+        "large_blue_circle" => "🔵",
+        "white_circle" => "⚪",
+        "black_circle" => "⚫",
+        "arrow_right" => "➡️",
+        "arrow_left" => "⬅️",
+        "arrow_up" => "⬆️",
+        "arrow_down" => "⬇️",
+        "arrows_counterclockwise" => "🔄",
+        "new" => "🆕",
+        "free" => "🆓",
+        "up" => "🆙",
+        "cool" => "🆒",
+        "ok" => "🆗",
+        "sos" => "🆘",
+        "copyright" => "©️",
+        "registered" => "®️",
+        "tm" => "™️",
+        "hash" => "#️⃣",
+        // Objects
+        "rocket" => "🚀",
+        "tada" => "🎉",
+        "confetti_ball" => "🎊",
+        "balloon" => "🎈",
+        "gift" => "🎁",
+        "trophy" => "🏆",
+        "medal_sports" => "🏅",
+        "crown" => "👑",
+        "gem" => "💎",
+        "bulb" => "💡",
+        "flashlight" => "🔦",
+        "wrench" => "🔧",
+        "hammer" => "🔨",
+        "hammer_and_wrench" => "🛠️",
+        "gear" => "⚙️",
+        "nut_and_bolt" => "🔩",
+        "link" => "🔗",
+        "paperclip" => "📎",
+        "pushpin" => "📌",
+        "round_pushpin" => "📍",
+        "scissors" => "✂️",
+        "lock" => "🔒",
+        "unlock" => "🔓",
+        "key" => "🔑",
+        "bell" => "🔔",
+        "no_bell" => "🔕",
+        "mag" => "🔍",
+        "mag_right" => "🔎",
+        "book" | "open_book" => "📖",
+        "books" => "📚",
+        "bookmark" => "🔖",
+        "memo" | "pencil" => "📝",
+        "pencil2" => "✏️",
+        "page_facing_up" => "📄",
+        "clipboard" => "📋",
+        "calendar" => "📆",
+        "date" => "📅",
+        "chart_with_upwards_trend" => "📈",
+        "chart_with_downwards_trend" => "📉",
+        "bar_chart" => "📊",
+        "file_folder" => "📁",
+        "open_file_folder" => "📂",
+        "package" => "📦",
+        "inbox_tray" => "📥",
+        "outbox_tray" => "📤",
+        "email" | "envelope" => "✉️",
+        "mailbox" => "📫",
+        "phone" | "telephone" => "☎️",
+        "iphone" => "📱",
+        "computer" => "💻",
+        "desktop_computer" => "🖥️",
+        "keyboard" => "⌨️",
+        "printer" => "🖨️",
+        "floppy_disk" => "💾",
+        "cd" => "💿",
+        "camera" => "📷",
+        "movie_camera" => "🎥",
+        "tv" => "📺",
+        "battery" => "🔋",
+        "electric_plug" => "🔌",
+        "hourglass" => "⌛",
+        "hourglass_flowing_sand" => "⏳",
+        "watch" => "⌚",
+        "alarm_clock" => "⏰",
+        "stopwatch" => "⏱️",
+        "timer_clock" => "⏲️",
+        "money_with_wings" => "💸",
+        "moneybag" => "💰",
+        "dollar" => "💵",
+        "credit_card" => "💳",
+        "shield" => "🛡️",
+        "dart" => "🎯",
+        "game_die" => "🎲",
+        "art" => "🎨",
+        "musical_note" => "🎵",
+        "microphone" => "🎤",
+        "headphones" => "🎧",
+        "loudspeaker" => "📢",
+        "mega" => "📣",
+        "speech_balloon" => "💬",
+        "thought_balloon" => "💭",
+        "label" => "🏷️",
+        "triangular_flag_on_post" => "🚩",
+        "checkered_flag" => "🏁",
+        "white_flag" => "🏳️",
+        "test_tube" => "🧪",
+        "microscope" => "🔬",
+        "telescope" => "🔭",
+        "satellite" => "📡",
+        "magnet" => "🧲",
+        "broom" => "🧹",
+        "recycle" => "♻️",
+        "wastebasket" => "🗑️",
+        "bomb" => "💣",
+        "pill" => "💊",
+        "syringe" => "💉",
+        "dna" => "🧬",
+        "compass" => "🧭",
+        "world_map" => "🗺️",
+        "house" => "🏠",
+        "office" => "🏢",
+        "factory" => "🏭",
+        "bridge_at_night" => "🌉",
+        "construction_worker" => "👷",
+        // Transport
+        "car" | "red_car" => "🚗",
+        "bus" => "🚌",
+        "train" => "🚋",
+        "airplane" => "✈️",
+        "ship" => "🚢",
+        "bike" => "🚲",
+        "motorcycle" => "🏍️",
+        "fuelpump" => "⛽",
+        "rotating_light" => "🚨",
+        "traffic_light" => "🚥",
+        // Nature
+        "seedling" => "🌱",
+        "evergreen_tree" => "🌲",
+        "deciduous_tree" => "🌳",
+        "palm_tree" => "🌴",
+        "cactus" => "🌵",
+        "herb" => "🌿",
+        "four_leaf_clover" => "🍀",
+        "maple_leaf" => "🍁",
+        "fallen_leaf" => "🍂",
+        "mushroom" => "🍄",
+        "sunflower" => "🌻",
+        "rose" => "🌹",
+        "tulip" => "🌷",
+        "cherry_blossom" => "🌸",
+        "bug" => "🐛",
+        "ant" => "🐜",
+        "bee" | "honeybee" => "🐝",
+        "beetle" => "🐞",
+        "butterfly" => "🦋",
+        "snail" => "🐌",
+        "snake" => "🐍",
+        "turtle" => "🐢",
+        "crab" => "🦀",
+        "octopus" => "🐙",
+        "whale" => "🐳",
+        "dolphin" => "🐬",
+        "fish" => "🐟",
+        "penguin" => "🐧",
+        "bird" => "🐦",
+        "owl" => "🦉",
+        "eagle" => "🦅",
+        "duck" => "🦆",
+        "chicken" => "🐔",
+        "dog" => "🐶",
+        "cat" => "🐱",
+        "mouse" => "🐭",
+        "rabbit" => "🐰",
+        "fox_face" => "🦊",
+        "bear" => "🐻",
+        "panda_face" => "🐼",
+        "koala" => "🐨",
+        "tiger" => "🐯",
+        "lion" => "🦁",
+        "cow" => "🐮",
+        "pig" => "🐷",
+        "frog" => "🐸",
+        "monkey" => "🐒",
+        "see_no_evil" => "🙈",
+        "unicorn" => "🦄",
+        "dragon" => "🐉",
+        "sauropod" => "🦕",
+        "t-rex" => "🦖",
+        "elephant" => "🐘",
+        "gopher" => "🐿️",
+        // Food
+        "apple" => "🍎",
+        "green_apple" => "🍏",
+        "banana" => "🍌",
+        "lemon" => "🍋",
+        "cherries" => "🍒",
+        "strawberry" => "🍓",
+        "grapes" => "🍇",
+        "watermelon" => "🍉",
+        "peach" => "🍑",
+        "avocado" => "🥑",
+        "tomato" => "🍅",
+        "hot_pepper" => "🌶️",
+        "corn" => "🌽",
+        "carrot" => "🥕",
+        "bread" => "🍞",
+        "cheese" => "🧀",
+        "egg" => "🥚",
+        "pizza" => "🍕",
+        "hamburger" => "🍔",
+        "fries" => "🍟",
+        "taco" => "🌮",
+        "sushi" => "🍣",
+        "ramen" => "🍜",
+        "cookie" => "🍪",
+        "cake" => "🍰",
+        "birthday" => "🎂",
+        "doughnut" => "🍩",
+        "candy" => "🍬",
+        "lollipop" => "🍭",
+        "coffee" => "☕",
+        "tea" => "🍵",
+        "beer" => "🍺",
+        "beers" => "🍻",
+        "wine_glass" => "🍷",
+        "cocktail" => "🍸",
+        "champagne" => "🍾",
+        "popcorn" => "🍿",
+        _ => return None,
+    };
+    Some(emoji)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_known() {
+        assert_eq!(expand("Launch :rocket: now"), "Launch 🚀 now");
+        assert_eq!(expand(":+1::tada:"), "👍🎉");
+        assert_eq!(expand(":white_check_mark: done"), "✅ done");
+    }
+
+    #[test]
+    fn test_expand_unknown_untouched() {
+        assert_eq!(expand("a :not_an_emoji: b"), "a :not_an_emoji: b");
+        assert_eq!(expand("time 10:30:00"), "time 10:30:00");
+        assert_eq!(expand("key: value"), "key: value");
+    }
+
+    #[test]
+    fn test_expand_adjacent_colons() {
+        // The closing colon of a non-match can open the next shortcode
+        assert_eq!(expand("ratio 1:2 :fire:"), "ratio 1:2 🔥");
+        assert_eq!(expand("::fire:"), ":🔥");
+    }
+}
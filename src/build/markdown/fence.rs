@@ -0,0 +1,146 @@
+//! Fenced code block info string parsing.
+//!
+//! The info string is the text after the opening fence, e.g.
+//! `` ```rust title="src/main.rs" ``. The first word is the language;
+//! the rest is a list of `key="value"` attributes.
+
+/// Parsed info string of a fenced code block.
+#[derive(Debug, Default, PartialEq)]
+pub struct FenceInfo {
+    /// The language (first word of the info string), or empty
+    pub language: String,
+    /// Optional title/filename shown above the block
+    pub title: Option<String>,
+}
+
+impl FenceInfo {
+    /// Parse an info string.
+    ///
+    /// Unknown attributes are ignored. Values may be double-quoted,
+    /// single-quoted, or bare words.
+    pub fn parse(info: &str) -> Self {
+        let info = info.trim();
+        let (language, rest) = match info.split_once(char::is_whitespace) {
+            Some((language, rest)) => (language, rest),
+            None => (info, ""),
+        };
+
+        let mut fence = FenceInfo {
+            language: language.to_string(),
+            title: None,
+        };
+
+        for (key, value) in parse_attributes(rest) {
+            if key == "title" {
+                fence.title = Some(value);
+            }
+        }
+
+        fence
+    }
+}
+
+/// Tracks whether a line-by-line scan is inside a fenced code block.
+///
+/// Used by the line-based preprocessors to leave code blocks untouched.
+#[derive(Debug, Default)]
+pub struct FenceTracker {
+    open: Option<(char, usize)>,
+}
+
+impl FenceTracker {
+    /// Feed the next line. Returns true if the line is a fence or is inside
+    /// a fenced code block.
+    pub fn in_code(&mut self, line: &str) -> bool {
+        match (fence_marker(line), self.open) {
+            (Some(fence), None) => {
+                self.open = Some(fence);
+                true
+            }
+            (Some((ch, len)), Some((open_ch, open_len))) if ch == open_ch && len >= open_len => {
+                self.open = None;
+                true
+            }
+            (_, open) => open.is_some(),
+        }
+    }
+}
+
+/// Detect a code fence line, returning the fence character and length.
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let ch = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then_some((ch, len))
+}
+
+/// Parse `key="value" key2='value' key3=value` pairs.
+fn parse_attributes(s: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = s.trim_start();
+
+    while let Some((key, after_key)) = rest.split_once('=') {
+        let key = key.trim();
+        let after_key = after_key.trim_start();
+
+        let (value, remaining) = match after_key.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let inner = &after_key[1..];
+                match inner.find(quote) {
+                    Some(end) => (&inner[..end], &inner[end + 1..]),
+                    None => (inner, ""),
+                }
+            }
+            _ => match after_key.find(char::is_whitespace) {
+                Some(end) => (&after_key[..end], &after_key[end..]),
+                None => (after_key, ""),
+            },
+        };
+
+        if !key.is_empty() && !key.contains(char::is_whitespace) {
+            attrs.push((key.to_string(), value.to_string()));
+        }
+        rest = remaining.trim_start();
+    }
+
+    attrs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_language_only() {
+        assert_eq!(
+            FenceInfo::parse("rust"),
+            FenceInfo {
+                language: "rust".to_string(),
+                title: None,
+            }
+        );
+        assert_eq!(FenceInfo::parse(""), FenceInfo::default());
+    }
+
+    #[test]
+    fn test_parse_title() {
+        let info = FenceInfo::parse("rust title=\"src/main.rs\"");
+        assert_eq!(info.language, "rust");
+        assert_eq!(info.title.as_deref(), Some("src/main.rs"));
+
+        let info = FenceInfo::parse("toml title='Cargo.toml'");
+        assert_eq!(info.title.as_deref(), Some("Cargo.toml"));
+
+        let info = FenceInfo::parse("sh title=install.sh other=1");
+        assert_eq!(info.title.as_deref(), Some("install.sh"));
+    }
+
+    #[test]
+    fn test_parse_title_with_spaces() {
+        let info = FenceInfo::parse("yaml title=\"My config file\"");
+        assert_eq!(info.title.as_deref(), Some("My config file"));
+    }
+}
@@ -0,0 +1,500 @@
+//! Handling of raw HTML written in markdown.
+//!
+//! Depending on `markdown.html_policy`, raw HTML events are passed through,
+//! turned into text (so the markup shows up literally), or sanitized. The
+//! sanitizer keeps an allowlist of tags and attributes, drops comments and
+//! unknown tags (but keeps their text), and drops elements such as `<script>`
+//! and `<style>` together with their content.
+//!
+//! The sanitizer also checks the URLs of markdown links and images, which
+//! aren't raw HTML but can carry a `javascript:` URL just the same.
+//!
+//! Code blocks and inline code are separate events, so their contents are
+//! never affected.
+
+use pulldown_cmark::{CowStr, Event, Tag};
+
+use crate::config::HtmlPolicy;
+
+/// Tags kept by the sanitizer.
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+    "wbr",
+];
+
+/// Tags dropped together with everything up to their closing tag.
+const DROPPED_ELEMENTS: &[&str] = &[
+    "iframe", "noembed", "noframes", "noscript", "object", "script", "style", "template",
+    "textarea", "title", "xmp",
+];
+
+/// Attributes kept on allowed tags (besides `aria-*` and `data-*`).
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "align", "alt", "cite", "class", "colspan", "datetime", "dir", "height", "href", "id", "lang",
+    "loading", "name", "open", "rel", "rowspan", "span", "src", "start", "target", "title",
+    "width",
+];
+
+/// Attributes holding URLs, which must not use a script scheme.
+const URL_ATTRIBUTES: &[&str] = &["cite", "href", "src"];
+
+/// URL schemes allowed in URL attributes.
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto", "tel"];
+
+/// Applies an [`HtmlPolicy`] to a stream of markdown events.
+pub struct HtmlFilter {
+    policy: HtmlPolicy,
+    skipping: Option<Skip>,
+}
+
+/// Content being dropped by the sanitizer, which may span several events.
+enum Skip {
+    Comment,
+    Element(String),
+}
+
+/// A parsed HTML tag.
+struct HtmlTag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    attrs: Vec<(String, Option<String>)>,
+}
+
+impl HtmlFilter {
+    pub fn new(policy: HtmlPolicy) -> Self {
+        Self {
+            policy,
+            skipping: None,
+        }
+    }
+
+    /// Apply the policy to an event, returning `None` if it is dropped.
+    pub fn apply<'a>(&mut self, event: Event<'a>) -> Option<Event<'a>> {
+        match (self.policy, event) {
+            (HtmlPolicy::Allow, event) => Some(event),
+            (HtmlPolicy::Escape, Event::Html(html) | Event::InlineHtml(html)) => {
+                Some(Event::Text(html))
+            }
+            (HtmlPolicy::Escape, event) => Some(event),
+            (HtmlPolicy::Sanitize, Event::Html(html)) => {
+                Some(Event::Html(self.clean(&html).into()))
+            }
+            (HtmlPolicy::Sanitize, Event::InlineHtml(html)) => {
+                Some(Event::InlineHtml(self.clean(&html).into()))
+            }
+            // Text between an inline `<script>` and `</script>`
+            (HtmlPolicy::Sanitize, Event::Text(_)) if self.skipping.is_some() => None,
+            (
+                HtmlPolicy::Sanitize,
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }),
+            ) => {
+                self.skipping = None;
+                Some(Event::Start(Tag::Link {
+                    link_type,
+                    dest_url: safe_url(dest_url),
+                    title,
+                    id,
+                }))
+            }
+            (
+                HtmlPolicy::Sanitize,
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }),
+            ) => {
+                self.skipping = None;
+                Some(Event::Start(Tag::Image {
+                    link_type,
+                    dest_url: safe_url(dest_url),
+                    title,
+                    id,
+                }))
+            }
+            (HtmlPolicy::Sanitize, event) => {
+                // Anything else ends the run of HTML, so an unclosed element
+                // can't swallow the rest of the document
+                self.skipping = None;
+                Some(event)
+            }
+        }
+    }
+
+    /// Sanitize a fragment of HTML.
+    fn clean(&mut self, html: &str) -> String {
+        let mut out = String::with_capacity(html.len());
+        let mut rest = html;
+
+        while !rest.is_empty() {
+            match &self.skipping {
+                Some(Skip::Comment) => match rest.find("-->") {
+                    Some(end) => {
+                        rest = &rest[end + 3..];
+                        self.skipping = None;
+                    }
+                    None => break,
+                },
+                Some(Skip::Element(name)) => match find_closing_tag(rest, name) {
+                    Some(end) => {
+                        rest = &rest[end..];
+                        self.skipping = None;
+                    }
+                    None => break,
+                },
+                None => {
+                    let Some(start) = rest.find('<') else {
+                        out.push_str(rest);
+                        break;
+                    };
+                    out.push_str(&rest[..start]);
+                    rest = &rest[start..];
+
+                    if let Some(comment) = rest.strip_prefix("<!--") {
+                        rest = comment;
+                        self.skipping = Some(Skip::Comment);
+                    } else if let Some((tag, len)) = parse_tag(rest) {
+                        self.push_tag(&mut out, tag);
+                        rest = &rest[len..];
+                    } else {
+                        out.push_str("&lt;");
+                        rest = &rest[1..];
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    fn push_tag(&mut self, out: &mut String, tag: HtmlTag) {
+        if DROPPED_ELEMENTS.contains(&tag.name.as_str()) {
+            if !tag.closing && !tag.self_closing {
+                self.skipping = Some(Skip::Element(tag.name));
+            }
+            return;
+        }
+        if !ALLOWED_TAGS.contains(&tag.name.as_str()) {
+            return;
+        }
+
+        if tag.closing {
+            out.push_str(&format!("</{}>", tag.name));
+            return;
+        }
+
+        out.push('<');
+        out.push_str(&tag.name);
+        for (name, value) in &tag.attrs {
+            if !attribute_allowed(name, value.as_deref()) {
+                continue;
+            }
+            match value {
+                Some(value) => {
+                    out.push_str(&format!(" {}=\"{}\"", name, value.replace('"', "&quot;")))
+                }
+                None => out.push_str(&format!(" {}", name)),
+            }
+        }
+        if tag.self_closing {
+            out.push_str(" /");
+        }
+        out.push('>');
+    }
+}
+
+/// Parse a tag at the start of `html`, returning it and its length.
+fn parse_tag(html: &str) -> Option<(HtmlTag, usize)> {
+    let bytes = html.as_bytes();
+    let mut i = 1;
+    let closing = bytes.get(i) == Some(&b'/');
+    if closing {
+        i += 1;
+    }
+
+    let name_start = i;
+    if !bytes.get(i)?.is_ascii_alphabetic() {
+        return None;
+    }
+    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+        i += 1;
+    }
+    let name = html[name_start..i].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut self_closing = false;
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        match bytes.get(i)? {
+            b'>' => {
+                return Some((
+                    HtmlTag {
+                        name,
+                        closing,
+                        self_closing,
+                        attrs,
+                    },
+                    i + 1,
+                ));
+            }
+            b'/' => {
+                self_closing = true;
+                i += 1;
+                continue;
+            }
+            _ => self_closing = false,
+        }
+
+        let attr_start = i;
+        while i < bytes.len()
+            && !bytes[i].is_ascii_whitespace()
+            && !matches!(bytes[i], b'=' | b'>' | b'/')
+        {
+            i += 1;
+        }
+        let attr_name = html[attr_start..i].to_ascii_lowercase();
+
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let value = if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i)? {
+                quote @ (b'"' | b'\'') => {
+                    let end = i + 1 + html[i + 1..].find(*quote as char)?;
+                    let value = &html[i + 1..end];
+                    i = end + 1;
+                    Some(value.to_string())
+                }
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    Some(html[start..i].to_string())
+                }
+            }
+        } else {
+            None
+        };
+
+        if !attr_name.is_empty() {
+            attrs.push((attr_name, value));
+        }
+    }
+}
+
+/// Find the end of `</name>` in `html`, ignoring case.
+fn find_closing_tag(html: &str, name: &str) -> Option<usize> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find(&format!("</{}", name))?;
+    let end = start + lower[start..].find('>')?;
+    Some(end + 1)
+}
+
+fn attribute_allowed(name: &str, value: Option<&str>) -> bool {
+    let known = ALLOWED_ATTRIBUTES.contains(&name)
+        || name.starts_with("aria-")
+        || name.starts_with("data-");
+    if !known {
+        return false;
+    }
+    if URL_ATTRIBUTES.contains(&name) {
+        return value.is_some_and(url_is_safe);
+    }
+    true
+}
+
+/// The URL of a markdown link or image, or an empty one if it isn't safe.
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    if url_is_safe(&url) {
+        url
+    } else {
+        CowStr::Borrowed("")
+    }
+}
+
+/// Whether a URL is relative or uses an allowed scheme.
+fn url_is_safe(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters in schemes
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect();
+    let prefix_end = url.find(['/', '?', '#']).unwrap_or(url.len());
+    let prefix = &url[..prefix_end];
+
+    // An entity before the path could spell out a scheme (`javascript&colon;`)
+    if prefix.contains('&') {
+        return false;
+    }
+    match prefix.split_once(':') {
+        Some((scheme, _)) => ALLOWED_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitize(html: &str) -> String {
+        HtmlFilter::new(HtmlPolicy::Sanitize).clean(html)
+    }
+
+    #[test]
+    fn test_sanitize_keeps_safe_markup() {
+        assert_eq!(
+            sanitize("<div class=\"note\" data-x='1'><b>Hi</b><br/></div>"),
+            "<div class=\"note\" data-x=\"1\"><b>Hi</b><br /></div>"
+        );
+        assert_eq!(
+            sanitize("<a href=\"/guide?a=1&amp;b=2\" title=x>Guide</a>"),
+            "<a href=\"/guide?a=1&amp;b=2\" title=\"x\">Guide</a>"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_drops_scripts_and_handlers() {
+        assert_eq!(sanitize("a<script>alert(1)</script>b"), "ab");
+        assert_eq!(sanitize("<STYLE>p {}</style>text"), "text");
+        assert_eq!(
+            sanitize("<img src=\"x.png\" onerror=\"alert(1)\" style=\"x\">"),
+            "<img src=\"x.png\">"
+        );
+        assert_eq!(sanitize("<!-- hidden -->shown"), "shown");
+        assert_eq!(sanitize("<form><input name=q></form>"), "");
+    }
+
+    #[test]
+    fn test_sanitize_urls() {
+        assert_eq!(
+            sanitize("<a href=\"javascript:alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            sanitize("<a href=\"java\tscript:alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            sanitize("<a href=\"javascript&colon;alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            sanitize("<a href=\"mailto:me@example.com\">x</a>"),
+            "<a href=\"mailto:me@example.com\">x</a>"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_across_events() {
+        let mut filter = HtmlFilter::new(HtmlPolicy::Sanitize);
+        assert_eq!(filter.clean("<script>\n"), "");
+        assert_eq!(filter.clean("alert(1)\n"), "");
+        assert_eq!(filter.clean("</script>\n"), "\n");
+        assert_eq!(filter.clean("<p>after</p>"), "<p>after</p>");
+    }
+
+    #[test]
+    fn test_sanitize_stray_angle_bracket() {
+        assert_eq!(sanitize("1 < 2 <3"), "1 &lt; 2 &lt;3");
+    }
+
+    #[test]
+    fn test_sanitize_markdown_link_urls() {
+        let mut filter = HtmlFilter::new(HtmlPolicy::Sanitize);
+        let link = |url: &'static str| {
+            Event::Start(Tag::Link {
+                link_type: pulldown_cmark::LinkType::Inline,
+                dest_url: url.into(),
+                title: "".into(),
+                id: "".into(),
+            })
+        };
+        assert_eq!(filter.apply(link("JavaScript:alert(1)")), Some(link("")));
+        assert_eq!(filter.apply(link("/guide/")), Some(link("/guide/")));
+    }
+
+    #[test]
+    fn test_escape_policy() {
+        let mut filter = HtmlFilter::new(HtmlPolicy::Escape);
+        assert_eq!(
+            filter.apply(Event::InlineHtml("<b>".into())),
+            Some(Event::Text("<b>".into()))
+        );
+        assert_eq!(
+            filter.apply(Event::Code("<b>".into())),
+            Some(Event::Code("<b>".into()))
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Language hints for inline code spans.
+//!
+//! With the `inline_highlight` extension, a code span directly followed by
+//! `{language}` is syntax highlighted:
+//!
+//! ```markdown
+//! Declare it with `let x = 1;`{rust}.
+//! ```
+//!
+//! pulldown-cmark parses the hint as ordinary text after the code span, so a
+//! pass over the event stream strips it from that text and puts a marker
+//! event before the code span. The render loop picks the marker up when it
+//! reaches the span.
+
+use pulldown_cmark::{CowStr, Event};
+
+const MARKER_PREFIX: &str = "<!-- undox:code-lang ";
+const MARKER_SUFFIX: &str = " -->";
+
+/// Move `{language}` hints after code spans into marker events.
+pub fn mark_languages<'a>(events: impl Iterator<Item = Event<'a>>) -> Vec<Event<'a>> {
+    let mut out: Vec<Event<'a>> = Vec::new();
+    let mut events = events.peekable();
+
+    while let Some(event) = events.next() {
+        let hint = match (&event, events.peek()) {
+            (Event::Code(_), Some(Event::Text(text))) => {
+                split_hint(text).map(|(language, rest)| (language.to_string(), rest.to_string()))
+            }
+            _ => None,
+        };
+
+        match hint {
+            Some((language, rest)) => {
+                events.next();
+                out.push(Event::InlineHtml(
+                    format!("{}{}{}", MARKER_PREFIX, language, MARKER_SUFFIX).into(),
+                ));
+                out.push(event);
+                if !rest.is_empty() {
+                    out.push(Event::Text(CowStr::from(rest)));
+                }
+            }
+            None => out.push(event),
+        }
+    }
+
+    out
+}
+
+/// The language named by a marker event, if this is one.
+pub fn parse_marker(html: &str) -> Option<&str> {
+    html.strip_prefix(MARKER_PREFIX)?
+        .strip_suffix(MARKER_SUFFIX)
+}
+
+/// Split a leading `{language}` off text, returning the language and the rest.
+fn split_hint(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix('{')?;
+    let end = rest.find('}')?;
+    let language = &rest[..end];
+    let valid = !language.is_empty()
+        && language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+' | '#' | '.'));
+    valid.then(|| (language, &rest[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_hint() {
+        assert_eq!(split_hint("{rust} and more"), Some(("rust", " and more")));
+        assert_eq!(split_hint("{c++}"), Some(("c++", "")));
+        assert_eq!(split_hint("{not a lang}"), None);
+        assert_eq!(split_hint("{}"), None);
+        assert_eq!(split_hint("rust"), None);
+    }
+
+    #[test]
+    fn test_mark_languages() {
+        let events = vec![
+            Event::Code("let x = 1;".into()),
+            Event::Text("{rust}.".into()),
+            Event::Code("plain".into()),
+            Event::Text(" {rust}".into()),
+        ];
+        let marked = mark_languages(events.into_iter());
+
+        assert_eq!(
+            marked,
+            vec![
+                Event::InlineHtml("<!-- undox:code-lang rust -->".into()),
+                Event::Code("let x = 1;".into()),
+                Event::Text(".".into()),
+                Event::Code("plain".into()),
+                Event::Text(" {rust}".into()),
+            ]
+        );
+        assert_eq!(parse_marker("<!-- undox:code-lang rust -->"), Some("rust"));
+    }
+}
@@ -0,0 +1,248 @@
+//! Tabbed content blocks.
+//!
+//! When the `tabs` extension is enabled, fenced containers group content
+//! into tabs:
+//!
+//! ```markdown
+//! ::: tabs
+//! ::: tab "macOS"
+//! Regular markdown, including code blocks.
+//! :::
+//! ::: tab "Linux"
+//! ...
+//! :::
+//! :::
+//! ```
+//!
+//! Each `:::` closes the innermost open container. As with admonitions, the
+//! containers are rewritten into marker comments before parsing and turned
+//! into HTML by the render loop. The output is a `<div class="tabs">` holding
+//! a radio input, label, and panel per tab, which works with CSS alone and
+//! can be enhanced by theme scripts via the `data-tab` attributes.
+
+use super::fence::FenceTracker;
+use crate::util::html_escape;
+
+const TABS_MARKER: &str = "<!-- undox:tabs -->";
+const TABS_CLOSE_MARKER: &str = "<!-- undox:/tabs -->";
+const TAB_MARKER: &str = "<!-- undox:tab ";
+const TAB_CLOSE_MARKER: &str = "<!-- undox:/tab -->";
+
+/// A marker found in an HTML event.
+#[derive(Debug, PartialEq)]
+pub enum Marker {
+    /// Start of a tab group
+    OpenTabs,
+    /// Start of a tab with its (already escaped) title
+    OpenTab(String),
+    /// End of a tab
+    CloseTab,
+    /// End of a tab group
+    CloseTabs,
+}
+
+/// An open container while expanding.
+#[derive(Clone, Copy, PartialEq)]
+enum Container {
+    Tabs,
+    Tab,
+}
+
+/// Rewrite `::: tabs` / `::: tab "Title"` containers into marker comments.
+///
+/// Containers left open at the end of the document are closed implicitly.
+/// Lines inside fenced code blocks are never touched.
+pub fn expand(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut fence = FenceTracker::default();
+    let mut stack: Vec<Container> = Vec::new();
+
+    for line in markdown.lines() {
+        if fence.in_code(line) {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+
+        let marker = match parse_line(line) {
+            Some(Line::OpenTabs) => {
+                stack.push(Container::Tabs);
+                Some(TABS_MARKER.to_string())
+            }
+            Some(Line::OpenTab(title)) if stack.last() == Some(&Container::Tabs) => {
+                stack.push(Container::Tab);
+                Some(format!("{}\"{}\" -->", TAB_MARKER, title))
+            }
+            Some(Line::Close) => stack
+                .pop()
+                .map(|container| close_marker(container).to_string()),
+            _ => None,
+        };
+
+        match marker {
+            Some(marker) => {
+                out.push('\n');
+                out.push_str(&marker);
+                out.push_str("\n\n");
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+
+    while let Some(container) = stack.pop() {
+        out.push_str(&format!("\n{}\n\n", close_marker(container)));
+    }
+
+    out
+}
+
+/// Parse a tabs marker out of an HTML event, if it is one.
+///
+/// Hand-written markers with unescaped titles are rejected.
+pub fn parse_marker(html: &str) -> Option<Marker> {
+    match html.trim() {
+        TABS_MARKER => Some(Marker::OpenTabs),
+        TABS_CLOSE_MARKER => Some(Marker::CloseTabs),
+        TAB_CLOSE_MARKER => Some(Marker::CloseTab),
+        other => {
+            let title = other
+                .strip_prefix(TAB_MARKER)?
+                .strip_suffix("-->")?
+                .trim()
+                .strip_prefix('"')?
+                .strip_suffix('"')?;
+            if title.contains(['<', '>', '"']) {
+                return None;
+            }
+            Some(Marker::OpenTab(title.to_string()))
+        }
+    }
+}
+
+/// Opening HTML for a tab group.
+pub fn open_tabs_html() -> &'static str {
+    "<div class=\"tabs\">\n"
+}
+
+/// Closing HTML for a tab group.
+pub fn close_tabs_html() -> &'static str {
+    "</div>\n"
+}
+
+/// Opening HTML for a tab: its radio input, label, and panel.
+///
+/// `group` identifies the tab group within the page and `index` is the
+/// tab's 1-based position in the group; the first tab starts selected.
+/// `title` must already be escaped.
+pub fn open_tab_html(group: usize, index: usize, title: &str) -> String {
+    let id = format!("tabs-{}-{}", group, index);
+    format!(
+        "<input type=\"radio\" class=\"tab-input\" name=\"tabs-{}\" id=\"{}\"{}>\n\
+         <label class=\"tab-label\" for=\"{}\">{}</label>\n\
+         <div class=\"tab-panel\" data-tab=\"{}\">\n",
+        group,
+        id,
+        if index == 1 { " checked" } else { "" },
+        id,
+        title,
+        title
+    )
+}
+
+/// Closing HTML for a tab.
+pub fn close_tab_html() -> &'static str {
+    "</div>\n"
+}
+
+enum Line {
+    OpenTabs,
+    OpenTab(String),
+    Close,
+}
+
+/// Parse a `:::` container line. Tab titles are returned escaped.
+fn parse_line(line: &str) -> Option<Line> {
+    let rest = line.trim().strip_prefix(":::")?.trim();
+    if rest.is_empty() {
+        return Some(Line::Close);
+    }
+    if rest == "tabs" {
+        return Some(Line::OpenTabs);
+    }
+
+    let title = rest.strip_prefix("tab")?;
+    if !title.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = title.trim();
+    let title = title
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(title);
+    Some(Line::OpenTab(html_escape(title)))
+}
+
+fn close_marker(container: Container) -> &'static str {
+    match container {
+        Container::Tabs => TABS_CLOSE_MARKER,
+        Container::Tab => TAB_CLOSE_MARKER,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markers(expanded: &str) -> Vec<Marker> {
+        expanded.lines().filter_map(parse_marker).collect()
+    }
+
+    #[test]
+    fn test_expand_tabs() {
+        let expanded = expand(
+            "::: tabs\n::: tab \"macOS\"\nbrew install\n:::\n::: tab Linux\napt install\n:::\n:::\n",
+        );
+        assert_eq!(
+            markers(&expanded),
+            vec![
+                Marker::OpenTabs,
+                Marker::OpenTab("macOS".to_string()),
+                Marker::CloseTab,
+                Marker::OpenTab("Linux".to_string()),
+                Marker::CloseTab,
+                Marker::CloseTabs,
+            ]
+        );
+        assert!(expanded.contains("\nbrew install\n"));
+    }
+
+    #[test]
+    fn test_expand_closes_unterminated() {
+        let expanded = expand("::: tabs\n::: tab \"A\"\ncontent");
+        assert_eq!(
+            markers(&expanded),
+            vec![
+                Marker::OpenTabs,
+                Marker::OpenTab("A".to_string()),
+                Marker::CloseTab,
+                Marker::CloseTabs,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_ignores_fenced_code_and_stray_lines() {
+        let markdown = "```\n::: tabs\n```\n::: tab \"Orphan\"\n:::\n";
+        assert_eq!(expand(markdown), markdown);
+    }
+
+    #[test]
+    fn test_tab_title_escaped() {
+        let expanded = expand("::: tabs\n::: tab \"<Windows>\"\n:::\n:::");
+        assert!(markers(&expanded).contains(&Marker::OpenTab("&lt;Windows&gt;".to_string())));
+        assert_eq!(parse_marker("<!-- undox:tab \"<b>\" -->"), None);
+    }
+}
@@ -4,24 +4,99 @@
 //! navigation based on document structure.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::config::NavItem;
-use crate::util::title_case;
+use serde::Deserialize;
 
+use crate::config::{NavItem, NavMode, OrphanSeverity};
+use crate::util::{split_numeric_prefix, title_case};
+
+use super::assets::has_scheme;
+use super::diagnostics::{Diagnostics, WarningKind};
 use super::document::{ContentItem, Document};
-use super::render::{NavLink, NavSection};
+use super::render::{NavLink, NavSection, PageLink};
 use super::source::ResolvedSource;
 
+/// Per-directory metadata files, used to title and order directories in
+/// navigation. The first one found in a directory is used.
+pub const DIR_META_FILES: [&str; 2] = ["_meta.yaml", ".nav.yml"];
+
+/// A configured nav entry that doesn't match any page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingNavEntry {
+    /// Name of the source whose nav contains the entry
+    pub source: String,
+    /// The path as written in the nav config
+    pub path: String,
+}
+
+/// Problems found in configured navigation.
+#[derive(Debug, Default)]
+pub struct NavReport {
+    /// Entries that point to missing files or empty directories
+    pub missing: Vec<MissingNavEntry>,
+    /// Pages left out of their source's configured nav, as (source, path).
+    /// Index pages and hidden pages don't count.
+    pub orphans: Vec<(String, PathBuf)>,
+}
+
+impl NavReport {
+    /// Whether every nav entry matched a page.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Report the missing entries as warnings, and the orphaned pages with
+    /// the given severity: a warning or an error each. The caller fails the
+    /// build on errors.
+    pub fn report(&self, diagnostics: &Diagnostics, orphans: OrphanSeverity) {
+        for entry in &self.missing {
+            diagnostics.warn(
+                WarningKind::Nav,
+                None,
+                format!(
+                    "Nav entry '{}' in source '{}' doesn't match any page",
+                    entry.path, entry.source
+                ),
+            );
+        }
+
+        if orphans == OrphanSeverity::Ignore {
+            return;
+        }
+        for (source, path) in &self.orphans {
+            let message = format!(
+                "Page {} in source '{}' isn't in the configured navigation",
+                path.display(),
+                source
+            );
+            if orphans == OrphanSeverity::Error {
+                diagnostics.error(WarningKind::Nav, Some(path), message);
+            } else {
+                diagnostics.warn(WarningKind::Nav, Some(path), message);
+            }
+        }
+    }
+}
+
 /// Build navigation structure grouped by source.
 ///
 /// Returns a map from source name to that source's navigation.
 /// Each source gets its own isolated navigation containing only its documents.
 /// If a source has configured nav, that is used; otherwise, nav is auto-generated.
+/// Configured nav is also checked for entries that don't match a page and
+/// pages it leaves out. Directory metadata that can't be parsed is reported
+/// to `diagnostics`.
+///
+/// In [`NavMode::Combined`], every source maps to the same merged
+/// navigation: a section per source, in the order the sources are listed.
+/// Sources in different languages get a merged navigation per language.
 pub fn build_navigation_by_source(
     items: &[(ContentItem, PathBuf)],
     resolved_sources: &[ResolvedSource],
-) -> HashMap<String, Vec<NavSection>> {
+    nav_mode: NavMode,
+    diagnostics: &Diagnostics,
+) -> (HashMap<String, Vec<NavSection>>, NavReport) {
     // Group documents by source
     let mut docs_by_source: HashMap<String, Vec<&Document>> = HashMap::new();
 
@@ -36,44 +111,324 @@ pub fn build_navigation_by_source(
 
     // Build navigation for each source
     let mut nav_by_source = HashMap::new();
+    let mut report = NavReport::default();
 
     for source in resolved_sources {
         let source_name = &source.config.name;
         let docs = docs_by_source.get(source_name).cloned().unwrap_or_default();
 
+        let dir_meta = load_dir_meta(&source.local_path, &docs, diagnostics);
+
         // Check if source has configured nav
         if let Some(nav_config) = &source.config.nav {
-            // Build lookup from relative source path to document URL
-            let path_to_doc: HashMap<String, &Document> = docs
-                .iter()
-                .map(|doc| {
-                    let path_str = doc.source_path.to_string_lossy().to_string();
-                    (path_str, *doc)
-                })
-                .collect();
+            let source_docs = SourceDocs::new(
+                source_name,
+                docs,
+                dir_meta,
+                source.config.strip_numeric_prefix,
+            );
 
             // Convert NavConfig to Vec<NavSection>
-            let nav = convert_nav_config(nav_config, &path_to_doc);
+            let nav = convert_nav_config(nav_config, &source_docs);
             nav_by_source.insert(source_name.clone(), nav);
+            source_docs.check(nav_config, &mut report);
         } else {
             // Auto-generate navigation from documents
-            let nav = auto_generate_nav(docs);
+            let nav = auto_generate_nav(docs, &dir_meta, source.config.strip_numeric_prefix);
             nav_by_source.insert(source_name.clone(), nav);
         }
     }
 
-    nav_by_source
+    if nav_mode == NavMode::Combined {
+        // Sources in different languages don't share a sidebar
+        let mut languages: Vec<Option<&String>> = Vec::new();
+        for source in resolved_sources {
+            if !languages.contains(&source.config.language.as_ref()) {
+                languages.push(source.config.language.as_ref());
+            }
+        }
+        for language in languages {
+            let sources: Vec<&ResolvedSource> = resolved_sources
+                .iter()
+                .filter(|source| source.config.language.as_ref() == language)
+                .collect();
+            let combined = combine_navigation(&nav_by_source, &sources);
+            for source in sources {
+                nav_by_source.insert(source.config.name.clone(), combined.clone());
+            }
+        }
+    }
+
+    (nav_by_source, report)
 }
 
-/// Convert a NavConfig to Vec<NavSection> using document lookup.
-fn convert_nav_config(
-    nav_config: &[NavItem],
-    path_to_doc: &HashMap<String, &Document>,
+/// Merge per-source navigation into one, with a section per source.
+///
+/// Sources without any navigation are left out.
+fn combine_navigation(
+    nav_by_source: &HashMap<String, Vec<NavSection>>,
+    resolved_sources: &[&ResolvedSource],
 ) -> Vec<NavSection> {
+    resolved_sources
+        .iter()
+        .filter_map(|source| {
+            let items = nav_by_source.get(&source.config.name)?;
+            if items.is_empty() {
+                return None;
+            }
+            Some(NavSection::Section {
+                section: source.display_title(),
+                items: items.clone(),
+                icon: None,
+                is_active_trail: false,
+            })
+        })
+        .collect()
+}
+
+/// A source's documents, for resolving its configured navigation.
+struct SourceDocs<'a> {
+    /// Name of the source, for warnings
+    source_name: &'a str,
+    /// All documents of the source
+    docs: Vec<&'a Document>,
+    /// Documents by their path relative to the source root
+    path_to_doc: HashMap<String, &'a Document>,
+    /// Directory metadata from `_meta.yaml` files
+    dir_meta: HashMap<String, DirMeta>,
+    /// Whether the source strips numeric prefixes from names
+    strip_numeric_prefix: bool,
+}
+
+impl<'a> SourceDocs<'a> {
+    fn new(
+        source_name: &'a str,
+        docs: Vec<&'a Document>,
+        dir_meta: HashMap<String, DirMeta>,
+        strip_numeric_prefix: bool,
+    ) -> Self {
+        // Build lookup from relative source path to document
+        let path_to_doc = docs
+            .iter()
+            .map(|doc| (doc.source_path.to_string_lossy().to_string(), *doc))
+            .collect();
+        Self {
+            source_name,
+            docs,
+            path_to_doc,
+            dir_meta,
+            strip_numeric_prefix,
+        }
+    }
+
+    fn get(&self, path: &str) -> Option<&&'a Document> {
+        self.path_to_doc.get(path)
+    }
+
+    /// Record the entries of `nav_config` that don't match a page, and the
+    /// visible pages other than index pages that no entry covers.
+    fn check(&self, nav_config: &[NavItem], report: &mut NavReport) {
+        let mut paths = Vec::new();
+        collect_nav_paths(nav_config, &mut paths);
+
+        let (dirs, files): (Vec<&str>, Vec<&str>) =
+            paths.into_iter().partition(|path| path.ends_with('/'));
+        let in_dir = |doc: &Document, dir: &str| doc.source_path.starts_with(dir.trim_matches('/'));
+
+        for path in &files {
+            if !self.path_to_doc.contains_key(*path) {
+                report.missing.push(MissingNavEntry {
+                    source: self.source_name.to_string(),
+                    path: path.to_string(),
+                });
+            }
+        }
+        for dir in &dirs {
+            if !self.docs.iter().any(|doc| in_dir(doc, dir)) {
+                report.missing.push(MissingNavEntry {
+                    source: self.source_name.to_string(),
+                    path: dir.to_string(),
+                });
+            }
+        }
+
+        let mut orphans: Vec<&Document> = self
+            .docs
+            .iter()
+            .filter(|doc| !doc.front_matter.hidden)
+            .filter(|doc| doc.source_path.file_stem().is_none_or(|s| s != "index"))
+            .filter(|doc| !files.contains(&doc.source_path.to_string_lossy().as_ref()))
+            .filter(|doc| !dirs.iter().any(|dir| in_dir(doc, dir)))
+            .copied()
+            .collect();
+        orphans.sort_by(|a, b| a.source_path.cmp(&b.source_path));
+        report.orphans.extend(
+            orphans
+                .into_iter()
+                .map(|doc| (self.source_name.to_string(), doc.source_path.clone())),
+        );
+    }
+
+    /// Expand a directory entry (`guides/`) into a section containing all
+    /// pages under it, arranged like auto-generated navigation.
+    ///
+    /// The section is titled from the directory's `_meta.yaml` or its name
+    /// unless `title` is given.
+    /// A directory without any pages is skipped.
+    fn expand_directory(&self, dir: &str, title: Option<&str>) -> Option<NavSection> {
+        let dir = dir.trim_matches('/');
+        let path_parts: Vec<&str> = dir.split('/').collect();
+        let in_dir: Vec<&Document> = self
+            .docs
+            .iter()
+            .filter(|doc| doc.source_path.starts_with(dir))
+            .copied()
+            .collect();
+
+        let node = build_nav_tree(in_dir, &self.dir_meta).take_child(&path_parts)?;
+        let meta_title = node.meta.title.clone();
+        let items = node.into_nav_sections(self.strip_numeric_prefix);
+        if items.is_empty() {
+            return None;
+        }
+
+        let title = title.map(str::to_string).or(meta_title).unwrap_or_else(|| {
+            let name = path_parts.last().copied().unwrap_or(dir);
+            if self.strip_numeric_prefix {
+                title_case(split_numeric_prefix(name).1)
+            } else {
+                title_case(name)
+            }
+        });
+        Some(NavSection::Section {
+            section: title,
+            items,
+            icon: None,
+            is_active_trail: false,
+        })
+    }
+}
+
+/// Mark the link to the page at `url` as active, and the sections and links
+/// containing it as the active trail.
+///
+/// Returns whether the page was found. Trailing slashes are ignored when
+/// comparing URLs, so a section's index page matches either form.
+pub fn mark_active(nav: &mut [NavSection], url: &str) -> bool {
+    let url = url.trim_end_matches('/');
+    let mut found = false;
+    for item in nav {
+        match item {
+            NavSection::Section {
+                items,
+                is_active_trail,
+                ..
+            } => {
+                *is_active_trail = mark_active(items, url);
+                found |= *is_active_trail;
+            }
+            NavSection::Link(link) => {
+                link.is_active = link.url.trim_end_matches('/') == url;
+                link.is_active_trail = mark_active(&mut link.children, url);
+                found |= link.is_active || link.is_active_trail;
+            }
+            NavSection::Divider | NavSection::Label { .. } => {}
+        }
+    }
+    found
+}
+
+/// Drop navigation entries nested deeper than `max_depth` levels.
+///
+/// Top-level entries are at depth 1, and the items of a section or the
+/// children of a link are one level deeper. Sections left without items are
+/// removed, while links just lose their children. Active trail flags are
+/// kept, so the visible ancestors of a hidden page stay highlighted.
+pub fn limit_depth(nav: &mut Vec<NavSection>, max_depth: usize) {
+    if max_depth == 0 {
+        nav.clear();
+        return;
+    }
+    for item in nav.iter_mut() {
+        match item {
+            NavSection::Section { items, .. } => limit_depth(items, max_depth - 1),
+            NavSection::Link(link) => limit_depth(&mut link.children, max_depth - 1),
+            NavSection::Divider | NavSection::Label { .. } => {}
+        }
+    }
+    nav.retain(|item| !matches!(item, NavSection::Section { items, .. } if items.is_empty()));
+}
+
+/// Collect every path referenced by a nav config, files and directories.
+fn collect_nav_paths<'a>(nav_config: &'a [NavItem], paths: &mut Vec<&'a str>) {
+    for item in nav_config {
+        match item {
+            NavItem::Section { items, .. } => collect_nav_paths(items, paths),
+            NavItem::LinkWithChildren { path, children, .. } => {
+                paths.push(path);
+                collect_nav_paths(children, paths);
+            }
+            NavItem::Titled(map) => paths.extend(map.values().map(String::as_str)),
+            NavItem::Path(path) => paths.push(path),
+            NavItem::Divider { .. } | NavItem::Label { .. } => {}
+        }
+    }
+}
+
+/// The navigation's page links in reading order.
+///
+/// The navigation is flattened depth-first, with each link followed by its
+/// children. Section headings, dividers, labels, and external links are
+/// skipped.
+pub fn reading_order(nav: &[NavSection]) -> Vec<&NavLink> {
+    fn flatten<'a>(nav: &'a [NavSection], links: &mut Vec<&'a NavLink>) {
+        for item in nav {
+            match item {
+                NavSection::Section { items, .. } => flatten(items, links),
+                NavSection::Link(link) => {
+                    if !has_scheme(&link.url) {
+                        links.push(link);
+                    }
+                    flatten(&link.children, links);
+                }
+                NavSection::Divider | NavSection::Label { .. } => {}
+            }
+        }
+    }
+
+    let mut links = Vec::new();
+    flatten(nav, &mut links);
+    links
+}
+
+/// Find the pages before and after the page at `url` in
+/// [reading order](reading_order). A page that isn't in the navigation has
+/// neither.
+pub fn prev_next(nav: &[NavSection], url: &str) -> (Option<PageLink>, Option<PageLink>) {
+    let links = reading_order(nav);
+
+    let url = url.trim_end_matches('/');
+    let Some(index) = links
+        .iter()
+        .position(|link| link.url.trim_end_matches('/') == url)
+    else {
+        return (None, None);
+    };
+    let page_link = |link: &NavLink| PageLink {
+        title: link.title.clone(),
+        url: link.url.clone(),
+    };
+    let prev = index.checked_sub(1).map(|i| page_link(links[i]));
+    let next = links.get(index + 1).map(|link| page_link(link));
+    (prev, next)
+}
+
+/// Convert a NavConfig to Vec<NavSection> using document lookup.
+fn convert_nav_config(nav_config: &[NavItem], source_docs: &SourceDocs) -> Vec<NavSection> {
     let mut result = Vec::new();
 
     for item in nav_config {
-        if let Some(nav_section) = convert_nav_item(item, path_to_doc) {
+        if let Some(nav_section) = convert_nav_item(item, source_docs) {
             result.push(nav_section);
         }
     }
@@ -82,22 +437,25 @@ fn convert_nav_config(
 }
 
 /// Convert a single NavItem to a NavSection (recursively handles children).
-fn convert_nav_item(
-    item: &NavItem,
-    path_to_doc: &HashMap<String, &Document>,
-) -> Option<NavSection> {
+fn convert_nav_item(item: &NavItem, source_docs: &SourceDocs) -> Option<NavSection> {
     match item {
-        NavItem::Section { section, items } => {
+        NavItem::Section {
+            section,
+            items,
+            icon,
+        } => {
             // Convert section items recursively
             let nav_items: Vec<NavSection> = items
                 .iter()
-                .filter_map(|item| convert_nav_item(item, path_to_doc))
+                .filter_map(|item| convert_nav_item(item, source_docs))
                 .collect();
 
             if !nav_items.is_empty() {
                 Some(NavSection::Section {
                     section: section.clone(),
                     items: nav_items,
+                    icon: icon.clone(),
+                    is_active_trail: false,
                 })
             } else {
                 None
@@ -106,51 +464,66 @@ fn convert_nav_item(
         NavItem::LinkWithChildren {
             path,
             title,
+            icon,
             children,
         } => {
             // A link with nested children
-            if let Some(doc) = path_to_doc.get(path) {
+            if let Some(doc) = source_docs.get(path) {
                 let child_sections: Vec<NavSection> = children
                     .iter()
-                    .filter_map(|child| convert_nav_item(child, path_to_doc))
+                    .filter_map(|child| convert_nav_item(child, source_docs))
                     .collect();
 
                 Some(NavSection::Link(NavLink {
                     title: title.clone().unwrap_or_else(|| doc.title()),
                     url: doc.url_path.clone(),
+                    icon: icon.clone(),
+                    is_active: false,
+                    is_active_trail: false,
                     children: child_sections,
                 }))
             } else {
                 None
             }
         }
+        NavItem::Divider { divider } => divider.then_some(NavSection::Divider),
+        NavItem::Label { label, icon } => Some(NavSection::Label {
+            label: label.clone(),
+            icon: icon.clone(),
+        }),
         NavItem::Titled(map) => {
-            // Single titled link
-            if let Some((title, path)) = map.iter().next()
-                && let Some(doc) = path_to_doc.get(path)
-            {
-                Some(NavSection::Link(NavLink {
+            // Single titled link, or a directory with a custom title
+            let (title, path) = map.iter().next()?;
+            if path.ends_with('/') {
+                return source_docs.expand_directory(path, Some(title));
+            }
+            source_docs.get(path).map(|doc| {
+                NavSection::Link(NavLink {
                     title: title.clone(),
                     url: doc.url_path.clone(),
+                    icon: None,
+                    is_active: false,
+                    is_active_trail: false,
                     children: vec![],
-                }))
-            } else {
-                None
-            }
+                })
+            })
         }
         NavItem::Path(path) => {
             if !path.ends_with('/') {
                 // It's a file path
-                path_to_doc.get(path).map(|doc| {
+                source_docs.get(path).map(|doc| {
                     NavSection::Link(NavLink {
                         title: doc.title(),
                         url: doc.url_path.clone(),
+                        icon: None,
+                        is_active: false,
+                        is_active_trail: false,
                         children: vec![],
                     })
                 })
             } else {
-                // Directory paths (ending with /) are not supported in this simple conversion
-                None
+                // A directory expands to all of its pages
+                source_docs.expand_directory(path, None)
             }
         }
     }
@@ -159,104 +532,243 @@ fn convert_nav_item(
 /// A tree node for building hierarchical navigation.
 #[derive(Default)]
 struct NavTreeNode {
-    /// Documents at this level: (is_index, link)
-    links: Vec<(bool, NavLink)>,
+    /// Documents at this level
+    links: Vec<TreeLink>,
     /// Subdirectories
     children: HashMap<String, NavTreeNode>,
+    /// Metadata of this directory from its `_meta.yaml`
+    meta: DirMeta,
+}
+
+/// A document link in the navigation tree.
+struct TreeLink {
+    /// File name of the document, for matching `items` in `_meta.yaml`
+    name: String,
+    is_index: bool,
+    /// Whether the title comes from the document rather than its filename
+    has_title: bool,
+    weight: Option<i64>,
+    link: NavLink,
+}
+
+/// A link or directory of a tree node, with the keys it's sorted by.
+struct NavEntry {
+    is_index: bool,
+    /// Position in the directory's `_meta.yaml` `items`
+    listed: Option<usize>,
+    weight: Option<i64>,
+    is_dir: bool,
+    sort_name: String,
+    section: NavSection,
+}
+
+/// Directory metadata read from a `_meta.yaml` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct DirMeta {
+    /// Section title, instead of one derived from the directory name
+    title: Option<String>,
+    /// Position of the directory in its parent's navigation
+    weight: Option<i64>,
+    /// Names of the directory's pages and subdirectories, in the order they
+    /// should appear. Unlisted entries follow in the usual order.
+    #[serde(default)]
+    items: Vec<String>,
+}
+
+impl DirMeta {
+    /// Position of the file or directory `name` in `items`. Entries may be
+    /// written with or without the file extension or a trailing slash.
+    fn position(&self, name: &str) -> Option<usize> {
+        self.items.iter().position(|item| {
+            let item = item.trim_matches('/');
+            item == name || name.rsplit_once('.').is_some_and(|(stem, _)| stem == item)
+        })
+    }
 }
 
 impl NavTreeNode {
     /// Insert a document into the tree at the appropriate depth.
-    fn insert(&mut self, path_parts: &[&str], is_index: bool, link: NavLink) {
+    fn insert(&mut self, path_parts: &[&str], link: TreeLink) {
         if path_parts.len() <= 1 {
             // This is a file at the current level
-            self.links.push((is_index, link));
+            self.links.push(link);
         } else {
             // Navigate into subdirectory
             let dir_name = path_parts[0].to_string();
             self.children
                 .entry(dir_name)
                 .or_default()
-                .insert(&path_parts[1..], is_index, link);
+                .insert(&path_parts[1..], link);
+        }
+    }
+
+    /// Remove and return the directory at `path_parts`, if it exists.
+    fn take_child(mut self, path_parts: &[&str]) -> Option<NavTreeNode> {
+        match path_parts.split_first() {
+            None => Some(self),
+            Some((dir, rest)) => self.children.remove(*dir)?.take_child(rest),
         }
     }
 
+    /// Set the metadata of the directory at `path_parts`, if it exists.
+    fn set_meta(&mut self, path_parts: &[&str], meta: &DirMeta) {
+        match path_parts.split_first() {
+            None => self.meta = meta.clone(),
+            Some((dir, rest)) => {
+                if let Some(child) = self.children.get_mut(*dir) {
+                    child.set_meta(rest, meta);
+                }
+            }
+        }
+    }
+
+    /// The directory's weight: from `_meta.yaml`, else from its index file.
+    fn directory_weight(&self) -> Option<i64> {
+        self.meta.weight.or_else(|| {
+            self.links
+                .iter()
+                .find(|link| link.is_index)
+                .and_then(|link| link.weight)
+        })
+    }
+
     /// Convert this tree node into a Vec<NavSection>.
     ///
     /// When a link's filename stem matches a child directory name,
     /// the directory contents are merged into the link's `children` field
-    /// instead of creating a separate section.
-    fn into_nav_sections(mut self) -> Vec<NavSection> {
-        let mut result = Vec::new();
-
-        // Sort links (index files come first, then alphabetically)
-        self.links.sort_by(|a, b| match (a.0, b.0) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.1.title.cmp(&b.1.title),
-        });
+    /// instead of creating a separate section. Likewise, a directory with an
+    /// `index.md` becomes a link to that page, titled by the page (or the
+    /// directory name if the page has no title), with the rest of the
+    /// directory as its children.
+    ///
+    /// Items are ordered with index files first, then as listed in the
+    /// directory's `_meta.yaml` `items`, then by weight, with unweighted items
+    /// after weighted ones. Remaining ties keep links before directories and
+    /// sort alphabetically. Directories are titled by their `_meta.yaml`
+    /// `title` if they have one.
+    ///
+    /// With `strip_numeric_prefix`, directory names lose their `01-` style
+    /// prefix, which becomes the directory's weight if it has no other.
+    fn into_nav_sections(mut self, strip_numeric_prefix: bool) -> Vec<NavSection> {
+        let dir_name = |name: &str| {
+            if strip_numeric_prefix {
+                split_numeric_prefix(name)
+            } else {
+                (None, name)
+            }
+            .1
+            .to_lowercase()
+        };
+
+        let mut entries: Vec<NavEntry> = Vec::new();
 
         // Build a set of link stems to check for matching directories
         // The stem is derived from the link's URL (last path component)
         let link_stems: std::collections::HashSet<String> = self
             .links
             .iter()
-            .filter_map(|(_, link)| {
-                link.url
-                    .trim_end_matches('/')
-                    .rsplit('/')
-                    .next()
-                    .map(|s| s.to_lowercase())
-            })
+            .filter_map(|tree_link| url_stem(&tree_link.link.url))
             .collect();
 
         // Process links, merging matching directory children
-        for (is_index, mut link) in self.links {
+        for TreeLink {
+            name,
+            is_index,
+            weight,
+            mut link,
+            ..
+        } in self.links
+        {
             // Find matching child directory by checking the link's URL stem
-            let link_stem = link
-                .url
-                .trim_end_matches('/')
-                .rsplit('/')
-                .next()
-                .map(|s| s.to_lowercase());
-
-            if let Some(stem) = link_stem
-                && let Some(child) = self.children.remove(&stem)
-            {
-                // Merge directory contents into link's children
-                link.children = child.into_nav_sections();
-            }
+            let link_stem = url_stem(&link.url);
 
-            // Skip adding index files as standalone links if they're the only
-            // thing at this level (they'll be shown via section navigation)
-            if is_index && result.is_empty() && self.children.is_empty() && link.children.is_empty()
-            {
-                // Still add it - it's the only item
+            let matching_dir = link_stem.and_then(|stem| {
+                self.children
+                    .keys()
+                    .find(|name| dir_name(name) == stem)
+                    .cloned()
+            });
+            if let Some(child) = matching_dir.and_then(|name| self.children.remove(&name)) {
+                // Merge directory contents into link's children
+                link.children = child.into_nav_sections(strip_numeric_prefix);
             }
 
-            result.push(NavSection::Link(link));
+            entries.push(NavEntry {
+                is_index,
+                listed: self.meta.position(&name),
+                weight,
+                is_dir: false,
+                sort_name: link.title.clone(),
+                section: NavSection::Link(link),
+            });
         }
 
-        // Add remaining children (directories without matching files) as sections
-        let mut remaining_children: Vec<_> = self.children.into_iter().collect();
-        remaining_children.sort_by(|a, b| a.0.cmp(&b.0));
-
-        for (name, child) in remaining_children {
+        // Add remaining children (directories without matching files): as a
+        // link to the directory's index page if it has one, else a section
+        for (dir, mut child) in self.children {
             // Skip if this directory was already merged with a link
-            if link_stems.contains(&name.to_lowercase()) {
+            if link_stems.contains(&dir_name(&dir)) {
                 continue;
             }
 
-            let items = child.into_nav_sections();
-            if !items.is_empty() {
-                result.push(NavSection::Section {
-                    section: title_case(&name),
+            let listed = self.meta.position(&dir);
+            let (prefix, name) = if strip_numeric_prefix {
+                split_numeric_prefix(&dir)
+            } else {
+                (None, dir.as_str())
+            };
+            let has_meta_title = child.meta.title.is_some();
+            let title = child.meta.title.clone().unwrap_or_else(|| title_case(name));
+            let weight = child
+                .directory_weight()
+                .or_else(|| prefix.map(|prefix| prefix as i64));
+            let index = child
+                .links
+                .iter()
+                .position(|link| link.is_index)
+                .map(|i| child.links.remove(i));
+            let items = child.into_nav_sections(strip_numeric_prefix);
+
+            let section = match index {
+                Some(index) => {
+                    let mut link = index.link;
+                    // "Index" from the filename is no use as a label
+                    if has_meta_title || !index.has_title {
+                        link.title = title;
+                    }
+                    link.children = items;
+                    NavSection::Link(link)
+                }
+                None if !items.is_empty() => NavSection::Section {
+                    section: title,
                     items,
-                });
-            }
+                    icon: None,
+                    is_active_trail: false,
+                },
+                None => continue,
+            };
+            entries.push(NavEntry {
+                is_index: false,
+                listed,
+                weight,
+                is_dir: true,
+                sort_name: name.to_string(),
+                section,
+            });
         }
 
-        result
+        entries.sort_by(|a, b| {
+            b.is_index
+                .cmp(&a.is_index)
+                .then_with(|| a.listed.is_none().cmp(&b.listed.is_none()))
+                .then_with(|| a.listed.cmp(&b.listed))
+                .then_with(|| a.weight.is_none().cmp(&b.weight.is_none()))
+                .then_with(|| a.weight.cmp(&b.weight))
+                .then_with(|| a.is_dir.cmp(&b.is_dir))
+                .then_with(|| a.sort_name.cmp(&b.sort_name))
+        });
+
+        entries.into_iter().map(|entry| entry.section).collect()
     }
 }
 
@@ -267,30 +779,116 @@ impl NavTreeNode {
 /// - Documents in subdirectories are grouped into sections
 /// - Nested directories create nested sections
 /// - Index files are sorted first within their level
-/// - Section names are derived from directory names using title case
-fn auto_generate_nav(mut docs: Vec<&Document>) -> Vec<NavSection> {
+/// - Other items are ordered by their directory's `_meta.yaml`, then their
+///   `weight`, then alphabetically
+/// - Section names come from `_meta.yaml` or the directory name in title case
+/// - Hidden documents are left out
+///
+/// `dir_meta` maps directory paths (relative to the source root) to their
+/// `_meta.yaml`. With `strip_numeric_prefix`, `01-` style
+/// prefixes on directory names order the directories and are dropped from
+/// section names.
+fn auto_generate_nav(
+    docs: Vec<&Document>,
+    dir_meta: &HashMap<String, DirMeta>,
+    strip_numeric_prefix: bool,
+) -> Vec<NavSection> {
+    build_nav_tree(docs, dir_meta).into_nav_sections(strip_numeric_prefix)
+}
+
+/// The last segment of a page's URL, lowercased and without `.html`, to match
+/// against directory names.
+fn url_stem(url: &str) -> Option<String> {
+    url.trim_end_matches('/')
+        .trim_end_matches(".html")
+        .rsplit('/')
+        .next()
+        .map(|s| s.to_lowercase())
+}
+
+/// Arrange documents into a tree by directory, skipping hidden documents.
+fn build_nav_tree(mut docs: Vec<&Document>, dir_meta: &HashMap<String, DirMeta>) -> NavTreeNode {
     // Sort by source path for consistent ordering
     docs.sort_by(|a, b| a.source_path.cmp(&b.source_path));
 
     // Build the navigation tree
     let mut root = NavTreeNode::default();
 
-    for doc in docs {
+    for doc in docs.into_iter().filter(|doc| !doc.front_matter.hidden) {
         let is_index = doc.source_path.file_stem().is_some_and(|s| s == "index");
-        let link = NavLink {
-            title: doc.title(),
-            url: doc.url_path.clone(),
-            children: vec![],
+        let link = TreeLink {
+            name: doc
+                .source_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            is_index,
+            has_title: doc.front_matter.title.is_some() || doc.heading_title.is_some(),
+            weight: doc.weight(),
+            link: NavLink {
+                title: doc.title(),
+                url: doc.url_path.clone(),
+                icon: None,
+                is_active: false,
+                is_active_trail: false,
+                children: vec![],
+            },
         };
 
         let path_str = doc.source_path.to_string_lossy();
         let path_parts: Vec<&str> = path_str.trim_matches('/').split('/').collect();
 
-        root.insert(&path_parts, is_index, link);
+        root.insert(&path_parts, link);
     }
 
-    // Convert tree to Vec<NavSection>
-    root.into_nav_sections()
+    for (dir, meta) in dir_meta {
+        // The source root's own metadata has an empty path
+        let dir = dir.trim_matches('/');
+        let path_parts: Vec<&str> = if dir.is_empty() {
+            vec![]
+        } else {
+            dir.split('/').collect()
+        };
+        root.set_meta(&path_parts, meta);
+    }
+
+    root
+}
+
+/// Read the `_meta.yaml` files of the source root and the directories
+/// containing `docs`.
+fn load_dir_meta(
+    source_root: &Path,
+    docs: &[&Document],
+    diagnostics: &Diagnostics,
+) -> HashMap<String, DirMeta> {
+    let dirs: std::collections::BTreeSet<&Path> = docs
+        .iter()
+        .flat_map(|doc| doc.source_path.ancestors().skip(1))
+        .collect();
+
+    let mut dir_meta = HashMap::new();
+    for dir in dirs {
+        let Some((meta_path, content)) = DIR_META_FILES.iter().find_map(|file| {
+            let path = source_root.join(dir).join(file);
+            std::fs::read_to_string(&path)
+                .ok()
+                .map(|content| (path, content))
+        }) else {
+            continue;
+        };
+        match serde_yaml::from_str::<DirMeta>(&content) {
+            Ok(meta) => {
+                dir_meta.insert(dir.to_string_lossy().to_string(), meta);
+            }
+            Err(e) => diagnostics.warn(
+                WarningKind::Nav,
+                Some(&meta_path),
+                format!("Failed to parse {}: {}", meta_path.display(), e),
+            ),
+        }
+    }
+    dir_meta
 }
 
 #[cfg(test)]
@@ -306,6 +904,8 @@ mod tests {
             url_path: url_path.to_string(),
             front_matter: FrontMatter::default(),
             raw_content: String::new(),
+            heading_title: None,
+            numeric_prefix: None,
         }
     }
 
@@ -318,7 +918,7 @@ mod tests {
         ];
         let doc_refs: Vec<&Document> = docs.iter().collect();
 
-        let nav = auto_generate_nav(doc_refs);
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
 
         assert_eq!(nav.len(), 3);
         // Index should be first
@@ -338,10 +938,10 @@ mod tests {
         ];
         let doc_refs: Vec<&Document> = docs.iter().collect();
 
-        let nav = auto_generate_nav(doc_refs);
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
 
         assert_eq!(nav.len(), 2); // One root link, one section
-        if let NavSection::Section { section, items } = &nav[1] {
+        if let NavSection::Section { section, items, .. } = &nav[1] {
             assert_eq!(section, "Commands");
             assert_eq!(items.len(), 2);
         } else {
@@ -362,7 +962,7 @@ mod tests {
         ];
         let doc_refs: Vec<&Document> = docs.iter().collect();
 
-        let nav = auto_generate_nav(doc_refs);
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
 
         // Expected structure (with file/directory merging):
         // - Link: Index (/cli)
@@ -384,7 +984,7 @@ mod tests {
         }
 
         // Second item is the Commands section
-        if let NavSection::Section { section, items } = &nav[1] {
+        if let NavSection::Section { section, items, .. } = &nav[1] {
             assert_eq!(section, "Commands");
             assert_eq!(items.len(), 1); // Just the Build link (with children)
 
@@ -409,6 +1009,138 @@ mod tests {
         }
     }
 
+    fn make_weighted_doc(source_path: &str, url_path: &str, weight: i64) -> Document {
+        let mut doc = make_doc("docs", source_path, url_path);
+        doc.front_matter.weight = Some(weight);
+        doc
+    }
+
+    fn nav_titles(nav: &[NavSection]) -> Vec<&str> {
+        nav.iter()
+            .map(|item| match item {
+                NavSection::Section { section, .. } => section.as_str(),
+                NavSection::Link(link) => link.title.as_str(),
+                NavSection::Divider => "---",
+                NavSection::Label { label, .. } => label.as_str(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_auto_generate_nav_weights() {
+        let docs = [
+            make_doc("docs", "zebra.md", "/zebra"),
+            make_weighted_doc("advanced.md", "/advanced", 20),
+            make_doc("docs", "index.md", "/"),
+            make_weighted_doc("getting-started.md", "/getting-started", 10),
+            make_doc("docs", "apple.md", "/apple"),
+            make_weighted_doc("tie-b.md", "/tie-b", 30),
+            make_weighted_doc("tie-a.md", "/tie-a", 30),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
+
+        assert_eq!(
+            nav_titles(&nav),
+            vec![
+                "Index",
+                "Getting Started",
+                "Advanced",
+                "Tie A",
+                "Tie B",
+                "Apple",
+                "Zebra"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_generate_nav_directory_weights() {
+        let docs = [
+            make_doc("docs", "intro.md", "/intro"),
+            make_doc("docs", "reference/api.md", "/reference/api"),
+            make_weighted_doc("guides/index.md", "/guides", 1),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+            make_doc("docs", "appendix/faq.md", "/appendix/faq"),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+
+        // `guides` is weighted by its index file, `reference` by `_meta.yaml`
+        let meta = DirMeta {
+            weight: Some(2),
+            ..Default::default()
+        };
+        let dir_meta = HashMap::from([("reference".to_string(), meta)]);
+        let nav = auto_generate_nav(doc_refs, &dir_meta, false);
+
+        assert_eq!(
+            nav_titles(&nav),
+            vec!["Guides", "Reference", "Intro", "Appendix"]
+        );
+    }
+
+    #[test]
+    fn test_auto_generate_nav_dir_meta() {
+        let docs = [
+            make_doc("docs", "intro.md", "/intro"),
+            make_doc("docs", "install.md", "/install"),
+            make_doc("docs", "faq/general.md", "/faq/general"),
+            make_doc("docs", "faq/billing.md", "/faq/billing"),
+            make_doc("docs", "faq/errors.md", "/faq/errors"),
+            make_doc("docs", "reference/api.md", "/reference/api"),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+
+        let meta = |yaml: &str| serde_yaml::from_str::<DirMeta>(yaml).unwrap();
+        let dir_meta = HashMap::from([
+            ("".to_string(), meta("items: [install, faq/]")),
+            ("faq".to_string(), meta("title: FAQ\nitems: [general.md]")),
+        ]);
+        let nav = auto_generate_nav(doc_refs, &dir_meta, false);
+
+        // Listed entries come first in their listed order
+        assert_eq!(
+            nav_titles(&nav),
+            vec!["Install", "FAQ", "Intro", "Reference"]
+        );
+        let NavSection::Section { items, .. } = &nav[1] else {
+            panic!("expected FAQ section");
+        };
+        assert_eq!(nav_titles(items), vec!["General", "Billing", "Errors"]);
+    }
+
+    #[test]
+    fn test_auto_generate_nav_numeric_prefixes() {
+        let prefixed = |path: &str, url: &str| {
+            let mut doc = make_doc("docs", path, url);
+            let name = doc.source_path.file_name().unwrap().to_string_lossy();
+            doc.numeric_prefix = split_numeric_prefix(&name).0;
+            doc
+        };
+        let docs = [
+            prefixed("10-install.md", "/install"),
+            prefixed("2-intro.md", "/intro"),
+            prefixed("05-guides/02-b.md", "/guides/b"),
+            prefixed("05-guides/01-a.md", "/guides/a"),
+            prefixed("99-faq.md", "/faq"),
+            prefixed("99-faq/01-more.md", "/faq/more"),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), true);
+
+        assert_eq!(nav_titles(&nav), vec!["Intro", "Guides", "Install", "Faq"]);
+        let NavSection::Section { items, .. } = &nav[1] else {
+            panic!("expected Guides section");
+        };
+        assert_eq!(nav_titles(items), vec!["A", "B"]);
+        let NavSection::Link(faq) = &nav[3] else {
+            panic!("expected Faq link");
+        };
+        assert_eq!(nav_titles(&faq.children), vec!["More"]);
+    }
+
     #[test]
     fn test_convert_nav_config_link_with_children() {
         // Create documents
@@ -418,26 +1150,20 @@ mod tests {
             make_doc("docs", "configuration/sub.md", "/docs/configuration/sub"),
         ];
 
-        // Build path_to_doc lookup
-        let path_to_doc: HashMap<String, &Document> = docs
-            .iter()
-            .map(|doc| {
-                let path_str = doc.source_path.to_string_lossy().to_string();
-                (path_str, doc)
-            })
-            .collect();
+        let source_docs = SourceDocs::new("docs", docs.iter().collect(), HashMap::new(), false);
 
         // Create nav config with LinkWithChildren
         let nav_config: Vec<NavItem> = vec![NavItem::LinkWithChildren {
             path: "configuration.md".to_string(),
             title: Some("Configuration".to_string()),
+            icon: None,
             children: vec![
                 NavItem::Path("configuration/root.md".to_string()),
                 NavItem::Path("configuration/sub.md".to_string()),
             ],
         }];
 
-        let nav = convert_nav_config(&nav_config, &path_to_doc);
+        let nav = convert_nav_config(&nav_config, &source_docs);
 
         // Should have one link with two children
         assert_eq!(nav.len(), 1);
@@ -464,4 +1190,418 @@ mod tests {
             panic!("Expected Link at nav[0]");
         }
     }
+
+    #[test]
+    fn test_convert_nav_config_directory() {
+        let mut hidden = make_doc("docs", "guides/internal.md", "/guides/internal");
+        hidden.front_matter.hidden = true;
+        let docs = [
+            make_doc("docs", "intro.md", "/intro"),
+            make_doc("docs", "guides/index.md", "/guides"),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+            make_doc("docs", "guides/deploy/cloud.md", "/guides/deploy/cloud"),
+            make_doc("docs", "guides-old/legacy.md", "/guides-old/legacy"),
+            hidden,
+        ];
+        let source_docs = SourceDocs::new("docs", docs.iter().collect(), HashMap::new(), false);
+
+        let nav_config = vec![
+            NavItem::Path("intro.md".to_string()),
+            NavItem::Path("guides/".to_string()),
+            NavItem::Titled(HashMap::from([(
+                "Cloud".to_string(),
+                "guides/deploy/".to_string(),
+            )])),
+            NavItem::Path("missing/".to_string()),
+        ];
+        let nav = convert_nav_config(&nav_config, &source_docs);
+
+        assert_eq!(nav_titles(&nav), vec!["Intro", "Guides", "Cloud"]);
+        let NavSection::Section { items, .. } = &nav[1] else {
+            panic!("expected Guides section");
+        };
+        assert_eq!(nav_titles(items), vec!["Index", "Setup", "Deploy"]);
+        let NavSection::Section { items, .. } = &nav[2] else {
+            panic!("expected Cloud section");
+        };
+        assert_eq!(nav_titles(items), vec!["Cloud"]);
+    }
+
+    #[test]
+    fn test_convert_nav_config_icons() {
+        let docs = [
+            make_doc("docs", "cli.md", "/cli"),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+        ];
+        let source_docs = SourceDocs::new("docs", docs.iter().collect(), HashMap::new(), false);
+
+        let nav_config: Vec<NavItem> = serde_yaml::from_str(
+            "- { title: CLI Reference, path: cli.md, icon: terminal }\n\
+             - section: Guides\n  icon: book\n  items: [guides/setup.md]\n",
+        )
+        .unwrap();
+        let nav = convert_nav_config(&nav_config, &source_docs);
+
+        let json = serde_json::to_value(&nav).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "type": "link",
+                    "title": "CLI Reference",
+                    "url": "/cli",
+                    "icon": "terminal",
+                    "is_active": false,
+                    "is_active_trail": false
+                },
+                {
+                    "type": "section",
+                    "section": "Guides",
+                    "icon": "book",
+                    "is_active_trail": false,
+                    "items": [{
+                        "type": "link",
+                        "title": "Setup",
+                        "url": "/guides/setup",
+                        "is_active": false,
+                        "is_active_trail": false
+                    }]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_convert_nav_config_dividers_and_labels() {
+        let docs = [
+            make_doc("docs", "intro.md", "/intro"),
+            make_doc("docs", "tuning.md", "/tuning"),
+        ];
+        let source_docs = SourceDocs::new("docs", docs.iter().collect(), HashMap::new(), false);
+
+        let nav_config: Vec<NavItem> = serde_yaml::from_str(
+            "- intro.md\n\
+             - divider: true\n\
+             - label: Advanced topics\n\
+             - tuning.md\n",
+        )
+        .unwrap();
+        let nav = convert_nav_config(&nav_config, &source_docs);
+        assert_eq!(
+            nav_titles(&nav),
+            vec!["Intro", "---", "Advanced topics", "Tuning"]
+        );
+
+        let json = serde_json::to_value(&nav[1..3]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "type": "divider" },
+                { "type": "label", "label": "Advanced topics" }
+            ])
+        );
+
+        // Presentational entries are skipped when moving between pages
+        let (prev, next) = prev_next(&nav, "/intro");
+        assert!(prev.is_none());
+        assert_eq!(next.unwrap().url, "/tuning");
+    }
+
+    #[test]
+    fn test_mark_active() {
+        let docs = [
+            make_doc("docs", "index.md", "/"),
+            make_doc("docs", "guides/index.md", "/guides"),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+            make_doc("docs", "guides/setup/linux.md", "/guides/setup/linux"),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
+
+        let flags = |nav: &[NavSection]| -> Vec<(String, bool, bool)> {
+            let mut flags = Vec::new();
+            let mut stack: Vec<&NavSection> = nav.iter().rev().collect();
+            while let Some(item) = stack.pop() {
+                match item {
+                    NavSection::Section {
+                        section,
+                        items,
+                        is_active_trail,
+                        ..
+                    } => {
+                        flags.push((section.clone(), false, *is_active_trail));
+                        stack.extend(items.iter().rev());
+                    }
+                    NavSection::Link(link) => {
+                        flags.push((link.title.clone(), link.is_active, link.is_active_trail));
+                        stack.extend(link.children.iter().rev());
+                    }
+                    NavSection::Divider | NavSection::Label { .. } => {}
+                }
+            }
+            flags
+        };
+
+        let mut active = nav.clone();
+        assert!(mark_active(&mut active, "/guides/setup/linux"));
+        assert_eq!(
+            flags(&active),
+            vec![
+                ("Index".to_string(), false, false),
+                ("Guides".to_string(), false, true),
+                ("Setup".to_string(), false, true),
+                ("Linux".to_string(), true, false),
+            ]
+        );
+
+        // Section index pages match with or without a trailing slash
+        let mut active = nav.clone();
+        assert!(mark_active(&mut active, "/guides/"));
+        assert_eq!(flags(&active)[1], ("Guides".to_string(), true, false));
+
+        let mut active = nav.clone();
+        assert!(mark_active(&mut active, "/"));
+        assert_eq!(flags(&active)[0], ("Index".to_string(), true, false));
+
+        let mut active = nav;
+        assert!(!mark_active(&mut active, "/missing"));
+    }
+
+    #[test]
+    fn test_nav_report() {
+        let mut hidden = make_doc("docs", "internal.md", "/internal");
+        hidden.front_matter.hidden = true;
+        let docs = [
+            make_doc("docs", "intro.md", "/intro"),
+            make_doc("docs", "install.md", "/install"),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+            make_doc("docs", "faq.md", "/faq"),
+            make_doc("docs", "index.md", "/"),
+            make_doc("docs", "reference/index.md", "/reference"),
+            hidden,
+        ];
+        let source_docs = SourceDocs::new("docs", docs.iter().collect(), HashMap::new(), false);
+
+        let nav_config: Vec<NavItem> = serde_yaml::from_str(
+            "- intro.md\n\
+             - section: Setup\n  items: [instal.md, guides/, old/]\n",
+        )
+        .unwrap();
+        let mut report = NavReport::default();
+        source_docs.check(&nav_config, &mut report);
+
+        assert!(!report.is_ok());
+        let missing: Vec<&str> = report.missing.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(missing, vec!["instal.md", "old/"]);
+        assert_eq!(
+            report.orphans,
+            vec![
+                ("docs".to_string(), PathBuf::from("faq.md")),
+                ("docs".to_string(), PathBuf::from("install.md")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prev_next() {
+        let docs = [
+            make_doc("docs", "index.md", "/"),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+            make_doc("docs", "guides/setup/linux.md", "/guides/setup/linux"),
+            make_doc("docs", "intro.md", "/intro"),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+        let mut nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
+        nav.push(NavSection::Link(NavLink {
+            title: "GitHub".to_string(),
+            url: "https://github.com/undox-rs/undox".to_string(),
+            icon: None,
+            is_active: false,
+            is_active_trail: false,
+            children: vec![],
+        }));
+
+        let urls = |(prev, next): (Option<PageLink>, Option<PageLink>)| {
+            (prev.map(|link| link.url), next.map(|link| link.url))
+        };
+        // Order: /, /intro, then the Guides section: /guides/setup, /guides/setup/linux
+        assert_eq!(
+            urls(prev_next(&nav, "/")),
+            (None, Some("/intro".to_string()))
+        );
+        assert_eq!(
+            urls(prev_next(&nav, "/intro")),
+            (Some("/".to_string()), Some("/guides/setup".to_string()))
+        );
+        assert_eq!(
+            urls(prev_next(&nav, "/guides/setup")),
+            (
+                Some("/intro".to_string()),
+                Some("/guides/setup/linux".to_string())
+            )
+        );
+        // The external link at the end is skipped
+        assert_eq!(
+            urls(prev_next(&nav, "/guides/setup/linux")),
+            (Some("/guides/setup".to_string()), None)
+        );
+        assert_eq!(urls(prev_next(&nav, "/unlisted")), (None, None));
+    }
+
+    #[test]
+    fn test_auto_generate_nav_directory_index_links() {
+        let mut titled_index = make_doc("docs", "reference/index.md", "/reference");
+        titled_index.front_matter.title = Some("API Reference".to_string());
+        let docs = [
+            make_doc("docs", "guides/index.md", "/guides"),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+            titled_index,
+            make_doc("docs", "build.md", "/build"),
+            make_doc("docs", "build/options.md", "/build/options"),
+            make_doc("docs", "misc/notes.md", "/misc/notes"),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
+
+        assert_eq!(
+            nav_titles(&nav),
+            vec!["Build", "Guides", "Misc", "API Reference"]
+        );
+        // `build.md` + `build/` still merge by name
+        let NavSection::Link(build) = &nav[0] else {
+            panic!("expected Build link");
+        };
+        assert_eq!(nav_titles(&build.children), vec!["Options"]);
+        // A directory with an index becomes a link to it
+        let NavSection::Link(guides) = &nav[1] else {
+            panic!("expected Guides link");
+        };
+        assert_eq!(guides.url, "/guides");
+        assert_eq!(nav_titles(&guides.children), vec!["Setup"]);
+        // Without an index it stays a plain section
+        assert!(matches!(&nav[2], NavSection::Section { .. }));
+        let NavSection::Link(reference) = &nav[3] else {
+            panic!("expected API Reference link");
+        };
+        assert_eq!(reference.url, "/reference");
+        assert!(reference.children.is_empty());
+    }
+
+    #[test]
+    fn test_limit_depth() {
+        let docs = [
+            make_doc("docs", "intro.md", "/intro"),
+            make_doc("docs", "guides/index.md", "/guides"),
+            make_doc("docs", "guides/setup.md", "/guides/setup"),
+            make_doc("docs", "guides/setup/linux.md", "/guides/setup/linux"),
+            make_doc("docs", "misc/notes.md", "/misc/notes"),
+            make_doc("docs", "misc/old/faq.md", "/misc/old/faq"),
+        ];
+        let doc_refs: Vec<&Document> = docs.iter().collect();
+        let nav = auto_generate_nav(doc_refs, &HashMap::new(), false);
+
+        // Depth 2: the Guides index link keeps Setup but loses Linux, and
+        // the Misc section keeps Notes but drops the Old section
+        let mut limited = nav.clone();
+        mark_active(&mut limited, "/guides/setup/linux");
+        limit_depth(&mut limited, 2);
+        assert_eq!(nav_titles(&limited), vec!["Intro", "Guides", "Misc"]);
+        let NavSection::Link(guides) = &limited[1] else {
+            panic!("expected Guides link");
+        };
+        assert_eq!(nav_titles(&guides.children), vec!["Setup"]);
+        let NavSection::Link(setup) = &guides.children[0] else {
+            panic!("expected Setup link");
+        };
+        assert!(setup.children.is_empty());
+        // The hidden page's ancestors still show the trail
+        assert!(setup.is_active_trail);
+        let NavSection::Section { items, .. } = &limited[2] else {
+            panic!("expected Misc section");
+        };
+        assert_eq!(nav_titles(items), vec!["Notes"]);
+
+        // Depth 1: index links stay, sections emptied by the limit are dropped
+        let mut limited = nav.clone();
+        limit_depth(&mut limited, 1);
+        assert_eq!(nav_titles(&limited), vec!["Intro", "Guides"]);
+
+        // Limited pages are still reachable through previous/next
+        assert_eq!(
+            prev_next(&nav, "/guides/setup/linux").1.unwrap().url,
+            "/misc/notes"
+        );
+    }
+
+    #[test]
+    fn test_combined_navigation() {
+        let source = |yaml: &str| ResolvedSource {
+            config: serde_yaml::from_str(yaml).unwrap(),
+            local_path: PathBuf::from("/nonexistent"),
+            checkout: None,
+            asset_dirs: Vec::new(),
+        };
+        let sources = [
+            source("name: guide\nurl_prefix: /\nlocal: { path: ./guide }"),
+            source("name: empty\nlocal: { path: ./empty }"),
+            source("name: cli\ntitle: CLI\nnav: [usage.md, install.md]\nlocal: { path: ./cli }"),
+        ];
+        let items: Vec<(ContentItem, PathBuf)> = [
+            make_doc("cli", "install.md", "/cli/install"),
+            make_doc("cli", "usage.md", "/cli/usage"),
+            make_doc("guide", "intro.md", "/intro"),
+        ]
+        .into_iter()
+        .map(|doc| (ContentItem::Document(doc), PathBuf::new()))
+        .collect();
+
+        let (nav_by_source, _) =
+            build_navigation_by_source(&items, &sources, NavMode::Combined, &Diagnostics::new());
+
+        let combined = &nav_by_source["cli"];
+        assert_eq!(nav_titles(combined), vec!["Guide", "CLI"]);
+        // The configured order of the cli source is kept
+        let NavSection::Section { items, .. } = &combined[1] else {
+            panic!("expected CLI section");
+        };
+        assert_eq!(nav_titles(items), vec!["Usage", "Install"]);
+        assert_eq!(nav_titles(&nav_by_source["guide"]), nav_titles(combined));
+    }
+
+    #[test]
+    fn test_report_orphans() {
+        let report = NavReport {
+            missing: Vec::new(),
+            orphans: vec![
+                ("cli".to_string(), PathBuf::from("install.md")),
+                ("cli".to_string(), PathBuf::from("usage.md")),
+            ],
+        };
+
+        // Each orphan is its own warning, about its file
+        let diagnostics = Diagnostics::new();
+        report.report(&diagnostics, OrphanSeverity::Warn);
+        let files: Vec<_> = diagnostics
+            .warnings()
+            .into_iter()
+            .map(|warning| warning.file.unwrap())
+            .collect();
+        assert_eq!(
+            files,
+            [PathBuf::from("install.md"), PathBuf::from("usage.md")]
+        );
+
+        // Or an error each, which don't count as warnings
+        let diagnostics = Diagnostics::new();
+        report.report(&diagnostics, OrphanSeverity::Error);
+        assert_eq!(diagnostics.count(), 0);
+        assert!(
+            diagnostics
+                .warnings()
+                .iter()
+                .all(|warning| warning.error && warning.kind == WarningKind::Nav)
+        );
+        assert_eq!(diagnostics.warnings().len(), 2);
+    }
 }
@@ -7,18 +7,25 @@
 
 use std::path::{Path, PathBuf};
 
+use crate::config::{UrlRewrite, UrlStyle};
+use crate::util::split_numeric_prefix;
+
 /// Convert a markdown file path to a URL path.
 ///
-/// Takes a source-relative path and a URL prefix, produces a URL path.
+/// Takes a source-relative path and a URL prefix, produces a URL path in
+/// the given style.
 ///
 /// # Examples
 /// ```ignore
-/// path_to_url("installation.md", "/cli") => "/cli/installation"
-/// path_to_url("getting-started/quickstart.md", "/cli") => "/cli/getting-started/quickstart"
-/// path_to_url("index.md", "/cli") => "/cli"
-/// path_to_url("index.md", "") => "/"
+/// path_to_url("installation.md", "/cli", Directory) => "/cli/installation"
+/// path_to_url("getting-started/quickstart.md", "/cli", Directory) => "/cli/getting-started/quickstart"
+/// path_to_url("index.md", "/cli", Directory) => "/cli"
+/// path_to_url("index.md", "", Directory) => "/"
+/// path_to_url("installation.md", "/cli", Html) => "/cli/installation.html"
+/// path_to_url("index.md", "/cli", Html) => "/cli/index.html"
+/// path_to_url("installation.md", "/cli", TrailingSlash) => "/cli/installation/"
 /// ```
-pub fn source_path_to_url(path: &Path, url_prefix: &str) -> String {
+pub fn source_path_to_url(path: &Path, url_prefix: &str, style: UrlStyle) -> String {
     let mut url = url_prefix.to_string();
     // Ensure we have a trailing slash for appending the path
     // (empty prefix becomes "/" which is then trimmed if needed)
@@ -31,7 +38,8 @@ pub fn source_path_to_url(path: &Path, url_prefix: &str) -> String {
     let path_str = path_str.replace('\\', "/");
 
     // Handle index files - they become the directory URL
-    let path_str = if path_str.ends_with("/index") || path_str == "index" {
+    let is_index = path_str.ends_with("/index") || path_str == "index";
+    let path_str = if is_index {
         path_str
             .trim_end_matches("/index")
             .trim_end_matches("index")
@@ -52,7 +60,43 @@ pub fn source_path_to_url(path: &Path, url_prefix: &str) -> String {
         url = "/".to_string();
     }
 
-    url
+    styled_url(&url, is_index, style)
+}
+
+/// Write a page's URL in directory style (`/cli/installation`, or `/cli`
+/// for an index page) in the given style.
+///
+/// # Examples
+/// ```ignore
+/// styled_url("/cli/print", false, Html) => "/cli/print.html"
+/// styled_url("/", true, Html) => "/index.html"
+/// styled_url("/cli", true, TrailingSlash) => "/cli/"
+/// ```
+pub fn styled_url(url: &str, is_index: bool, style: UrlStyle) -> String {
+    match style {
+        UrlStyle::Directory => url.to_string(),
+        UrlStyle::TrailingSlash if url.ends_with('/') => url.to_string(),
+        UrlStyle::TrailingSlash => format!("{}/", url),
+        UrlStyle::Html if is_index => format!("{}/index.html", url.trim_end_matches('/')),
+        UrlStyle::Html => format!("{}.html", url.trim_end_matches('/')),
+    }
+}
+
+/// URL of a source's root page, with the site's base path, for links to the
+/// source as a whole (like its tab): `{base}{prefix}/`, or
+/// `{base}{prefix}/index.html` in [`UrlStyle::Html`].
+///
+/// # Examples
+/// ```ignore
+/// source_root_url("/docs", "/cli", Directory) => "/docs/cli/"
+/// source_root_url("", "/", Html) => "/index.html"
+/// ```
+pub fn source_root_url(base_path: &str, url_prefix: &str, style: UrlStyle) -> String {
+    let root = format!("{}{}/", base_path, url_prefix.trim_end_matches('/'));
+    match style {
+        UrlStyle::Html => format!("{}index.html", root),
+        UrlStyle::Directory | UrlStyle::TrailingSlash => root,
+    }
 }
 
 /// Convert a static file path to a URL path.
@@ -78,18 +122,56 @@ pub fn static_path_to_url(path: &Path, url_prefix: &str) -> String {
     url
 }
 
+/// Remove numeric ordering prefixes from every component of a path.
+///
+/// # Examples
+/// ```ignore
+/// strip_numeric_prefixes("10-guides/01-install.md") => "guides/install.md"
+/// ```
+pub fn strip_numeric_prefixes(path: &Path) -> PathBuf {
+    path.iter()
+        .map(|part| {
+            let part = part.to_string_lossy();
+            split_numeric_prefix(&part).1.to_string()
+        })
+        .collect()
+}
+
+/// Move a source path to where its URL should be, by the first rewrite
+/// whose `from` directory contains it, or else by dropping up to `flatten`
+/// leading directories. The file name itself is never dropped.
+///
+/// # Examples
+/// ```ignore
+/// // with `from: reference/api/v2, to: api`
+/// rewrite_source_path("reference/api/v2/users.md", rewrites, 0) => "api/users.md"
+/// rewrite_source_path("docs/guides/install.md", &[], 1) => "guides/install.md"
+/// ```
+pub fn rewrite_source_path(path: &Path, rewrites: &[UrlRewrite], flatten: usize) -> PathBuf {
+    for rewrite in rewrites {
+        if let Ok(rest) = path.strip_prefix(&rewrite.from) {
+            return rewrite.to.join(rest);
+        }
+    }
+
+    let dirs = path.components().count().saturating_sub(1);
+    path.components().skip(flatten.min(dirs)).collect()
+}
+
 /// Convert a URL path to an output file path.
 ///
-/// Documents (no extension) become `path/index.html`.
+/// Documents (no extension) become `path/index.html`, or `path.html` in
+/// [`UrlStyle::Html`]. URLs ending in `/` always become `path/index.html`.
 /// Static files (with extension) keep their path.
 ///
 /// # Examples
 /// ```ignore
-/// url_to_output_path("/cli/installation", output_dir) => output_dir/cli/installation/index.html
-/// url_to_output_path("/", output_dir) => output_dir/index.html
-/// url_to_output_path("/cli/style.css", output_dir) => output_dir/cli/style.css
+/// url_to_output_path("/cli/installation", output_dir, Directory) => output_dir/cli/installation/index.html
+/// url_to_output_path("/cli/installation", output_dir, Html) => output_dir/cli/installation.html
+/// url_to_output_path("/", output_dir, Directory) => output_dir/index.html
+/// url_to_output_path("/cli/style.css", output_dir, Directory) => output_dir/cli/style.css
 /// ```
-pub fn url_to_output_path(url_path: &str, output_dir: &Path) -> PathBuf {
+pub fn url_to_output_path(url_path: &str, output_dir: &Path, style: UrlStyle) -> PathBuf {
     let url_path = url_path.trim_start_matches('/');
 
     if url_path.is_empty() {
@@ -98,12 +180,101 @@ pub fn url_to_output_path(url_path: &str, output_dir: &Path) -> PathBuf {
     } else if url_path.contains('.') {
         // Already has extension (static file)
         output_dir.join(url_path)
+    } else if style == UrlStyle::Html && !url_path.ends_with('/') {
+        output_dir.join(format!("{}.html", url_path))
     } else {
         // Document - create directory with index.html
         output_dir.join(url_path).join("index.html")
     }
 }
 
+/// Join the site URL and a URL path into an absolute URL.
+///
+/// # Examples
+/// ```ignore
+/// absolute_url("https://docs.example.com", "/cli/installation") => "https://docs.example.com/cli/installation"
+/// absolute_url("https://example.com/docs/", "/") => "https://example.com/docs/"
+/// ```
+pub fn absolute_url(site_url: &str, url_path: &str) -> String {
+    format!(
+        "{}/{}",
+        site_url.trim_end_matches('/'),
+        url_path.trim_start_matches('/')
+    )
+}
+
+/// Normalize the `site.base_path` setting to `/segment` form, with a leading
+/// slash and no trailing one. A site served from the root gets `""`.
+///
+/// # Examples
+/// ```ignore
+/// normalize_base_path(Some("docs/")) => "/docs"
+/// normalize_base_path(Some("/")) => ""
+/// ```
+pub fn normalize_base_path(base_path: Option<&str>) -> String {
+    match base_path.map(|p| p.trim().trim_matches('/')) {
+        Some(p) if !p.is_empty() => format!("/{}", p),
+        _ => String::new(),
+    }
+}
+
+/// Prefix a URL path with the site's base path.
+///
+/// The root URL becomes the base path itself, matching how other prefixed
+/// roots (like a source's `/cli`) have no trailing slash.
+///
+/// # Examples
+/// ```ignore
+/// with_base_path("/docs", "/cli/installation") => "/docs/cli/installation"
+/// with_base_path("/docs", "/") => "/docs"
+/// with_base_path("", "/") => "/"
+/// ```
+pub fn with_base_path(base_path: &str, url_path: &str) -> String {
+    if base_path.is_empty() {
+        url_path.to_string()
+    } else if url_path == "/" {
+        base_path.to_string()
+    } else {
+        format!("{}{}", base_path, url_path)
+    }
+}
+
+/// Turn a URL path (without the base path) into a name for file names and
+/// anchors: its segments joined with `-`, or `index` for the root.
+///
+/// # Examples
+/// ```ignore
+/// url_slug("/guide/install/") => "guide-install"
+/// url_slug("/") => "index"
+/// ```
+pub fn url_slug(url_path: &str) -> String {
+    let slug = url_path.trim_matches('/').replace('/', "-");
+    if slug.is_empty() {
+        "index".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Remove the site's base path from a URL path, giving the path relative to
+/// the site root. URLs outside the base path are returned unchanged.
+///
+/// # Examples
+/// ```ignore
+/// strip_base_path("/docs", "/docs/cli/installation") => "/cli/installation"
+/// strip_base_path("/docs", "/docs") => "/"
+/// ```
+pub fn strip_base_path<'a>(base_path: &str, url_path: &'a str) -> &'a str {
+    if base_path.is_empty() {
+        return url_path;
+    }
+    match url_path.strip_prefix(base_path) {
+        Some("") => "/",
+        Some(rest) if rest.starts_with('/') => rest,
+        _ => url_path,
+    }
+}
+
 /// Get the base path from a config file path (its parent directory).
 pub fn base_path_from_config(config_path: &Path) -> PathBuf {
     config_path
@@ -120,7 +291,7 @@ mod tests {
     #[test]
     fn test_source_path_to_url_simple() {
         assert_eq!(
-            source_path_to_url(Path::new("installation.md"), "/cli"),
+            source_path_to_url(Path::new("installation.md"), "/cli", UrlStyle::Directory),
             "/cli/installation"
         );
     }
@@ -128,21 +299,31 @@ mod tests {
     #[test]
     fn test_source_path_to_url_nested() {
         assert_eq!(
-            source_path_to_url(Path::new("getting-started/quickstart.md"), "/cli"),
+            source_path_to_url(
+                Path::new("getting-started/quickstart.md"),
+                "/cli",
+                UrlStyle::Directory
+            ),
             "/cli/getting-started/quickstart"
         );
     }
 
     #[test]
     fn test_source_path_to_url_index() {
-        assert_eq!(source_path_to_url(Path::new("index.md"), "/cli"), "/cli");
-        assert_eq!(source_path_to_url(Path::new("index.md"), ""), "/");
+        assert_eq!(
+            source_path_to_url(Path::new("index.md"), "/cli", UrlStyle::Directory),
+            "/cli"
+        );
+        assert_eq!(
+            source_path_to_url(Path::new("index.md"), "", UrlStyle::Directory),
+            "/"
+        );
     }
 
     #[test]
     fn test_source_path_to_url_nested_index() {
         assert_eq!(
-            source_path_to_url(Path::new("guides/index.md"), "/cli"),
+            source_path_to_url(Path::new("guides/index.md"), "/cli", UrlStyle::Directory),
             "/cli/guides"
         );
     }
@@ -151,11 +332,11 @@ mod tests {
     fn test_source_path_to_url_root_source() {
         // Source with empty url_prefix (root source)
         assert_eq!(
-            source_path_to_url(Path::new("installation.md"), ""),
+            source_path_to_url(Path::new("installation.md"), "", UrlStyle::Directory),
             "/installation"
         );
         assert_eq!(
-            source_path_to_url(Path::new("guides/quickstart.md"), ""),
+            source_path_to_url(Path::new("guides/quickstart.md"), "", UrlStyle::Directory),
             "/guides/quickstart"
         );
     }
@@ -176,7 +357,7 @@ mod tests {
     fn test_url_to_output_path_document() {
         let output = Path::new("/site");
         assert_eq!(
-            url_to_output_path("/cli/installation", output),
+            url_to_output_path("/cli/installation", output, UrlStyle::Directory),
             PathBuf::from("/site/cli/installation/index.html")
         );
     }
@@ -185,20 +366,197 @@ mod tests {
     fn test_url_to_output_path_root() {
         let output = Path::new("/site");
         assert_eq!(
-            url_to_output_path("/", output),
+            url_to_output_path("/", output, UrlStyle::Directory),
             PathBuf::from("/site/index.html")
         );
     }
 
+    #[test]
+    fn test_url_styles() {
+        let url = |path: &str, style| source_path_to_url(Path::new(path), "/cli", style);
+        let output = |url: &str, style| url_to_output_path(url, Path::new("/site"), style);
+        let cases = [
+            (
+                UrlStyle::Directory,
+                ["/cli", "/cli/guides", "/cli/guides/setup"],
+            ),
+            (
+                UrlStyle::Html,
+                [
+                    "/cli/index.html",
+                    "/cli/guides/index.html",
+                    "/cli/guides/setup.html",
+                ],
+            ),
+            (
+                UrlStyle::TrailingSlash,
+                ["/cli/", "/cli/guides/", "/cli/guides/setup/"],
+            ),
+        ];
+        for (style, urls) in cases {
+            let pages = ["index.md", "guides/index.md", "guides/setup.md"];
+            for (page, expected) in pages.into_iter().zip(urls) {
+                assert_eq!(url(page, style), expected, "{:?}", style);
+            }
+            // Every style writes the same files, apart from `.html` pages
+            assert_eq!(
+                output(urls[0], style),
+                PathBuf::from("/site/cli/index.html")
+            );
+            assert_eq!(
+                output(urls[1], style),
+                PathBuf::from("/site/cli/guides/index.html")
+            );
+        }
+        assert_eq!(
+            source_path_to_url(Path::new("index.md"), "", UrlStyle::Html),
+            "/index.html"
+        );
+        assert_eq!(
+            source_path_to_url(Path::new("index.md"), "", UrlStyle::TrailingSlash),
+            "/"
+        );
+        assert_eq!(
+            output("/cli/guides/setup.html", UrlStyle::Html),
+            PathBuf::from("/site/cli/guides/setup.html")
+        );
+        assert_eq!(
+            output("/cli/guides/setup/", UrlStyle::TrailingSlash),
+            PathBuf::from("/site/cli/guides/setup/index.html")
+        );
+        // Generated pages without an extension get one
+        assert_eq!(
+            output("/cli/print", UrlStyle::Html),
+            PathBuf::from("/site/cli/print.html")
+        );
+    }
+
+    #[test]
+    fn test_source_root_url() {
+        assert_eq!(
+            source_root_url("/docs", "/cli", UrlStyle::Directory),
+            "/docs/cli/"
+        );
+        assert_eq!(source_root_url("", "/", UrlStyle::TrailingSlash), "/");
+        assert_eq!(source_root_url("", "/", UrlStyle::Html), "/index.html");
+        assert_eq!(
+            source_root_url("", "/cli/v2", UrlStyle::Html),
+            "/cli/v2/index.html"
+        );
+    }
+
     #[test]
     fn test_url_to_output_path_static() {
         let output = Path::new("/site");
         assert_eq!(
-            url_to_output_path("/cli/style.css", output),
+            url_to_output_path("/cli/style.css", output, UrlStyle::Directory),
             PathBuf::from("/site/cli/style.css")
         );
     }
 
+    #[test]
+    fn test_strip_numeric_prefixes() {
+        assert_eq!(
+            strip_numeric_prefixes(Path::new("10-guides/01_install.md")),
+            PathBuf::from("guides/install.md")
+        );
+        assert_eq!(
+            strip_numeric_prefixes(Path::new("v2/index.md")),
+            PathBuf::from("v2/index.md")
+        );
+    }
+
+    #[test]
+    fn test_rewrite_source_path() {
+        let rewrites = vec![
+            UrlRewrite {
+                from: PathBuf::from("reference/api/v2"),
+                to: PathBuf::from("api"),
+            },
+            UrlRewrite {
+                from: PathBuf::from("internal"),
+                to: PathBuf::new(),
+            },
+        ];
+        assert_eq!(
+            rewrite_source_path(Path::new("reference/api/v2/users.md"), &rewrites, 0),
+            PathBuf::from("api/users.md")
+        );
+        assert_eq!(
+            rewrite_source_path(Path::new("internal/notes.md"), &rewrites, 0),
+            PathBuf::from("notes.md")
+        );
+        // Prefixes match whole directories
+        assert_eq!(
+            rewrite_source_path(Path::new("internals/notes.md"), &rewrites, 0),
+            PathBuf::from("internals/notes.md")
+        );
+
+        assert_eq!(
+            rewrite_source_path(Path::new("docs/guides/install.md"), &[], 1),
+            PathBuf::from("guides/install.md")
+        );
+        assert_eq!(
+            rewrite_source_path(Path::new("docs/guides/install.md"), &[], 5),
+            PathBuf::from("install.md")
+        );
+        assert_eq!(
+            rewrite_source_path(Path::new("index.md"), &[], 2),
+            PathBuf::from("index.md")
+        );
+    }
+
+    #[test]
+    fn test_absolute_url() {
+        assert_eq!(
+            absolute_url("https://docs.example.com", "/cli/installation"),
+            "https://docs.example.com/cli/installation"
+        );
+        assert_eq!(
+            absolute_url("https://docs.example.com/", "/cli/installation"),
+            "https://docs.example.com/cli/installation"
+        );
+        assert_eq!(
+            absolute_url("https://example.com/docs/", "/guide"),
+            "https://example.com/docs/guide"
+        );
+        assert_eq!(
+            absolute_url("https://docs.example.com", "/"),
+            "https://docs.example.com/"
+        );
+        assert_eq!(
+            absolute_url("https://docs.example.com/", "/"),
+            "https://docs.example.com/"
+        );
+    }
+
+    #[test]
+    fn test_base_path() {
+        assert_eq!(normalize_base_path(None), "");
+        assert_eq!(normalize_base_path(Some("/")), "");
+        assert_eq!(normalize_base_path(Some("docs/")), "/docs");
+        assert_eq!(normalize_base_path(Some("/docs/v2")), "/docs/v2");
+
+        assert_eq!(with_base_path("/docs", "/cli/install"), "/docs/cli/install");
+        assert_eq!(with_base_path("/docs", "/"), "/docs");
+        assert_eq!(with_base_path("", "/guide"), "/guide");
+
+        assert_eq!(
+            strip_base_path("/docs", "/docs/cli/install"),
+            "/cli/install"
+        );
+        assert_eq!(strip_base_path("/docs", "/docs"), "/");
+        assert_eq!(strip_base_path("/docs", "/docsite/page"), "/docsite/page");
+        assert_eq!(strip_base_path("", "/guide"), "/guide");
+    }
+
+    #[test]
+    fn test_url_slug() {
+        assert_eq!(url_slug("/guide/install/"), "guide-install");
+        assert_eq!(url_slug("/cli"), "cli");
+        assert_eq!(url_slug("/"), "index");
+    }
+
     #[test]
     fn test_base_path_from_config() {
         assert_eq!(
@@ -1,12 +1,22 @@
 //! Pipeline context for sharing state across stages.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use crate::build::assets::{AssetRef, StaticFileIndex, has_scheme};
+use crate::build::diagnostics::{Diagnostics, WarningKind};
 use crate::build::format::FormatRegistry;
 use crate::build::highlight::SyntaxHighlighter;
-use crate::build::render::{NavSection, Renderer, SiteContext, SourceTab, UndoxContext};
+use crate::build::i18n::Translations;
+use crate::build::paths::{absolute_url, strip_base_path, url_to_output_path, with_base_path};
+use crate::build::pipeline::ProcessingDocument;
+use crate::build::progress::Progress;
+use crate::build::render::{
+    NavSection, Renderer, SiteContext, SourceTab, UndoxContext, VersionLink,
+};
+use crate::build::sizes::SizeReport;
 use crate::config::MarkdownConfig;
+use crate::theme::ThemeConfig;
 
 /// Shared context for pipeline stages.
 ///
@@ -18,19 +28,42 @@ pub struct PipelineContext<'a> {
     pub output_dir: &'a Path,
 
     // === Site-level data ===
-    /// Site metadata (name, URL, favicon)
+    /// Site metadata (name, URL, language, etc.)
     pub site: &'a SiteContext,
 
     /// Theme settings passed to templates
     pub theme_settings: &'a serde_json::Value,
 
+    /// Site-wide values from the `extra` config section
+    pub extra: &'a serde_yaml::Mapping,
+
     /// Markdown processing configuration
     pub markdown_config: &'a MarkdownConfig,
 
+    /// Markdown configuration for sources that override parts of it
+    pub markdown_by_source: &'a HashMap<String, MarkdownConfig>,
+
+    /// Theme configuration (search filters, reading time)
+    pub theme_config: &'a ThemeConfig,
+
     // === Navigation ===
     /// Per-source navigation structure
     pub nav_by_source: &'a HashMap<String, Vec<NavSection>>,
 
+    /// Sidebar depth limits for sources that have one
+    pub nav_depth_by_source: &'a HashMap<String, usize>,
+
+    /// Template of each source's "Edit this page" links, with `{path}` for
+    /// the page's path in the source, for sources whose repository is known
+    pub edit_url_by_source: &'a HashMap<String, String>,
+
+    /// Every version of each versioned source, by the name of the source
+    /// each version is built as
+    pub versions_by_source: &'a HashMap<String, Vec<VersionLink>>,
+
+    /// Sources' languages and the pages in each, for language switchers
+    pub translations: &'a Translations,
+
     /// Source tabs for top-level navigation
     pub source_tabs: &'a [SourceTab],
 
@@ -44,6 +77,21 @@ pub struct PipelineContext<'a> {
     /// Content format registry for rendering different file types
     pub format_registry: &'a FormatRegistry,
 
+    /// Static files, for resolving relative image paths
+    pub static_files: &'a StaticFileIndex,
+
+    /// Collects warnings for the end-of-build summary and `--strict`
+    pub diagnostics: &'a Diagnostics,
+
+    /// Build progress, which stages count documents against
+    pub progress: &'a Progress,
+
+    /// Size of each page written, filled in by the write stage
+    pub sizes: SizeReport,
+
+    /// URLs of the pages whose HTML changed, filled in by the write stage
+    pub changed_pages: Vec<String>,
+
     // === Mode flags ===
     /// Undox context (dev mode, live reload, version)
     pub undox: UndoxContext,
@@ -56,12 +104,22 @@ impl<'a> PipelineContext<'a> {
         output_dir: &'a Path,
         site: &'a SiteContext,
         theme_settings: &'a serde_json::Value,
+        extra: &'a serde_yaml::Mapping,
         markdown_config: &'a MarkdownConfig,
+        markdown_by_source: &'a HashMap<String, MarkdownConfig>,
+        theme_config: &'a ThemeConfig,
         nav_by_source: &'a HashMap<String, Vec<NavSection>>,
+        nav_depth_by_source: &'a HashMap<String, usize>,
+        edit_url_by_source: &'a HashMap<String, String>,
+        versions_by_source: &'a HashMap<String, Vec<VersionLink>>,
+        translations: &'a Translations,
         source_tabs: &'a [SourceTab],
         highlighter: &'a SyntaxHighlighter,
         renderer: &'a mut Renderer,
         format_registry: &'a FormatRegistry,
+        static_files: &'a StaticFileIndex,
+        diagnostics: &'a Diagnostics,
+        progress: &'a Progress,
         dev_mode: bool,
         live_reload: bool,
     ) -> Self {
@@ -69,12 +127,24 @@ impl<'a> PipelineContext<'a> {
             output_dir,
             site,
             theme_settings,
+            extra,
             markdown_config,
+            markdown_by_source,
+            theme_config,
             nav_by_source,
+            nav_depth_by_source,
+            edit_url_by_source,
+            versions_by_source,
+            translations,
             source_tabs,
             highlighter,
             renderer,
             format_registry,
+            static_files,
+            diagnostics,
+            progress,
+            sizes: SizeReport::new(),
+            changed_pages: Vec::new(),
             undox: UndoxContext {
                 dev: dev_mode,
                 live_reload: dev_mode && live_reload,
@@ -91,17 +161,150 @@ impl<'a> PipelineContext<'a> {
             .unwrap_or_default()
     }
 
-    /// Build source tabs with the current source highlighted.
+    /// Get the number of navigation levels the sidebar of a source shows,
+    /// if it is limited.
+    pub fn nav_max_depth_for(&self, source_name: &str) -> Option<usize> {
+        self.nav_depth_by_source.get(source_name).copied()
+    }
+
+    /// Get the "Edit this page" URL of a document: its source's edit URL
+    /// template with its path in the source.
+    pub fn edit_url_for(&self, doc: &ProcessingDocument) -> Option<String> {
+        let template = self.edit_url_by_source.get(doc.source_name())?;
+        let path: Vec<String> = doc
+            .doc
+            .source_path
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        Some(template.replace("{path}", &path.join("/")))
+    }
+
+    /// Get markdown configuration for a specific source.
+    pub fn markdown_config_for(&self, source_name: &str) -> &'a MarkdownConfig {
+        self.markdown_by_source
+            .get(source_name)
+            .unwrap_or(self.markdown_config)
+    }
+
+    /// Get the canonical URL of a document.
+    ///
+    /// The front matter `canonical_url` wins; otherwise the page URL is
+    /// joined to the site URL, if one is configured.
+    pub fn canonical_url_for(&self, doc: &ProcessingDocument) -> Option<String> {
+        doc.doc
+            .front_matter
+            .canonical_url
+            .clone()
+            .or_else(|| self.absolute_url_for(doc.url_path()))
+    }
+
+    /// Get the URL of a document's social media image: its `image` front
+    /// matter, resolved like an image in the page, else its generated social
+    /// card, else `site.social_image`.
+    ///
+    /// The URL is absolute when a site URL is configured. A missing image is
+    /// warned about and falls back to the site's.
+    pub fn image_url_for(&self, doc: &ProcessingDocument) -> Option<String> {
+        let page_image = doc.doc.front_matter.image.as_deref().and_then(|image| {
+            let assets = self
+                .static_files
+                .for_document(&doc.source_path, &doc.doc.source_path);
+            match assets.resolve(image) {
+                AssetRef::Found(url) => Some(url),
+                AssetRef::Unchanged => Some(image.to_string()),
+                AssetRef::Missing => {
+                    self.diagnostics.warn(
+                        WarningKind::Image,
+                        Some(&doc.doc.source_path),
+                        format!(
+                            "Image not found in {}: {}",
+                            doc.doc.source_path.display(),
+                            image
+                        ),
+                    );
+                    None
+                }
+            }
+        });
+
+        let image = page_image
+            .or_else(|| {
+                let card = doc.social_card.as_ref()?;
+                Some(with_base_path(&self.site.base_path, &format!("/{}", card)))
+            })
+            .or_else(|| self.site.social_image.clone())?;
+        if has_scheme(&image) {
+            return Some(image);
+        }
+        Some(self.absolute_url_for(&image).unwrap_or(image))
+    }
+
+    /// Join a URL path to the site URL, if one is configured.
+    ///
+    /// The site URL already includes the base path, so it is removed from
+    /// `url_path` first.
+    pub fn absolute_url_for(&self, url_path: &str) -> Option<String> {
+        let site_url = self.site.url.as_ref()?;
+        Some(absolute_url(
+            site_url,
+            strip_base_path(&self.site.base_path, url_path),
+        ))
+    }
+
+    /// Get the file a URL path is written to in the output directory.
+    pub fn output_path_for(&self, url_path: &str) -> PathBuf {
+        url_to_output_path(
+            strip_base_path(&self.site.base_path, url_path),
+            self.output_dir,
+            self.site.url_style,
+        )
+    }
+
+    /// Get the language of a source's pages: the source's, else the site's.
+    pub fn language_for(&self, source_name: &str) -> Option<String> {
+        self.translations
+            .language_of(source_name)
+            .map(str::to_string)
+            .or_else(|| self.site.language.clone())
+    }
+
+    /// Get the site context for a source's pages, with the source's
+    /// language.
+    pub fn site_for(&self, source_name: &str) -> SiteContext {
+        SiteContext {
+            language: self.language_for(source_name),
+            ..self.site.clone()
+        }
+    }
+
+    /// Build source tabs with the current source highlighted. Sources in
+    /// other languages than the current source's are left out.
     pub fn source_tabs_for(&self, current_source: &str) -> Vec<SourceTab> {
+        let language = self.translations.language_of(current_source);
+        // Every version of a source is under the latest version's tab
+        let current_source = self
+            .versions_for(current_source)
+            .into_iter()
+            .find(|version| version.is_latest)
+            .map_or(current_source.to_string(), |version| version.source_id);
         self.source_tabs
             .iter()
+            .filter(|tab| {
+                language.is_none() || tab.language.is_none() || tab.language.as_deref() == language
+            })
             .map(|tab| SourceTab {
-                name: tab.name.clone(),
-                source_id: tab.source_id.clone(),
-                url: tab.url.clone(),
                 is_current: tab.source_id == current_source,
-                is_top_level: tab.is_top_level,
+                ..tab.clone()
             })
             .collect()
     }
+
+    /// Get the versions of a source, with its own marked current.
+    pub fn versions_for(&self, source_name: &str) -> Vec<VersionLink> {
+        self.versions_by_source
+            .get(source_name)
+            .cloned()
+            .unwrap_or_default()
+    }
 }
@@ -2,8 +2,15 @@
 
 use std::path::PathBuf;
 
+use chrono::{DateTime, FixedOffset};
+
 use crate::build::document::Document;
+use crate::build::git_history::Contributor;
 use crate::build::render::TocEntry;
+use crate::util::{html_to_text, truncate_text};
+
+/// Maximum length of a description taken from the page content.
+const DESCRIPTION_MAX_CHARS: usize = 160;
 
 /// A document being processed through the pipeline.
 ///
@@ -12,7 +19,8 @@ use crate::build::render::TocEntry;
 ///
 /// 1. Initially: `content` = raw markdown, `toc` = empty
 /// 2. After tera: `content` = processed markdown (macros expanded)
-/// 3. After markdown: `content` = HTML, `toc` = populated
+/// 3. After markdown: `content` = HTML, `toc` = populated, `markdown` =
+///    the processed markdown
 /// 4. After template: `output_html` = final page HTML
 #[derive(Debug)]
 pub struct ProcessingDocument {
@@ -30,11 +38,39 @@ pub struct ProcessingDocument {
     /// After markdown stage: HTML fragment (just the content, no page wrapper).
     pub content: String,
 
+    /// Content as it was before being rendered to HTML (after the tera
+    /// stage), for output that wants the source text.
+    ///
+    /// Empty until the markdown stage populates it.
+    pub markdown: String,
+
     /// Table of contents extracted during markdown rendering.
     ///
     /// Empty until the markdown stage populates it.
     pub toc: Vec<TocEntry>,
 
+    /// Whether the rendered content contains math.
+    ///
+    /// False until the markdown stage populates it.
+    pub has_math: bool,
+
+    /// When the document's file last changed, with `site.git_dates`.
+    ///
+    /// Set by the builder before the pipeline runs.
+    pub last_updated: Option<DateTime<FixedOffset>>,
+
+    /// People who committed to the document's file, with
+    /// `site.git_contributors`.
+    ///
+    /// Set by the builder before the pipeline runs.
+    pub contributors: Vec<Contributor>,
+
+    /// Where the document's generated social card goes, relative to the
+    /// output directory, with `site.social_cards` and no `image`.
+    ///
+    /// Set by the builder before the pipeline runs.
+    pub social_card: Option<String>,
+
     /// Final HTML output after template rendering.
     ///
     /// None until the template stage populates it.
@@ -49,7 +85,12 @@ impl ProcessingDocument {
             doc,
             source_path,
             content,
+            markdown: String::new(),
             toc: Vec::new(),
+            has_math: false,
+            last_updated: None,
+            contributors: Vec::new(),
+            social_card: None,
             output_html: None,
         }
     }
@@ -68,4 +109,107 @@ impl ProcessingDocument {
     pub fn title(&self) -> String {
         self.doc.title()
     }
+
+    /// Get the table of contents to show on the page.
+    ///
+    /// Empty when the front matter sets `toc: false`.
+    pub fn page_toc(&self) -> Vec<TocEntry> {
+        if self.doc.front_matter.toc == Some(false) {
+            Vec::new()
+        } else {
+            self.toc.clone()
+        }
+    }
+
+    /// Get the page description.
+    ///
+    /// Uses the front matter `description` if set, otherwise the text of the
+    /// first paragraph of the rendered content, shortened to about 160
+    /// characters. Only meaningful after the markdown stage.
+    pub fn description(&self) -> Option<String> {
+        if let Some(description) = &self.doc.front_matter.description {
+            return Some(description.clone());
+        }
+
+        let mut rest = self.content.as_str();
+        while let Some(start) = rest.find("<p>") {
+            let after = &rest[start + "<p>".len()..];
+            let end = after.find("</p>").unwrap_or(after.len());
+            let text = html_to_text(&after[..end]);
+            if !text.is_empty() {
+                return Some(truncate_text(&text, DESCRIPTION_MAX_CHARS));
+            }
+            rest = &after[end..];
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::document::FrontMatter;
+
+    fn doc(front_matter: FrontMatter, html: &str) -> ProcessingDocument {
+        let document = Document::new(
+            "docs".to_string(),
+            PathBuf::from("page.md"),
+            "/page".to_string(),
+            front_matter,
+            String::new(),
+        );
+        let mut doc = ProcessingDocument::new(document, PathBuf::from("/tmp/docs"));
+        doc.content = html.to_string();
+        doc.toc = vec![TocEntry {
+            level: 2,
+            id: "usage".to_string(),
+            text: "Usage".to_string(),
+        }];
+        doc
+    }
+
+    #[test]
+    fn test_description_from_first_paragraph() {
+        let html = "<h1 id=\"intro\">Intro</h1>\n<p></p>\n\
+                    <p>Install <strong>undox</strong> with <code>cargo</code> &amp; go.</p>\n\
+                    <p>Second paragraph.</p>";
+        let doc = doc(FrontMatter::default(), html);
+        assert_eq!(
+            doc.description().as_deref(),
+            Some("Install undox with cargo & go.")
+        );
+
+        let long = format!("<p>{}</p>", "word ".repeat(50));
+        let description = self::doc(FrontMatter::default(), &long)
+            .description()
+            .unwrap();
+        assert!(description.chars().count() <= DESCRIPTION_MAX_CHARS);
+        assert!(description.ends_with("word…"));
+
+        assert_eq!(
+            self::doc(FrontMatter::default(), "<h1>Only</h1>").description(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_front_matter_description_wins() {
+        let front_matter = FrontMatter {
+            description: Some("Explicit".to_string()),
+            ..Default::default()
+        };
+        let doc = doc(front_matter, "<p>From content</p>");
+        assert_eq!(doc.description().as_deref(), Some("Explicit"));
+    }
+
+    #[test]
+    fn test_toc_disabled_by_front_matter() {
+        assert_eq!(doc(FrontMatter::default(), "").page_toc().len(), 1);
+
+        let front_matter = FrontMatter {
+            toc: Some(false),
+            ..Default::default()
+        };
+        assert!(doc(front_matter, "").page_toc().is_empty());
+    }
 }
@@ -1,10 +1,16 @@
 //! Build pipeline for document processing.
 //!
 //! The pipeline transforms documents through a series of stages:
-//! 1. Tera processing (macro expansion in markdown)
-//! 2. Markdown rendering (to HTML with TOC)
-//! 3. Template rendering (page template wrapper)
-//! 4. File writing (output to disk)
+//! 1. Snippet includes (inline other files)
+//! 2. Tera processing (macro expansion in markdown)
+//! 3. Markdown rendering (to HTML with TOC)
+//! 4. Template rendering (page template wrapper)
+//! 5. File writing (output to disk)
+//! 6. Alias redirects (pages for old URLs)
+//!
+//! Finalize stages then write build-wide output such as site redirects,
+//! feeds, `robots.txt`, the 404 page, print views and their PDFs, social
+//! cards, and the build manifest.
 //!
 //! Custom stages can be inserted before or after any named stage.
 //! Build-wide stages run after all documents are processed.
@@ -14,11 +20,19 @@ mod document;
 mod error;
 mod stages;
 
+use crate::build::timing::Timings;
+
 pub use context::PipelineContext;
 pub use document::ProcessingDocument;
 pub use error::PipelineError;
 
-use stages::{MarkdownStage, TemplateStage, TeraStage, WriteStage};
+#[cfg(feature = "social-cards")]
+pub use stages::SocialCardStage;
+use stages::{AliasStage, IncludeStage, MarkdownStage, TemplateStage, TeraStage, WriteStage};
+pub use stages::{
+    FeedStage, HtmlRewriteStage, LlmsStage, MANIFEST_PATH, ManifestStage, NotFoundStage, PdfStage,
+    PrintStage, RedirectStage, RobotsStage,
+};
 
 /// A stage in the document processing pipeline.
 ///
@@ -54,13 +68,17 @@ pub trait FinalizeStage: Send + Sync {
     fn name(&self) -> &'static str;
 
     /// Run finalization after all documents are processed and written.
-    fn finalize(&self, ctx: &PipelineContext) -> Result<(), PipelineError>;
+    fn finalize(
+        &self,
+        docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError>;
 }
 
 /// The document processing pipeline.
 ///
 /// Orchestrates document transformation through a series of stages.
-/// The default pipeline includes: tera → markdown → template → write.
+/// The default pipeline includes: include → tera → markdown → template → write → aliases.
 ///
 /// # Extension Points
 ///
@@ -93,13 +111,15 @@ impl Pipeline {
 
     /// Create the default pipeline with standard stages.
     ///
-    /// Stages: tera → markdown → template → write
+    /// Stages: include → tera → markdown → template → write → aliases
     pub fn default_pipeline() -> Self {
         let mut pipeline = Self::new();
+        pipeline.add_stage(IncludeStage);
         pipeline.add_stage(TeraStage);
         pipeline.add_stage(MarkdownStage);
         pipeline.add_stage(TemplateStage);
         pipeline.add_stage(WriteStage);
+        pipeline.add_stage(AliasStage);
         pipeline
     }
 
@@ -130,7 +150,6 @@ impl Pipeline {
     /// # Panics
     ///
     /// Panics if no stage with the given name exists.
-    #[allow(dead_code)]
     pub fn insert_after<S: Stage + 'static>(&mut self, name: &str, stage: S) -> &mut Self {
         let pos = self
             .stages
@@ -142,7 +161,6 @@ impl Pipeline {
     }
 
     /// Add a finalize stage (runs after all documents are processed).
-    #[allow(dead_code)]
     pub fn add_finalize_stage<S: FinalizeStage + 'static>(&mut self, stage: S) -> &mut Self {
         self.finalize_stages.push(Box::new(stage));
         self
@@ -153,15 +171,23 @@ impl Pipeline {
         &self,
         docs: &mut [ProcessingDocument],
         ctx: &mut PipelineContext,
+        timings: &mut Timings,
     ) -> Result<(), PipelineError> {
-        // Run each stage in sequence
+        // Run each stage in sequence, counting documents as stages process
+        // them
         for stage in &self.stages {
-            stage.process(docs, ctx)?;
+            ctx.progress.start(stage.name(), docs.len());
+            timings.time(format!("stage: {}", stage.name()), || {
+                stage.process(docs, ctx)
+            })?;
         }
+        ctx.progress.finish();
 
         // Run finalize stages
         for stage in &self.finalize_stages {
-            stage.finalize(ctx)?;
+            timings.time(format!("finalize: {}", stage.name()), || {
+                stage.finalize(docs, ctx)
+            })?;
         }
 
         Ok(())
@@ -0,0 +1,43 @@
+//! Alias redirect stage.
+//!
+//! Writes a redirect page for every alias listed in document front matter.
+
+use crate::build::aliases::{collect_aliases, redirect_html};
+use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument, Stage};
+
+/// Stage that writes redirect pages for document aliases.
+///
+/// Each alias in a document's `aliases` front matter becomes a page at that
+/// URL which redirects to the document. The build fails if an alias is the
+/// URL of a real page or is claimed by more than one document.
+pub struct AliasStage;
+
+impl Stage for AliasStage {
+    fn name(&self) -> &'static str {
+        "aliases"
+    }
+
+    fn process(
+        &self,
+        docs: &mut [ProcessingDocument],
+        ctx: &mut PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let aliases =
+            collect_aliases(docs).map_err(|e| PipelineError::stage("aliases", e.to_string()))?;
+
+        for alias in aliases {
+            // Canonical links should be absolute when the site URL is known
+            let canonical = ctx
+                .absolute_url_for(&alias.target)
+                .unwrap_or_else(|| alias.target.clone());
+
+            let output_path = ctx.output_path_for(&alias.url);
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&output_path, redirect_html(&alias.target, &canonical))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! Atom feed stage.
+//!
+//! Writes the feed configured in `feed`.
+
+use crate::build::dates::parse_timestamp;
+use crate::build::diagnostics::WarningKind;
+use crate::build::feed::{Feed, FeedContent, FeedEntry};
+use crate::build::paths::{absolute_url, strip_base_path};
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+use crate::config::FeedConfig;
+
+/// Stage that writes an Atom feed of one source's pages.
+///
+/// Pages are listed newest first by their `date` front matter; pages
+/// without a date are left out with a warning. Feeds need absolute URLs, so
+/// nothing is written when `site.url` isn't set.
+pub struct FeedStage {
+    config: FeedConfig,
+}
+
+impl FeedStage {
+    pub fn new(config: FeedConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl FinalizeStage for FeedStage {
+    fn name(&self) -> &'static str {
+        "feed"
+    }
+
+    fn finalize(
+        &self,
+        docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let Some(site_url) = &ctx.site.url else {
+            ctx.diagnostics.warn(
+                WarningKind::Feed,
+                None,
+                "Skipping the feed because site.url isn't set",
+            );
+            return Ok(());
+        };
+
+        let mut entries = Vec::new();
+        for doc in docs
+            .iter()
+            .filter(|doc| doc.source_name() == self.config.source)
+        {
+            let front_matter = &doc.doc.front_matter;
+            let Some(published) = front_matter.date.as_deref().and_then(parse_timestamp) else {
+                ctx.diagnostics.warn(
+                    WarningKind::Feed,
+                    Some(&doc.doc.source_path),
+                    format!(
+                        "{} in source '{}' has no date and is left out of the feed",
+                        doc.doc.source_path.display(),
+                        self.config.source
+                    ),
+                );
+                continue;
+            };
+            let updated = front_matter
+                .updated
+                .as_deref()
+                .and_then(parse_timestamp)
+                .unwrap_or(published);
+            let content = if self.config.full_content {
+                Some(FeedContent::Html(doc.content.clone()))
+            } else {
+                doc.description().map(FeedContent::Summary)
+            };
+
+            entries.push(FeedEntry {
+                title: doc.title(),
+                url: absolute_url(
+                    site_url,
+                    strip_base_path(&ctx.site.base_path, doc.url_path()),
+                ),
+                published,
+                updated,
+                content,
+            });
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.published));
+        entries.truncate(self.config.limit);
+
+        let feed = Feed {
+            title: self
+                .config
+                .title
+                .clone()
+                .unwrap_or_else(|| ctx.site.name.clone()),
+            author: ctx
+                .site
+                .author
+                .clone()
+                .unwrap_or_else(|| ctx.site.name.clone()),
+            site_url: site_url.clone(),
+            feed_url: absolute_url(site_url, &self.config.output),
+            entries,
+        };
+
+        let output_path = ctx.output_dir.join(&self.config.output);
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&output_path, feed.to_atom())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+//! HTML rewriting stage.
+//!
+//! Applies the `html.rules` from the config to each page's final HTML, with
+//! a streaming rewriter, so rules don't need to be built into themes.
+
+use lol_html::html_content::{ContentType, Element};
+use lol_html::{ElementContentHandlers, RewriteStrSettings, Selector, element, rewrite_str};
+use std::borrow::Cow;
+
+use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument, Stage};
+use crate::config::{HtmlRule, InjectPosition};
+
+/// Stage that rewrites the HTML of rendered pages.
+///
+/// Runs after the template stage, on `doc.output_html`, applying each rule
+/// in config order.
+pub struct HtmlRewriteStage {
+    rules: Vec<HtmlRule>,
+}
+
+impl HtmlRewriteStage {
+    pub fn new(rules: Vec<HtmlRule>) -> Self {
+        Self { rules }
+    }
+}
+
+impl Stage for HtmlRewriteStage {
+    fn name(&self) -> &'static str {
+        "html_rewrite"
+    }
+
+    fn process(
+        &self,
+        docs: &mut [ProcessingDocument],
+        ctx: &mut PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let site_url = ctx.site.url.as_deref();
+        for doc in docs {
+            ctx.progress.tick();
+            if let Some(html) = &doc.output_html {
+                doc.output_html = Some(rewrite(html, &self.rules, site_url)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+type Handler<'h> = (Cow<'static, Selector>, ElementContentHandlers<'h>);
+
+/// Apply `rules` to a page. Links under `site_url` aren't external.
+fn rewrite(
+    html: &str,
+    rules: &[HtmlRule],
+    site_url: Option<&str>,
+) -> Result<String, PipelineError> {
+    let handlers: Vec<Handler> = rules
+        .iter()
+        .map(|rule| match rule {
+            HtmlRule::WrapTables(rule) => {
+                let open = format!("<div class=\"{}\">", rule.class);
+                element!("table", move |el| {
+                    el.before(&open, ContentType::Html);
+                    el.after("</div>", ContentType::Html);
+                    Ok(())
+                })
+            }
+            HtmlRule::ExternalLinks(rule) => element!("a[href]", move |el| {
+                let href = el.get_attribute("href").unwrap_or_default();
+                let is_web = ["http://", "https://", "//"]
+                    .iter()
+                    .any(|prefix| href.starts_with(prefix));
+                let leaves_site = is_web
+                    && site_url.is_none_or(|url| !href.starts_with(url.trim_end_matches('/')));
+                if leaves_site {
+                    set_missing(el, "rel", &rule.rel)?;
+                    set_missing(el, "target", &rule.target)?;
+                }
+                Ok(())
+            }),
+            HtmlRule::Inject(rule) => {
+                let selector = match rule.position {
+                    InjectPosition::Head => "head",
+                    InjectPosition::Body => "body",
+                };
+                element!(selector, move |el| {
+                    el.append(&rule.html, ContentType::Html);
+                    Ok(())
+                })
+            }
+        })
+        .collect();
+
+    rewrite_str(
+        html,
+        RewriteStrSettings {
+            element_content_handlers: handlers,
+            ..RewriteStrSettings::new()
+        },
+    )
+    .map_err(|e| PipelineError::stage("html_rewrite", e.to_string()))
+}
+
+/// Set an attribute the element doesn't have yet, unless `value` is empty.
+fn set_missing(
+    el: &mut Element,
+    name: &str,
+    value: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !value.is_empty() && !el.has_attribute(name) {
+        el.set_attribute(name, value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RootConfig;
+
+    fn rules(yaml: &str) -> Vec<HtmlRule> {
+        let config: RootConfig = serde_yaml::from_str(&format!(
+            "site: {{ name: Docs }}\nsources: []\nhtml: {{ rules: {} }}",
+            yaml
+        ))
+        .unwrap();
+        config.html.rules
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let rules = rules(
+            "[wrap_tables, external_links: { target: '' }, \
+             inject: { position: head, html: '<script src=\"/a.js\"></script>' }]",
+        );
+        let html = concat!(
+            "<html><head><title>T</title></head><body>",
+            "<table><tr><td>1</td></tr></table>",
+            r#"<a href="https://example.com/">Elsewhere</a>"#,
+            r#"<a href="https://docs.example.com/guide" rel="me">Home</a>"#,
+            r#"<a href="/guide">Guide</a>"#,
+            "</body></html>",
+        );
+        assert_eq!(
+            rewrite(html, &rules, Some("https://docs.example.com/")).unwrap(),
+            concat!(
+                r#"<html><head><title>T</title><script src="/a.js"></script></head><body>"#,
+                r#"<div class="table-wrapper"><table><tr><td>1</td></tr></table></div>"#,
+                r#"<a href="https://example.com/" rel="noopener noreferrer">Elsewhere</a>"#,
+                r#"<a href="https://docs.example.com/guide" rel="me">Home</a>"#,
+                r#"<a href="/guide">Guide</a>"#,
+                "</body></html>",
+            )
+        );
+    }
+
+    #[test]
+    fn test_unknown_rule() {
+        let error = serde_yaml::from_str::<RootConfig>(
+            "site: { name: Docs }\nsources: []\nhtml: { rules: [wrap_table] }",
+        )
+        .unwrap_err();
+        // Rules that need settings can't be given by name alone
+        assert!(
+            serde_yaml::from_str::<RootConfig>(
+                "site: { name: Docs }\nsources: []\nhtml: { rules: [inject] }",
+            )
+            .is_err()
+        );
+        assert!(
+            error.to_string().contains("unknown variant `wrap_table`"),
+            "{}",
+            error
+        );
+    }
+}
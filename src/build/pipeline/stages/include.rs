@@ -0,0 +1,38 @@
+//! Snippet include stage.
+//!
+//! Expands `--8<-- "path"` directives before any other processing, so
+//! included content goes through Tera and markdown like the rest of the page.
+
+use crate::build::include::expand_includes;
+use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument, Stage};
+
+/// Stage that inlines the contents of other files into documents.
+///
+/// Paths are resolved relative to the document's source root. Errors
+/// (missing files, unknown regions, recursive includes) fail the build and
+/// name the including document.
+pub struct IncludeStage;
+
+impl Stage for IncludeStage {
+    fn name(&self) -> &'static str {
+        "include"
+    }
+
+    fn process(
+        &self,
+        docs: &mut [ProcessingDocument],
+        ctx: &mut PipelineContext,
+    ) -> Result<(), PipelineError> {
+        for doc in docs {
+            ctx.progress.tick();
+            doc.content = expand_includes(&doc.content, &doc.source_path).map_err(|e| {
+                PipelineError::stage(
+                    "include",
+                    format!("{}: {}", doc.doc.source_path.display(), e),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
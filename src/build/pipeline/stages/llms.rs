@@ -0,0 +1,169 @@
+//! llms.txt stage.
+//!
+//! With `site.llms`, writes `llms.txt`, an index of the site's pages for AI
+//! tools, and `llms-full.txt`, every page's markdown in one file.
+
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+
+/// Stage that writes `llms.txt` and `llms-full.txt`.
+///
+/// Pages are grouped by source, in tab order, and sorted by URL within a
+/// source. Hidden and draft pages are left out.
+pub struct LlmsStage {
+    /// `(name, title)` of each source, in order
+    sources: Vec<(String, String)>,
+}
+
+impl LlmsStage {
+    pub fn new(sources: Vec<(String, String)>) -> Self {
+        Self { sources }
+    }
+}
+
+/// A source's pages, as listed in the files.
+struct Section<'a> {
+    title: &'a str,
+    pages: Vec<Page<'a>>,
+}
+
+struct Page<'a> {
+    title: String,
+    /// Absolute URL when the site URL is known
+    url: String,
+    description: Option<String>,
+    markdown: &'a str,
+}
+
+impl FinalizeStage for LlmsStage {
+    fn name(&self) -> &'static str {
+        "llms"
+    }
+
+    fn finalize(
+        &self,
+        docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let sections: Vec<Section> = self
+            .sources
+            .iter()
+            .map(|(name, title)| {
+                let mut source_docs: Vec<&ProcessingDocument> = docs
+                    .iter()
+                    .filter(|doc| doc.source_name() == name)
+                    .filter(|doc| !doc.doc.front_matter.hidden && !doc.doc.front_matter.draft)
+                    .collect();
+                source_docs.sort_by(|a, b| a.url_path().cmp(b.url_path()));
+                let pages = source_docs
+                    .into_iter()
+                    .map(|doc| Page {
+                        title: doc.title(),
+                        url: ctx
+                            .absolute_url_for(doc.url_path())
+                            .unwrap_or_else(|| doc.url_path().to_string()),
+                        description: doc.description(),
+                        markdown: &doc.markdown,
+                    })
+                    .collect();
+                Section { title, pages }
+            })
+            .filter(|section: &Section| !section.pages.is_empty())
+            .collect();
+
+        std::fs::write(
+            ctx.output_dir.join("llms.txt"),
+            llms_txt(&ctx.site.name, ctx.site.description.as_deref(), &sections),
+        )?;
+        std::fs::write(
+            ctx.output_dir.join("llms-full.txt"),
+            llms_full_txt(&sections),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The `llms.txt` index: the site name and description, then a list of
+/// links to the pages under a heading per source.
+fn llms_txt(site_name: &str, description: Option<&str>, sections: &[Section]) -> String {
+    let mut out = format!("# {}\n", site_name);
+    if let Some(description) = description {
+        out.push_str(&format!("\n> {}\n", description));
+    }
+    for section in sections {
+        out.push_str(&format!("\n## {}\n\n", section.title));
+        for page in &section.pages {
+            out.push_str(&format!("- [{}]({})", page.title, page.url));
+            if let Some(description) = &page.description {
+                out.push_str(&format!(": {}", description));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// The `llms-full.txt` file: each page's title, URL, and markdown.
+fn llms_full_txt(sections: &[Section]) -> String {
+    sections
+        .iter()
+        .flat_map(|section| &section.pages)
+        .map(|page| {
+            format!(
+                "# {}\n\nURL: {}\n\n{}\n",
+                page.title,
+                page.url,
+                page.markdown.trim()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n---\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_llms_files() {
+        let page = |title: &str, url: &str, description: Option<&str>, markdown| Page {
+            title: title.to_string(),
+            url: url.to_string(),
+            description: description.map(str::to_string),
+            markdown,
+        };
+        let sections = vec![
+            Section {
+                title: "Guide",
+                pages: vec![
+                    page(
+                        "Home",
+                        "https://example.com/",
+                        Some("Start here"),
+                        "Welcome.\n",
+                    ),
+                    page("Install", "https://example.com/install", None, "Run it."),
+                ],
+            },
+            Section {
+                title: "API",
+                pages: vec![page("Client", "https://example.com/api/client", None, "")],
+            },
+        ];
+
+        assert_eq!(
+            llms_txt("Docs", Some("All about it"), &sections),
+            "# Docs\n\n> All about it\n\n\
+             ## Guide\n\n\
+             - [Home](https://example.com/): Start here\n\
+             - [Install](https://example.com/install)\n\n\
+             ## API\n\n\
+             - [Client](https://example.com/api/client)\n"
+        );
+        assert_eq!(
+            llms_full_txt(&sections[..1]),
+            "# Home\n\nURL: https://example.com/\n\nWelcome.\n\n---\n\n\
+             # Install\n\nURL: https://example.com/install\n\nRun it.\n"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Build manifest stage.
+//!
+//! Writes `_undox/manifest.json`, a machine-readable list of everything the
+//! build produced, for CI and tools such as link checkers and deploy diffs.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::build::document::StaticFile;
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+use crate::util::word_count;
+
+/// Where the manifest is written, relative to the output directory.
+pub const MANIFEST_PATH: &str = "_undox/manifest.json";
+
+/// Stage that writes the build manifest.
+pub struct ManifestStage {
+    static_files: Vec<StaticFile>,
+    /// `(source, URL)` of each PDF that may have been written
+    pdfs: Vec<(String, String)>,
+}
+
+impl ManifestStage {
+    pub fn new(static_files: Vec<StaticFile>) -> Self {
+        Self {
+            static_files,
+            pdfs: Vec::new(),
+        }
+    }
+
+    /// List the PDFs among these that were written.
+    pub fn with_pdfs(mut self, pdfs: Vec<(String, String)>) -> Self {
+        self.pdfs = pdfs;
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    undox_version: &'a str,
+    /// When the build ran, as an RFC 3339 timestamp
+    built_at: String,
+    documents: Vec<ManifestDocument<'a>>,
+    static_files: Vec<ManifestStaticFile<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pdfs: Vec<ManifestPdf<'a>>,
+}
+
+#[derive(Serialize)]
+struct ManifestDocument<'a> {
+    source: &'a str,
+    /// Path relative to the source root
+    source_path: &'a Path,
+    url: &'a str,
+    /// File written, relative to the output directory
+    output_file: String,
+    title: String,
+    description: Option<String>,
+    /// When the page's file last changed, with `site.git_dates`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_updated: Option<String>,
+    word_count: usize,
+    /// Front matter fields undox doesn't use itself
+    extra: BTreeMap<&'a str, &'a serde_yaml::Value>,
+}
+
+#[derive(Serialize)]
+struct ManifestStaticFile<'a> {
+    source: &'a str,
+    source_path: &'a Path,
+    url: &'a str,
+}
+
+#[derive(Serialize)]
+struct ManifestPdf<'a> {
+    source: &'a str,
+    url: &'a str,
+}
+
+impl FinalizeStage for ManifestStage {
+    fn name(&self) -> &'static str {
+        "manifest"
+    }
+
+    fn finalize(
+        &self,
+        docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let documents = docs
+            .iter()
+            .map(|doc| {
+                let output_path = ctx.output_path_for(doc.url_path());
+                let output_file = output_path
+                    .strip_prefix(ctx.output_dir)
+                    .unwrap_or(&output_path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                ManifestDocument {
+                    source: doc.source_name(),
+                    source_path: &doc.doc.source_path,
+                    url: doc.url_path(),
+                    output_file,
+                    title: doc.title(),
+                    description: doc.description(),
+                    last_updated: doc.last_updated.map(|date| date.to_rfc3339()),
+                    word_count: word_count(&doc.content),
+                    extra: doc
+                        .doc
+                        .front_matter
+                        .extra
+                        .iter()
+                        .map(|(key, value)| (key.as_str(), value))
+                        .collect(),
+                }
+            })
+            .collect();
+        let static_files = self
+            .static_files
+            .iter()
+            .map(|file| ManifestStaticFile {
+                source: &file.source_name,
+                source_path: &file.source_path,
+                url: &file.output_path,
+            })
+            .collect();
+        let pdfs = self
+            .pdfs
+            .iter()
+            .filter(|(_, url)| ctx.output_path_for(url).is_file())
+            .map(|(source, url)| ManifestPdf { source, url })
+            .collect();
+
+        let manifest = Manifest {
+            undox_version: &ctx.undox.version,
+            built_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            documents,
+            static_files,
+            pdfs,
+        };
+        let mut json = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| PipelineError::stage("manifest", e.to_string()))?;
+        json.push('\n');
+
+        let output_path = ctx.output_dir.join(MANIFEST_PATH);
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&output_path, json)?;
+
+        Ok(())
+    }
+}
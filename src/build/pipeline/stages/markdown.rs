@@ -3,6 +3,7 @@
 //! Renders document content to HTML using the appropriate format
 //! from the format registry (Markdown, AsciiDoc, etc.).
 
+use crate::build::diagnostics::WarningKind;
 use crate::build::format::FormatContext;
 use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument, Stage};
 
@@ -11,10 +12,11 @@ use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument,
 /// This stage:
 /// - Looks up the appropriate format based on file extension
 /// - Renders content to HTML (with syntax highlighting for code blocks)
+/// - Rewrites relative image paths to the static file's output URL
 /// - Extracts heading structure for table of contents
 ///
-/// After this stage, `doc.content` contains HTML and `doc.toc`
-/// contains the extracted headings.
+/// After this stage, `doc.content` contains HTML, `doc.markdown` the content
+/// it was rendered from, and `doc.toc` the extracted headings.
 ///
 /// Note: This stage is named "markdown" for backwards compatibility
 /// with pipeline extension points, even though it now handles all formats.
@@ -30,13 +32,18 @@ impl Stage for MarkdownStage {
         docs: &mut [ProcessingDocument],
         ctx: &mut PipelineContext,
     ) -> Result<(), PipelineError> {
-        // Create format context for rendering
-        let format_ctx = FormatContext {
-            highlighter: ctx.highlighter,
-            markdown_config: ctx.markdown_config,
-        };
-
         for doc in docs {
+            ctx.progress.tick();
+            // Create format context for rendering this document
+            let format_ctx = FormatContext {
+                highlighter: ctx.highlighter,
+                markdown_config: ctx.markdown_config_for(doc.source_name()),
+                assets: Some(
+                    ctx.static_files
+                        .for_document(&doc.source_path, &doc.doc.source_path),
+                ),
+            };
+
             // Look up format based on file extension
             let format = ctx
                 .format_registry
@@ -63,9 +70,22 @@ impl Stage for MarkdownStage {
                 )
             })?;
 
+            for src in &output.missing_images {
+                ctx.diagnostics.warn(
+                    WarningKind::Image,
+                    Some(&doc.doc.source_path),
+                    format!(
+                        "Image not found in {}: {}",
+                        doc.doc.source_path.display(),
+                        src
+                    ),
+                );
+            }
+
             // Update document with rendered HTML and TOC
-            doc.content = output.html;
+            doc.markdown = std::mem::replace(&mut doc.content, output.html);
             doc.toc = output.toc;
+            doc.has_math = output.has_math;
         }
 
         Ok(())
@@ -2,17 +2,58 @@
 //!
 //! The standard document processing pipeline consists of:
 //!
-//! 1. **TeraStage** - Process Tera syntax in markdown (macros, variables, loops)
-//! 2. **MarkdownStage** - Convert markdown to HTML with syntax highlighting
-//! 3. **TemplateStage** - Wrap content in the page template
-//! 4. **WriteStage** - Write final HTML to output directory
+//! 1. **IncludeStage** - Inline `--8<--` snippet includes
+//! 2. **TeraStage** - Process Tera syntax in markdown (macros, variables, loops)
+//! 3. **MarkdownStage** - Convert markdown to HTML with syntax highlighting
+//! 4. **TemplateStage** - Wrap content in the page template
+//!    - **HtmlRewriteStage** - Apply `html.rules` to the pages, when there are any
+//! 5. **WriteStage** - Write final HTML to output directory
+//! 6. **AliasStage** - Write redirect pages for `aliases` in front matter
+//!
+//! Finalize stages, added by the builder:
+//!
+//! - **RedirectStage** - Write the redirects from `site.redirects`
+//! - **FeedStage** - Write the Atom feed configured in `feed`
+//! - **RobotsStage** - Write `robots.txt`
+//! - **NotFoundStage** - Write the `404.html` page
+//! - **ManifestStage** - Write `_undox/manifest.json`, listing what was built
+//! - **LlmsStage** - Write `llms.txt` and `llms-full.txt` when `site.llms` is set
+//! - **PrintStage** - Write each source's `print/` page when `site.print` is set
+//! - **PdfStage** - Write a PDF of each print view when `pdf` is set
+//! - **SocialCardStage** - Draw social card images when `site.social_cards` is set
 
+mod aliases;
+mod feed;
+mod html_rewrite;
+mod include;
+mod llms;
+mod manifest;
 mod markdown;
+mod not_found;
+mod pdf;
+mod print;
+mod redirects;
+mod robots;
+#[cfg(feature = "social-cards")]
+mod social_cards;
 mod template;
 mod tera;
 mod write;
 
+pub use aliases::AliasStage;
+pub use feed::FeedStage;
+pub use html_rewrite::HtmlRewriteStage;
+pub use include::IncludeStage;
+pub use llms::LlmsStage;
+pub use manifest::{MANIFEST_PATH, ManifestStage};
 pub use markdown::MarkdownStage;
+pub use not_found::NotFoundStage;
+pub use pdf::PdfStage;
+pub use print::PrintStage;
+pub use redirects::RedirectStage;
+pub use robots::RobotsStage;
+#[cfg(feature = "social-cards")]
+pub use social_cards::SocialCardStage;
 pub use template::TemplateStage;
 pub use tera::TeraStage;
 pub use write::WriteStage;
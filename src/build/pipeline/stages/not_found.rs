@@ -0,0 +1,64 @@
+//! Not-found page stage.
+//!
+//! Writes `404.html` to the output root, which hosts such as GitHub Pages
+//! and Netlify (and `undox serve`) show for unknown URLs.
+
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+use crate::build::render::NotFoundContext;
+use crate::util::html_escape;
+
+/// Stage that writes the not-found page.
+///
+/// Uses the theme's `404.html` template when it has one, and a minimal
+/// built-in page otherwise.
+pub struct NotFoundStage;
+
+impl FinalizeStage for NotFoundStage {
+    fn name(&self) -> &'static str {
+        "not_found"
+    }
+
+    fn finalize(
+        &self,
+        _docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let context = NotFoundContext {
+            site: ctx.site.clone(),
+            sources: ctx.source_tabs.to_vec(),
+            theme: ctx.theme_settings.clone(),
+            extra: ctx.extra.clone(),
+            undox: ctx.undox.clone(),
+        };
+        let html = match ctx.renderer.render_not_found(&context)? {
+            Some(html) => html,
+            None => not_found_html(&ctx.site.name, &format!("{}/", ctx.site.base_path)),
+        };
+
+        std::fs::write(ctx.output_dir.join("404.html"), html)?;
+        Ok(())
+    }
+}
+
+/// HTML for the built-in not-found page, linking back to `home`.
+///
+/// The body is ignored by the search index.
+fn not_found_html(site_name: &str, home: &str) -> String {
+    let site_name = html_escape(site_name);
+    let home = html_escape(home);
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>Page not found - {site_name}</title>\n\
+         <meta name=\"robots\" content=\"noindex\">\n\
+         </head>\n\
+         <body data-pagefind-ignore=\"all\">\n\
+         <h1>Page not found</h1>\n\
+         <p>This page doesn't exist. Go to the <a href=\"{home}\">{site_name} home page</a>.</p>\n\
+         </body>\n\
+         </html>\n"
+    )
+}
@@ -0,0 +1,169 @@
+//! PDF export stage.
+//!
+//! With `pdf`, turns each source's print view into a PDF by running
+//! headless Chromium or WeasyPrint on it. Pages get a header with the site
+//! name and a footer with page numbers, through CSS `@page` rules added to a
+//! copy of the print view.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+use crate::build::render::SourceTab;
+use crate::config::{PdfConfig, PdfConverter};
+
+use super::PrintStage;
+
+/// Stage that writes a PDF of each source's print view.
+///
+/// Runs after [`PrintStage`](super::PrintStage). Sources without a print
+/// view (no pages in their navigation) get no PDF.
+pub struct PdfStage {
+    config: PdfConfig,
+    /// Every source, in tab order
+    sources: Vec<SourceTab>,
+}
+
+impl PdfStage {
+    pub fn new(config: PdfConfig, sources: Vec<SourceTab>) -> Self {
+        Self { config, sources }
+    }
+
+    /// URL of a source's PDF: `print/<source name>.pdf` under its root.
+    pub fn output_url(source: &SourceTab) -> String {
+        let root = source.url.trim_end_matches("index.html");
+        format!("{}print/{}.pdf", root, source.source_id)
+    }
+}
+
+impl FinalizeStage for PdfStage {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn finalize(
+        &self,
+        _docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        for source in &self.sources {
+            let print_path =
+                ctx.output_path_for(&PrintStage::output_url(source, ctx.site.url_style));
+            let Ok(html) = std::fs::read_to_string(&print_path) else {
+                continue;
+            };
+
+            // The converter reads a copy next to the print view, so
+            // relative links still resolve
+            let input = print_path.with_extension("pdf.html");
+            std::fs::write(&input, with_page_style(&html, &ctx.site.name))?;
+            let result = convert(
+                &self.config,
+                &input,
+                &ctx.output_path_for(&Self::output_url(source)),
+            );
+            std::fs::remove_file(&input)?;
+            result?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Add `@page` rules for a header with the site name and a footer with page
+/// numbers.
+fn with_page_style(html: &str, site_name: &str) -> String {
+    let name = site_name
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('<', "\\3c ");
+    let style = format!(
+        "<style>\n\
+         @page {{\n\
+         margin: 2cm 1.5cm;\n\
+         @top-center {{ content: \"{}\"; font-size: 9pt; color: #666; }}\n\
+         @bottom-center {{ content: counter(page) \" / \" counter(pages); font-size: 9pt; color: #666; }}\n\
+         }}\n\
+         </style>\n",
+        name
+    );
+    match html.find("</head>") {
+        Some(pos) => format!("{}{}{}", &html[..pos], style, &html[pos..]),
+        None => format!("{}{}", style, html),
+    }
+}
+
+/// Run the configured converter to turn `input` into the PDF `output`.
+fn convert(config: &PdfConfig, input: &Path, output: &Path) -> Result<(), PipelineError> {
+    let program = config.command();
+    let mut command = Command::new(program);
+    match config.converter {
+        PdfConverter::Chromium => command
+            .args(["--headless", "--disable-gpu", "--no-pdf-header-footer"])
+            .arg(format!("--print-to-pdf={}", output.display()))
+            .arg(input),
+        PdfConverter::Weasyprint => command.arg(input).arg(output),
+    };
+
+    let result = command.output().map_err(|e| {
+        let message = if e.kind() == std::io::ErrorKind::NotFound {
+            let name = match config.converter {
+                PdfConverter::Chromium => "Chromium",
+                PdfConverter::Weasyprint => "WeasyPrint",
+            };
+            format!(
+                "PDF converter '{}' not found; install {} or set pdf.command to its path",
+                program, name
+            )
+        } else {
+            format!("couldn't run PDF converter '{}': {}", program, e)
+        };
+        PipelineError::stage("pdf", message)
+    })?;
+    if !result.status.success() {
+        return Err(PipelineError::stage(
+            "pdf",
+            format!(
+                "'{}' failed to convert {} ({}): {}",
+                program,
+                input.display(),
+                result.status,
+                String::from_utf8_lossy(&result.stderr).trim()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_style() {
+        let html = with_page_style(
+            "<html><head><title>T</title></head><body></body></html>",
+            "\"Docs\" </style>",
+        );
+        assert!(html.contains("content: \"\\\"Docs\\\" \\3c /style>\";"));
+        assert!(html.contains("counter(page) \" / \" counter(pages)"));
+        assert!(html.ends_with("</style>\n</head><body></body></html>"));
+    }
+
+    #[test]
+    fn test_missing_converter() {
+        let config = PdfConfig {
+            enabled: true,
+            converter: PdfConverter::Weasyprint,
+            command: Some("undox-no-such-converter".to_string()),
+        };
+        let error = convert(&config, Path::new("in.html"), Path::new("out.pdf")).unwrap_err();
+        assert!(
+            error.to_string().contains(
+                "'undox-no-such-converter' not found; install WeasyPrint or set pdf.command"
+            ),
+            "{}",
+            error
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Print view stage.
+//!
+//! With `site.print`, writes a `print` page under each source's root: all of
+//! the source's pages in navigation order, as one document to print or save
+//! as a PDF.
+
+use std::collections::HashMap;
+
+use crate::build::links::{is_external, resolve};
+use crate::build::nav::reading_order;
+use crate::build::paths::{strip_base_path, styled_url, url_slug};
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+use crate::build::render::{PrintContext, PrintPage, SourceTab};
+use crate::config::UrlStyle;
+use crate::util::{decode_entities, html_escape};
+
+/// Stage that writes each source's print view.
+///
+/// Each page's content follows an `<h1>` whose id is the page's slug, and
+/// links between the source's pages point to those headings instead. Pages
+/// that aren't in the navigation are left out. The theme's `print.html`
+/// template is used when it has one, and a minimal built-in page otherwise.
+pub struct PrintStage {
+    /// Every source, in tab order
+    sources: Vec<SourceTab>,
+}
+
+impl PrintStage {
+    pub fn new(sources: Vec<SourceTab>) -> Self {
+        Self { sources }
+    }
+
+    /// URL of a source's print view, `print` under its root in the site's
+    /// URL style.
+    pub fn output_url(source: &SourceTab, style: UrlStyle) -> String {
+        let root = source.url.trim_end_matches("index.html");
+        styled_url(&format!("{}print", root), false, style)
+    }
+}
+
+impl FinalizeStage for PrintStage {
+    fn name(&self) -> &'static str {
+        "print"
+    }
+
+    fn finalize(
+        &self,
+        docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        for source in &self.sources {
+            // Sources sharing a combined sidebar each print only their pages
+            let source_docs: HashMap<&str, &ProcessingDocument> = docs
+                .iter()
+                .filter(|doc| doc.source_name() == source.source_id)
+                .map(|doc| (doc.url_path().trim_end_matches('/'), doc))
+                .collect();
+            let nav = ctx.nav_for_source(&source.source_id);
+            let mut pages: Vec<(PrintPage, &ProcessingDocument)> = Vec::new();
+            for link in reading_order(&nav) {
+                let Some(doc) = source_docs.get(link.url.trim_end_matches('/')) else {
+                    continue;
+                };
+                if pages.iter().any(|(_, seen)| std::ptr::eq(*seen, *doc)) {
+                    continue;
+                }
+                let page = PrintPage {
+                    title: doc.title(),
+                    url: doc.url_path().to_string(),
+                    anchor: url_slug(strip_base_path(&ctx.site.base_path, doc.url_path())),
+                };
+                pages.push((page, doc));
+            }
+            if pages.is_empty() {
+                continue;
+            }
+
+            let print_url = Self::output_url(source, ctx.site.url_style);
+            if source_docs.contains_key(print_url.trim_end_matches('/')) {
+                return Err(PipelineError::stage(
+                    "print",
+                    format!(
+                        "source '{}' has a page at {}, where its print view goes",
+                        source.source_id, print_url
+                    ),
+                ));
+            }
+
+            let anchors: HashMap<&str, &str> = pages
+                .iter()
+                .map(|(page, _)| (page.url.trim_end_matches('/'), page.anchor.as_str()))
+                .collect();
+            let content: String = pages
+                .iter()
+                .map(|(page, doc)| {
+                    format!(
+                        "<h1 id=\"{}\">{}</h1>\n{}\n",
+                        html_escape(&page.anchor),
+                        html_escape(&page.title),
+                        rewrite_links(&doc.content, doc.url_path(), &anchors)
+                    )
+                })
+                .collect();
+
+            let context = PrintContext {
+                site: ctx.site_for(&source.source_id),
+                source: source.clone(),
+                pages: pages.into_iter().map(|(page, _)| page).collect(),
+                content,
+                theme: ctx.theme_settings.clone(),
+                extra: ctx.extra.clone(),
+                undox: ctx.undox.clone(),
+            };
+            let html = match ctx.renderer.render_print(&context)? {
+                Some(html) => html,
+                None => print_html(&context),
+            };
+
+            let output_path = ctx.output_path_for(&print_url);
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(output_path, html)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Point the links in a page's content at the print view: links to pages in
+/// `anchors` (by URL) become links to their headings, or to the fragment
+/// they name, and other relative links are made absolute, since the content
+/// is no longer at `page_url`.
+fn rewrite_links(html: &str, page_url: &str, anchors: &HashMap<&str, &str>) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(pos) = rest.find("href=\"") {
+        let start = pos + "href=\"".len();
+        let Some(len) = rest[start..].find('"') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let href = decode_entities(&rest[start..start + len]);
+        rest = &rest[start + len..];
+
+        let (path, fragment) = match href.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (href.as_str(), None),
+        };
+        if path.is_empty() || is_external(&href) {
+            out.push_str(&html_escape(&href));
+            continue;
+        }
+        let resolved = resolve(page_url, path);
+        let page = resolved.split('?').next().unwrap_or_default();
+        let rewritten = match anchors.get(page.trim_end_matches('/')) {
+            Some(anchor) => format!("#{}", fragment.filter(|f| !f.is_empty()).unwrap_or(anchor)),
+            None if path.starts_with('/') => href.clone(),
+            None => match fragment {
+                Some(fragment) => format!("{}#{}", resolved, fragment),
+                None => resolved,
+            },
+        };
+        out.push_str(&html_escape(&rewritten));
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// HTML for the built-in print view.
+fn print_html(context: &PrintContext) -> String {
+    let lang = context.site.language.as_deref().unwrap_or("en");
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"{}\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{} - {}</title>\n\
+         <meta name=\"robots\" content=\"noindex\">\n\
+         </head>\n\
+         <body data-pagefind-ignore=\"all\">\n\
+         {}\
+         </body>\n\
+         </html>\n",
+        html_escape(lang),
+        html_escape(&context.source.name),
+        html_escape(&context.site.name),
+        context.content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_links() {
+        let anchors = HashMap::from([("/guide", "guide"), ("/guide/install", "guide-install")]);
+        let html = concat!(
+            r#"<a href="install">Install</a> "#,
+            r#"<a href="/guide/install/#linux">Linux</a> "#,
+            r##"<a href="#usage">Usage</a> "##,
+            r#"<a href="../api/client?v=1&amp;x">Client</a> "#,
+            r#"<img src="diagram.png"> "#,
+            r#"<a href="https://example.com/">Elsewhere</a>"#,
+        );
+        assert_eq!(
+            rewrite_links(html, "/guide", &anchors),
+            concat!(
+                r##"<a href="#guide-install">Install</a> "##,
+                r##"<a href="#linux">Linux</a> "##,
+                r##"<a href="#usage">Usage</a> "##,
+                r#"<a href="/api/client?v=1&amp;x">Client</a> "#,
+                r#"<img src="diagram.png"> "#,
+                r#"<a href="https://example.com/">Elsewhere</a>"#,
+            )
+        );
+    }
+}
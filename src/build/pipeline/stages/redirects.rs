@@ -0,0 +1,65 @@
+//! Site redirects stage.
+//!
+//! Writes the redirects configured in `site.redirects`.
+
+use crate::build::aliases::redirect_html;
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+use crate::build::redirects::{Redirect, netlify_redirects, vercel_redirects};
+use crate::config::RedirectsFormat;
+
+/// Stage that writes the site-wide redirects.
+///
+/// Exact redirects get a page at the old URL, like aliases. With a
+/// `redirects_format`, all redirects are also written to the host's
+/// redirects file in the output root.
+pub struct RedirectStage {
+    redirects: Vec<Redirect>,
+    format: Option<RedirectsFormat>,
+}
+
+impl RedirectStage {
+    pub fn new(redirects: Vec<Redirect>, format: Option<RedirectsFormat>) -> Self {
+        Self { redirects, format }
+    }
+}
+
+impl FinalizeStage for RedirectStage {
+    fn name(&self) -> &'static str {
+        "redirects"
+    }
+
+    fn finalize(
+        &self,
+        _docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        for redirect in self.redirects.iter().filter(|r| !r.wildcard) {
+            let canonical = if redirect.to.starts_with('/') {
+                ctx.absolute_url_for(&redirect.to)
+                    .unwrap_or_else(|| redirect.to.clone())
+            } else {
+                redirect.to.clone()
+            };
+
+            let output_path = ctx.output_path_for(&redirect.from);
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&output_path, redirect_html(&redirect.to, &canonical))?;
+        }
+
+        match self.format {
+            Some(RedirectsFormat::Netlify) => std::fs::write(
+                ctx.output_dir.join("_redirects"),
+                netlify_redirects(&self.redirects),
+            )?,
+            Some(RedirectsFormat::Vercel) => std::fs::write(
+                ctx.output_dir.join("vercel.json"),
+                vercel_redirects(&self.redirects),
+            )?,
+            None => {}
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! robots.txt stage.
+//!
+//! Writes `robots.txt` to the output root.
+
+use crate::build::paths::absolute_url;
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+
+/// Stage that writes `robots.txt`.
+///
+/// `site.robots` replaces the whole file. Otherwise everything is allowed,
+/// and the sitemap is listed when the site has one and its URL is known.
+pub struct RobotsStage {
+    body: Option<String>,
+    has_sitemap: bool,
+}
+
+impl RobotsStage {
+    pub fn new(body: Option<String>, has_sitemap: bool) -> Self {
+        Self { body, has_sitemap }
+    }
+}
+
+impl FinalizeStage for RobotsStage {
+    fn name(&self) -> &'static str {
+        "robots"
+    }
+
+    fn finalize(
+        &self,
+        _docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let body = match &self.body {
+            Some(body) if body.ends_with('\n') => body.clone(),
+            Some(body) => format!("{}\n", body),
+            None => {
+                let sitemap = match &ctx.site.url {
+                    Some(site_url) if self.has_sitemap => {
+                        Some(absolute_url(site_url, "sitemap.xml"))
+                    }
+                    _ => None,
+                };
+                robots_txt(sitemap.as_deref())
+            }
+        };
+
+        std::fs::write(ctx.output_dir.join("robots.txt"), body)?;
+        Ok(())
+    }
+}
+
+/// The default `robots.txt`: allow everything, and point to the sitemap.
+fn robots_txt(sitemap_url: Option<&str>) -> String {
+    let mut out = String::from("User-agent: *\nAllow: /\n");
+    if let Some(url) = sitemap_url {
+        out.push_str(&format!("\nSitemap: {}\n", url));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robots_txt() {
+        assert_eq!(robots_txt(None), "User-agent: *\nAllow: /\n");
+        assert_eq!(
+            robots_txt(Some("https://example.com/docs/sitemap.xml")),
+            "User-agent: *\nAllow: /\n\nSitemap: https://example.com/docs/sitemap.xml\n"
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Social card stage.
+//!
+//! Draws the cards the builder assigned to pages with `site.social_cards`.
+
+use std::path::PathBuf;
+
+use crate::build::pipeline::{FinalizeStage, PipelineContext, PipelineError, ProcessingDocument};
+use crate::build::social_card::{CardColors, SocialCard};
+
+/// Stage that writes a social card image for each page that has one.
+///
+/// A card's file name changes with what's drawn on it, so cards that
+/// already exist are left alone.
+pub struct SocialCardStage {
+    colors: CardColors,
+}
+
+impl SocialCardStage {
+    pub fn new(colors: CardColors) -> Self {
+        Self { colors }
+    }
+}
+
+impl FinalizeStage for SocialCardStage {
+    fn name(&self) -> &'static str {
+        "social cards"
+    }
+
+    fn finalize(
+        &self,
+        docs: &[ProcessingDocument],
+        ctx: &PipelineContext,
+    ) -> Result<(), PipelineError> {
+        let cards: Vec<(&ProcessingDocument, PathBuf)> = docs
+            .iter()
+            .filter_map(|doc| Some((doc, ctx.output_dir.join(doc.social_card.as_ref()?))))
+            .filter(|(_, output_path)| !output_path.exists())
+            .collect();
+
+        ctx.progress.start("social cards", cards.len());
+        for (doc, output_path) in cards {
+            ctx.progress.tick();
+
+            let card = SocialCard {
+                title: &doc.title(),
+                site_name: &ctx.site.name,
+                colors: self.colors,
+            };
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&output_path, card.render())?;
+        }
+        ctx.progress.finish();
+
+        Ok(())
+    }
+}
@@ -3,16 +3,22 @@
 //! Wraps rendered HTML content in the page template,
 //! adding navigation, site chrome, and other page elements.
 
+use crate::build::dates::format_date;
+use crate::build::nav::{limit_depth, mark_active, prev_next};
 use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument, Stage};
-use crate::build::render::{PageContext, PageInfo};
+use crate::build::render::{OpenGraph, PageContext, PageInfo};
+use crate::build::search::filter_html;
+use crate::util::word_count;
 
 /// Stage that applies the page template to rendered content.
 ///
 /// This stage wraps the HTML content (from the markdown stage) in
 /// the full page template, adding:
 /// - Site header and navigation
-/// - Sidebar navigation for the current source
+/// - Sidebar navigation for the current source, with the current page and
+///   its ancestors marked active
 /// - Table of contents
+/// - Search filters for the configured front matter fields
 /// - Footer and other chrome
 ///
 /// After this stage, `doc.output_html` contains the complete HTML page.
@@ -29,23 +35,79 @@ impl Stage for TemplateStage {
         ctx: &mut PipelineContext,
     ) -> Result<(), PipelineError> {
         for doc in docs {
+            ctx.progress.tick();
+            // Only count words for themes that show reading time
+            let words = ctx
+                .theme_config
+                .reading_time
+                .as_ref()
+                .map(|_| word_count(&doc.content));
+
+            // Highlight the current page in the navigation
+            let mut nav = ctx.nav_for_source(doc.source_name());
+            mark_active(&mut nav, doc.url_path());
+            let (prev, next) = prev_next(&nav, doc.url_path());
+
+            // Trim the sidebar only after finding the neighbours, so pages
+            // below the depth limit still get previous/next links
+            if let Some(max_depth) = ctx.nav_max_depth_for(doc.source_name()) {
+                limit_depth(&mut nav, max_depth);
+            }
+
             // Build page info
             let page_info = PageInfo {
                 title: doc.title(),
                 url: doc.doc.url_path.clone(),
-                description: doc.doc.front_matter.description.clone(),
+                description: doc.description(),
+                canonical: ctx.canonical_url_for(doc),
+                image: ctx.image_url_for(doc),
+                edit_url: ctx.edit_url_for(doc),
+                date: doc.doc.front_matter.date.clone(),
+                date_formatted: doc.doc.front_matter.date.as_deref().and_then(format_date),
+                updated: doc.doc.front_matter.updated.clone(),
+                updated_formatted: doc
+                    .doc
+                    .front_matter
+                    .updated
+                    .as_deref()
+                    .and_then(format_date),
+                last_updated: doc.last_updated.map(|date| date.to_rfc3339()),
+                contributors: doc.contributors.clone(),
+                word_count: words,
+                reading_time: ctx
+                    .theme_config
+                    .reading_time
+                    .as_ref()
+                    .zip(words)
+                    .map(|(config, words)| config.minutes(words)),
+                draft: doc.doc.front_matter.draft,
+                language: ctx.language_for(doc.source_name()),
+                translations: ctx.translations.for_page(doc),
+                prev,
+                next,
                 extra: doc.doc.front_matter.extra.clone(),
             };
 
             // Build full page context
             let page_context = PageContext {
-                site: ctx.site.clone(),
+                site: ctx.site_for(doc.source_name()),
+                og: OpenGraph::new(&page_info, ctx.site),
                 page: page_info,
-                content: doc.content.clone(),
-                nav: ctx.nav_for_source(doc.source_name()),
+                content: format!(
+                    "{}{}",
+                    filter_html(
+                        &doc.doc.front_matter.extra,
+                        &ctx.theme_config.pagefind.filters
+                    ),
+                    doc.content
+                ),
+                nav,
                 sources: ctx.source_tabs_for(doc.source_name()),
-                toc: doc.toc.clone(),
+                versions: ctx.versions_for(doc.source_name()),
+                toc: doc.page_toc(),
+                has_math: doc.has_math,
                 theme: ctx.theme_settings.clone(),
+                extra: ctx.extra.clone(),
                 undox: ctx.undox.clone(),
             };
 
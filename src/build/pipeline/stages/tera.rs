@@ -3,6 +3,8 @@
 //! Processes Tera syntax in markdown content, expanding macros,
 //! variables, and control structures before markdown rendering.
 
+use crate::build::dates::format_date;
+use crate::build::nav::prev_next;
 use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument, Stage};
 use crate::build::render::{ContentRenderContext, PageInfo};
 
@@ -29,19 +31,47 @@ impl Stage for TeraStage {
         ctx: &mut PipelineContext,
     ) -> Result<(), PipelineError> {
         for doc in docs {
+            ctx.progress.tick();
+            let (prev, next) = prev_next(&ctx.nav_for_source(doc.source_name()), doc.url_path());
+
             // Build page info for template context
             let page_info = PageInfo {
                 title: doc.title(),
                 url: doc.doc.url_path.clone(),
                 description: doc.doc.front_matter.description.clone(),
+                canonical: ctx.canonical_url_for(doc),
+                // Resolved for the page template, so a missing image is
+                // only warned about once
+                image: None,
+                edit_url: ctx.edit_url_for(doc),
+                date: doc.doc.front_matter.date.clone(),
+                date_formatted: doc.doc.front_matter.date.as_deref().and_then(format_date),
+                updated: doc.doc.front_matter.updated.clone(),
+                updated_formatted: doc
+                    .doc
+                    .front_matter
+                    .updated
+                    .as_deref()
+                    .and_then(format_date),
+                last_updated: doc.last_updated.map(|date| date.to_rfc3339()),
+                contributors: doc.contributors.clone(),
+                // Not known until the markdown is rendered
+                word_count: None,
+                reading_time: None,
+                draft: doc.doc.front_matter.draft,
+                language: ctx.language_for(doc.source_name()),
+                translations: ctx.translations.for_page(doc),
+                prev,
+                next,
                 extra: doc.doc.front_matter.extra.clone(),
             };
 
             // Create context for Tera rendering
             let content_context = ContentRenderContext {
-                site: ctx.site.clone(),
+                site: ctx.site_for(doc.source_name()),
                 page: page_info,
                 theme: ctx.theme_settings.clone(),
+                extra: ctx.extra.clone(),
                 undox: ctx.undox.clone(),
             };
 
@@ -2,14 +2,16 @@
 //!
 //! Writes the final HTML output to the filesystem.
 
-use crate::build::paths::url_to_output_path;
 use crate::build::pipeline::{PipelineContext, PipelineError, ProcessingDocument, Stage};
+use crate::build::sizes::PageSize;
 
 /// Stage that writes rendered documents to the output directory.
 ///
 /// This stage takes the final HTML from `doc.output_html` and writes
 /// it to the appropriate location in the output directory, creating
-/// any necessary parent directories.
+/// any necessary parent directories. Each page's size is recorded in
+/// `ctx.sizes`. Pages whose HTML is already there as it is are left alone,
+/// and the others are listed in `ctx.changed_pages`.
 pub struct WriteStage;
 
 impl Stage for WriteStage {
@@ -23,6 +25,7 @@ impl Stage for WriteStage {
         ctx: &mut PipelineContext,
     ) -> Result<(), PipelineError> {
         for doc in docs {
+            ctx.progress.tick();
             // Get the final HTML output
             let html = doc.output_html.as_ref().ok_or_else(|| {
                 PipelineError::stage(
@@ -35,15 +38,24 @@ impl Stage for WriteStage {
             })?;
 
             // Determine output path
-            let output_path = url_to_output_path(doc.url_path(), ctx.output_dir);
+            let output_path = ctx.output_path_for(doc.url_path());
 
             // Create parent directories if needed
             if let Some(parent) = output_path.parent() {
                 std::fs::create_dir_all(parent)?;
             }
 
-            // Write the file
-            std::fs::write(&output_path, html)?;
+            // Write the file, unless it's unchanged
+            if std::fs::read(&output_path).ok().as_deref() != Some(html.as_bytes()) {
+                std::fs::write(&output_path, html)?;
+                ctx.changed_pages.push(doc.url_path().to_string());
+            }
+            ctx.sizes.record_page(PageSize {
+                url: doc.url_path().to_string(),
+                source: doc.source_name().to_string(),
+                file: doc.doc.source_path.clone(),
+                bytes: html.len() as u64,
+            });
         }
 
         Ok(())
@@ -0,0 +1,159 @@
+//! Build progress output.
+//!
+//! On a terminal, the long steps of a build show a progress bar. Elsewhere,
+//! as in CI logs, they print a line every few seconds instead, so a big
+//! build never looks stuck. `--quiet` turns both off, along with the
+//! headers announcing each phase of the build.
+
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// How often a step prints its progress when there's no terminal.
+const LINE_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Bar,
+    Lines,
+    Hidden,
+}
+
+/// The step being counted.
+struct Step {
+    label: String,
+    total: usize,
+    done: usize,
+    bar: Option<ProgressBar>,
+    last_line: Instant,
+}
+
+/// Progress of a build. Clones share the same output, so stages can count
+/// the step the pipeline started.
+#[derive(Clone)]
+pub struct Progress {
+    output: Output,
+    step: Arc<Mutex<Option<Step>>>,
+}
+
+impl Progress {
+    /// Progress shown as a bar when stderr is a terminal and as lines
+    /// otherwise, or not at all when `quiet`.
+    pub fn new(quiet: bool) -> Self {
+        if quiet {
+            Self::hidden()
+        } else if std::io::stderr().is_terminal() {
+            Self::with_output(Output::Bar)
+        } else {
+            Self::with_output(Output::Lines)
+        }
+    }
+
+    /// Progress that's never shown.
+    pub fn hidden() -> Self {
+        Self::with_output(Output::Hidden)
+    }
+
+    fn with_output(output: Output) -> Self {
+        Self {
+            output,
+            step: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Announce a phase of the build, such as "Rendering".
+    pub fn phase(&self, name: &str) {
+        if self.output != Output::Hidden {
+            println!("{}...", name);
+        }
+    }
+
+    /// Start counting a step of `total` items, ending the previous step.
+    pub fn start(&self, label: impl Into<String>, total: usize) {
+        self.finish();
+        if self.output == Output::Hidden || total == 0 {
+            return;
+        }
+
+        let label = label.into();
+        let bar = (self.output == Output::Bar).then(|| {
+            let style = ProgressStyle::with_template("  {msg:<16} [{bar:30}] {pos}/{len}")
+                .expect("the template is valid")
+                .progress_chars("=> ");
+            ProgressBar::new(total as u64)
+                .with_style(style)
+                .with_message(label.clone())
+        });
+        *self.lock() = Some(Step {
+            label,
+            total,
+            done: 0,
+            bar,
+            last_line: Instant::now(),
+        });
+    }
+
+    /// Count one item of the current step.
+    pub fn tick(&self) {
+        let mut step = self.lock();
+        let Some(step) = step.as_mut() else {
+            return;
+        };
+        step.done += 1;
+        if let Some(bar) = &step.bar {
+            bar.inc(1);
+        } else if step.last_line.elapsed() >= LINE_INTERVAL {
+            println!("  {}: {}/{}", step.label, step.done, step.total);
+            step.last_line = Instant::now();
+        }
+    }
+
+    /// End the current step, clearing its bar.
+    pub fn finish(&self) {
+        if let Some(step) = self.lock().take()
+            && let Some(bar) = step.bar
+        {
+            bar.finish_and_clear();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Step>> {
+        // A panic while holding the lock can't leave the step half-written
+        self.step.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps() {
+        let position = |progress: &Progress| {
+            progress
+                .lock()
+                .as_ref()
+                .map(|step| (step.label.clone(), step.done, step.total))
+        };
+
+        let progress = Progress::with_output(Output::Lines);
+        progress.start("markdown", 3);
+        progress.clone().tick();
+        progress.tick();
+        assert_eq!(position(&progress), Some(("markdown".to_string(), 2, 3)));
+
+        // Starting a step ends the last one
+        progress.start("write", 3);
+        assert_eq!(position(&progress), Some(("write".to_string(), 0, 3)));
+        progress.finish();
+        assert_eq!(position(&progress), None);
+        progress.tick();
+
+        let hidden = Progress::hidden();
+        hidden.start("markdown", 3);
+        hidden.tick();
+        assert_eq!(position(&hidden), None);
+    }
+}
@@ -0,0 +1,188 @@
+//! Site-wide redirects.
+//!
+//! `site.redirects` maps old URLs to new ones in bulk, for moves too large
+//! to list as page aliases. Exact entries get a redirect page like aliases
+//! do; when `site.redirects_format` is set, every entry is also written to
+//! the host's redirects file, which is the only way wildcards can work.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde_json::json;
+
+use super::aliases::normalize_alias;
+use super::paths::with_base_path;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RedirectError {
+    #[error("redirect {from} collides with an existing page")]
+    CollidesWithPage { from: String },
+}
+
+/// A redirect from `site.redirects`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redirect {
+    /// URL path redirected from, without the trailing `/*` of a wildcard
+    pub from: String,
+    /// URL path or full URL redirected to
+    pub to: String,
+    /// Whether everything under `from` is redirected
+    pub wildcard: bool,
+}
+
+impl Redirect {
+    /// The source pattern, with `/*` restored for wildcards.
+    fn pattern(&self) -> String {
+        if self.wildcard {
+            format!("{}/*", self.from.trim_end_matches('/'))
+        } else {
+            self.from.clone()
+        }
+    }
+}
+
+/// Collect the configured redirects, with URL paths under the site's base
+/// path.
+///
+/// `pages` holds every URL the build writes (pages, aliases, and static
+/// files). Fails if an exact redirect would replace one of them.
+pub fn collect_redirects(
+    config: &BTreeMap<String, String>,
+    base_path: &str,
+    pages: &HashSet<String>,
+) -> Result<Vec<Redirect>, RedirectError> {
+    let mut redirects = Vec::new();
+
+    for (from, to) in config {
+        let (from, wildcard) = match from.strip_suffix("/*") {
+            Some(prefix) => (prefix, true),
+            None => (from.as_str(), false),
+        };
+        let from = with_base_path(base_path, &normalize_alias(from));
+        let to = if to.starts_with('/') {
+            with_base_path(base_path, to)
+        } else {
+            to.clone()
+        };
+
+        if !wildcard && pages.contains(&from) {
+            return Err(RedirectError::CollidesWithPage { from });
+        }
+
+        redirects.push(Redirect { from, to, wildcard });
+    }
+
+    Ok(redirects)
+}
+
+/// Contents of a Netlify `_redirects` file.
+///
+/// Netlify passes the part matched by `*` on as `:splat`.
+pub fn netlify_redirects(redirects: &[Redirect]) -> String {
+    redirects
+        .iter()
+        .map(|r| format!("{} {} 301\n", r.pattern(), r.to))
+        .collect()
+}
+
+/// Contents of a `vercel.json` file.
+///
+/// Vercel uses named parameters rather than `*`, so wildcards become
+/// `:path*` and a `:splat` in the target refers to it.
+pub fn vercel_redirects(redirects: &[Redirect]) -> String {
+    let entries: Vec<_> = redirects
+        .iter()
+        .map(|r| {
+            let source = if r.wildcard {
+                format!("{}/:path*", r.from.trim_end_matches('/'))
+            } else {
+                r.from.clone()
+            };
+            json!({
+                "source": source,
+                "destination": r.to.replace(":splat", ":path*"),
+                "permanent": true,
+            })
+        })
+        .collect();
+    let mut out = serde_json::to_string_pretty(&json!({ "redirects": entries }))
+        .expect("redirects serialize to JSON");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_redirects() {
+        let entries = config(&[
+            ("/old/getting-started/", "/start"),
+            ("/v1/*", "/latest/:splat"),
+            ("/chat", "https://discord.gg/example"),
+        ]);
+        let redirects = collect_redirects(&entries, "/docs", &HashSet::new()).unwrap();
+        assert_eq!(
+            redirects,
+            vec![
+                Redirect {
+                    from: "/docs/chat".to_string(),
+                    to: "https://discord.gg/example".to_string(),
+                    wildcard: false,
+                },
+                Redirect {
+                    from: "/docs/old/getting-started".to_string(),
+                    to: "/docs/start".to_string(),
+                    wildcard: false,
+                },
+                Redirect {
+                    from: "/docs/v1".to_string(),
+                    to: "/docs/latest/:splat".to_string(),
+                    wildcard: true,
+                },
+            ]
+        );
+
+        let pages = HashSet::from(["/start".to_string()]);
+        assert!(matches!(
+            collect_redirects(&config(&[("/start/", "/other")]), "", &pages),
+            Err(RedirectError::CollidesWithPage { .. })
+        ));
+        // Wildcards only apply where no file exists
+        assert!(collect_redirects(&config(&[("/start/*", "/other")]), "", &pages).is_ok());
+    }
+
+    #[test]
+    fn test_host_files() {
+        let redirects = vec![
+            Redirect {
+                from: "/old".to_string(),
+                to: "/new".to_string(),
+                wildcard: false,
+            },
+            Redirect {
+                from: "/v1".to_string(),
+                to: "/latest/:splat".to_string(),
+                wildcard: true,
+            },
+        ];
+        assert_eq!(
+            netlify_redirects(&redirects),
+            "/old /new 301\n/v1/* /latest/:splat 301\n"
+        );
+
+        let vercel: serde_json::Value =
+            serde_json::from_str(&vercel_redirects(&redirects)).unwrap();
+        assert_eq!(vercel["redirects"][0]["source"], "/old");
+        assert_eq!(vercel["redirects"][1]["source"], "/v1/:path*");
+        assert_eq!(vercel["redirects"][1]["destination"], "/latest/:path*");
+        assert_eq!(vercel["redirects"][1]["permanent"], true);
+    }
+}
@@ -4,6 +4,12 @@ use std::sync::Arc;
 use serde::Serialize;
 use tera::{Context, Tera, Value};
 
+use super::diagnostics::{Diagnostics, WarningKind};
+use super::fingerprint::AssetManifest;
+use super::git_history::Contributor;
+use super::paths::with_base_path;
+use crate::config::{FooterConfig, UrlStyle};
+
 #[derive(thiserror::Error, Debug)]
 pub enum RenderError {
     #[error("template error: {0}")]
@@ -16,7 +22,6 @@ pub enum RenderError {
 /// The template renderer, wrapping Tera.
 pub struct Renderer {
     tera: Tera,
-    #[allow(dead_code)]
     theme_path: PathBuf,
 }
 
@@ -34,8 +39,11 @@ impl Renderer {
 
         // Register the icon() function for inlining SVG icons
         // Usage: {{ icon(name="search") }} or {{ icon(name="menu", class="nav-icon", size=20) }}
-        let icons_path = Arc::new(theme_path.join("static/icons"));
-        tera.register_function("icon", MakeIconFunction(icons_path));
+        tera.register_function(
+            "icon",
+            MakeIconFunction::new(theme_path, Diagnostics::default()),
+        );
+        tera.register_function("asset", AssetFunction::default());
 
         Ok(Self {
             tera,
@@ -43,21 +51,85 @@ impl Renderer {
         })
     }
 
+    /// Report icons that templates ask for but the theme doesn't have to
+    /// `diagnostics`.
+    pub fn set_diagnostics(&mut self, diagnostics: Diagnostics) {
+        self.tera
+            .register_function("icon", MakeIconFunction::new(&self.theme_path, diagnostics));
+    }
+
+    /// Set the fingerprinted files and base path the `asset()` function
+    /// links to.
+    pub fn set_assets(&mut self, manifest: AssetManifest, base_path: &str) {
+        self.tera.register_function(
+            "asset",
+            AssetFunction {
+                manifest: Arc::new(manifest),
+                base_path: base_path.to_string(),
+            },
+        );
+    }
+
     /// Render a page with the given context.
     pub fn render_page(&self, context: &PageContext) -> Result<String, RenderError> {
         let mut tera_context = Context::new();
         tera_context.insert("site", &context.site);
         tera_context.insert("page", &context.page);
+        tera_context.insert("og", &context.og);
         tera_context.insert("content", &context.content);
         tera_context.insert("nav", &context.nav);
         tera_context.insert("sources", &context.sources);
+        tera_context.insert("versions", &context.versions);
         tera_context.insert("toc", &context.toc);
+        tera_context.insert("has_math", &context.has_math);
         tera_context.insert("theme", &context.theme);
+        tera_context.insert("extra", &context.extra);
         tera_context.insert("undox", &context.undox);
 
         Ok(self.tera.render("page.html", &tera_context)?)
     }
 
+    /// Render the theme's `404.html` template, or `None` if it has none.
+    pub fn render_not_found(
+        &self,
+        context: &NotFoundContext,
+    ) -> Result<Option<String>, RenderError> {
+        if !self.has_template("404.html") {
+            return Ok(None);
+        }
+
+        let mut tera_context = Context::new();
+        tera_context.insert("site", &context.site);
+        tera_context.insert("sources", &context.sources);
+        tera_context.insert("theme", &context.theme);
+        tera_context.insert("extra", &context.extra);
+        tera_context.insert("undox", &context.undox);
+
+        Ok(Some(self.tera.render("404.html", &tera_context)?))
+    }
+
+    /// Render the theme's `print.html` template, or `None` if it has none.
+    pub fn render_print(&self, context: &PrintContext) -> Result<Option<String>, RenderError> {
+        if !self.has_template("print.html") {
+            return Ok(None);
+        }
+
+        let mut tera_context = Context::new();
+        tera_context.insert("site", &context.site);
+        tera_context.insert("source", &context.source);
+        tera_context.insert("pages", &context.pages);
+        tera_context.insert("content", &context.content);
+        tera_context.insert("theme", &context.theme);
+        tera_context.insert("extra", &context.extra);
+        tera_context.insert("undox", &context.undox);
+
+        Ok(Some(self.tera.render("print.html", &tera_context)?))
+    }
+
+    fn has_template(&self, name: &str) -> bool {
+        self.tera.get_template_names().any(|n| n == name)
+    }
+
     /// Render raw content (markdown) through Tera before markdown processing.
     /// This allows markdown files to use Tera syntax like macros, loops, and variables.
     ///
@@ -72,6 +144,7 @@ impl Renderer {
         tera_context.insert("site", &context.site);
         tera_context.insert("page", &context.page);
         tera_context.insert("theme", &context.theme);
+        tera_context.insert("extra", &context.extra);
         tera_context.insert("undox", &context.undox);
 
         // Prepend import for macros so content can use them as `macros::name(...)`
@@ -100,23 +173,71 @@ pub struct ContentRenderContext {
     pub site: SiteContext,
     pub page: PageInfo,
     pub theme: serde_json::Value,
+    /// Site-wide values from the `extra` config section
+    pub extra: serde_yaml::Mapping,
     pub undox: UndoxContext,
 }
 
+/// Context passed to the `404.html` template. There is no page, so this is
+/// the site-wide part of [`PageContext`].
+#[derive(Debug, Serialize)]
+pub struct NotFoundContext {
+    pub site: SiteContext,
+    /// All sources/projects for top-level tabs
+    pub sources: Vec<SourceTab>,
+    pub theme: serde_json::Value,
+    pub extra: serde_yaml::Mapping,
+    pub undox: UndoxContext,
+}
+
+/// Context passed to the `print.html` template, for a source's print view.
+#[derive(Debug, Serialize)]
+pub struct PrintContext {
+    pub site: SiteContext,
+    /// The source being printed
+    pub source: SourceTab,
+    /// The pages, in reading order
+    pub pages: Vec<PrintPage>,
+    /// Every page's content, each under an `<h1>` with its anchor
+    pub content: String,
+    pub theme: serde_json::Value,
+    pub extra: serde_yaml::Mapping,
+    pub undox: UndoxContext,
+}
+
+/// A page in a print view, for a table of contents.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintPage {
+    pub title: String,
+    /// The page's own URL
+    pub url: String,
+    /// Id of the page's heading in the print view
+    pub anchor: String,
+}
+
 /// Context passed to page templates.
 #[derive(Debug, Serialize)]
 pub struct PageContext {
     pub site: SiteContext,
     pub page: PageInfo,
+    /// OpenGraph and Twitter card metadata for the page
+    pub og: OpenGraph,
     pub content: String,
     /// Navigation for the current source only
     pub nav: Vec<NavSection>,
     /// All sources/projects for top-level tabs
     pub sources: Vec<SourceTab>,
+    /// Every version of the current source, for a version picker (empty
+    /// unless the source has `versions`)
+    pub versions: Vec<VersionLink>,
     /// Table of contents for the current page
     pub toc: Vec<TocEntry>,
+    /// Whether the page contains math, so themes can conditionally load KaTeX
+    pub has_math: bool,
     /// Theme settings from config, accessible as `theme.*` in templates
     pub theme: serde_json::Value,
+    /// Site-wide values from config, accessible as `extra.*` in templates
+    pub extra: serde_yaml::Mapping,
     /// Undox-specific context (dev mode, version, etc.)
     pub undox: UndoxContext,
 }
@@ -129,12 +250,46 @@ pub struct SourceTab {
     /// Source identifier (the config name, used for matching)
     #[serde(skip_serializing)]
     pub source_id: String,
+    /// Group the source belongs to, for rendering related tabs together
+    pub group: Option<String>,
     /// URL to the source's root page
     pub url: String,
     /// Whether this is the currently active source
     pub is_current: bool,
     /// Whether this is a top-level source (url_prefix is "/")
     pub is_top_level: bool,
+    /// Language the source is written in, if it has one
+    pub language: Option<String>,
+}
+
+/// A version of a source, for version pickers.
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionLink {
+    /// Name of the version
+    pub name: String,
+    /// URL of the version's root page
+    pub url: String,
+    /// Whether this is the latest version, also served without the version
+    /// in its URLs
+    pub is_latest: bool,
+    /// Whether the current page belongs to this version
+    pub is_current: bool,
+    /// Name of the source the version is built as
+    #[serde(skip_serializing)]
+    pub source_id: String,
+}
+
+/// A page in one of the site's languages, for language switchers.
+#[derive(Debug, Clone, Serialize)]
+pub struct Translation {
+    /// Language code, like `de`
+    pub language: String,
+    /// Name of the language, from `languages`
+    pub name: String,
+    /// URL of the page in this language (null if it hasn't been translated)
+    pub url: Option<String>,
+    /// Whether this is the current page's language
+    pub is_current: bool,
 }
 
 /// Site-level information.
@@ -143,6 +298,31 @@ pub struct SiteContext {
     pub name: String,
     pub url: Option<String>,
     pub favicon: Option<String>,
+    pub description: Option<String>,
+    /// BCP-47 language tag, for `<html lang>`
+    pub language: Option<String>,
+    pub author: Option<String>,
+    pub logo: Option<String>,
+    /// Image shown when pages without their own `image` are shared
+    pub social_image: Option<String>,
+    /// URL path the site is served under, like `/docs` (empty at the root),
+    /// for themes to prefix asset URLs with
+    pub base_path: String,
+    /// How page URLs are written
+    pub url_style: UrlStyle,
+    /// Banner shown at the top of every page
+    pub announcement: Option<Announcement>,
+    /// Copyright notice and links for the page footer
+    pub footer: Option<FooterConfig>,
+}
+
+/// A banner shown at the top of every page.
+#[derive(Debug, Clone, Serialize)]
+pub struct Announcement {
+    /// Identifies the announcement, for remembering it was dismissed
+    pub id: String,
+    /// The announcement's text, as inline HTML
+    pub html: String,
 }
 
 /// Information about the current page.
@@ -151,22 +331,132 @@ pub struct PageInfo {
     pub title: String,
     pub url: String,
     pub description: Option<String>,
+    /// Absolute URL of the page, for `<link rel="canonical">` (none without
+    /// `site.url`, unless the page sets `canonical_url`)
+    pub canonical: Option<String>,
+    /// Absolute URL of the page's `image`, else of `site.social_image` (a
+    /// URL path without `site.url`)
+    pub image: Option<String>,
+    /// Where to edit the page's source file (none unless the source's
+    /// repository is known)
+    pub edit_url: Option<String>,
+    /// Publication date as written in front matter (omitted if not set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// `date` formatted for display, e.g. "January 5, 2024"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_formatted: Option<String>,
+    /// Last update date as written in front matter (omitted if not set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    /// `updated` formatted for display
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_formatted: Option<String>,
+    /// When the page's file was last committed (or modified, if git doesn't
+    /// track it), as an RFC 3339 timestamp (only with `site.git_dates`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    /// People who committed to the page's file, most recent first (empty
+    /// unless `site.git_contributors` is set)
+    pub contributors: Vec<Contributor>,
+    /// Words of prose on the page, excluding code blocks (only when the
+    /// theme enables `reading_time`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub word_count: Option<usize>,
+    /// Estimated reading time in minutes (only when the theme enables
+    /// `reading_time`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reading_time: Option<usize>,
+    /// Whether the page is a draft (only rendered in dev mode)
+    pub draft: bool,
+    /// The page's language: its source's, else `site.language`
+    pub language: Option<String>,
+    /// The page in each of the site's languages (empty unless its source has
+    /// a `language`)
+    pub translations: Vec<Translation>,
+    /// Previous page in the navigation (null on the first page, or if the
+    /// page isn't in the navigation)
+    pub prev: Option<PageLink>,
+    /// Next page in the navigation (null on the last page, or if the page
+    /// isn't in the navigation)
+    pub next: Option<PageLink>,
     /// Custom front matter fields (flattened to top level, e.g., `page.author`)
     #[serde(flatten)]
     pub extra: std::collections::HashMap<String, serde_yaml::Value>,
 }
 
+/// Social media metadata for a page, for OpenGraph `og:*` and Twitter card
+/// tags.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenGraph {
+    pub title: String,
+    /// The page's description, else the site's
+    pub description: Option<String>,
+    /// Absolute URL of the page (none without `site.url`)
+    pub url: Option<String>,
+    /// `article` for pages with a `date`, else `website`
+    #[serde(rename = "type")]
+    pub og_type: &'static str,
+    pub site_name: String,
+    /// The page's image, else the site's `social_image`
+    pub image: Option<String>,
+}
+
+impl OpenGraph {
+    pub fn new(page: &PageInfo, site: &SiteContext) -> Self {
+        Self {
+            title: page.title.clone(),
+            description: page
+                .description
+                .clone()
+                .or_else(|| site.description.clone()),
+            url: page.canonical.clone(),
+            og_type: if page.date.is_some() {
+                "article"
+            } else {
+                "website"
+            },
+            site_name: site.name.clone(),
+            image: page.image.clone(),
+        }
+    }
+}
+
+/// A link to another page, for previous/next navigation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PageLink {
+    pub title: String,
+    pub url: String,
+}
+
 /// A navigation section (group of links and other sections).
+///
+/// Serialized with a `type` field (`section`, `link`, `divider`, or `label`)
+/// so templates can tell the kinds apart.
 #[derive(Debug, Clone, Serialize)]
-#[serde(untagged)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum NavSection {
     /// A section with a title and nested items
     Section {
         section: String,
         items: Vec<NavSection>,
+        /// Icon name from the nav config (for the `icon()` function)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+        /// Whether the current page is somewhere in this section
+        is_active_trail: bool,
     },
     /// A standalone link (no section header)
     Link(NavLink),
+    /// A horizontal rule between items
+    Divider,
+    /// A heading without a link or nested items
+    Label {
+        label: String,
+        /// Icon name from the nav config (for the `icon()` function)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+    },
 }
 
 /// A single navigation link, optionally with nested children.
@@ -177,6 +467,13 @@ pub enum NavSection {
 pub struct NavLink {
     pub title: String,
     pub url: String,
+    /// Icon name from the nav config (for the `icon()` function)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Whether this link is the current page
+    pub is_active: bool,
+    /// Whether the current page is one of this link's children (at any depth)
+    pub is_active_trail: bool,
     /// Nested navigation items (when this link has child pages)
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<NavSection>,
@@ -204,11 +501,44 @@ pub struct UndoxContext {
     pub version: String,
 }
 
-struct MakeIconFunction(Arc<PathBuf>);
+/// The `asset()` Tera function, which gives the URL of a file in the output
+/// directory, under the base path and fingerprinted when enabled.
+///
+/// Usage in templates:
+///   <link rel="stylesheet" href="{{ asset(path="_theme/style.css") }}">
+#[derive(Default)]
+struct AssetFunction {
+    manifest: Arc<AssetManifest>,
+    base_path: String,
+}
+
+impl tera::Function for AssetFunction {
+    fn call(&self, args: &std::collections::HashMap<String, Value>) -> tera::Result<Value> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| tera::Error::msg("asset() requires a 'path' argument"))?
+            .trim_start_matches('/');
+        let path = self.manifest.get(path).unwrap_or(path);
+        Ok(Value::String(with_base_path(
+            &self.base_path,
+            &format!("/{}", path),
+        )))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+struct MakeIconFunction {
+    icons_path: Arc<PathBuf>,
+    diagnostics: Diagnostics,
+}
 
 impl tera::Function for MakeIconFunction {
     fn call(&self, args: &std::collections::HashMap<String, Value>) -> tera::Result<Value> {
-        Self::make_icon(&self.0, args)
+        self.make_icon(args)
     }
 
     fn is_safe(&self) -> bool {
@@ -217,16 +547,20 @@ impl tera::Function for MakeIconFunction {
 }
 
 impl MakeIconFunction {
+    fn new(theme_path: &Path, diagnostics: Diagnostics) -> Self {
+        Self {
+            icons_path: Arc::new(theme_path.join("static/icons")),
+            diagnostics,
+        }
+    }
+
     /// Create the icon() Tera function for inlining SVG icons.
     ///
     /// Usage in templates:
     ///   {{ icon(name="search") }}
     ///   {{ icon(name="menu", class="nav-icon") }}
     ///   {{ icon(name="star", size=20) }}
-    fn make_icon(
-        icons_path: &Arc<PathBuf>,
-        args: &std::collections::HashMap<String, Value>,
-    ) -> tera::Result<Value> {
+    fn make_icon(&self, args: &std::collections::HashMap<String, Value>) -> tera::Result<Value> {
         // Get required "name" argument
         let name = args
             .get("name")
@@ -240,11 +574,15 @@ impl MakeIconFunction {
         let size = args.get("size").and_then(|v| v.as_i64()).map(|s| s as u32);
 
         // Read the SVG file
-        let svg_path = icons_path.join(format!("{}.svg", name));
+        let svg_path = self.icons_path.join(format!("{}.svg", name));
         let svg_content = match std::fs::read_to_string(&svg_path) {
             Ok(content) => content,
             Err(_) => {
-                eprintln!("Warning: icon '{}' not found at {:?}", name, svg_path);
+                self.diagnostics.warn(
+                    WarningKind::Icon,
+                    Some(&svg_path),
+                    format!("icon '{}' not found at {:?}", name, svg_path),
+                );
                 return Ok(Value::String(String::new()));
             }
         };
@@ -267,3 +605,77 @@ impl MakeIconFunction {
         Ok(Value::String(svg))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site() -> SiteContext {
+        SiteContext {
+            name: "Docs".to_string(),
+            url: Some("https://example.com".to_string()),
+            favicon: None,
+            description: Some("All about it".to_string()),
+            language: None,
+            author: None,
+            logo: None,
+            social_image: None,
+            base_path: String::new(),
+            url_style: UrlStyle::Directory,
+            announcement: None,
+            footer: None,
+        }
+    }
+
+    fn page() -> PageInfo {
+        PageInfo {
+            title: "Install".to_string(),
+            url: "/install".to_string(),
+            description: None,
+            canonical: Some("https://example.com/install".to_string()),
+            image: None,
+            edit_url: None,
+            date: None,
+            date_formatted: None,
+            updated: None,
+            updated_formatted: None,
+            last_updated: None,
+            contributors: Vec::new(),
+            word_count: None,
+            reading_time: None,
+            draft: false,
+            language: None,
+            translations: Vec::new(),
+            prev: None,
+            next: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_open_graph_falls_back_to_site() {
+        let og = OpenGraph::new(&page(), &site());
+        assert_eq!(
+            og,
+            OpenGraph {
+                title: "Install".to_string(),
+                description: Some("All about it".to_string()),
+                url: Some("https://example.com/install".to_string()),
+                og_type: "website",
+                site_name: "Docs".to_string(),
+                image: None,
+            }
+        );
+
+        let page = PageInfo {
+            description: Some("How to install".to_string()),
+            date: Some("2024-01-05".to_string()),
+            image: Some("https://example.com/card.png".to_string()),
+            ..page()
+        };
+        let og = OpenGraph::new(&page, &site());
+        assert_eq!(og.description.as_deref(), Some("How to install"));
+        assert_eq!(og.og_type, "article");
+        assert_eq!(og.image.as_deref(), Some("https://example.com/card.png"));
+    }
+}
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use pagefind::api::PagefindIndex;
 use pagefind::options::PagefindServiceConfig;
 
+use crate::config::UrlStyle;
 use crate::theme::PagefindConfig;
+use crate::util::html_escape;
 
 #[derive(thiserror::Error, Debug)]
 pub enum SearchError {
@@ -18,22 +21,30 @@ pub enum SearchError {
 }
 
 /// Build a search index for the output directory using pagefind.
+///
+/// `site_language` is the site's `language` setting, used when the theme
+/// doesn't force one. A `multilingual` site is indexed in each page's
+/// `<html lang>` instead, giving an index per language. With
+/// [`UrlStyle::Html`], results link to index pages' `index.html` rather than
+/// their directory.
 pub async fn build_search_index(
     output_dir: &Path,
     pagefind_config: &PagefindConfig,
+    site_language: Option<&str>,
+    multilingual: bool,
+    url_style: UrlStyle,
 ) -> Result<usize, SearchError> {
     // Configure pagefind from theme settings
-    let language = pagefind_config
-        .force_language
-        .clone()
-        .unwrap_or_else(|| "en".to_string());
-
-    let config = PagefindServiceConfig::builder()
-        .keep_index_url(false)
-        .root_selector(pagefind_config.root_selector.clone())
-        .exclude_selectors(pagefind_config.exclude_selectors.clone())
-        .force_language(language)
-        .build();
+    let builder = || {
+        PagefindServiceConfig::builder()
+            .keep_index_url(url_style == UrlStyle::Html)
+            .root_selector(pagefind_config.root_selector.clone())
+            .exclude_selectors(pagefind_config.exclude_selectors.clone())
+    };
+    let config = match search_language(pagefind_config, site_language, multilingual) {
+        Some(language) => builder().force_language(language).build(),
+        None => builder().build(),
+    };
 
     // Create the index
     let mut index =
@@ -55,3 +66,130 @@ pub async fn build_search_index(
 
     Ok(page_count)
 }
+
+/// The language to index in: the theme's `force_language`, else none for a
+/// multilingual site, else the site's language, else English.
+fn search_language(
+    pagefind_config: &PagefindConfig,
+    site_language: Option<&str>,
+    multilingual: bool,
+) -> Option<String> {
+    if let Some(language) = &pagefind_config.force_language {
+        return Some(language.clone());
+    }
+    if multilingual {
+        return None;
+    }
+    Some(site_language.unwrap_or("en").to_string())
+}
+
+/// HTML carrying a page's front matter values as pagefind filters and
+/// metadata, for the fields listed in `filters`.
+///
+/// Each value becomes its own hidden `data-pagefind-filter` element, so a
+/// sequence gives the page several values for one filter. The values are
+/// also joined into a single `data-pagefind-meta` entry. Fields the page
+/// doesn't set are skipped.
+pub fn filter_html(extra: &HashMap<String, serde_yaml::Value>, filters: &[String]) -> String {
+    let mut html = String::new();
+    for key in filters {
+        let values = match extra.get(key) {
+            Some(serde_yaml::Value::Sequence(items)) => {
+                items.iter().filter_map(scalar_string).collect()
+            }
+            Some(value) => scalar_string(value).into_iter().collect(),
+            None => Vec::new(),
+        };
+        if values.is_empty() {
+            continue;
+        }
+
+        for value in &values {
+            html.push_str(&format!(
+                "<span data-pagefind-filter=\"{}\" hidden></span>",
+                html_escape(&format!("{}:{}", key, escape_commas(value)))
+            ));
+        }
+        let joined: Vec<String> = values.iter().map(|v| escape_commas(v)).collect();
+        html.push_str(&format!(
+            "<span data-pagefind-meta=\"{}\" hidden></span>",
+            html_escape(&format!("{}:{}", key, joined.join("\\, ")))
+        ));
+    }
+    html
+}
+
+/// Render a scalar YAML value as text.
+fn scalar_string(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Pagefind separates multiple filters in one attribute with commas.
+fn escape_commas(value: &str) -> String {
+    value.replace(',', "\\,")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extra(yaml: &str) -> HashMap<String, serde_yaml::Value> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_filter_html() {
+        let extra = extra("category: guide\nversion: [1.0, \"2.0\"]\nauthor: someone\n");
+        let filters = [
+            "category".to_string(),
+            "version".to_string(),
+            "missing".to_string(),
+        ];
+        assert_eq!(
+            filter_html(&extra, &filters),
+            "<span data-pagefind-filter=\"category:guide\" hidden></span>\
+             <span data-pagefind-meta=\"category:guide\" hidden></span>\
+             <span data-pagefind-filter=\"version:1.0\" hidden></span>\
+             <span data-pagefind-filter=\"version:2.0\" hidden></span>\
+             <span data-pagefind-meta=\"version:1.0\\, 2.0\" hidden></span>"
+        );
+    }
+
+    #[test]
+    fn test_search_language() {
+        let mut config = PagefindConfig::default();
+        let language = |config: &PagefindConfig, site: Option<&str>, multilingual: bool| {
+            search_language(config, site, multilingual)
+        };
+        assert_eq!(language(&config, None, false).as_deref(), Some("en"));
+        assert_eq!(
+            language(&config, Some("pt-BR"), false).as_deref(),
+            Some("pt-BR")
+        );
+        // Multilingual sites index each page in its own language
+        assert_eq!(language(&config, Some("pt-BR"), true), None);
+
+        // The theme's setting wins over the site's
+        config.force_language = Some("de".to_string());
+        assert_eq!(
+            language(&config, Some("pt-BR"), false).as_deref(),
+            Some("de")
+        );
+        assert_eq!(language(&config, None, true).as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn test_filter_values_are_escaped() {
+        let extra = extra("tag: \"a, <b>\"\n");
+        assert_eq!(
+            filter_html(&extra, &["tag".to_string()]),
+            "<span data-pagefind-filter=\"tag:a\\, &lt;b&gt;\" hidden></span>\
+             <span data-pagefind-meta=\"tag:a\\, &lt;b&gt;\" hidden></span>"
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Output sizes.
+//!
+//! Records how large each page and static file a build writes is, for the
+//! report `--sizes` prints and the `budget.max_page_kb` check.
+
+use std::path::PathBuf;
+
+use super::static_files::format_size;
+
+/// A page the build wrote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageSize {
+    pub url: String,
+    /// Name of the page's source
+    pub source: String,
+    /// The page's file, relative to its source
+    pub file: PathBuf,
+    /// Size of the page's HTML
+    pub bytes: u64,
+}
+
+/// How much one source wrote.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SourceSize {
+    name: String,
+    pages: usize,
+    page_bytes: u64,
+    static_bytes: u64,
+}
+
+/// Sizes of the pages and static files of a build, in the order they were
+/// written.
+#[derive(Debug, Clone, Default)]
+pub struct SizeReport {
+    pages: Vec<PageSize>,
+    sources: Vec<SourceSize>,
+}
+
+impl SizeReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a page's size.
+    pub fn record_page(&mut self, page: PageSize) {
+        let source = self.source(&page.source);
+        source.pages += 1;
+        source.page_bytes += page.bytes;
+        self.pages.push(page);
+    }
+
+    /// Record the size of a source's static file.
+    pub fn record_static(&mut self, source: &str, bytes: u64) {
+        self.source(source).static_bytes += bytes;
+    }
+
+    /// Pages larger than `max_bytes`, largest first.
+    pub fn pages_over(&self, max_bytes: u64) -> Vec<&PageSize> {
+        let mut pages: Vec<&PageSize> = self
+            .pages
+            .iter()
+            .filter(|page| page.bytes > max_bytes)
+            .collect();
+        pages.sort_by_key(|page| std::cmp::Reverse(page.bytes));
+        pages
+    }
+
+    /// Format the `largest` pages and each source's total as tables.
+    pub fn table(&self, largest: usize) -> String {
+        let mut pages: Vec<&PageSize> = self.pages.iter().collect();
+        pages.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.url.cmp(&b.url)));
+        pages.truncate(largest);
+        let width = pages.iter().map(|page| page.url.len()).max().unwrap_or(0);
+
+        let mut out = String::from("Largest pages:\n");
+        for page in pages {
+            out.push_str(&format!(
+                "  {:<width$}  {:>11}  ({})\n",
+                page.url,
+                format_size(page.bytes),
+                page.source
+            ));
+        }
+
+        let width = self
+            .sources
+            .iter()
+            .map(|source| source.name.len())
+            .max()
+            .unwrap_or(0);
+        out.push_str("Output size by source:\n");
+        for source in &self.sources {
+            out.push_str(&format!(
+                "  {:<width$}  {:>11}  ({} page(s), {} of static files)\n",
+                source.name,
+                format_size(source.page_bytes + source.static_bytes),
+                source.pages,
+                format_size(source.static_bytes)
+            ));
+        }
+        out
+    }
+
+    /// Print the tables from [`SizeReport::table`].
+    pub fn print(&self, largest: usize) {
+        print!("{}", self.table(largest));
+    }
+
+    fn source(&mut self, name: &str) -> &mut SourceSize {
+        let index = match self.sources.iter().position(|source| source.name == name) {
+            Some(index) => index,
+            None => {
+                self.sources.push(SourceSize {
+                    name: name.to_string(),
+                    ..SourceSize::default()
+                });
+                self.sources.len() - 1
+            }
+        };
+        &mut self.sources[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(url: &str, source: &str, bytes: u64) -> PageSize {
+        PageSize {
+            url: url.to_string(),
+            source: source.to_string(),
+            file: PathBuf::from("page.md"),
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_table() {
+        let mut report = SizeReport::new();
+        report.record_page(page("/guide", "guide", 2048));
+        report.record_page(page("/guide/big", "guide", 600 * 1024));
+        report.record_page(page("/api", "api", 100));
+        report.record_static("guide", 1024);
+
+        assert_eq!(
+            report.table(2),
+            "Largest pages:\n\
+             \x20 /guide/big     600.0 KB  (guide)\n\
+             \x20 /guide           2.0 KB  (guide)\n\
+             Output size by source:\n\
+             \x20 guide     603.0 KB  (2 page(s), 1.0 KB of static files)\n\
+             \x20 api      100 bytes  (1 page(s), 0 bytes of static files)\n"
+        );
+        let over: Vec<&str> = report
+            .pages_over(500 * 1024)
+            .iter()
+            .map(|page| page.url.as_str())
+            .collect();
+        assert_eq!(over, ["/guide/big"]);
+    }
+}
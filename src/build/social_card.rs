@@ -0,0 +1,226 @@
+//! Generated social cards.
+//!
+//! With `site.social_cards`, pages without an `image` get a 1200×630 PNG
+//! showing their title and the site name, used as their `og.image`. Card
+//! file names include a hash of everything drawn on them, so a card is only
+//! drawn again when its page's title (or the site name or colors) change.
+
+use super::font::Font;
+use super::image::Image;
+use super::paths::url_slug;
+use crate::theme::SocialCardsConfig;
+use crate::util::fnv1a;
+
+/// Directory cards are written to, under the output directory.
+pub const CARD_DIR: &str = "_social";
+
+const WIDTH: usize = 1200;
+const HEIGHT: usize = 630;
+/// Space around the text
+const MARGIN: usize = 80;
+const ACCENT_BAR_WIDTH: usize = 24;
+const SITE_NAME_SIZE: f32 = 40.0;
+const TITLE_SIZE: f32 = 72.0;
+const TITLE_MAX_LINES: usize = 4;
+
+/// Card colors, parsed from the theme's `social_cards` settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CardColors {
+    background: [u8; 3],
+    foreground: [u8; 3],
+    accent: [u8; 3],
+}
+
+impl CardColors {
+    pub fn from_config(config: &SocialCardsConfig) -> Result<Self, String> {
+        let parse = |setting: &str, value: &str| {
+            parse_hex_color(value).ok_or_else(|| {
+                format!(
+                    "social_cards.{} '{}' must be a #rrggbb color",
+                    setting, value
+                )
+            })
+        };
+        Ok(Self {
+            background: parse("background", &config.background)?,
+            foreground: parse("foreground", &config.foreground)?,
+            accent: parse("accent", &config.accent)?,
+        })
+    }
+}
+
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// A page's social card.
+pub struct SocialCard<'a> {
+    pub title: &'a str,
+    pub site_name: &'a str,
+    pub colors: CardColors,
+}
+
+impl SocialCard<'_> {
+    /// Where the card is written, relative to the output directory, for a
+    /// page at `url_path` (without the base path): `_social/<slug>.<hash>.png`.
+    pub fn output_path(&self, url_path: &str) -> String {
+        let slug = url_slug(url_path);
+
+        // Everything drawn on the card, so the hash changes when the card would
+        let mut drawn = Vec::new();
+        drawn.extend(self.title.as_bytes());
+        drawn.push(0);
+        drawn.extend(self.site_name.as_bytes());
+        drawn.push(0);
+        drawn.extend(self.colors.background);
+        drawn.extend(self.colors.foreground);
+        drawn.extend(self.colors.accent);
+        let hash = fnv1a(&drawn);
+
+        format!("{}/{}.{:08x}.png", CARD_DIR, slug, hash)
+    }
+
+    /// Draw the card as a PNG.
+    pub fn render(&self) -> Vec<u8> {
+        let font = Font::bundled();
+        let colors = self.colors;
+        let mut image = Image::new(WIDTH, HEIGHT, colors.background);
+        image.fill_rect(0, 0, ACCENT_BAR_WIDTH, HEIGHT, colors.accent);
+
+        let text_width = (WIDTH - 2 * MARGIN) as f32;
+        let site_name = fit_lines(font, self.site_name, SITE_NAME_SIZE, text_width, 1);
+        font.draw_text(
+            &mut image,
+            &site_name[0],
+            MARGIN as f32,
+            MARGIN as f32 + SITE_NAME_SIZE,
+            SITE_NAME_SIZE,
+            colors.accent,
+        );
+
+        // The title is centered in the space below the site name
+        let lines = fit_lines(font, self.title, TITLE_SIZE, text_width, TITLE_MAX_LINES);
+        let line_height = TITLE_SIZE * 1.2;
+        let top = MARGIN as f32 + SITE_NAME_SIZE * 2.0;
+        let space = HEIGHT as f32 - MARGIN as f32 - top;
+        let first_baseline = top + (space - line_height * lines.len() as f32) / 2.0 + TITLE_SIZE;
+        for (i, line) in lines.iter().enumerate() {
+            font.draw_text(
+                &mut image,
+                line,
+                MARGIN as f32,
+                first_baseline + line_height * i as f32,
+                TITLE_SIZE,
+                colors.foreground,
+            );
+        }
+
+        image.to_png()
+    }
+}
+
+/// Wrap `text` into at most `max_lines` lines no wider than `max_width`,
+/// ending the last line with an ellipsis if the text doesn't fit. Words too
+/// long for a line of their own are cut.
+fn fit_lines(font: &Font, text: &str, size: f32, max_width: f32, max_lines: usize) -> Vec<String> {
+    let fits = |line: &str| font.text_width(line, size) <= max_width;
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut truncated = false;
+    for word in text.split_whitespace() {
+        if let Some(line) = lines.last_mut()
+            && fits(&format!("{} {}", line, word))
+        {
+            line.push(' ');
+            line.push_str(word);
+            continue;
+        }
+        if lines.len() == max_lines {
+            truncated = true;
+            break;
+        }
+
+        let mut line = String::new();
+        for c in word.chars() {
+            line.push(c);
+            if !fits(&line) {
+                line.pop();
+                truncated = true;
+                break;
+            }
+        }
+        lines.push(line);
+        if truncated {
+            break;
+        }
+    }
+
+    if truncated && let Some(line) = lines.last_mut() {
+        while !line.is_empty() && !fits(&format!("{}…", line)) {
+            line.pop();
+        }
+        line.truncate(line.trim_end().len());
+        line.push('…');
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(title: &str) -> SocialCard<'_> {
+        SocialCard {
+            title,
+            site_name: "Docs",
+            colors: CardColors::from_config(&SocialCardsConfig::default()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_output_path() {
+        let path = card("Installation").output_path("/guide/install");
+        assert!(path.starts_with("_social/guide-install."));
+        assert!(path.ends_with(".png"));
+        assert_eq!(path, card("Installation").output_path("/guide/install/"));
+        assert_ne!(path, card("Install").output_path("/guide/install"));
+        assert!(card("Home").output_path("/").starts_with("_social/index."));
+    }
+
+    #[test]
+    fn test_fit_lines() {
+        let font = Font::bundled();
+        let width = font.text_width("Getting started", 72.0);
+
+        assert_eq!(
+            fit_lines(font, "Getting started with the CLI", 72.0, width, 3),
+            ["Getting started", "with the CLI"]
+        );
+        let lines = fit_lines(font, "Getting started with the CLI", 72.0, width, 1);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with('…'));
+        assert!(font.text_width(&lines[0], 72.0) <= width);
+    }
+
+    #[test]
+    fn test_colors() {
+        let config = SocialCardsConfig {
+            background: "#ffffff".to_string(),
+            foreground: "#1E293B".to_string(),
+            accent: "blue".to_string(),
+        };
+        assert_eq!(
+            CardColors::from_config(&config).unwrap_err(),
+            "social_cards.accent 'blue' must be a #rrggbb color"
+        );
+        assert_eq!(parse_hex_color("#1E293B"), Some([0x1e, 0x29, 0x3b]));
+    }
+}
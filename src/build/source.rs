@@ -1,13 +1,29 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
+use serde_yaml::Mapping;
 
-use crate::config::{Location, NavConfig, SourceConfig, SourceLocation};
+use crate::config::{
+    FrontMatterConfig, Location, NavConfig, SiteConfig, SourceConfig, SourceLocation, UrlStyle,
+    find_config_file, read_config_file,
+};
 use crate::git::GitFetcher;
+use crate::util::{split_numeric_prefix, title_case};
 
-use super::document::{ContentItem, Document, FrontMatter, StaticFile, parse_front_matter};
+use super::dates::parse_date;
+use super::diagnostics::{Diagnostics, WarningKind};
+use super::document::{
+    ContentItem, Document, FrontMatter, FrontMatterError, StaticFile,
+    parse_front_matter_with_defaults,
+};
 use super::format::FormatRegistry;
-use super::paths::{source_path_to_url, static_path_to_url};
+use super::nav::DIR_META_FILES;
+use super::paths::{
+    rewrite_source_path, source_path_to_url, static_path_to_url, strip_numeric_prefixes,
+};
 
 /// Partial config for local sub-docs (just the fields we need)
 #[derive(Deserialize)]
@@ -45,8 +61,29 @@ pub enum SourceError {
 
     #[error("git fetch error: {0}")]
     Git(#[from] crate::git::GitError),
+
+    #[error("invalid front matter in {path}: {source}")]
+    FrontMatter {
+        path: PathBuf,
+        source: FrontMatterError,
+    },
 }
 
+/// State of one walk of a content or assets directory.
+struct Walk {
+    /// The directory walked, canonicalized, to tell which links lead out of
+    /// it
+    root: PathBuf,
+    /// Canonical paths of the directories being walked, from the root down
+    /// to the current one, so that a link back up the tree isn't followed
+    ancestors: Vec<PathBuf>,
+    /// How documents' URLs are written
+    url_style: UrlStyle,
+}
+
+/// Per-directory front matter defaults, applied to the directory and below.
+const DEFAULTS_FILE: &str = "_defaults.yaml";
+
 // =============================================================================
 // Resolved source
 // =============================================================================
@@ -58,18 +95,87 @@ pub struct ResolvedSource {
     pub config: SourceConfig,
     /// The resolved local path to the content directory
     pub local_path: PathBuf,
+    /// Where the repository was checked out, for git sources
+    pub checkout: Option<GitCheckout>,
+    /// The resolved `assets` directories
+    pub asset_dirs: Vec<PathBuf>,
+}
+
+/// The clone of a git source.
+#[derive(Debug, Clone)]
+pub struct GitCheckout {
+    /// Repository URL
+    pub url: String,
+    /// Branch, tag, or commit that is checked out
+    pub git_ref: String,
+    /// Root of the clone
+    pub repo_path: PathBuf,
+}
+
+impl GitCheckout {
+    /// The checkout of the git repository a local directory is in, if any.
+    fn containing(dir: &Path) -> Option<Self> {
+        let repo = git2::Repository::discover(dir).ok()?;
+        let repo_path = repo.workdir()?.canonicalize().ok()?;
+        Some(Self {
+            url: String::new(),
+            git_ref: GitFetcher::checked_out_ref(&repo_path)?,
+            repo_path,
+        })
+    }
+
+    /// Path of `content_dir` in the repository, with `/` separators.
+    fn path_in_repo(&self, content_dir: &Path) -> String {
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let content_dir = canonical(content_dir);
+        let relative = content_dir
+            .strip_prefix(canonical(&self.repo_path))
+            .unwrap_or(Path::new(""));
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Edit URL templates for repositories on hosts whose edit URLs are known.
+fn forge_edit_template(repository: &str) -> Option<&'static str> {
+    let host = repository
+        .split_once("://")
+        .map_or(repository, |(_, rest)| rest)
+        .split('/')
+        .next()?;
+    match host.trim_start_matches("www.") {
+        "github.com" => Some("{repository}/edit/{ref}/{path}"),
+        "gitlab.com" => Some("{repository}/-/edit/{ref}/{path}"),
+        _ => None,
+    }
 }
 
 impl ResolvedSource {
     /// Resolve a source configuration to a local path.
     ///
     /// For local sources, this validates the path exists.
-    /// For git sources, this clones/fetches the repo to the cache directory.
+    /// For git sources, this clones/fetches the repo with `fetcher`.
     pub fn resolve(
         mut config: SourceConfig,
         base_path: &Path,
-        cache_dir: &Path,
+        fetcher: &GitFetcher,
     ) -> Result<Self, SourceError> {
+        let mut asset_dirs = Vec::new();
+        for dir in &config.assets {
+            let resolved = base_path.join(dir);
+            if !resolved.exists() {
+                return Err(SourceError::PathNotFound(resolved));
+            }
+            if !resolved.is_dir() {
+                return Err(SourceError::NotADirectory(resolved));
+            }
+            asset_dirs.push(resolved);
+        }
+
+        let mut checkout = None;
         let local_path = match &config.location {
             SourceLocation::Local { local } => {
                 // Local content - must be a path, not git
@@ -112,12 +218,10 @@ impl ResolvedSource {
                             return Err(SourceError::NotADirectory(resolved));
                         }
 
-                        // Check for undox.yaml to get content path and nav
-                        let child_config_path = resolved.join("undox.yaml");
-                        if child_config_path.exists()
-                            && let Ok(content) = std::fs::read_to_string(&child_config_path)
+                        // Check for a child config to get content path and nav
+                        if let Some(child_config_path) = find_config_file(&resolved)
                             && let Ok(subdocs_config) =
-                                serde_yaml::from_str::<LocalSubdocsConfig>(&content)
+                                read_config_file::<LocalSubdocsConfig>(&child_config_path)
                         {
                             // Apply nav from child config if not set in parent
                             if config.nav.is_none()
@@ -135,6 +239,8 @@ impl ResolvedSource {
                                     return Ok(Self {
                                         config,
                                         local_path: content_dir,
+                                        checkout,
+                                        asset_dirs,
                                     });
                                 }
                             }
@@ -151,8 +257,16 @@ impl ResolvedSource {
                     Location::Git { git } => {
                         // Remote git source
                         let git_loc = git.to_location();
-                        let fetcher = GitFetcher::new(cache_dir.to_path_buf());
                         let repo_path = fetcher.fetch_location(&git_loc)?;
+                        checkout = Some(GitCheckout {
+                            url: git_loc.url.clone(),
+                            git_ref: git_loc
+                                .git_ref
+                                .clone()
+                                .or_else(|| GitFetcher::checked_out_ref(&repo_path))
+                                .unwrap_or_else(|| "HEAD".to_string()),
+                            repo_path: repo_path.clone(),
+                        });
 
                         // Apply path if specified
                         let resolved = if let Some(ref path) = git_loc.path {
@@ -168,12 +282,10 @@ impl ResolvedSource {
                             return Err(SourceError::NotADirectory(resolved));
                         }
 
-                        // Check for undox.yaml to get content path and nav
-                        let child_config_path = resolved.join("undox.yaml");
-                        if child_config_path.exists()
-                            && let Ok(content) = std::fs::read_to_string(&child_config_path)
+                        // Check for a child config to get content path and nav
+                        if let Some(child_config_path) = find_config_file(&resolved)
                             && let Ok(subdocs_config) =
-                                serde_yaml::from_str::<LocalSubdocsConfig>(&content)
+                                read_config_file::<LocalSubdocsConfig>(&child_config_path)
                         {
                             // Apply nav from child config if not set in parent
                             if config.nav.is_none()
@@ -191,6 +303,8 @@ impl ResolvedSource {
                                     return Ok(Self {
                                         config,
                                         local_path: content_dir,
+                                        checkout,
+                                        asset_dirs,
                                     });
                                 }
                             }
@@ -208,43 +322,184 @@ impl ResolvedSource {
             }
         };
 
-        Ok(Self { config, local_path })
+        Ok(Self {
+            config,
+            local_path,
+            checkout,
+            asset_dirs,
+        })
     }
 
     /// Get the URL prefix for this source, defaulting to /{name}
     pub fn url_prefix(&self) -> String {
+        self.config.url_prefix()
+    }
+
+    /// Get the template of this source's "Edit this page" links, with
+    /// `{path}` standing for a file's path in the source, if the repository
+    /// is known.
+    ///
+    /// The source's `repository`, `edit_path`, and `edit_url_template` win
+    /// over the site's, and a git source's own repository comes before the
+    /// site's. In an `edit_url_template`, `{repository}` and `{ref}` are
+    /// filled in here, and `{path}` also gets the content's directory in the
+    /// repository: `edit_path` if set, else where the content is in its
+    /// clone or the local repository it's in. Without a template, GitHub and
+    /// GitLab repositories use their host's edit URLs, unless `edit_path` is
+    /// set; other repositories get the repository URL followed by
+    /// `edit_path` (`edit/<ref>/<content dir>` for git sources, as GitHub
+    /// and Gitea use).
+    pub fn edit_url_template(&self, site: &SiteConfig) -> Option<String> {
+        let checkout = self
+            .checkout
+            .clone()
+            .or_else(|| GitCheckout::containing(&self.local_path));
+        let repository = self
+            .config
+            .repository
+            .clone()
+            .or_else(|| {
+                let url = self.checkout.as_ref()?.url.trim_end_matches(".git");
+                Some(url.to_string())
+            })
+            .or_else(|| site.repository.clone())?;
+        let repository = repository.trim_end_matches('/');
+        // A git source's own repository comes with its own paths
+        let edit_path = self
+            .config
+            .edit_path
+            .clone()
+            .or_else(|| site.edit_path.clone().filter(|_| self.checkout.is_none()))
+            .map(|path| path.trim_matches('/').to_string());
+        let template = self
+            .config
+            .edit_url_template
+            .as_deref()
+            .or(site.edit_url_template.as_deref())
+            .or_else(|| forge_edit_template(repository).filter(|_| edit_path.is_none()));
+
+        let Some(template) = template else {
+            let edit_path = edit_path.or_else(|| {
+                let checkout = self.checkout.as_ref()?;
+                let dir = checkout.path_in_repo(&self.local_path);
+                let edit_path = format!("edit/{}/{}", checkout.git_ref, dir);
+                Some(edit_path.trim_end_matches('/').to_string())
+            });
+            return Some(match edit_path.as_deref() {
+                Some(edit_path) if !edit_path.is_empty() => {
+                    format!("{}/{}/{{path}}", repository, edit_path)
+                }
+                _ => format!("{}/{{path}}", repository),
+            });
+        };
+
+        let dir = edit_path
+            .or_else(|| Some(checkout.as_ref()?.path_in_repo(&self.local_path)))
+            .unwrap_or_default();
+        let git_ref = checkout.map_or_else(|| "main".to_string(), |c| c.git_ref);
+        let path = if dir.is_empty() {
+            "{path}".to_string()
+        } else {
+            format!("{}/{{path}}", dir)
+        };
+        Some(
+            template
+                .replace("{repository}", repository)
+                .replace("{ref}", &git_ref)
+                .replace("{path}", &path),
+        )
+    }
+
+    /// Get the display name of this source: its title if set, otherwise
+    /// its name in title case.
+    pub fn display_title(&self) -> String {
         self.config
-            .url_prefix
+            .title
             .clone()
-            .unwrap_or_else(|| format!("/{}", self.config.name))
+            .unwrap_or_else(|| title_case(&self.config.name))
+    }
+
+    /// Order of this source's tab relative to `other`'s: by group, with
+    /// ungrouped sources first, then by weight, then by name. Unweighted
+    /// sources come after weighted ones and keep their config order.
+    pub fn tab_order(&self, other: &ResolvedSource) -> Ordering {
+        let (a, b) = (&self.config, &other.config);
+        a.group
+            .cmp(&b.group)
+            .then_with(|| match (a.weight, b.weight) {
+                (Some(x), Some(y)) => x.cmp(&y).then_with(|| a.name.cmp(&b.name)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
     }
 
     /// Discover all content in this source.
     ///
-    /// Walks the directory tree and returns all documents and static files found.
-    /// Uses the format registry to determine which files are documents.
+    /// Walks the directory tree and returns all documents and static files found,
+    /// each with the directory its path is relative to. Uses the format registry
+    /// to determine which files are documents. Everything in the `assets`
+    /// directories is a static file. Documents get URLs in `url_style`.
+    /// Files that can't be read or parsed are reported to `diagnostics`.
     pub fn discover_content(
         &self,
         format_registry: &FormatRegistry,
-    ) -> Result<Vec<ContentItem>, SourceError> {
+        front_matter: &FrontMatterConfig,
+        url_style: UrlStyle,
+        diagnostics: &Diagnostics,
+    ) -> Result<Vec<(ContentItem, PathBuf)>, SourceError> {
         let mut items = Vec::new();
-        self.walk_directory(
-            &self.local_path,
-            &PathBuf::new(),
-            format_registry,
-            &mut items,
-        )?;
+        let roots = std::iter::once((&self.local_path, false))
+            .chain(self.asset_dirs.iter().map(|dir| (dir, true)));
+        for (root, assets) in roots {
+            let mut found = Vec::new();
+            let mut walk = Walk {
+                root: root.canonicalize().unwrap_or_else(|_| root.clone()),
+                ancestors: Vec::new(),
+                url_style,
+            };
+            self.walk_directory(
+                root,
+                &PathBuf::new(),
+                format_registry,
+                front_matter,
+                diagnostics,
+                &Mapping::new(),
+                assets,
+                &mut walk,
+                &mut found,
+            )?;
+            items.extend(found.into_iter().map(|item| (item, root.clone())));
+        }
         Ok(items)
     }
 
     /// Recursively walk a directory and collect content items.
+    ///
+    /// `defaults` is the front matter inherited from `_defaults.yaml` files in
+    /// parent directories. In an `assets` directory, every file is static.
+    /// Symbolic links are followed as `follow_symlinks` allows.
+    #[allow(clippy::too_many_arguments)]
     fn walk_directory(
         &self,
         dir: &Path,
         relative_path: &Path,
         format_registry: &FormatRegistry,
+        front_matter: &FrontMatterConfig,
+        diagnostics: &Diagnostics,
+        defaults: &Mapping,
+        assets: bool,
+        walk: &mut Walk,
         items: &mut Vec<ContentItem>,
     ) -> Result<(), SourceError> {
+        walk.ancestors
+            .push(dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf()));
+        let defaults = if assets {
+            Mapping::new()
+        } else {
+            self.load_defaults(dir, defaults, front_matter, diagnostics)?
+        };
+
         let entries = std::fs::read_dir(dir).map_err(|e| SourceError::ReadDir {
             path: dir.to_path_buf(),
             source: e,
@@ -265,6 +520,23 @@ impl ResolvedSource {
                 continue;
             }
 
+            let is_symlink = entry
+                .file_type()
+                .map_err(|e| SourceError::ReadEntry {
+                    path: dir.to_path_buf(),
+                    source: e,
+                })?
+                .is_symlink();
+            if is_symlink && !self.follow_symlink(&path, walk, diagnostics) {
+                continue;
+            }
+
+            // Directory metadata is read when building navigation, and front
+            // matter defaults above; neither is published
+            if DIR_META_FILES.contains(&file_name_str.as_ref()) || file_name_str == DEFAULTS_FILE {
+                continue;
+            }
+
             // Skip common non-content directories
             if path.is_dir()
                 && matches!(
@@ -279,68 +551,357 @@ impl ResolvedSource {
 
             if path.is_dir() {
                 // Recurse into subdirectory
-                self.walk_directory(&path, &item_relative_path, format_registry, items)?;
+                self.walk_directory(
+                    &path,
+                    &item_relative_path,
+                    format_registry,
+                    front_matter,
+                    diagnostics,
+                    &defaults,
+                    assets,
+                    walk,
+                    items,
+                )?;
+            } else if path.is_file() && assets {
+                // Assets keep their path under the source's URL prefix
+                let output_path = static_path_to_url(&item_relative_path, &self.url_prefix());
+                items.push(ContentItem::Static(StaticFile::new(
+                    self.config.name.clone(),
+                    item_relative_path,
+                    output_path,
+                )));
             } else if path.is_file() {
                 // Determine if this is a document or static file
-                let item = self.classify_file(&path, &item_relative_path, format_registry);
+                let item = self.classify_file(
+                    &path,
+                    &item_relative_path,
+                    format_registry,
+                    front_matter,
+                    walk.url_style,
+                    diagnostics,
+                    &defaults,
+                )?;
                 items.push(item);
             }
         }
 
+        walk.ancestors.pop();
         Ok(())
     }
 
+    /// Whether to follow the symbolic link at `path`. Links `follow_symlinks`
+    /// doesn't allow, and links to a directory containing the link (which
+    /// would loop forever), are skipped with a warning. Links out of the walked directory are followed with one.
+    fn follow_symlink(&self, path: &Path, walk: &Walk, diagnostics: &Diagnostics) -> bool {
+        // Broken links are skipped like any other missing file
+        let Ok(target) = path.canonicalize() else {
+            return false;
+        };
+        let is_dir = target.is_dir();
+        let follow = self.config.follow_symlinks;
+        if !(if is_dir {
+            follow.directories
+        } else {
+            follow.files
+        }) {
+            diagnostics.warn(
+                WarningKind::Symlink,
+                Some(path),
+                format!(
+                    "Skipping {}, a symbolic link to a {} (see follow_symlinks)",
+                    path.display(),
+                    if is_dir { "directory" } else { "file" }
+                ),
+            );
+            return false;
+        }
+        if is_dir && walk.ancestors.contains(&target) {
+            diagnostics.warn(
+                WarningKind::Symlink,
+                Some(path),
+                format!(
+                    "Skipping {}, a symbolic link to {}, which contains it",
+                    path.display(),
+                    target.display()
+                ),
+            );
+            return false;
+        }
+        if !target.starts_with(&walk.root) {
+            diagnostics.warn(
+                WarningKind::Symlink,
+                Some(path),
+                format!(
+                    "{} is a symbolic link to {}, outside the source",
+                    path.display(),
+                    target.display()
+                ),
+            );
+        }
+        true
+    }
+
+    /// Merge a directory's `_defaults.yaml`, if any, over the inherited defaults.
+    ///
+    /// An invalid defaults file is an error in strict mode; otherwise it is
+    /// reported and ignored.
+    fn load_defaults(
+        &self,
+        dir: &Path,
+        inherited: &Mapping,
+        front_matter_config: &FrontMatterConfig,
+        diagnostics: &Diagnostics,
+    ) -> Result<Mapping, SourceError> {
+        let path = dir.join(DEFAULTS_FILE);
+        if !path.is_file() {
+            return Ok(inherited.clone());
+        }
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                diagnostics.warn(
+                    WarningKind::Read,
+                    Some(&path),
+                    format!("Failed to read {}: {}", path.display(), e),
+                );
+                return Ok(inherited.clone());
+            }
+        };
+
+        // Check the keys against the front matter fields before merging
+        let parsed = serde_yaml::from_str::<Option<FrontMatter>>(&content)
+            .and_then(|_| serde_yaml::from_str::<Option<Mapping>>(&content));
+        match parsed {
+            Ok(own) => {
+                let mut merged = inherited.clone();
+                merged.extend(own.unwrap_or_default());
+                Ok(merged)
+            }
+            Err(e) => {
+                let error = FrontMatterError::from_yaml(&e, 0);
+                if front_matter_config.strict {
+                    return Err(SourceError::FrontMatter {
+                        path,
+                        source: error,
+                    });
+                }
+                diagnostics.warn(
+                    WarningKind::FrontMatter,
+                    Some(&path),
+                    format!(
+                        "Failed to parse front matter defaults in {}: {}",
+                        path.display(),
+                        error
+                    ),
+                );
+                Ok(inherited.clone())
+            }
+        }
+    }
+
     /// Classify a file as either a Document or StaticFile.
     ///
     /// Uses the format registry to determine if a file is a document based on
     /// its extension. Files with registered format extensions are documents;
     /// all others are static files.
+    ///
+    /// Invalid front matter is an error in strict mode and a warning
+    /// otherwise.
+    #[allow(clippy::too_many_arguments)]
     fn classify_file(
         &self,
         full_path: &Path,
         relative_path: &Path,
         format_registry: &FormatRegistry,
-    ) -> ContentItem {
+        front_matter_config: &FrontMatterConfig,
+        url_style: UrlStyle,
+        diagnostics: &Diagnostics,
+        defaults: &Mapping,
+    ) -> Result<ContentItem, SourceError> {
         let url_prefix = self.url_prefix();
+        let url_source_path =
+            rewrite_source_path(relative_path, &self.config.url_rewrite, self.config.flatten);
+        let url_source_path = if self.config.strip_numeric_prefix {
+            strip_numeric_prefixes(&url_source_path)
+        } else {
+            url_source_path
+        };
 
         if format_registry.is_document(relative_path) {
             // It's a document - read and parse front matter + content
-            let url_path = source_path_to_url(relative_path, &url_prefix);
+            let url_path = source_path_to_url(&url_source_path, &url_prefix, url_style);
 
             // Read file and parse front matter, storing both metadata and content
             let (front_matter, raw_content) = match std::fs::read_to_string(full_path) {
                 Ok(content) => {
-                    let parsed = parse_front_matter(&content);
-                    (parsed.front_matter, parsed.content)
+                    let parsed = parse_front_matter_with_defaults(&content, defaults);
+                    if let Some(error) = parsed.error {
+                        if front_matter_config.strict {
+                            return Err(SourceError::FrontMatter {
+                                path: full_path.to_path_buf(),
+                                source: error,
+                            });
+                        }
+                        diagnostics.warn(
+                            WarningKind::FrontMatter,
+                            Some(full_path),
+                            format!(
+                                "Failed to parse front matter in {}: {}",
+                                full_path.display(),
+                                error
+                            ),
+                        );
+                    }
+                    if let Some(fields) = &front_matter_config.fields {
+                        let mut unknown: Vec<&String> = parsed
+                            .front_matter
+                            .extra
+                            .keys()
+                            .filter(|key| !fields.contains(key))
+                            .collect();
+                        unknown.sort();
+                        for key in unknown {
+                            diagnostics.warn(
+                                WarningKind::FrontMatter,
+                                Some(full_path),
+                                format!(
+                                    "Unknown front matter field '{}' in {}",
+                                    key,
+                                    full_path.display()
+                                ),
+                            );
+                        }
+                    }
+                    let mut front_matter = parsed.front_matter;
+                    for (field, value) in [
+                        ("date", &mut front_matter.date),
+                        ("updated", &mut front_matter.updated),
+                    ] {
+                        if value.as_deref().is_some_and(|v| parse_date(v).is_none()) {
+                            diagnostics.warn(
+                                WarningKind::FrontMatter,
+                                Some(full_path),
+                                format!(
+                                    "Invalid {} '{}' in {} (expected YYYY-MM-DD or RFC 3339)",
+                                    field,
+                                    value.take().unwrap_or_default(),
+                                    full_path.display()
+                                ),
+                            );
+                        }
+                    }
+                    (front_matter, parsed.content)
                 }
                 Err(e) => {
-                    eprintln!("Warning: Failed to read {}: {}", full_path.display(), e);
+                    diagnostics.warn(
+                        WarningKind::Read,
+                        Some(full_path),
+                        format!("Failed to read {}: {}", full_path.display(), e),
+                    );
                     (FrontMatter::default(), String::new())
                 }
             };
 
-            ContentItem::Document(Document::new(
+            let mut doc = Document::new(
                 self.config.name.clone(),
                 relative_path.to_path_buf(),
                 url_path,
                 front_matter,
                 raw_content,
-            ))
+            );
+            if self.config.strip_numeric_prefix {
+                doc.numeric_prefix = relative_path
+                    .file_name()
+                    .and_then(|name| split_numeric_prefix(&name.to_string_lossy()).0);
+            }
+            Ok(ContentItem::Document(doc))
         } else {
             // It's a static file
-            let output_path = static_path_to_url(relative_path, &url_prefix);
-            ContentItem::Static(StaticFile::new(
+            let output_path = static_path_to_url(&url_source_path, &url_prefix);
+            Ok(ContentItem::Static(StaticFile::new(
                 self.config.name.clone(),
                 relative_path.to_path_buf(),
                 output_path,
-            ))
+            )))
         }
     }
 }
 
+/// Files of one or more sources that end up at the same URL, as when a
+/// source has both `guide.md` and `guide/index.md`, or a source at `/` has
+/// a `guides/` directory and another source is at `/guides`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlCollision {
+    pub url: String,
+    /// Each file as `path (source 'name')`, sorted. Files are shown by
+    /// their path in the source, or in full if they're from an `assets`
+    /// directory.
+    pub files: Vec<String>,
+}
+
+impl fmt::Display for UrlCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is produced by {}", self.url, self.files.join(", "))
+    }
+}
+
+/// Find every URL that more than one file ends up at, documents and static
+/// files alike, sorted by URL. `content_dirs` are the sources' content
+/// directories; files under any other root are from `assets` directories.
+/// `site_files` are the favicon, logo, and social image, as `(setting, path)`
+/// pairs, which are copied to the output root by file name.
+pub fn find_url_collisions(
+    items: &[(ContentItem, PathBuf)],
+    content_dirs: &[&Path],
+    site_files: &[(&str, PathBuf)],
+) -> Vec<UrlCollision> {
+    let mut files_by_url: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut site_paths: Vec<&Path> = Vec::new();
+    for (setting, path) in site_files {
+        // One file can be several settings
+        if site_paths.contains(&path.as_path()) {
+            continue;
+        }
+        site_paths.push(path);
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        files_by_url
+            .entry(format!("/{}", file_name))
+            .or_default()
+            .push(format!("{} (site.{})", path.display(), setting));
+    }
+    for (item, root) in items {
+        let (url, source, path) = match item {
+            ContentItem::Document(doc) => (&doc.url_path, &doc.source_name, &doc.source_path),
+            ContentItem::Static(file) => (&file.output_path, &file.source_name, &file.source_path),
+        };
+        let path = if content_dirs.contains(&root.as_path()) {
+            path.clone()
+        } else {
+            root.join(path)
+        };
+        files_by_url.entry(url.clone()).or_default().push(format!(
+            "{} (source '{}')",
+            path.display(),
+            source
+        ));
+    }
+
+    files_by_url
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(url, mut files)| {
+            // Directory order varies, so report the files sorted
+            files.sort();
+            UrlCollision { url, files }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::FollowSymlinks;
 
     #[test]
     fn test_path_to_url() {
@@ -351,6 +912,20 @@ mod tests {
             title: Some("CLI".to_string()),
             url_prefix: Some("/cli".to_string()),
             nav: None,
+            html_policy: None,
+            strip_numeric_prefix: false,
+            weight: None,
+            group: None,
+            nav_max_depth: None,
+            repository: None,
+            edit_path: None,
+            edit_url_template: None,
+            url_rewrite: Vec::new(),
+            flatten: 0,
+            assets: Vec::new(),
+            follow_symlinks: FollowSymlinks::default(),
+            versions: Vec::new(),
+            language: None,
             location: SourceLocation::Local {
                 local: Location::Path {
                     path: PathBuf::from("./docs"),
@@ -361,13 +936,19 @@ mod tests {
         let source = ResolvedSource {
             config,
             local_path: PathBuf::from("/tmp/docs"),
+            checkout: None,
+            asset_dirs: Vec::new(),
         };
 
         assert_eq!(source.url_prefix(), "/cli");
 
         // Verify integration with path functions
         assert_eq!(
-            source_path_to_url(Path::new("installation.md"), &source.url_prefix()),
+            source_path_to_url(
+                Path::new("installation.md"),
+                &source.url_prefix(),
+                UrlStyle::Directory
+            ),
             "/cli/installation"
         );
     }
@@ -379,6 +960,20 @@ mod tests {
             title: Some("Docs".to_string()),
             url_prefix: Some("/".to_string()),
             nav: None,
+            html_policy: None,
+            strip_numeric_prefix: false,
+            weight: None,
+            group: None,
+            nav_max_depth: None,
+            repository: None,
+            edit_path: None,
+            edit_url_template: None,
+            url_rewrite: Vec::new(),
+            flatten: 0,
+            assets: Vec::new(),
+            follow_symlinks: FollowSymlinks::default(),
+            versions: Vec::new(),
+            language: None,
             location: SourceLocation::Local {
                 local: Location::Path {
                     path: PathBuf::from("./docs"),
@@ -389,6 +984,8 @@ mod tests {
         let source = ResolvedSource {
             config,
             local_path: PathBuf::from("/tmp/docs"),
+            checkout: None,
+            asset_dirs: Vec::new(),
         };
 
         // Root source has "/" prefix
@@ -397,10 +994,13 @@ mod tests {
 
         // With "/" prefix, paths should work correctly
         assert_eq!(
-            source_path_to_url(Path::new("installation.md"), &prefix),
+            source_path_to_url(Path::new("installation.md"), &prefix, UrlStyle::Directory),
             "/installation"
         );
-        assert_eq!(source_path_to_url(Path::new("index.md"), &prefix), "/");
+        assert_eq!(
+            source_path_to_url(Path::new("index.md"), &prefix, UrlStyle::Directory),
+            "/"
+        );
     }
 
     #[test]
@@ -410,4 +1010,301 @@ mod tests {
             "/cli/images/screenshot.png"
         );
     }
+
+    #[test]
+    fn test_tab_order() {
+        let source = |yaml: &str| ResolvedSource {
+            config: serde_yaml::from_str(yaml).unwrap(),
+            local_path: PathBuf::from("/tmp/docs"),
+            checkout: None,
+            asset_dirs: Vec::new(),
+        };
+        let mut sources = [
+            source("{ name: go, group: SDKs, local: { path: ./go } }"),
+            source("{ name: guide, local: { path: ./guide } }"),
+            source("{ name: python, group: SDKs, weight: 1, local: { path: ./py } }"),
+            source("{ name: api, local: { path: ./api } }"),
+            source("{ name: cli, weight: 5, local: { path: ./cli } }"),
+            source("{ name: rust, group: SDKs, weight: 1, local: { path: ./rs } }"),
+        ];
+        sources.sort_by(|a, b| a.tab_order(b));
+
+        let names: Vec<&str> = sources.iter().map(|s| s.config.name.as_str()).collect();
+        assert_eq!(names, vec!["cli", "guide", "api", "python", "rust", "go"]);
+    }
+
+    #[test]
+    fn test_edit_url_template() {
+        let site: SiteConfig = serde_yaml::from_str(
+            "name: Docs\nrepository: https://github.com/example/site/\nedit_path: edit/main/content",
+        )
+        .unwrap();
+        let source = |yaml: &str, checkout: Option<GitCheckout>| ResolvedSource {
+            config: serde_yaml::from_str(yaml).unwrap(),
+            local_path: PathBuf::from("/cache/abc/docs/content"),
+            checkout,
+            asset_dirs: Vec::new(),
+        };
+
+        // Local sources fall back to the site's settings
+        let local = source("{ name: guide, local: { path: ./guide } }", None);
+        assert_eq!(
+            local.edit_url_template(&site).as_deref(),
+            Some("https://github.com/example/site/edit/main/content/{path}")
+        );
+
+        // Git sources use their own repository and checked-out ref
+        let checkout = GitCheckout {
+            url: "https://github.com/example/cli.git".to_string(),
+            git_ref: "v2".to_string(),
+            repo_path: PathBuf::from("/cache/abc"),
+        };
+        let git = source(
+            "{ name: cli, location: { git: https://github.com/example/cli } }",
+            Some(checkout.clone()),
+        );
+        assert_eq!(
+            git.edit_url_template(&site).as_deref(),
+            Some("https://github.com/example/cli/edit/v2/docs/content/{path}")
+        );
+
+        // GitLab has its own edit URLs
+        let gitlab = source(
+            "{ name: cli, repository: https://gitlab.com/example/cli, location: { git: https://gitlab.com/example/cli } }",
+            Some(checkout.clone()),
+        );
+        assert_eq!(
+            gitlab.edit_url_template(&site).as_deref(),
+            Some("https://gitlab.com/example/cli/-/edit/v2/docs/content/{path}")
+        );
+
+        // Other hosts get the GitHub-style path, or a template
+        let gitea = source(
+            "{ name: cli, location: { git: https://git.example.com/example/cli } }",
+            Some(GitCheckout {
+                url: "https://git.example.com/example/cli".to_string(),
+                ..checkout.clone()
+            }),
+        );
+        assert_eq!(
+            gitea.edit_url_template(&site).as_deref(),
+            Some("https://git.example.com/example/cli/edit/v2/docs/content/{path}")
+        );
+        let templated = source(
+            "{ name: cli, edit_url_template: '{repository}/_edit/{ref}/{path}', location: { git: https://git.example.com/example/cli } }",
+            gitea.checkout.clone(),
+        );
+        assert_eq!(
+            templated.edit_url_template(&site).as_deref(),
+            Some("https://git.example.com/example/cli/_edit/v2/docs/content/{path}")
+        );
+
+        // The source's own settings win
+        let custom = source(
+            "{ name: cli, repository: https://gitlab.com/example/cli, edit_path: /-/edit/main/, location: { git: https://github.com/example/cli } }",
+            Some(checkout),
+        );
+        assert_eq!(
+            custom.edit_url_template(&site).as_deref(),
+            Some("https://gitlab.com/example/cli/-/edit/main/{path}")
+        );
+
+        let bare: SiteConfig = serde_yaml::from_str("name: Docs").unwrap();
+        assert_eq!(local.edit_url_template(&bare), None);
+    }
+
+    #[test]
+    fn test_url_collisions() {
+        let doc = |source: &str, path: &str, url: &str| {
+            let item = ContentItem::Document(Document::new(
+                source.to_string(),
+                PathBuf::from(path),
+                url.to_string(),
+                FrontMatter::default(),
+                String::new(),
+            ));
+            (item, PathBuf::from("/site").join(source))
+        };
+        let asset = |source: &str, root: &str, path: &str| {
+            let item = ContentItem::Static(StaticFile::new(
+                source.to_string(),
+                PathBuf::from(path),
+                format!("/{}/{}", source, path),
+            ));
+            (item, PathBuf::from(root))
+        };
+        let content_dirs = [Path::new("/site/guide"), Path::new("/site/guides")];
+
+        let items = vec![
+            doc("guide", "index.md", "/"),
+            doc("guide", "guides/setup.md", "/guides/setup"),
+            doc("guides", "index.md", "/guides"),
+        ];
+        assert!(find_url_collisions(&items, &content_dirs, &[]).is_empty());
+
+        // Every collision is found, within a source and across sources
+        let items = vec![
+            doc("guides", "setup.md", "/guides/setup"),
+            doc("guide", "guides/setup.md", "/guides/setup"),
+            doc("guide", "intro.md", "/guide/intro"),
+            doc("guide", "intro/index.md", "/guide/intro"),
+            asset("guide", "/site/guide", "logo.png"),
+            // Files from `assets` directories are shown in full
+            asset("guide", "/site/assets", "logo.png"),
+        ];
+        let collisions: Vec<String> = find_url_collisions(&items, &content_dirs, &[])
+            .iter()
+            .map(|collision| collision.to_string())
+            .collect();
+        assert_eq!(
+            collisions,
+            [
+                "/guide/intro is produced by intro.md (source 'guide'), intro/index.md (source 'guide')",
+                "/guide/logo.png is produced by /site/assets/logo.png (source 'guide'), logo.png (source 'guide')",
+                "/guides/setup is produced by guides/setup.md (source 'guide'), setup.md (source 'guides')",
+            ]
+        );
+
+        // The favicon, logo, and social image are copied to the root by
+        // file name, so they can collide with each other and with files of
+        // a source at the root
+        let items = vec![(
+            ContentItem::Static(StaticFile::new(
+                "root".to_string(),
+                PathBuf::from("favicon.ico"),
+                "/favicon.ico".to_string(),
+            )),
+            PathBuf::from("/site/root"),
+        )];
+        let site_files = [
+            ("favicon", PathBuf::from("/site/favicon.ico")),
+            ("logo", PathBuf::from("/site/brand/logo.svg")),
+            ("social_image", PathBuf::from("/site/brand/logo.svg")),
+            ("social_image", PathBuf::from("/site/icons/logo.svg")),
+        ];
+        let collisions: Vec<String> =
+            find_url_collisions(&items, &[Path::new("/site/root")], &site_files)
+                .iter()
+                .map(|collision| collision.to_string())
+                .collect();
+        assert_eq!(
+            collisions,
+            [
+                "/favicon.ico is produced by /site/favicon.ico (site.favicon), favicon.ico (source 'root')",
+                "/logo.svg is produced by /site/brand/logo.svg (site.logo), /site/icons/logo.svg (site.social_image)",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let content = dir.join("content");
+        let shared = dir.join("shared/snippets");
+        std::fs::create_dir_all(content.join("guide")).unwrap();
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::write(content.join("index.md"), "# Home").unwrap();
+        std::fs::write(content.join("guide/setup.md"), "# Setup").unwrap();
+        std::fs::write(shared.join("note.md"), "# Note").unwrap();
+        std::fs::create_dir_all(content.join("partials")).unwrap();
+        std::fs::write(content.join("partials/tip.md"), "# Tip").unwrap();
+        symlink(content.join("index.md"), content.join("home.md")).unwrap();
+        // A link back up the tree, and one to snippets shared between sites
+        symlink(&content, content.join("guide/again")).unwrap();
+        symlink(&shared, content.join("snippets")).unwrap();
+        // Links to a sibling directory, whichever order the directory and
+        // the links are read in
+        symlink("../partials", content.join("guide/tips")).unwrap();
+        symlink("partials", content.join("a-tips")).unwrap();
+        symlink("partials", content.join("z-tips")).unwrap();
+        let canonical_dir = dir.canonicalize().unwrap();
+
+        let discover = |follow_directories: bool| {
+            let source = ResolvedSource {
+                config: serde_yaml::from_str(&format!(
+                    "name: docs\nlocal: {{ path: . }}\nfollow_symlinks: {{ directories: {} }}",
+                    follow_directories
+                ))
+                .unwrap(),
+                local_path: content.clone(),
+                checkout: None,
+                asset_dirs: Vec::new(),
+            };
+            let diagnostics = Diagnostics::new();
+            let items = source
+                .discover_content(
+                    &FormatRegistry::with_defaults(),
+                    &FrontMatterConfig::default(),
+                    UrlStyle::Directory,
+                    &diagnostics,
+                )
+                .unwrap();
+            let mut paths: Vec<String> = items
+                .iter()
+                .map(|(item, _)| match item {
+                    ContentItem::Document(doc) => doc.source_path.display().to_string(),
+                    ContentItem::Static(file) => file.source_path.display().to_string(),
+                })
+                .collect();
+            paths.sort();
+            let mut warnings: Vec<String> = diagnostics
+                .warnings()
+                .into_iter()
+                .map(|warning| {
+                    // Link targets are shown canonicalized
+                    warning
+                        .message
+                        .replace(canonical_dir.to_str().unwrap(), "")
+                        .replace(dir.to_str().unwrap(), "")
+                })
+                .collect();
+            warnings.sort();
+            (paths, warnings)
+        };
+
+        // Links to files are followed, and links to directories skipped
+        let (paths, warnings) = discover(false);
+        assert_eq!(
+            paths,
+            ["guide/setup.md", "home.md", "index.md", "partials/tip.md"]
+        );
+        assert_eq!(
+            warnings,
+            [
+                "Skipping /content/a-tips, a symbolic link to a directory (see follow_symlinks)",
+                "Skipping /content/guide/again, a symbolic link to a directory (see follow_symlinks)",
+                "Skipping /content/guide/tips, a symbolic link to a directory (see follow_symlinks)",
+                "Skipping /content/snippets, a symbolic link to a directory (see follow_symlinks)",
+                "Skipping /content/z-tips, a symbolic link to a directory (see follow_symlinks)",
+            ]
+        );
+
+        // Followed directory links can't loop, and leaving the source is
+        // allowed but warned about
+        let (paths, warnings) = discover(true);
+        assert_eq!(
+            paths,
+            [
+                "a-tips/tip.md",
+                "guide/setup.md",
+                "guide/tips/tip.md",
+                "home.md",
+                "index.md",
+                "partials/tip.md",
+                "snippets/note.md",
+                "z-tips/tip.md",
+            ]
+        );
+        assert_eq!(
+            warnings,
+            [
+                "/content/snippets is a symbolic link to /shared/snippets, outside the source",
+                "Skipping /content/guide/again, a symbolic link to /content, which contains it",
+            ]
+        );
+    }
 }
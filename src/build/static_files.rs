@@ -0,0 +1,100 @@
+//! Publishing static files to the output directory.
+//!
+//! A file whose output copy has the same size and modification time is
+//! left alone, so large assets aren't copied again on every build. Copies
+//! take their source's modification time to make that check work.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// What publishing a static file did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Published {
+    Copied,
+    /// Hard-linked to the source file
+    Linked,
+    /// The output was already up to date
+    Skipped,
+}
+
+/// Publish `input` at `output`, as a hard link if `link` is set and the
+/// file system allows, else as a copy.
+pub fn publish_static_file(input: &Path, output: &Path, link: bool) -> io::Result<Published> {
+    let source = std::fs::metadata(input)?;
+    if let Ok(existing) = std::fs::metadata(output)
+        && existing.len() == source.len()
+        && existing.modified().ok() == source.modified().ok()
+    {
+        return Ok(Published::Skipped);
+    }
+
+    // Replace rather than write over the old output, which may be a link
+    // to a source file
+    if output.exists() {
+        std::fs::remove_file(output)?;
+    }
+    if link && std::fs::hard_link(input, output).is_ok() {
+        return Ok(Published::Linked);
+    }
+    std::fs::copy(input, output)?;
+    File::options()
+        .write(true)
+        .open(output)?
+        .set_modified(source.modified()?)?;
+    Ok(Published::Copied)
+}
+
+/// Format a size in bytes for people, e.g. `1.5 MB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} bytes", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_static_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let input = dir.join("video.mp4");
+        let output = dir.join("out.mp4");
+        std::fs::write(&input, "frames").unwrap();
+
+        let publish = |link| publish_static_file(&input, &output, link).unwrap();
+        assert_eq!(publish(false), Published::Copied);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "frames");
+        assert_eq!(publish(false), Published::Skipped);
+
+        // A changed source is copied again
+        std::fs::write(&input, "more frames").unwrap();
+        assert_eq!(publish(false), Published::Copied);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "more frames");
+
+        // A link is the source file itself, so it's always up to date
+        std::fs::remove_file(&output).unwrap();
+        assert_eq!(publish(true), Published::Linked);
+        std::fs::write(&input, "new frames").unwrap();
+        assert_eq!(publish(false), Published::Skipped);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "new frames");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 bytes");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(250 * 1024 * 1024), "250.0 MB");
+    }
+}
@@ -0,0 +1,93 @@
+//! Build timing.
+//!
+//! Records how long each part of a build takes, such as resolving a source
+//! or running a pipeline stage, for the table `--timing` prints.
+
+use std::time::{Duration, Instant};
+
+/// How long each timed step of a build took, in the order they ran.
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    entries: Vec<(String, Duration)>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record how long a step took.
+    pub fn record(&mut self, label: impl Into<String>, duration: Duration) {
+        self.entries.push((label.into(), duration));
+    }
+
+    /// Run `f` and record how long it took.
+    pub fn time<T>(&mut self, label: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(label, start.elapsed());
+        result
+    }
+
+    /// Format the steps as a table, with each step's share of `total`.
+    pub fn table(&self, total: Duration) -> String {
+        let width = self
+            .entries
+            .iter()
+            .map(|(label, _)| label.len())
+            .chain(std::iter::once("total".len()))
+            .max()
+            .unwrap_or_default();
+
+        let mut out = String::from("Timing:\n");
+        for (label, duration) in &self.entries {
+            let percent = if total.is_zero() {
+                0.0
+            } else {
+                duration.as_secs_f64() / total.as_secs_f64() * 100.0
+            };
+            out.push_str(&format!(
+                "  {:<width$}  {:>10}  {:>5.1}%\n",
+                label,
+                format_duration(*duration),
+                percent
+            ));
+        }
+        out.push_str(&format!(
+            "  {:<width$}  {:>10}\n",
+            "total",
+            format_duration(total)
+        ));
+        out
+    }
+
+    /// Print the table from [`Timings::table`].
+    pub fn print(&self, total: Duration) {
+        print!("{}", self.table(total));
+    }
+}
+
+/// Format a duration in milliseconds, e.g. `12.3 ms`.
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let mut timings = Timings::new();
+        timings.record("resolve source 'docs'", Duration::from_millis(30));
+        timings.record("stage: markdown", Duration::from_millis(50));
+
+        assert_eq!(
+            timings.table(Duration::from_millis(100)),
+            "Timing:\n\
+             \x20 resolve source 'docs'     30.0 ms   30.0%\n\
+             \x20 stage: markdown           50.0 ms   50.0%\n\
+             \x20 total                    100.0 ms\n"
+        );
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{self, Receiver};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::time::Duration;
 
 use notify::event::ModifyKind;
@@ -56,8 +56,8 @@ pub struct WatchPaths {
     pub source_dirs: HashMap<String, PathBuf>,
     /// Theme directory (for template changes).
     pub theme_dir: PathBuf,
-    /// Config file path.
-    pub config_path: PathBuf,
+    /// Config files: the site's, and a child config's parent's.
+    pub config_paths: Vec<PathBuf>,
 }
 
 /// Classifies file paths into change types.
@@ -67,10 +67,12 @@ pub struct PathClassifier {
     source_dirs: HashMap<String, PathBuf>,
     /// Theme directory path.
     theme_dir: PathBuf,
-    /// Config file path.
-    config_path: PathBuf,
+    /// Config file paths.
+    config_paths: Vec<PathBuf>,
     /// Theme config file path.
     theme_config_path: PathBuf,
+    /// Theme static files directory path.
+    theme_static_dir: PathBuf,
 }
 
 impl PathClassifier {
@@ -78,15 +80,17 @@ impl PathClassifier {
     pub fn new(
         source_dirs: HashMap<String, PathBuf>,
         theme_dir: PathBuf,
-        config_path: PathBuf,
+        config_paths: Vec<PathBuf>,
     ) -> Self {
         let theme_config_path = theme_dir.join("undox-theme.yaml");
+        let theme_static_dir = theme_dir.join("static");
 
         Self {
             source_dirs,
             theme_dir,
-            config_path,
+            config_paths,
             theme_config_path,
+            theme_static_dir,
         }
     }
 
@@ -100,8 +104,8 @@ impl PathClassifier {
             return None;
         }
 
-        // Check if it's the main config
-        if path == self.config_path {
+        // Check if it's a config file
+        if self.config_paths.iter().any(|config| config == path) {
             return Some(ChangeKind::Config);
         }
 
@@ -132,14 +136,21 @@ impl PathClassifier {
             }
         }
 
-        // Check if it's a template (after sources, to allow theme docs to be sources)
+        // Check if it's a template or static file (after sources, to allow
+        // theme docs to be sources)
         if path.starts_with(&self.theme_dir) {
+            if path.starts_with(&self.theme_static_dir) {
+                return Some(ChangeKind::ThemeStatic {
+                    path: path.to_path_buf(),
+                    deleted,
+                });
+            }
             if path.extension().is_some_and(|e| e == "html") {
                 return Some(ChangeKind::Template {
                     path: path.to_path_buf(),
                 });
             }
-            // Other theme files (CSS, etc.) - ignore for now
+            // Other theme files - ignore
             return None;
         }
 
@@ -251,6 +262,22 @@ impl FileWatcher {
             FileWatcher::Polling { rx, .. } => rx.recv().ok(),
         }
     }
+
+    /// Receive the next watch event, waiting at most `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<WatchEvent, RecvTimeoutError> {
+        match self {
+            FileWatcher::Native { rx, .. } => rx.recv_timeout(timeout),
+            FileWatcher::Polling { rx, .. } => rx.recv_timeout(timeout),
+        }
+    }
+
+    /// Receive the watch events that are already waiting, without blocking.
+    pub fn drain(&self) -> Vec<WatchEvent> {
+        match self {
+            FileWatcher::Native { rx, .. } => rx.try_iter().collect(),
+            FileWatcher::Polling { rx, .. } => rx.try_iter().collect(),
+        }
+    }
 }
 
 /// Add watch paths to a debouncer.
@@ -270,11 +297,16 @@ fn add_watch_paths_to_debouncer<W: Watcher, C: notify_debouncer_full::FileIdCach
         debouncer.watch(&paths.theme_dir, RecursiveMode::Recursive)?;
     }
 
-    // Watch config file's parent directory (to catch config changes)
-    if let Some(parent) = paths.config_path.parent()
-        && parent.exists()
-    {
-        debouncer.watch(parent, RecursiveMode::NonRecursive)?;
+    // Watch config files' parent directories (to catch config changes)
+    let mut config_dirs: Vec<&Path> = Vec::new();
+    for config_path in &paths.config_paths {
+        if let Some(parent) = config_path.parent()
+            && parent.exists()
+            && !config_dirs.contains(&parent)
+        {
+            debouncer.watch(parent, RecursiveMode::NonRecursive)?;
+            config_dirs.push(parent);
+        }
     }
 
     Ok(())
@@ -1,4 +1,5 @@
 pub mod build;
+pub mod check;
 pub mod clean;
 pub mod init;
 pub mod serve;
@@ -1,46 +1,85 @@
+use std::path::Path;
+use std::time::Instant;
+
 use crate::{
     BuildArgs,
-    build::{Builder, base_path_from_config, build_search_index},
-    config::Config,
+    build::{Builder, Progress, base_path_from_config, build_search_index},
+    config::{Config, default_config_file, git_cache_dir},
+    git,
     theme::ThemeConfig,
 };
 
 pub async fn run(args: &BuildArgs) -> Result<(), anyhow::Error> {
+    if args.offline {
+        git::set_offline(true);
+    }
+
     // Determine the config file path
     let config_path = args
         .config_file
         .clone()
-        .unwrap_or_else(|| "undox.yaml".into());
+        .unwrap_or_else(|| default_config_file(Path::new("")));
     let config_path = if config_path.is_relative() {
         std::env::current_dir()?.join(&config_path)
     } else {
         config_path
     };
 
-    let config = Config::load_from_arg(Some(config_path.as_path())).await?;
+    let config = Config::load_from_arg(
+        Some(config_path.as_path()),
+        args.strict_config,
+        args.profile.as_deref(),
+    )
+    .await?;
 
     // Get the base path for resolving relative paths
     let base_path = base_path_from_config(&config_path);
 
     // Resolve config to root config and optional parent path
-    let (root_config, parent_path) = match config {
+    let (mut root_config, parent_path) = match config {
         Config::Root(root) => (root, None),
         Config::Child(child) => {
             // Resolve child config by fetching parent
-            let cache_dir = base_path.join(".undox/cache/git");
+            let cache_dir = git_cache_dir(None, &base_path);
             let resolved = child.resolve(&base_path, &cache_dir)?;
             (resolved.config, Some(resolved.parent_path))
         }
     };
 
+    // A full URL sets the site URL too; a bare path just the base path
+    if let Some(base_url) = &args.base_url {
+        match base_url.split_once("://") {
+            Some((_, rest)) => {
+                let path = rest.find('/').map_or("/", |i| &rest[i..]);
+                root_config.site.base_path = Some(path.to_string());
+                root_config.site.url = Some(base_url.clone());
+            }
+            None => root_config.site.base_path = Some(base_url.clone()),
+        }
+    }
+
+    if args.pdf {
+        root_config.pdf.enabled = true;
+    }
+
     // Build the site
     // Future: Using notify, we can invalidate certain files and rebuild
     // incrementally. We should be able to register callbacks for changes.
-    let mut builder = Builder::new(root_config, base_path);
+    let site_language = root_config.site.language.clone();
+    let multilingual = !root_config.languages.is_empty();
+    let url_style = root_config.site.url_style;
+    let progress = Progress::new(args.quiet);
+    let mut builder = Builder::new(root_config, base_path)
+        .with_progress(progress.clone())
+        .with_strict_links(args.strict_links)
+        .with_strict_nav(args.strict_nav)
+        .with_strict(args.strict)
+        .with_drafts(args.drafts);
     if let Some(parent_path) = parent_path {
         builder = builder.with_theme_base_path(parent_path);
     }
-    let result = builder.build().await?;
+    let start = Instant::now();
+    let mut result = builder.build().await?;
 
     println!(
         "Built site to {} ({} documents, {} static files)",
@@ -48,14 +87,36 @@ pub async fn run(args: &BuildArgs) -> Result<(), anyhow::Error> {
         result.documents,
         result.static_files
     );
+    for pdf in &result.pdfs {
+        println!("Wrote PDF to {}", pdf.display());
+    }
+    println!("Wrote build manifest to {}", result.manifest_path.display());
 
     // Load theme config for pagefind settings
     let theme_config = ThemeConfig::load(&result.theme_path)?;
 
     // Build search index
-    print!("Building search index...");
-    let page_count = build_search_index(&result.output_dir, &theme_config.pagefind).await?;
-    println!(" indexed {} pages", page_count);
+    progress.phase("Indexing");
+    let search_start = Instant::now();
+    let page_count = build_search_index(
+        &result.output_dir,
+        &theme_config.pagefind,
+        site_language.as_deref(),
+        multilingual,
+        url_style,
+    )
+    .await?;
+    println!("Indexed {} pages for search", page_count);
+    result
+        .timings
+        .record("search index", search_start.elapsed());
+
+    if args.sizes {
+        result.sizes.print(10);
+    }
+    if args.timing {
+        result.timings.print(start.elapsed());
+    }
 
     Ok(())
 }
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use crate::{
+    CheckArgs,
+    build::{Builder, base_path_from_config},
+    config::{Config, default_config_file, git_cache_dir},
+};
+
+pub async fn run(args: &CheckArgs) -> Result<(), anyhow::Error> {
+    // Determine the config file path
+    let config_path = args
+        .config_file
+        .clone()
+        .unwrap_or_else(|| default_config_file(Path::new("")));
+    let config_path = if config_path.is_relative() {
+        std::env::current_dir()?.join(&config_path)
+    } else {
+        config_path
+    };
+
+    let config =
+        Config::load_from_arg(Some(config_path.as_path()), args.strict_config, None).await?;
+    println!("Loaded {}", config_path.display());
+
+    // Get the base path for resolving relative paths
+    let base_path = base_path_from_config(&config_path);
+
+    // Resolve config to root config and optional parent path
+    let (root_config, parent_path) = match config {
+        Config::Root(root) => (root, None),
+        Config::Child(child) => {
+            let cache_dir = git_cache_dir(None, &base_path);
+            let resolved = child.resolve(&base_path, &cache_dir)?;
+            (resolved.config, Some(resolved.parent_path))
+        }
+    };
+
+    let mut builder = Builder::new(root_config, base_path);
+    if let Some(parent_path) = parent_path {
+        builder = builder.with_theme_base_path(parent_path);
+    }
+    let report = builder.check();
+
+    if !report.is_ok() {
+        eprintln!("Found {} problem(s):", report.problems.len());
+        report.print();
+        anyhow::bail!("check failed");
+    }
+
+    println!("No problems found ({} documents)", report.documents);
+    Ok(())
+}
@@ -1,57 +1,79 @@
-use crate::{CleanArgs, build::base_path_from_config, config::Config};
+use std::path::Path;
+
+use crate::{
+    CleanArgs,
+    build::base_path_from_config,
+    config::{Config, cache_dir, default_config_file, git_cache_dir},
+    util::normalize_relative_path,
+};
 
 pub async fn run(args: &CleanArgs) -> Result<(), anyhow::Error> {
     // Determine the config file path
     let config_path = args
         .config_file
         .clone()
-        .unwrap_or_else(|| "undox.yaml".into());
+        .unwrap_or_else(|| default_config_file(Path::new("")));
     let config_path = if config_path.is_relative() {
         std::env::current_dir()?.join(&config_path)
     } else {
         config_path
     };
 
-    let config = Config::load_from_arg(Some(config_path.as_path())).await?;
+    let config = Config::load_from_arg(Some(config_path.as_path()), false, None).await?;
 
     // Get the base path for resolving relative paths
     let base_path = base_path_from_config(&config_path);
 
-    // Look up the name of the build folder
-    let build_folder = match config {
-        Config::Root(root) => root.site.output,
+    // Look up the name of the build folder, the configured cache, and any
+    // extra paths to delete
+    let root = match config {
+        Config::Root(root) => root,
         Config::Child(child) => {
-            todo!()
+            // Resolve child config by fetching parent
+            let cache_dir = git_cache_dir(None, &base_path);
+            child.resolve(&base_path, &cache_dir)?.config
         }
     };
+    let (build_folder, configured_cache, clean) = (root.site.output, root.cache_dir, root.clean);
 
-    // Delete the generated site folder
-    let site_path = base_path
-        .join(build_folder)
-        .canonicalize()
-        .ok()
-        .unwrap_or("_site".into());
-    if site_path.exists() {
-        if args.dry_run {
-            println!("Would delete {}", site_path.display());
-        } else {
-            tokio::fs::remove_dir_all(&site_path).await?;
-            println!("Deleted {}", site_path.display());
+    let mut targets = Vec::new();
+    if !args.cache_only {
+        // The generated site folder
+        targets.push(base_path.join(build_folder));
+
+        // Extra paths, checked before anything is deleted
+        for path in &clean.extra_paths {
+            let relative = normalize_relative_path(path)
+                .filter(|relative| !relative.as_os_str().is_empty())
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "clean.extra_paths entry {} must be inside {}",
+                        path.display(),
+                        base_path.display()
+                    )
+                })?;
+            targets.push(base_path.join(relative));
         }
     }
+    if !args.keep_cache {
+        // The undox cache folder
+        targets.push(cache_dir(configured_cache.as_deref(), &base_path));
+    }
 
-    // Delete the undox cache folder
-    let cache_path = base_path
-        .join(".undox/cache")
-        .canonicalize()
-        .ok()
-        .unwrap_or(base_path.join(".undox/cache").into());
-    if cache_path.exists() {
+    for path in targets {
+        // Symlinks are removed, not followed
+        let Ok(metadata) = tokio::fs::symlink_metadata(&path).await else {
+            continue;
+        };
         if args.dry_run {
-            println!("Would delete {}", cache_path.display());
+            println!("Would delete {}", path.display());
         } else {
-            tokio::fs::remove_dir_all(&cache_path).await?;
-            println!("Deleted {}", cache_path.display());
+            if metadata.is_dir() {
+                tokio::fs::remove_dir_all(&path).await?;
+            } else {
+                tokio::fs::remove_file(&path).await?;
+            }
+            println!("Deleted {}", path.display());
         }
     }
 
@@ -1,3 +1,7 @@
+mod mkdocs;
+
+use std::path::Path;
+
 use indoc::indoc;
 
 use crate::InitArgs;
@@ -40,6 +44,10 @@ pub async fn run(args: &InitArgs) -> Result<(), anyhow::Error> {
         }
     }
 
+    if let Some(mkdocs_yml) = &args.from_mkdocs {
+        return import_mkdocs(&path, mkdocs_yml, args.force).await;
+    }
+
     let mut read_dir = tokio::fs::read_dir(path.clone()).await?;
     let is_empty = read_dir.next_entry().await?.is_none();
 
@@ -66,3 +74,51 @@ pub async fn run(args: &InitArgs) -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+/// Write an `undox.yaml` in `path` made from an MkDocs config, alongside the
+/// existing files.
+async fn import_mkdocs(path: &Path, mkdocs_yml: &Path, force: bool) -> Result<(), anyhow::Error> {
+    let config_path = path.join("undox.yaml");
+    if config_path.exists() && !force {
+        return Err(anyhow::anyhow!(
+            "{path} already exists\nUse --force to overwrite",
+            path = config_path.display()
+        ));
+    }
+
+    let mkdocs_yml = std::path::absolute(mkdocs_yml)?;
+    let content = tokio::fs::read_to_string(&mkdocs_yml)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to read {}: {}", mkdocs_yml.display(), e))?;
+
+    // MkDocs resolves docs_dir against mkdocs.yml; undox against undox.yaml
+    let docs_dir = serde_yaml::from_str::<serde_yaml::Value>(&content)
+        .ok()
+        .and_then(|value| value.get("docs_dir")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| "docs".to_string());
+    let docs_dir = mkdocs_yml.parent().unwrap_or(Path::new("")).join(docs_dir);
+    let docs_dir = docs_dir.canonicalize().unwrap_or(docs_dir);
+    let base = path.canonicalize().unwrap_or(path.to_path_buf());
+    let docs_path = match docs_dir.strip_prefix(&base) {
+        Ok(relative) => format!("./{}", relative.display()),
+        Err(_) => docs_dir.display().to_string(),
+    };
+
+    let imported = mkdocs::import(&content, &docs_path).map_err(|e| anyhow::anyhow!(e))?;
+    for warning in &imported.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let config_text = format!(
+        "# Imported from {}\n{}",
+        mkdocs_yml.file_name().unwrap_or_default().to_string_lossy(),
+        imported.config
+    );
+    tokio::fs::write(&config_path, config_text).await?;
+    if !path.join(".gitignore").exists() {
+        tokio::fs::write(path.join(".gitignore"), GITIGNORE_CONTENT).await?;
+    }
+
+    println!("Created {}", base.join("undox.yaml").display());
+    Ok(())
+}
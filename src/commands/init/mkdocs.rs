@@ -0,0 +1,246 @@
+//! Importing an MkDocs site.
+//!
+//! `undox init --from-mkdocs` turns a `mkdocs.yml` into an `undox.yaml`
+//! with one inline source for the docs directory. MkDocs options with no
+//! undox equivalent are reported as warnings and left out.
+
+use std::collections::HashMap;
+
+use serde_yaml::{Mapping, Value};
+
+use crate::config::{Config, NavItem, unknown_keys};
+
+/// `mkdocs.yml` keys with an undox equivalent.
+const SUPPORTED_KEYS: &[&str] = &[
+    "site_name",
+    "site_url",
+    "site_description",
+    "site_author",
+    "site_dir",
+    "docs_dir",
+    "repo_url",
+    "edit_uri",
+    "nav",
+    "extra",
+];
+
+/// An `undox.yaml` made from a `mkdocs.yml`.
+#[derive(Debug)]
+pub struct MkdocsImport {
+    /// The config, as YAML
+    pub config: String,
+    /// MkDocs settings that couldn't be carried over
+    pub warnings: Vec<String>,
+}
+
+/// Convert the contents of a `mkdocs.yml`.
+///
+/// `docs_path` is where the docs directory is, relative to the new config.
+/// Fails if the file isn't an MkDocs config, or if the result isn't a config
+/// undox can read.
+pub fn import(mkdocs_yml: &str, docs_path: &str) -> Result<MkdocsImport, String> {
+    let mkdocs: Mapping =
+        serde_yaml::from_str(mkdocs_yml).map_err(|e| format!("invalid mkdocs.yml: {}", e))?;
+    let mut warnings = Vec::new();
+
+    let mut site = Mapping::new();
+    let name = match mkdocs.get("site_name").and_then(Value::as_str) {
+        Some(name) => name.to_string(),
+        None => {
+            warnings.push("mkdocs.yml has no site_name; using \"Docs\"".to_string());
+            "Docs".to_string()
+        }
+    };
+    site.insert("name".into(), name.into());
+    if let Some(url) = mkdocs.get("site_url").and_then(Value::as_str) {
+        site.insert("url".into(), url.into());
+        // MkDocs serves under the URL's path; undox needs it spelled out
+        let path = url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+            .unwrap_or("/");
+        if !path.trim_matches('/').is_empty() {
+            site.insert("base_path".into(), path.trim_end_matches('/').into());
+        }
+    }
+    let site_keys = [
+        ("site_description", "description"),
+        ("site_author", "author"),
+        ("site_dir", "output"),
+        ("repo_url", "repository"),
+        ("edit_uri", "edit_path"),
+    ];
+    for (from, to) in site_keys {
+        if let Some(value) = mkdocs.get(from).and_then(Value::as_str) {
+            site.insert(to.into(), value.into());
+        }
+    }
+
+    let mut source = Mapping::new();
+    source.insert("name".into(), "docs".into());
+    let mut local = Mapping::new();
+    local.insert("path".into(), docs_path.into());
+    source.insert("local".into(), local.into());
+    if let Some(nav) = mkdocs.get("nav") {
+        let nav = convert_nav(nav, &mut warnings);
+        let nav = serde_yaml::to_value(nav).map_err(|e| e.to_string())?;
+        source.insert("nav".into(), nav);
+    }
+
+    let mut root = Mapping::new();
+    root.insert("site".into(), site.into());
+    root.insert("sources".into(), vec![Value::from(source)].into());
+    if let Some(extra) = mkdocs.get("extra").filter(|extra| extra.is_mapping()) {
+        root.insert("extra".into(), extra.clone());
+    }
+
+    for key in mkdocs.keys().filter_map(Value::as_str) {
+        if !SUPPORTED_KEYS.contains(&key) {
+            warnings.push(format!(
+                "`{}` has no undox equivalent and was left out",
+                key
+            ));
+        }
+    }
+
+    let config = serde_yaml::to_string(&root).map_err(|e| e.to_string())?;
+
+    // Make sure undox reads back what was written
+    let value: Value = serde_yaml::from_str(&config).map_err(|e| e.to_string())?;
+    if let Some(key) = unknown_keys(&value).first() {
+        return Err(format!("imported config has an {}", key));
+    }
+    match serde_yaml::from_value::<Config>(value) {
+        Ok(Config::Root(_)) => {}
+        Ok(Config::Child(_)) => return Err("imported config isn't a root config".to_string()),
+        Err(e) => return Err(format!("imported config doesn't parse: {}", e)),
+    }
+
+    Ok(MkdocsImport { config, warnings })
+}
+
+/// Convert an MkDocs `nav`. Entries are page paths, `Title: page` maps, or
+/// `Section: [entries]` maps, all with paths relative to the docs directory,
+/// as in undox. External links are dropped, since undox nav only links pages.
+fn convert_nav(nav: &Value, warnings: &mut Vec<String>) -> Vec<NavItem> {
+    let Some(entries) = nav.as_sequence() else {
+        warnings.push("`nav` isn't a list and was left out".to_string());
+        return Vec::new();
+    };
+
+    let mut items = Vec::new();
+    for entry in entries {
+        match entry {
+            Value::String(path) => items.push(NavItem::Path(path.clone())),
+            Value::Mapping(map) => {
+                for (title, target) in map {
+                    let Some(title) = title.as_str() else {
+                        continue;
+                    };
+                    match target {
+                        Value::String(path) if path.contains("://") => {
+                            warnings.push(format!(
+                                "nav link `{}` to {} is external and was left out",
+                                title, path
+                            ));
+                        }
+                        Value::String(path) => {
+                            let link = HashMap::from([(title.to_string(), path.clone())]);
+                            items.push(NavItem::Titled(link));
+                        }
+                        Value::Sequence(_) => items.push(NavItem::Section {
+                            section: title.to_string(),
+                            items: convert_nav(target, warnings),
+                            icon: None,
+                        }),
+                        _ => warnings.push(format!(
+                            "nav entry `{}` isn't a page or section and was left out",
+                            title
+                        )),
+                    }
+                }
+            }
+            _ => warnings.push("nav entry that isn't a page or section was left out".to_string()),
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MKDOCS_YML: &str = r#"
+site_name: My Project
+site_url: https://example.com/project/
+repo_url: https://github.com/example/project
+edit_uri: edit/main/docs/
+theme:
+  name: material
+markdown_extensions:
+  - admonition
+extra:
+  version: "2.1"
+nav:
+  - index.md
+  - Getting Started: start.md
+  - User Guide:
+      - guide/install.md
+      - Advanced:
+          - Tuning: guide/tuning.md
+  - GitHub: https://github.com/example/project
+"#;
+
+    #[test]
+    fn test_import() {
+        let imported = import(MKDOCS_YML, "./docs").unwrap();
+        let Config::Root(root) = serde_yaml::from_str(&imported.config).unwrap() else {
+            panic!("expected a root config");
+        };
+
+        assert_eq!(root.site.name, "My Project");
+        assert_eq!(
+            root.site.url.as_deref(),
+            Some("https://example.com/project/")
+        );
+        assert_eq!(root.site.base_path.as_deref(), Some("/project"));
+        assert_eq!(root.site.edit_path.as_deref(), Some("edit/main/docs/"));
+        assert_eq!(root.extra.get("version"), Some(&Value::from("2.1")));
+
+        let source = &root.sources[0];
+        assert_eq!(source.name, "docs");
+        let nav = serde_yaml::to_value(source.nav.as_ref().unwrap()).unwrap();
+        let expected: Value = serde_yaml::from_str(
+            r#"
+- index.md
+- Getting Started: start.md
+- section: User Guide
+  items:
+    - guide/install.md
+    - section: Advanced
+      items:
+        - Tuning: guide/tuning.md
+"#,
+        )
+        .unwrap();
+        assert_eq!(nav, expected);
+
+        assert_eq!(
+            imported.warnings,
+            vec![
+                "nav link `GitHub` to https://github.com/example/project is external and was left out",
+                "`theme` has no undox equivalent and was left out",
+                "`markdown_extensions` has no undox equivalent and was left out",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_minimal() {
+        let imported = import("site_name: Docs", "./docs").unwrap();
+        assert!(imported.warnings.is_empty());
+        assert!(imported.config.contains("path: ./docs"));
+
+        assert!(import("- not a config", "./docs").is_err());
+    }
+}
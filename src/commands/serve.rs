@@ -1,41 +1,213 @@
 use std::collections::HashMap;
 use std::convert::Infallible;
-use std::net::SocketAddr;
+use std::io::ErrorKind;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use std::time::{Duration, Instant};
 
-use axum::Router;
-use axum::extract::State;
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Request, Response, header};
+use axum::middleware::{self, Next};
 use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Redirect};
 use axum::routing::get;
+use axum::{Json, Router};
 use futures_util::stream::Stream;
-use tokio::sync::broadcast;
-use tower_http::services::ServeDir;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, watch};
+use tower_http::services::{ServeDir, ServeFile};
+use tower_http::trace::TraceLayer;
+use tracing::{Span, info};
 
 use crate::{
     ServeArgs,
     build::{
-        Builder, FileWatcher, PathClassifier, WatchEvent, WatchPaths, base_path_from_config,
-        build_search_index,
+        BuildResult, Builder, ChangeKind, FileWatcher, PathClassifier, WatchEvent, WatchPaths,
+        base_path_from_config, build_search_index, format_duration, normalize_base_path,
     },
-    config::{Config, RootConfig},
-    theme::ThemeConfig,
+    config::{Config, DevHeaders, RootConfig, UrlStyle, default_config_file, git_cache_dir},
+    git,
+    logging::REQUESTS,
+    theme::{PagefindConfig, ThemeConfig},
+    util::{glob_match, normalize_relative_path},
 };
 
+/// The live reload client, for themes to include in dev mode.
+const LIVE_RELOAD_SCRIPT: &str = include_str!("../../assets/live-reload.js");
+
+/// How often live reload clients get a heartbeat event. The client script
+/// reconnects after missing a few.
+const LIVE_RELOAD_HEARTBEAT: Duration = Duration::from_secs(10);
+
+/// What connected browsers should do after files change.
+#[derive(Debug, Clone)]
+enum Reload {
+    /// Reload the pages at these URL paths, or every page when `None`, now
+    /// that the site is at `version`.
+    Pages {
+        paths: Option<Vec<String>>,
+        version: String,
+    },
+    /// Swap in these theme stylesheets, by URL, without reloading.
+    Css(Vec<String>),
+    /// The server is shutting down, so end the event stream.
+    Shutdown,
+}
+
+#[derive(Deserialize)]
+struct LiveReloadParams {
+    /// Whether the client can swap stylesheets; other clients reload the
+    /// page instead.
+    #[serde(default)]
+    css: bool,
+}
+
+/// State shared by the dev server's own endpoints.
+#[derive(Clone)]
+struct AppState {
+    reload_tx: broadcast::Sender<Reload>,
+    status: Arc<RwLock<ServeState>>,
+}
+
+/// What the dev server's builds are doing, for `/_undox/status`.
+#[derive(Debug)]
+struct ServeState {
+    /// When the server started, in milliseconds since the Unix epoch
+    started: i64,
+    /// How many builds have succeeded
+    builds: u64,
+    /// Whether a rebuild is running
+    rebuilding: bool,
+    /// The last build that succeeded
+    last_build: Option<BuildStatus>,
+    /// Why the last build failed, if it did
+    error: Option<String>,
+}
+
+/// A finished build.
+#[derive(Debug, Clone, Serialize)]
+struct BuildStatus {
+    /// When the build finished, in RFC 3339 format
+    finished_at: String,
+    duration_ms: u64,
+    documents: usize,
+    static_files: usize,
+}
+
+impl ServeState {
+    fn new() -> Self {
+        Self {
+            started: chrono::Utc::now().timestamp_millis(),
+            builds: 0,
+            rebuilding: false,
+            last_build: None,
+            error: None,
+        }
+    }
+
+    /// The version of the site being served, which changes with each build
+    /// and each time the server starts, so live reload clients that were
+    /// disconnected can tell whether they missed anything.
+    fn version(&self) -> String {
+        format!("{}-{}", self.started, self.builds)
+    }
+
+    /// Record a build that succeeded.
+    fn built(&mut self, result: &BuildResult) {
+        self.builds += 1;
+        self.rebuilding = false;
+        self.error = None;
+        self.last_build = Some(BuildStatus {
+            finished_at: chrono::Utc::now().to_rfc3339(),
+            duration_ms: result.duration.as_millis() as u64,
+            documents: result.documents,
+            static_files: result.static_files,
+        });
+    }
+
+    /// Record a build that failed.
+    fn failed(&mut self, error: String) {
+        self.rebuilding = false;
+        self.error = Some(error);
+    }
+}
+
+/// Lock the serve state for writing.
+fn write_status(status: &RwLock<ServeState>) -> RwLockWriteGuard<'_, ServeState> {
+    // A panic while holding the lock can't leave the state half-written
+    status.write().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Handler for the build status, for scripts waiting on the dev server.
+async fn status_handler(State(app): State<AppState>) -> Json<serde_json::Value> {
+    let status = app.status.read().unwrap_or_else(|e| e.into_inner());
+    let state = if status.rebuilding {
+        "rebuilding"
+    } else if status.error.is_some() {
+        "error"
+    } else {
+        "ready"
+    };
+    Json(serde_json::json!({
+        "status": state,
+        "rebuilding": status.rebuilding,
+        "last_build": status.last_build,
+        "error": status.error,
+        "version": status.version(),
+        "live_reload_clients": app.reload_tx.receiver_count(),
+    }))
+}
+
 /// SSE handler for live reload notifications.
 async fn live_reload_handler(
-    State(tx): State<broadcast::Sender<()>>,
+    State(app): State<AppState>,
+    Query(params): Query<LiveReloadParams>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let rx = tx.subscribe();
+    let rx = app.reload_tx.subscribe();
+    let version = app
+        .status
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .version();
+    info!(target: REQUESTS, "Live reload client connected");
+    let status = app.status.clone();
+    let current_version = move || status.read().unwrap_or_else(|e| e.into_inner()).version();
+    // Reload events carry the version as their ID, so clients that don't
+    // reload still know which build they've heard about
+    let reload =
+        |paths: &str, version: String| Event::default().event("reload").id(version).data(paths);
     let stream = async_stream::stream! {
         let mut rx = rx;
+        let _connection = LiveReloadConnection;
+        // Tell the client which build it's connected to, so after
+        // reconnecting it can reload if it missed one
+        yield Ok(Event::default().event("hello").data(version));
         loop {
             match rx.recv().await {
-                Ok(_) => {
-                    yield Ok(Event::default().event("reload").data("reload"));
+                Ok(Reload::Shutdown) => break,
+                Ok(Reload::Css(urls)) if params.css => {
+                    let urls = serde_json::to_string(&urls).unwrap_or_default();
+                    yield Ok(Event::default().event("css").data(urls));
+                }
+                Ok(Reload::Pages { paths: Some(paths), version }) => {
+                    let paths = serde_json::to_string(&paths).unwrap_or_default();
+                    yield Ok(reload(&paths, version));
+                }
+                Ok(Reload::Pages { paths: None, version }) => {
+                    yield Ok(reload(r#"["*"]"#, version));
+                }
+                Ok(Reload::Css(_)) => {
+                    yield Ok(reload(r#"["*"]"#, current_version()));
                 }
                 Err(broadcast::error::RecvError::Lagged(_)) => {
-                    // Missed some messages, but that's fine - we just need the latest
-                    continue;
+                    // A missed message may have been the reload for the
+                    // client's page, so reload every page
+                    yield Ok(reload(r#"["*"]"#, current_version()));
                 }
                 Err(broadcast::error::RecvError::Closed) => {
                     break;
@@ -43,104 +215,231 @@ async fn live_reload_handler(
             }
         }
     };
-    Sse::new(stream).keep_alive(KeepAlive::default())
+    // Heartbeats let the client notice a connection that died without
+    // closing, such as over a laptop's sleep, and let the server drop the
+    // streams of clients that went away
+    Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(LIVE_RELOAD_HEARTBEAT)
+            .event(Event::default().event("heartbeat").data("heartbeat")),
+    )
+}
+
+/// Logs a live reload client disconnecting when its event stream is dropped.
+struct LiveReloadConnection;
+
+impl Drop for LiveReloadConnection {
+    fn drop(&mut self) {
+        info!(target: REQUESTS, "Live reload client disconnected");
+    }
+}
+
+/// Handler for the live reload client script.
+async fn live_reload_script_handler() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/javascript")],
+        LIVE_RELOAD_SCRIPT,
+    )
 }
 
 pub async fn run(args: &ServeArgs) -> Result<(), anyhow::Error> {
+    if args.offline {
+        git::set_offline(true);
+    }
+
+    // Serve a folder as it is, with no config to load
+    if let Some(dir) = &args.dir {
+        let site = Site {
+            output_dir: dir.clone(),
+            base: String::new(),
+            home: "/".to_string(),
+            url_style: UrlStyle::default(),
+            headers: ResponseHeaders::default(),
+        };
+        return serve_existing(args, site).await;
+    }
+
     // Determine the config file path
     let config_path = args
         .config_file
         .clone()
-        .unwrap_or_else(|| "undox.yaml".into());
+        .unwrap_or_else(|| default_config_file(Path::new("")));
     let config_path = if config_path.is_relative() {
         std::env::current_dir()?.join(&config_path)
     } else {
         config_path
     };
 
-    let config = Config::load_from_arg(Some(config_path.as_path())).await?;
-
     // Get the base path for resolving relative paths
     let base_path = base_path_from_config(&config_path);
 
-    // Resolve config to root config and optional parent path
-    let (root_config, parent_path) = match config {
-        Config::Root(root) => (root, None),
-        Config::Child(child) => {
-            // Resolve child config by fetching parent
-            let cache_dir = base_path.join(".undox/cache/git");
-            let resolved = child.resolve(&base_path, &cache_dir)?;
-            (resolved.config, Some(resolved.parent_path))
-        }
-    };
+    let (root_config, parent_path) =
+        load_config(&config_path, &base_path, args.profile.as_deref()).await?;
+    let headers = ResponseHeaders::new(&root_config.dev.headers)?;
+
+    // Serve the last build as it is; the config only says where it is
+    if args.no_build {
+        let output = &root_config.site.output;
+        let output_dir = if output.is_relative() {
+            base_path.join(output)
+        } else {
+            output.clone()
+        };
+        let base = normalize_base_path(root_config.site.base_path.as_deref());
+        let site = Site {
+            output_dir,
+            home: format!("{}/", base),
+            base,
+            url_style: root_config.site.url_style,
+            headers,
+        };
+        return serve_existing(args, site).await;
+    }
+
+    // Bind before building, so a busy port fails fast
+    let listeners = bind_listeners(&args.bind, args.port).await?;
 
     // Create broadcast channel for live reload
-    let (reload_tx, _) = broadcast::channel::<()>(16);
+    let (reload_tx, _) = broadcast::channel::<Reload>(16);
 
     // Build the site first
-    println!("Building site...");
-    let result = do_build(&root_config, &base_path, parent_path.as_deref(), true).await?;
+    info!("Building site...");
+    let start = Instant::now();
+    let mut result = do_build(&root_config, &base_path, parent_path.as_deref(), true).await?;
+    let status = Arc::new(RwLock::new(ServeState::new()));
+    write_status(&status).built(&result);
 
-    println!(
-        "Built {} documents, {} static files",
-        result.documents, result.static_files
+    info!(
+        "Built {} documents, {} static files in {}",
+        result.documents,
+        result.static_files,
+        format_duration(result.duration)
     );
 
     // Build search index
     let theme_config = ThemeConfig::load(&result.theme_path)?;
-    print!("Building search index...");
-    let page_count = build_search_index(&result.output_dir, &theme_config.pagefind).await?;
-    println!(" indexed {} pages", page_count);
-
-    // Set up file watcher if enabled
-    let _watcher_handle = if args.watch {
-        // Collect source directories to watch
-        let cache_dir = base_path.join(".undox/cache/git");
-        let source_dirs: HashMap<String, PathBuf> = root_config
-            .sources
-            .iter()
-            .filter_map(|source| {
-                use crate::build::source::ResolvedSource;
-                ResolvedSource::resolve(source.clone(), &base_path, &cache_dir)
-                    .ok()
-                    .map(|resolved| {
-                        // Canonicalize the path to ensure consistent matching with file events
-                        let canonical = resolved
-                            .local_path
-                            .canonicalize()
-                            .unwrap_or(resolved.local_path);
-                        (source.name.clone(), canonical)
-                    })
-            })
-            .collect();
+    if !args.no_index {
+        info!("Building search index...");
+        let search_start = Instant::now();
+        let page_count = build_search_index(
+            &result.output_dir,
+            &theme_config.pagefind,
+            root_config.site.language.as_deref(),
+            !root_config.languages.is_empty(),
+            root_config.site.url_style,
+        )
+        .await?;
+        info!("Indexed {} pages", page_count);
+        result
+            .timings
+            .record("search index", search_start.elapsed());
+    }
+    if args.timing {
+        result.timings.print(start.elapsed());
+    }
 
-        let watch_paths = WatchPaths {
-            source_dirs: source_dirs.clone(),
-            theme_dir: result.theme_path.clone(),
-            config_path: config_path.clone(),
-        };
+    // From here on, Ctrl+C lets a rebuild finish before stopping
+    let shutdown = shutdown_on_ctrl_c(reload_tx.clone());
 
-        let classifier =
-            PathClassifier::new(source_dirs, result.theme_path.clone(), config_path.clone());
+    // Set up file watcher if enabled
+    let watcher_handle = if args.watch {
+        let watch_paths = watch_paths(
+            &result.source_dirs,
+            parent_path.as_deref(),
+            &config_path,
+            &result.theme_path,
+        );
+        let classifier = PathClassifier::new(
+            watch_paths.source_dirs.clone(),
+            watch_paths.theme_dir.clone(),
+            watch_paths.config_paths.clone(),
+        );
 
         let watch_config = root_config.dev.watch.clone();
         match FileWatcher::new(&watch_config, &watch_paths, classifier) {
             Ok(watcher) => {
-                println!("Watching for changes...");
+                info!("Watching for changes...");
 
                 // Spawn rebuild task
-                let rebuild_config = root_config.clone();
+                let mut rebuild_config = root_config.clone();
                 let rebuild_base = base_path.clone();
-                let rebuild_parent = parent_path.clone();
+                let mut rebuild_parent = parent_path.clone();
+                let rebuild_config_path = config_path.clone();
+                let profile = args.profile.clone();
                 let rebuild_output = result.output_dir.clone();
-                let pagefind_config = theme_config.pagefind.clone();
+                let indexer = (!args.no_index).then(|| {
+                    SearchIndexer::spawn(result.output_dir.clone(), theme_config.pagefind.clone())
+                });
+                let theme_static = result.theme_path.join("static");
                 let watcher_reload_tx = reload_tx.clone();
+                let watcher_status = status.clone();
+                let timing = args.timing;
+                let watcher_shutdown = shutdown.clone();
 
                 Some(tokio::task::spawn_blocking(move || {
-                    while let Some(event) = watcher.recv() {
+                    let mut rebuilds = 0;
+                    while !*watcher_shutdown.borrow() {
+                        let event = match watcher.recv_timeout(SHUTDOWN_CHECK_INTERVAL) {
+                            Ok(event) => event,
+                            Err(RecvTimeoutError::Timeout) => continue,
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        };
                         match event {
-                            WatchEvent::FilesChanged(changes) => {
-                                println!("\nDetected {} change(s), rebuilding...", changes.len());
+                            WatchEvent::FilesChanged(first) => {
+                                // Merge in the changes that queued up during
+                                // the last rebuild, so a burst of saves
+                                // rebuilds once
+                                let mut changes: Vec<ChangeKind> = Vec::new();
+                                let queued =
+                                    watcher.drain().into_iter().filter_map(|event| match event {
+                                        WatchEvent::FilesChanged(changes) => Some(changes),
+                                        WatchEvent::Error(e) => {
+                                            eprintln!("Watch error: {}", e);
+                                            None
+                                        }
+                                    });
+                                for change in std::iter::once(first).chain(queued).flatten() {
+                                    if !changes.contains(&change) {
+                                        changes.push(change);
+                                    }
+                                }
+
+                                // Copy changed stylesheets over and swap
+                                // them in, rather than rebuilding
+                                if let Some(stylesheets) =
+                                    changed_stylesheets(&changes, &theme_static)
+                                {
+                                    match copy_stylesheets(
+                                        &stylesheets,
+                                        &theme_static,
+                                        &rebuild_output.join("_theme"),
+                                    ) {
+                                        Ok(()) => {
+                                            info!("\nUpdated {} stylesheet(s)", stylesheets.len());
+                                            let site_base = normalize_base_path(
+                                                rebuild_config.site.base_path.as_deref(),
+                                            );
+                                            let urls = stylesheets
+                                                .iter()
+                                                .map(|path| {
+                                                    format!(
+                                                        "{}/_theme/{}",
+                                                        site_base,
+                                                        url_path(path)
+                                                    )
+                                                })
+                                                .collect();
+                                            let _ = watcher_reload_tx.send(Reload::Css(urls));
+                                            continue;
+                                        }
+                                        Err(e) => {
+                                            eprintln!("Failed to copy stylesheets: {}", e)
+                                        }
+                                    }
+                                }
+
+                                info!("\nDetected {} change(s), rebuilding...", changes.len());
+                                write_status(&watcher_status).rebuilding = true;
 
                                 // Create a new runtime for the rebuild
                                 let rt = tokio::runtime::Builder::new_current_thread()
@@ -148,7 +447,29 @@ pub async fn run(args: &ServeArgs) -> Result<(), anyhow::Error> {
                                     .build()
                                     .expect("Failed to create runtime");
 
-                                let rebuild_succeeded = rt.block_on(async {
+                                // Reload a changed config, so every page
+                                // picks up its new settings
+                                if changes.contains(&ChangeKind::Config) {
+                                    match rt.block_on(load_config(
+                                        &rebuild_config_path,
+                                        &rebuild_base,
+                                        profile.as_deref(),
+                                    )) {
+                                        Ok((config, parent)) => {
+                                            rebuild_config = config;
+                                            rebuild_parent = parent;
+                                        }
+                                        Err(e) => {
+                                            eprintln!("Config error: {}", e);
+                                            write_status(&watcher_status)
+                                                .failed(format!("config error: {}", e));
+                                            continue;
+                                        }
+                                    }
+                                }
+
+                                let changed_pages = rt.block_on(async {
+                                    let start = Instant::now();
                                     match do_build(
                                         &rebuild_config,
                                         &rebuild_base,
@@ -158,32 +479,46 @@ pub async fn run(args: &ServeArgs) -> Result<(), anyhow::Error> {
                                     .await
                                     {
                                         Ok(result) => {
-                                            println!(
-                                                "Rebuilt {} documents, {} static files",
-                                                result.documents, result.static_files
+                                            write_status(&watcher_status).built(&result);
+                                            info!(
+                                                "Rebuilt {} documents, {} static files in {}",
+                                                result.documents,
+                                                result.static_files,
+                                                format_duration(result.duration)
                                             );
-                                            // Rebuild search index
-                                            match build_search_index(
-                                                &rebuild_output,
-                                                &pagefind_config,
-                                            )
-                                            .await
-                                            {
-                                                Ok(count) => println!("Re-indexed {} pages", count),
-                                                Err(e) => eprintln!("Search index error: {}", e),
+                                            if timing {
+                                                result.timings.print(start.elapsed());
                                             }
-                                            true
+                                            Some(result.changed_pages)
                                         }
                                         Err(e) => {
                                             eprintln!("Build error: {}", e);
-                                            false
+                                            write_status(&watcher_status).failed(e.to_string());
+                                            None
                                         }
                                     }
                                 });
 
                                 // Notify connected browsers to reload
-                                if rebuild_succeeded {
-                                    let _ = watcher_reload_tx.send(());
+                                rebuilds += 1;
+                                if let Some(changed_pages) = changed_pages {
+                                    let version = watcher_status
+                                        .read()
+                                        .unwrap_or_else(|e| e.into_inner())
+                                        .version();
+                                    let _ = watcher_reload_tx.send(Reload::Pages {
+                                        paths: reloaded_pages(&changes, changed_pages),
+                                        version,
+                                    });
+
+                                    // Re-index in the background, so the
+                                    // reload doesn't wait on it, unless no
+                                    // page's text could change
+                                    if let Some(indexer) = &indexer
+                                        && changes.iter().any(ChangeKind::affects_page_text)
+                                    {
+                                        indexer.index(&rebuild_config);
+                                    }
                                 }
                             }
                             WatchEvent::Error(e) => {
@@ -191,6 +526,12 @@ pub async fn run(args: &ServeArgs) -> Result<(), anyhow::Error> {
                             }
                         }
                     }
+                    // Let an index run finish, so the index isn't left
+                    // half-written
+                    if let Some(indexer) = indexer {
+                        indexer.finish();
+                    }
+                    rebuilds
                 }))
             }
             Err(e) => {
@@ -202,43 +543,596 @@ pub async fn run(args: &ServeArgs) -> Result<(), anyhow::Error> {
         None
     };
 
-    // Create the static file server
-    let serve_dir = ServeDir::new(&result.output_dir).append_index_html_on_directories(true);
+    let site = Site {
+        output_dir: result.output_dir,
+        base: normalize_base_path(root_config.site.base_path.as_deref()),
+        home: result.home_url,
+        url_style: root_config.site.url_style,
+        headers,
+    };
+    serve(args, listeners, site, reload_tx, status, shutdown).await?;
 
-    // Build router with SSE endpoint for live reload
-    let app = Router::new()
-        .route("/_undox/live-reload", get(live_reload_handler))
-        .with_state(reload_tx)
-        .fallback_service(serve_dir);
+    // The watcher stops once its rebuild, if any, is done
+    match watcher_handle {
+        Some(handle) => {
+            let rebuilds = handle.await.unwrap_or(0);
+            println!("Stopped the server after {} rebuild(s)", rebuilds);
+        }
+        None => println!("Stopped the server"),
+    }
+    Ok(())
+}
 
-    // Parse the address
-    let addr: SocketAddr = format!("{}:{}", args.bind, args.port).parse()?;
+/// Serve an output directory that's already built, without watching it.
+async fn serve_existing(args: &ServeArgs, site: Site) -> Result<(), anyhow::Error> {
+    let output_dir = &site.output_dir;
+    if !output_dir.is_dir() {
+        if args.dir.is_some() {
+            anyhow::bail!("directory {} doesn't exist", output_dir.display());
+        }
+        anyhow::bail!(
+            "output directory {} doesn't exist; build the site first, or serve without --no-build",
+            output_dir.display()
+        );
+    }
 
-    // Determine the URL to display
-    let display_host = if args.bind == "0.0.0.0" {
-        "localhost"
-    } else {
-        &args.bind
-    };
-    let url = format!("http://{}:{}", display_host, args.port);
+    let listeners = bind_listeners(&args.bind, args.port).await?;
+    info!("Serving {} without building", output_dir.display());
 
-    println!("\nServing site at {}", url);
+    let (reload_tx, _) = broadcast::channel::<Reload>(16);
+    let shutdown = shutdown_on_ctrl_c(reload_tx.clone());
+    let status = Arc::new(RwLock::new(ServeState::new()));
+    serve(args, listeners, site, reload_tx, status, shutdown).await?;
+    println!("Stopped the server");
+    Ok(())
+}
+
+/// A built site to serve.
+struct Site {
+    output_dir: PathBuf,
+    /// Path the site is served under, empty at the root
+    base: String,
+    /// URL of the site's home page, opened by `--open`
+    home: String,
+    url_style: UrlStyle,
+    headers: ResponseHeaders,
+}
+
+/// Serve a site until `shutdown` turns true and the open requests are
+/// answered.
+async fn serve(
+    args: &ServeArgs,
+    listeners: Vec<TcpListener>,
+    site: Site,
+    reload_tx: broadcast::Sender<Reload>,
+    status: Arc<RwLock<ServeState>>,
+    shutdown: watch::Receiver<bool>,
+) -> Result<(), anyhow::Error> {
+    let open_path = args
+        .open
+        .as_ref()
+        .map(|path| open_path(&site, path.as_deref()));
+
+    // Determine the URLs to display, one per address
+    let mut origins: Vec<String> = Vec::new();
+    for listener in &listeners {
+        let origin = origin(listener.local_addr()?);
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+    if let [origin] = origins.as_slice() {
+        println!("\nServing site at {}{}", origin, site.base);
+    } else {
+        println!("\nServing site at:");
+        for origin in &origins {
+            println!("  {}{}", origin, site.base);
+        }
+    }
     println!("Press Ctrl+C to stop\n");
 
     // Open browser if requested
-    if args.open
-        && let Err(e) = open::that(&url)
+    if let Some(path) = open_path
+        && let Err(e) = open::that(format!("{}{}", origins[0], path))
     {
         eprintln!("Failed to open browser: {}", e);
     }
 
-    // Start the server
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // Start the server, on every address
+    let app = router(site, AppState { reload_tx, status });
+    let servers = listeners.into_iter().map(|listener| {
+        let mut shutdown = shutdown.clone();
+        axum::serve(listener, app.clone())
+            .with_graceful_shutdown(async move {
+                let _ = shutdown.wait_for(|stop| *stop).await;
+            })
+            .into_future()
+    });
+    futures_util::future::try_join_all(servers).await?;
 
     Ok(())
 }
 
+/// The URL path `--open` opens: `path` under the site's base path, or the
+/// site's home page.
+fn open_path(site: &Site, path: Option<&str>) -> String {
+    let Some(path) = path else {
+        return site.home.clone();
+    };
+    let path = format!("/{}", path.trim_start_matches('/'));
+    let base = &site.base;
+    if base.is_empty() || path == *base || path.starts_with(&format!("{}/", base)) {
+        path
+    } else {
+        format!("{}{}", base, path)
+    }
+}
+
+/// Build the router serving a site's files and the dev server's endpoints.
+fn router(site: Site, state: AppState) -> Router {
+    // Create the static file server, answering unknown paths with the
+    // site's 404 page as hosts do. Only its responses get the configured
+    // headers, so they can't interfere with live reload's event stream.
+    let site_base = site.base.clone();
+    let serve_dir = ServeDir::new(&site.output_dir)
+        .append_index_html_on_directories(true)
+        .not_found_service(ServeFile::new(site.output_dir.join("404.html")));
+    let serve_dir =
+        Router::new()
+            .fallback_service(serve_dir)
+            .layer(middleware::from_fn_with_state(
+                Arc::new(site),
+                site_middleware,
+            ));
+
+    // Build router with the dev server's endpoints, at the root and under
+    // any base path. Under a base path the site is served there, as it will
+    // be when deployed.
+    let mut prefixes = vec![""];
+    if !site_base.is_empty() {
+        prefixes.push(&site_base);
+    }
+    let mut app = Router::new();
+    for prefix in prefixes {
+        app = app
+            .route(
+                &format!("{}/_undox/live-reload", prefix),
+                get(live_reload_handler),
+            )
+            .route(
+                &format!("{}/_undox/live-reload.js", prefix),
+                get(live_reload_script_handler),
+            )
+            .route(&format!("{}/_undox/status", prefix), get(status_handler));
+    }
+    let app = app.with_state(state);
+    let app = if site_base.is_empty() {
+        app.fallback_service(serve_dir)
+    } else {
+        app.nest_service(&site_base, serve_dir)
+    };
+
+    // Log each request with its status and how long it took
+    app.layer(
+        TraceLayer::new_for_http()
+            .make_span_with(|request: &Request<Body>| {
+                tracing::info_span!(
+                    target: REQUESTS,
+                    "request",
+                    method = %request.method(),
+                    path = %request.uri().path()
+                )
+            })
+            .on_request(())
+            .on_response(|response: &Response<Body>, latency: Duration, _: &Span| {
+                info!(
+                    target: REQUESTS,
+                    "{} in {}",
+                    response.status(),
+                    format_duration(latency)
+                )
+            })
+            .on_failure(()),
+    )
+}
+
+/// How often the watcher checks whether the server is shutting down.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Shut down on Ctrl+C: the returned receiver turns true and live reload
+/// clients are disconnected. A second Ctrl+C quits at once.
+fn shutdown_on_ctrl_c(reload_tx: broadcast::Sender<Reload>) -> watch::Receiver<bool> {
+    let (tx, rx) = watch::channel(false);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        println!("\nShutting down (press Ctrl+C again to quit now)...");
+        let _ = tx.send(true);
+        let _ = reload_tx.send(Reload::Shutdown);
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+    rx
+}
+
+/// The `dev.headers` for the site's files, parsed.
+#[derive(Default)]
+struct ResponseHeaders(Vec<(String, HeaderMap)>);
+
+impl ResponseHeaders {
+    fn new(config: &DevHeaders) -> Result<Self, anyhow::Error> {
+        let rules = config
+            .0
+            .iter()
+            .map(|rule| {
+                let mut headers = HeaderMap::new();
+                for (name, value) in &rule.headers {
+                    let name = HeaderName::try_from(name.as_str()).map_err(|_| {
+                        anyhow::anyhow!("dev.headers: '{}' isn't a valid header name", name)
+                    })?;
+                    let value = HeaderValue::try_from(value.as_str()).map_err(|_| {
+                        anyhow::anyhow!("dev.headers: {} has an invalid value '{}'", name, value)
+                    })?;
+                    headers.insert(name, value);
+                }
+                Ok((rule.pattern.clone(), headers))
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+        Ok(Self(rules))
+    }
+
+    /// Add the headers for `path` to a response's, and keep browsers from
+    /// caching pages between reloads unless a pattern says otherwise.
+    fn apply(&self, path: &str, response: &mut HeaderMap) {
+        for (pattern, headers) in &self.0 {
+            if glob_match(pattern, path) {
+                for (name, value) in headers {
+                    response.insert(name, value.clone());
+                }
+            }
+        }
+        let html = response
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/html"));
+        if html && !response.contains_key(header::CACHE_CONTROL) {
+            response.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        }
+    }
+}
+
+/// Middleware answering requests for the site's files as hosts do.
+///
+/// A directory's URL without a trailing slash gets its `index.html` when
+/// pages have directory-style URLs, which link to them that way, and
+/// otherwise redirects to the URL with the slash. The redirect is temporary,
+/// so browsers don't remember it after `site.url_style` changes. Files
+/// without an extension are sent as text, and responses get the
+/// `dev.headers` for their path.
+async fn site_middleware(
+    State(site): State<Arc<Site>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response<Body> {
+    let path = request.uri().path().to_string();
+    if !path.ends_with('/')
+        && let Some(dir) = normalize_relative_path(Path::new(path.trim_start_matches('/')))
+        && site.output_dir.join(&dir).is_dir()
+    {
+        let query = request
+            .uri()
+            .query()
+            .map(|query| format!("?{}", query))
+            .unwrap_or_default();
+        if site.url_style == UrlStyle::Directory
+            && site.output_dir.join(&dir).join("index.html").is_file()
+            && let Ok(uri) = format!("{}/{}", path, query).parse()
+        {
+            *request.uri_mut() = uri;
+        } else {
+            return Redirect::temporary(&format!("{}{}/{}", site.base, path, query))
+                .into_response();
+        }
+    }
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    let extensionless = !path.rsplit('/').next().unwrap_or_default().contains('.');
+    if extensionless
+        && headers
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|value| value == "application/octet-stream")
+    {
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+    }
+    site.headers.apply(&path, headers);
+    response
+}
+
+/// Port tried first when `--port` isn't given.
+const DEFAULT_PORT: u16 = 3000;
+
+/// How many ports after the default are tried before letting the OS pick one.
+const PORT_ATTEMPTS: u16 = 10;
+
+/// Bind the server's listeners, one per `--bind` address, all on the same
+/// port. An explicit `port` must be free; otherwise the default port is
+/// tried, then the ones after it, then any free port.
+async fn bind_listeners(
+    binds: &[String],
+    port: Option<u16>,
+) -> Result<Vec<TcpListener>, anyhow::Error> {
+    let mut addrs: Vec<IpAddr> = Vec::new();
+    for bind in binds {
+        let addr = parse_bind_addr(bind)?;
+        if !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+    }
+    // An IPv6 socket also takes IPv4 connections, unless it's told not to,
+    // which leaves the port to the other addresses
+    let v6_only = addrs.len() > 1;
+    let bind_port = |port: u16| {
+        // With port 0 the OS picks the first address's port, and the rest
+        // get the same one
+        let first = bind_socket(SocketAddr::new(addrs[0], port), v6_only)?;
+        let port = first.local_addr()?.port();
+        let mut listeners = vec![first];
+        for addr in &addrs[1..] {
+            listeners.push(bind_socket(SocketAddr::new(*addr, port), v6_only)?);
+        }
+        Ok::<_, std::io::Error>(listeners)
+    };
+
+    if let Some(port) = port {
+        return bind_port(port).map_err(|e| match e.kind() {
+            ErrorKind::AddrInUse => anyhow::anyhow!(
+                "port {} is already in use; pass a different --port, or leave it out to use a free one",
+                port
+            ),
+            _ => anyhow::anyhow!("couldn't bind to port {}: {}", port, e),
+        });
+    }
+
+    for port in DEFAULT_PORT..DEFAULT_PORT + PORT_ATTEMPTS {
+        match bind_port(port) {
+            Ok(listeners) => {
+                if port != DEFAULT_PORT {
+                    println!("Port {} is in use, so using port {}", DEFAULT_PORT, port);
+                }
+                return Ok(listeners);
+            }
+            Err(e) if e.kind() == ErrorKind::AddrInUse => continue,
+            Err(e) => return Err(anyhow::anyhow!("couldn't bind to port {}: {}", port, e)),
+        }
+    }
+
+    let listeners = bind_port(0)?;
+    println!(
+        "Port {} is in use, so using port {}",
+        DEFAULT_PORT,
+        listeners[0].local_addr()?.port()
+    );
+    Ok(listeners)
+}
+
+/// Parse a `--bind` address: an IPv4 or IPv6 address, with or without
+/// brackets, or a hostname, which is resolved to its first address.
+fn parse_bind_addr(bind: &str) -> Result<IpAddr, anyhow::Error> {
+    let unbracketed = bind
+        .strip_prefix('[')
+        .and_then(|addr| addr.strip_suffix(']'))
+        .unwrap_or(bind);
+    if let Ok(addr) = unbracketed.parse() {
+        return Ok(addr);
+    }
+    (bind, 0)
+        .to_socket_addrs()
+        .map_err(|e| anyhow::anyhow!("couldn't resolve bind address {}: {}", bind, e))?
+        .next()
+        .map(|addr| addr.ip())
+        .ok_or_else(|| anyhow::anyhow!("bind address {} has no addresses", bind))
+}
+
+/// Listen on `addr`, only taking IPv6 connections on an IPv6 address when
+/// `v6_only`.
+fn bind_socket(addr: SocketAddr, v6_only: bool) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(v6_only)?;
+    }
+    // As `TcpListener::bind` does, so a restarted server gets its port back
+    // right away
+    if cfg!(unix) {
+        socket.set_reuse_address(true)?;
+    }
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    TcpListener::from_std(socket.into())
+}
+
+/// The origin URL of a listener's address, with the unspecified address
+/// shown as `localhost`.
+fn origin(addr: SocketAddr) -> String {
+    match addr.ip() {
+        ip if ip.is_unspecified() => format!("http://localhost:{}", addr.port()),
+        IpAddr::V4(ip) => format!("http://{}:{}", ip, addr.port()),
+        IpAddr::V6(ip) => format!("http://[{}]:{}", ip, addr.port()),
+    }
+}
+
+/// The changed theme stylesheets, relative to the theme's `static/`
+/// directory, when they're the only changes.
+fn changed_stylesheets(changes: &[ChangeKind], theme_static: &Path) -> Option<Vec<PathBuf>> {
+    let mut stylesheets = changes
+        .iter()
+        .map(|change| match change {
+            ChangeKind::ThemeStatic {
+                path,
+                deleted: false,
+            } if path.extension().is_some_and(|e| e == "css") => {
+                path.strip_prefix(theme_static).ok().map(Path::to_path_buf)
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<PathBuf>>>()?;
+    stylesheets.sort();
+    stylesheets.dedup();
+    Some(stylesheets)
+}
+
+/// The pages browsers should reload after a rebuild for `changes`, which
+/// changed the HTML of `changed_pages`: just those when only documents were
+/// edited, or every page, as anything else can change a page without
+/// changing its HTML.
+fn reloaded_pages(changes: &[ChangeKind], changed_pages: Vec<String>) -> Option<Vec<String>> {
+    changes
+        .iter()
+        .all(|change| matches!(change, ChangeKind::Document { deleted: false, .. }))
+        .then_some(changed_pages)
+}
+
+/// Copy stylesheets from the theme's `static/` directory to `_theme/` in the
+/// output.
+fn copy_stylesheets(
+    stylesheets: &[PathBuf],
+    theme_static: &Path,
+    theme_output: &Path,
+) -> std::io::Result<()> {
+    for path in stylesheets {
+        let to = theme_output.join(path);
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(theme_static.join(path), to)?;
+    }
+    Ok(())
+}
+
+/// A relative path as a URL path, with forward slashes.
+fn url_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Settings of a search index run that change with the config.
+struct IndexJob {
+    language: Option<String>,
+    multilingual: bool,
+    url_style: UrlStyle,
+}
+
+/// Re-indexes a site for search on its own thread, so rebuilds don't wait
+/// on it.
+struct SearchIndexer {
+    jobs: std::sync::mpsc::Sender<IndexJob>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl SearchIndexer {
+    /// Start the thread indexing `output_dir`.
+    fn spawn(output_dir: PathBuf, pagefind: PagefindConfig) -> Self {
+        let (jobs, queue) = std::sync::mpsc::channel::<IndexJob>();
+        let thread = std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create runtime");
+            while let Ok(job) = queue.recv() {
+                // Rebuilds that finished while the last run went only need
+                // the latest config indexed
+                let job = queue.try_iter().last().unwrap_or(job);
+                let start = Instant::now();
+                match rt.block_on(build_search_index(
+                    &output_dir,
+                    &pagefind,
+                    job.language.as_deref(),
+                    job.multilingual,
+                    job.url_style,
+                )) {
+                    Ok(count) => info!(
+                        "Re-indexed {} pages in {}",
+                        count,
+                        format_duration(start.elapsed())
+                    ),
+                    Err(e) => eprintln!("Search index error: {}", e),
+                }
+            }
+        });
+        Self { jobs, thread }
+    }
+
+    /// Queue a run indexing the site as built with `config`.
+    fn index(&self, config: &RootConfig) {
+        let _ = self.jobs.send(IndexJob {
+            language: config.site.language.clone(),
+            multilingual: !config.languages.is_empty(),
+            url_style: config.site.url_style,
+        });
+    }
+
+    /// Wait for queued runs to finish.
+    fn finish(self) {
+        drop(self.jobs);
+        let _ = self.thread.join();
+    }
+}
+
+/// The paths to watch for a build: each source's directory, as the build
+/// resolved it, the theme, and the config file, along with a child config's
+/// parent's. A child config's sources are its synthetic root config's, so
+/// its local content and its parent's other sources are both watched. Paths
+/// are canonical, as file events name them.
+fn watch_paths(
+    source_dirs: &HashMap<String, PathBuf>,
+    parent_path: Option<&Path>,
+    config_path: &Path,
+    theme_dir: &Path,
+) -> WatchPaths {
+    let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let source_dirs = source_dirs
+        .iter()
+        .map(|(name, dir)| (name.clone(), canonical(dir)))
+        .collect();
+    let mut config_paths = vec![canonical(config_path)];
+    if let Some(parent_path) = parent_path {
+        config_paths.push(canonical(&default_config_file(parent_path)));
+    }
+    WatchPaths {
+        source_dirs,
+        theme_dir: canonical(theme_dir),
+        config_paths,
+    }
+}
+
+/// Load the config, resolving a child config to its parent's root config
+/// and the parent's directory.
+async fn load_config(
+    config_path: &Path,
+    base_path: &Path,
+    profile: Option<&str>,
+) -> Result<(RootConfig, Option<PathBuf>), anyhow::Error> {
+    let config = Config::load_from_arg(Some(config_path), false, profile).await?;
+    Ok(match config {
+        Config::Root(root) => (root, None),
+        Config::Child(child) => {
+            // Resolve child config by fetching parent
+            let cache_dir = git_cache_dir(None, base_path);
+            let resolved = child.resolve(base_path, &cache_dir)?;
+            (resolved.config, Some(resolved.parent_path))
+        }
+    })
+}
+
 /// Helper function to run the build
 async fn do_build(
     config: &RootConfig,
@@ -254,3 +1148,233 @@ async fn do_build(
     }
     Ok(builder.build().await?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::source::ResolvedSource;
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    /// A site with a page, a 404 page, and an extensionless file, served
+    /// under `base`, along with the directory it's in.
+    fn site(base: &str, url_style: UrlStyle) -> (tempfile::TempDir, Site) {
+        let temp = tempfile::tempdir().unwrap();
+        let output_dir = temp.path().to_path_buf();
+        std::fs::create_dir_all(output_dir.join("guide")).unwrap();
+        std::fs::write(output_dir.join("guide/index.html"), "<h1>Guide</h1>").unwrap();
+        std::fs::write(output_dir.join("404.html"), "<h1>Not here</h1>").unwrap();
+        std::fs::write(output_dir.join("CNAME"), "docs.example.com").unwrap();
+        let site = Site {
+            output_dir,
+            base: base.to_string(),
+            home: format!("{}/", base),
+            url_style,
+            headers: ResponseHeaders::default(),
+        };
+        (temp, site)
+    }
+
+    /// Request `path` from the site, and delete the site.
+    async fn get(
+        (_temp, site): (tempfile::TempDir, Site),
+        path: &str,
+    ) -> (StatusCode, HeaderMap, String) {
+        let (reload_tx, _) = broadcast::channel(1);
+        let state = AppState {
+            reload_tx,
+            status: Arc::new(RwLock::new(ServeState::new())),
+        };
+        let request = Request::get(path).body(Body::empty()).unwrap();
+        let response = router(site, state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, headers, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_directory_urls() {
+        let (status, headers, body) = get(site("", UrlStyle::Directory), "/guide").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "<h1>Guide</h1>");
+        assert_eq!(headers[header::CACHE_CONTROL], "no-store");
+
+        let (status, headers, _) =
+            get(site("/docs", UrlStyle::TrailingSlash), "/docs/guide?tab=1").await;
+        assert_eq!(status, StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(headers[header::LOCATION], "/docs/guide/?tab=1");
+    }
+
+    #[tokio::test]
+    async fn test_not_found_and_content_types() {
+        let (status, _, body) = get(site("/docs", UrlStyle::Directory), "/docs/nope").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, "<h1>Not here</h1>");
+
+        let (status, headers, body) = get(site("", UrlStyle::Directory), "/CNAME").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "docs.example.com");
+        assert_eq!(headers[header::CONTENT_TYPE], "text/plain; charset=utf-8");
+    }
+
+    #[tokio::test]
+    async fn test_watch_paths_for_child_config() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let parent = dir.join("parent");
+        let child = dir.join("child");
+        for path in ["guide", "api", "theme"] {
+            std::fs::create_dir_all(parent.join(path)).unwrap();
+        }
+        std::fs::create_dir_all(child.join("docs")).unwrap();
+        std::fs::write(
+            parent.join("undox.yaml"),
+            "site: { name: Docs }\n\
+             sources:\n\
+             \x20 - { name: guide, local: { path: ./guide } }\n\
+             \x20 - { name: api, local: { path: ./api } }\n",
+        )
+        .unwrap();
+        let child_config = child.join("undox.yaml");
+        std::fs::write(
+            &child_config,
+            "name: guide\nparent: { path: ../parent }\ncontent: { path: ./docs }\n",
+        )
+        .unwrap();
+
+        // The sources as the build resolves them
+        let (config, parent_path) = load_config(&child_config, &child, None).await.unwrap();
+        let fetcher = git::GitFetcher::new(git_cache_dir(None, &child));
+        let source_dirs: HashMap<String, PathBuf> = config
+            .expanded_sources()
+            .into_iter()
+            .map(|source| {
+                let resolved = ResolvedSource::resolve(source, &child, &fetcher).unwrap();
+                (resolved.config.name.clone(), resolved.local_path)
+            })
+            .collect();
+        let paths = watch_paths(
+            &source_dirs,
+            parent_path.as_deref(),
+            &child_config,
+            &child.join("../parent/theme"),
+        );
+        let canonical = |path: PathBuf| path.canonicalize().unwrap();
+        assert_eq!(paths.source_dirs["guide"], canonical(child.join("docs")));
+        assert_eq!(paths.source_dirs["api"], canonical(parent.join("api")));
+        assert_eq!(paths.theme_dir, canonical(parent.join("theme")));
+        assert_eq!(
+            paths.config_paths,
+            [
+                canonical(child_config),
+                canonical(parent.join("undox.yaml"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reloaded_pages() {
+        let edit = |path: &str, deleted: bool| ChangeKind::Document {
+            source_name: "guide".to_string(),
+            path: PathBuf::from(path),
+            deleted,
+        };
+        let pages = || vec!["/guide/setup".to_string()];
+        assert_eq!(
+            reloaded_pages(&[edit("setup.md", false), edit("setup.md", false)], pages()),
+            Some(pages())
+        );
+        assert_eq!(
+            reloaded_pages(&[edit("setup.md", false), edit("old.md", true)], pages()),
+            None
+        );
+        assert_eq!(
+            reloaded_pages(&[edit("setup.md", false), ChangeKind::Config], pages()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_open_path() {
+        let site = |base: &str, home: &str| Site {
+            output_dir: PathBuf::new(),
+            base: base.to_string(),
+            home: home.to_string(),
+            url_style: UrlStyle::Directory,
+            headers: ResponseHeaders::default(),
+        };
+        assert_eq!(open_path(&site("", "/guide/"), None), "/guide/");
+        assert_eq!(
+            open_path(&site("", "/"), Some("guide/setup")),
+            "/guide/setup"
+        );
+        let docs = site("/docs", "/docs/guide/");
+        assert_eq!(open_path(&docs, None), "/docs/guide/");
+        assert_eq!(open_path(&docs, Some("/guide")), "/docs/guide");
+        assert_eq!(open_path(&docs, Some("/docs/guide")), "/docs/guide");
+        assert_eq!(open_path(&docs, Some("/docs")), "/docs");
+        assert_eq!(open_path(&docs, Some("/docsite")), "/docs/docsite");
+    }
+
+    #[test]
+    fn test_parse_bind_addr() {
+        let v4: IpAddr = "0.0.0.0".parse().unwrap();
+        let v6: IpAddr = "::".parse().unwrap();
+        assert_eq!(parse_bind_addr("0.0.0.0").unwrap(), v4);
+        assert_eq!(parse_bind_addr("::").unwrap(), v6);
+        assert_eq!(parse_bind_addr("[::]").unwrap(), v6);
+        assert_eq!(
+            parse_bind_addr("[::1]").unwrap(),
+            "::1".parse::<IpAddr>().unwrap()
+        );
+        assert!(parse_bind_addr("localhost").unwrap().is_loopback());
+        assert!(parse_bind_addr("no such host!").is_err());
+
+        assert_eq!(origin(SocketAddr::new(v6, 3000)), "http://localhost:3000");
+        assert_eq!(origin("[::1]:3000".parse().unwrap()), "http://[::1]:3000");
+        assert_eq!(
+            origin("127.0.0.1:3000".parse().unwrap()),
+            "http://127.0.0.1:3000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bind_listeners_dual_stack() {
+        // Hosts without IPv6, such as some CI containers, can't bind `::`
+        if std::net::TcpListener::bind("[::1]:0").is_err() {
+            return;
+        }
+        // Both unspecified addresses share a port when the IPv6 socket
+        // leaves IPv4 alone
+        let binds = ["0.0.0.0".to_string(), "::".to_string()];
+        let listeners = bind_listeners(&binds, Some(0)).await.unwrap();
+        let addrs: Vec<SocketAddr> = listeners
+            .iter()
+            .map(|listener| listener.local_addr().unwrap())
+            .collect();
+        assert!(addrs[0].is_ipv4() && addrs[1].is_ipv6());
+        assert_eq!(addrs[0].port(), addrs[1].port());
+        assert!(
+            bind_listeners(&binds[1..], Some(addrs[0].port()))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let (status, headers, body) =
+            get(site("/docs", UrlStyle::Directory), "/docs/_undox/status").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["rebuilding"], false);
+        assert_eq!(body["last_build"], serde_json::Value::Null);
+        assert_eq!(body["live_reload_clients"], 0);
+        assert!(body["version"].as_str().unwrap().ends_with("-0"));
+    }
+}
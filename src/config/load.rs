@@ -1,32 +1,734 @@
 //! Configuration loading from files.
 //!
-//! This module handles reading and parsing configuration files.
+//! This module handles reading and parsing configuration files, including
+//! the files they `extends` and the selected profile.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use super::{Config, ConfigError};
+use serde::de::DeserializeOwned;
+
+use serde_yaml::Value;
+use tracing::info;
+
+use super::merge::deep_merge;
+use super::schema::{UnknownKey, unknown_keys};
+use super::{Config, ConfigError, Location};
+use crate::git::GitFetcher;
+
+/// Config file names looked for when none is given, in order.
+pub const CONFIG_FILE_NAMES: [&str; 4] = ["undox.yaml", "undox.yml", "undox.toml", "undox.json"];
+
+/// How many files deep `extends` may go.
+const MAX_EXTENDS_DEPTH: usize = 8;
+
+/// Environment variable that overrides the cache directory.
+pub const CACHE_DIR_ENV: &str = "UNDOX_CACHE_DIR";
+
+/// Cache directory used when none is configured.
+const DEFAULT_CACHE_DIR: &str = ".undox/cache";
+
+/// The format of a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file's extension. Anything that isn't
+    /// `.toml` or `.json` is read as YAML.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => ConfigFormat::Toml,
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Yaml,
+        }
+    }
+
+    /// Parse `content` in this format.
+    pub fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T, String> {
+        match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Find the config file in `dir`, trying each of [`CONFIG_FILE_NAMES`].
+pub fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// The config file to use in `dir` when none is given: the first one that
+/// exists, else `undox.yaml`.
+pub fn default_config_file(dir: &Path) -> PathBuf {
+    find_config_file(dir).unwrap_or_else(|| dir.join(CONFIG_FILE_NAMES[0]))
+}
+
+/// The directory undox caches data in: `UNDOX_CACHE_DIR` if set, else the
+/// configured `cache_dir`, else `.undox/cache`. A relative path is resolved
+/// against `base_path`.
+pub fn cache_dir(configured: Option<&Path>, base_path: &Path) -> PathBuf {
+    let env = std::env::var_os(CACHE_DIR_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    choose_cache_dir(env, configured, base_path)
+}
+
+/// The directory git repositories are cloned into, within [`cache_dir`].
+pub fn git_cache_dir(configured: Option<&Path>, base_path: &Path) -> PathBuf {
+    cache_dir(configured, base_path).join("git")
+}
+
+fn choose_cache_dir(env: Option<PathBuf>, configured: Option<&Path>, base_path: &Path) -> PathBuf {
+    let dir = env
+        .or_else(|| configured.map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_DIR));
+    if dir.is_relative() {
+        base_path.join(dir)
+    } else {
+        dir
+    }
+}
+
+/// Read and parse a config file of any supported format.
+pub(crate) fn read_config_file<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    ConfigFormat::from_path(path)
+        .parse(&content)
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
 
 impl Config {
-    /// Load the config from the command line argument, defaulting to `undox.yaml`
-    pub async fn load_from_arg(config_file: Option<&Path>) -> Result<Self, ConfigError> {
-        let config_file = config_file.unwrap_or(Path::new("undox.yaml"));
+    /// Load the config from the command line argument, defaulting to the
+    /// first of `undox.yaml`, `undox.yml`, `undox.toml`, and `undox.json`
+    ///
+    /// Unknown keys are printed as warnings, or are an error if `strict`.
+    /// `profile` names a profile to merge over the base config.
+    pub async fn load_from_arg(
+        config_file: Option<&Path>,
+        strict: bool,
+        profile: Option<&str>,
+    ) -> Result<Self, ConfigError> {
+        let config_file = config_file
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| default_config_file(Path::new("")));
         let config_file = if config_file.is_relative() {
             std::env::current_dir()
                 .map_err(ConfigError::CwdFailure)?
                 .join(config_file)
         } else {
-            config_file.to_path_buf()
+            config_file
         };
 
-        Self::load_from_file(&config_file).await
+        Self::load_from_file(&config_file, strict, profile).await
+    }
+
+    /// Load the config from a file path, in the format given by its extension
+    pub(crate) async fn load_from_file(
+        path: &Path,
+        strict: bool,
+        profile: Option<&str>,
+    ) -> Result<Self, ConfigError> {
+        // A git-hosted `extends` needs the cache before the config is parsed
+        let configured = read_config_file::<Value>(path)
+            .ok()
+            .and_then(|value| value.get("cache_dir")?.as_str().map(PathBuf::from));
+        let cache_dir = git_cache_dir(
+            configured.as_deref(),
+            path.parent().unwrap_or(Path::new(".")),
+        );
+        read_config(path, &cache_dir, strict, profile)
+    }
+}
+
+/// Read a config file, with the files it `extends` and the selected profile
+/// merged in, and check it. Git-hosted `extends` are cloned to `cache_dir`.
+pub(crate) fn read_config(
+    path: &Path,
+    cache_dir: &Path,
+    strict: bool,
+    profile: Option<&str>,
+) -> Result<Config, ConfigError> {
+    let mut unknown = Vec::new();
+    let mut value = read_with_extends(path, cache_dir, &mut Vec::new(), &mut unknown)?;
+    apply_profile(&mut value, path, profile, strict)?;
+
+    let config: Config = serde_yaml::from_value(value).map_err(|e| {
+        // A misspelled key is often why parsing failed
+        let mut msg = format!("failed to parse {}: {}", path.display(), e);
+        for (file, keys) in &unknown {
+            for key in keys {
+                msg.push_str(&format!("\n  - {}: {}", file.display(), key));
+            }
+        }
+        ConfigError::Validation(msg)
+    })?;
+
+    for (file, keys) in &unknown {
+        report_unknown(file, keys, strict)?;
+    }
+
+    if let Config::Root(root) = &config {
+        root.validate().map_err(ConfigError::Validation)?;
+    }
+    Ok(config)
+}
+
+/// Read a config file with the file it `extends` merged under it, so the
+/// extending file's settings win (see [`deep_merge`]).
+///
+/// `chain` holds the files being read, to catch cycles. The unknown keys of
+/// every file read are added to `unknown`.
+fn read_with_extends(
+    path: &Path,
+    cache_dir: &Path,
+    chain: &mut Vec<PathBuf>,
+    unknown: &mut Vec<(PathBuf, Vec<UnknownKey>)>,
+) -> Result<Value, ConfigError> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if chain.contains(&canonical) {
+        let cycle: Vec<String> = chain
+            .iter()
+            .chain([&canonical])
+            .map(|file| file.display().to_string())
+            .collect();
+        return Err(ConfigError::Validation(format!(
+            "config extends itself: {}",
+            cycle.join(" -> ")
+        )));
+    }
+    if chain.len() > MAX_EXTENDS_DEPTH {
+        return Err(ConfigError::Validation(format!(
+            "config extends more than {} files deep at {}",
+            MAX_EXTENDS_DEPTH,
+            path.display()
+        )));
+    }
+    chain.push(canonical);
+
+    let mut value: Value = read_config_file(path).map_err(ConfigError::Validation)?;
+    unknown.push((path.to_path_buf(), unknown_keys(&value)));
+
+    let extends = value.as_mapping_mut().and_then(|map| map.remove("extends"));
+    if let Some(extends) = extends {
+        let file = resolve_extends(extends, path, cache_dir)?;
+        let mut base = read_with_extends(&file, cache_dir, chain, unknown)?;
+        rebase_paths(&mut base, file.parent().unwrap_or(Path::new(".")));
+        deep_merge(&mut base, value);
+        value = base;
+    }
+
+    chain.pop();
+    Ok(value)
+}
+
+/// Find the file an `extends` value points to: a path relative to the
+/// extending file, or a file in a git repository. A directory means the
+/// config file in it.
+fn resolve_extends(
+    extends: Value,
+    config_path: &Path,
+    cache_dir: &Path,
+) -> Result<PathBuf, ConfigError> {
+    let location = match extends {
+        Value::String(path) => Location::Path { path: path.into() },
+        other => serde_yaml::from_value(other).map_err(|e| {
+            ConfigError::Validation(format!(
+                "invalid 'extends' in {}: {}",
+                config_path.display(),
+                e
+            ))
+        })?,
+    };
+
+    let target = match location {
+        Location::Path { path } => config_path.parent().unwrap_or(Path::new(".")).join(path),
+        Location::Git { git } => {
+            let git_loc = git.to_location();
+            info!("Fetching extended config from {}...", git_loc.url);
+            let fetcher = GitFetcher::new(cache_dir.to_path_buf());
+            let repo_path = fetcher.fetch_location(&git_loc)?;
+            match git_loc.path {
+                Some(path) => repo_path.join(path),
+                None => repo_path,
+            }
+        }
+    };
+
+    if target.is_dir() {
+        Ok(default_config_file(&target))
+    } else {
+        Ok(target)
+    }
+}
+
+/// Make the relative source and theme paths of an extended config relative
+/// to `dir`, the directory it was read from.
+fn rebase_paths(value: &mut Value, dir: &Path) {
+    fn rebase(location: Option<&mut Value>, dir: &Path) {
+        if let Some(Value::String(path)) = location.and_then(|location| location.get_mut("path"))
+            && Path::new(path.as_str()).is_relative()
+        {
+            *path = dir.join(path.as_str()).to_string_lossy().into_owned();
+        }
+    }
+
+    if let Some(Value::Sequence(sources)) = value.get_mut("sources") {
+        for source in sources {
+            rebase(source.get_mut("local"), dir);
+            rebase(source.get_mut("location"), dir);
+        }
+    }
+    if let Some(theme) = value.get_mut("theme") {
+        rebase(theme.get_mut("location"), dir);
+    }
+}
+
+/// Replace the `profiles` section of a raw config with the selected
+/// profile merged over it (see [`deep_merge`]).
+///
+/// The profile comes from `profiles.<name>` if there is one, else from the
+/// profile's own file next to the config.
+fn apply_profile(
+    value: &mut Value,
+    config_path: &Path,
+    profile: Option<&str>,
+    strict: bool,
+) -> Result<(), ConfigError> {
+    let profiles = value
+        .as_mapping_mut()
+        .and_then(|map| map.remove("profiles"));
+    let Some(name) = profile else {
+        return Ok(());
+    };
+
+    let overlay = match profiles.as_ref().and_then(|profiles| profiles.get(name)) {
+        Some(overlay) => overlay.clone(),
+        None => {
+            let file = profile_file(config_path, name)
+                .ok_or_else(|| unknown_profile(config_path, name, profiles.as_ref()))?;
+            let overlay = read_config_file(&file).map_err(ConfigError::Validation)?;
+            report_unknown(&file, &unknown_keys(&overlay), strict)?;
+            overlay
+        }
+    };
+    deep_merge(value, overlay);
+    Ok(())
+}
+
+/// Print a warning for each unknown key, or fail if `strict`.
+fn report_unknown(path: &Path, unknown: &[UnknownKey], strict: bool) -> Result<(), ConfigError> {
+    if strict && !unknown.is_empty() {
+        let keys: Vec<String> = unknown.iter().map(|key| format!("  - {}", key)).collect();
+        return Err(ConfigError::Validation(format!(
+            "{} has {} unknown key(s):\n{}",
+            path.display(),
+            unknown.len(),
+            keys.join("\n")
+        )));
+    }
+    for key in unknown {
+        eprintln!("Warning: {}: {}", path.display(), key);
     }
+    Ok(())
+}
+
+/// Find a profile's own file next to the config, like `undox.production.yaml`
+/// for `undox.yaml` and the `production` profile, in any supported format.
+fn profile_file(config_path: &Path, name: &str) -> Option<PathBuf> {
+    let stem = config_path.file_stem()?.to_str()?;
+    ["yaml", "yml", "toml", "json"]
+        .iter()
+        .map(|ext| config_path.with_file_name(format!("{}.{}.{}", stem, name, ext)))
+        .find(|path| path.is_file())
+}
+
+fn unknown_profile(config_path: &Path, name: &str, profiles: Option<&Value>) -> ConfigError {
+    let available: Vec<&str> = profiles
+        .and_then(|profiles| profiles.as_mapping())
+        .map(|profiles| profiles.keys().filter_map(|key| key.as_str()).collect())
+        .unwrap_or_default();
+    let available = if available.is_empty() {
+        "no profiles are defined".to_string()
+    } else {
+        format!("available profiles: {}", available.join(", "))
+    };
+    ConfigError::Validation(format!(
+        "unknown profile '{}': {} has no 'profiles.{}' section and there is no profile file next to it ({})",
+        name,
+        config_path.display(),
+        name,
+        available
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DevConfig, PdfConfig, PdfConverter, RootConfig, SiteConfig, StaticConfig};
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("undox.yml")),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("undox.TOML")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("undox.json")),
+            ConfigFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let toml = r#"
+            [site]
+            name = "Docs"
+
+            [extra]
+            latest_version = "2.4.1"
+
+            [[sources]]
+            name = "guide"
+            local = { path = "./guide" }
+            nav = ["index.md", { "Getting Started" = "start.md" }]
+        "#;
+        let json = r#"{ "parent": { "path": ".." }, "name": "cli", "content": { "path": "." } }"#;
+
+        let Config::Root(root) = ConfigFormat::Toml.parse::<Config>(toml).unwrap() else {
+            panic!("expected a root config");
+        };
+        assert_eq!(root.site.name, "Docs");
+        assert_eq!(root.sources[0].nav.as_ref().unwrap().len(), 2);
+        assert_eq!(root.extra["latest_version"], "2.4.1");
+        assert!(matches!(
+            ConfigFormat::Json.parse::<Config>(json).unwrap(),
+            Config::Child(_)
+        ));
+
+        // The site/parent check applies to every format
+        let err = ConfigFormat::Json.parse::<Config>(r#"{ "sources": [] }"#);
+        assert!(err.unwrap_err().contains("must have either 'site'"));
+    }
+
+    #[test]
+    fn test_choose_cache_dir() {
+        let base = Path::new("/site");
+        assert_eq!(
+            choose_cache_dir(None, None, base),
+            PathBuf::from("/site/.undox/cache")
+        );
+        assert_eq!(
+            choose_cache_dir(None, Some(Path::new("tmp/cache")), base),
+            PathBuf::from("/site/tmp/cache")
+        );
+        // The environment wins over the config
+        assert_eq!(
+            choose_cache_dir(
+                Some(PathBuf::from("/ci/cache")),
+                Some(Path::new("tmp/cache")),
+                base
+            ),
+            PathBuf::from("/ci/cache")
+        );
+    }
+
+    #[test]
+    fn test_validate_redirects() {
+        let site = |yaml: &str| -> SiteConfig { serde_yaml::from_str(yaml).unwrap() };
+
+        let ok = site("name: Docs\nredirects: { /old: /new, /chat: 'https://example.com' }");
+        assert!(ok.validate().is_ok());
+
+        let err = site("name: Docs\nredirects: { old: /new }").validate();
+        assert!(err.unwrap_err().contains("must start with '/'"));
+        let err = site("name: Docs\nredirects: { /v1/*/x: /new }").validate();
+        assert!(err.unwrap_err().contains("last segment"));
+        let err = site("name: Docs\nredirects: { /v1/*: /latest }").validate();
+        assert!(err.unwrap_err().contains("redirects_format"));
+        let err = site("name: Docs\nredirects: { /old: new }").validate();
+        assert!(err.unwrap_err().contains("must be a path"));
+
+        let ok = site("name: Docs\nredirects: { /v1/*: /latest }\nredirects_format: vercel");
+        assert!(ok.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_favicon() {
+        let site = |yaml: &str| -> SiteConfig { serde_yaml::from_str(yaml).unwrap() };
+
+        assert!(
+            site("name: Docs\nfavicon: assets/favicon.ICO")
+                .validate()
+                .is_ok()
+        );
+        assert!(site("name: Docs\nfavicon: icon.svg").validate().is_ok());
+        // URLs are used as they are
+        assert!(
+            site("name: Docs\nfavicon: 'https://cdn.example.com/icon'")
+                .validate()
+                .is_ok()
+        );
+
+        let err = site("name: Docs\nfavicon: icon.jpg").validate();
+        assert!(
+            err.unwrap_err()
+                .contains("must be an .ico, .png, or .svg file")
+        );
+        assert!(site("name: Docs\nfavicon: favicon").validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_feed() {
+        let root = |feed: &str| -> RootConfig {
+            serde_yaml::from_str(&format!(
+                "site: {{ name: Docs }}\nsources: [{{ name: blog, local: {{ path: . }} }}]\nfeed: {}",
+                feed
+            ))
+            .unwrap()
+        };
+
+        let ok = root("{ source: blog }");
+        assert!(ok.validate().is_ok());
+        let feed = ok.feed.unwrap();
+        assert_eq!((feed.output.as_str(), feed.limit), ("atom.xml", 20));
+
+        let err = root("{ source: news }").validate().unwrap_err();
+        assert_eq!(err, "feed.source 'news' isn't a source");
+        assert!(
+            root("{ source: blog, output: ../atom.xml }")
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_static_max_file_size() {
+        let size = |value: &str| {
+            serde_yaml::from_str::<StaticConfig>(&format!("max_file_size: {}", value))
+                .map(|config| config.max_file_size.unwrap().0)
+        };
+        assert_eq!(size("1000").unwrap(), 1000);
+        assert_eq!(size("50MB").unwrap(), 50 * 1024 * 1024);
+        assert_eq!(size("1.5 gb").unwrap(), 1536 * 1024 * 1024);
+        assert!(size("lots").is_err());
+        assert!(size("10 TB").is_err());
+    }
+
+    #[test]
+    fn test_pdf() {
+        let pdf = |value: &str| serde_yaml::from_str::<PdfConfig>(value).unwrap();
+        assert!(pdf("true").enabled);
+        assert_eq!(pdf("true").command(), "chromium");
+        assert!(!pdf("false").enabled);
+
+        let config = pdf("{ converter: weasyprint }");
+        assert!(config.enabled);
+        assert_eq!(config.converter, PdfConverter::Weasyprint);
+        assert_eq!(config.command(), "weasyprint");
+        assert_eq!(
+            pdf("{ command: /opt/chrome/chrome }").command(),
+            "/opt/chrome/chrome"
+        );
+        assert!(serde_yaml::from_str::<PdfConfig>("{ converter: prince }").is_err());
+    }
+
+    #[test]
+    fn test_dev_headers() {
+        let dev: DevConfig = serde_yaml::from_str(
+            "headers: { '/**': { Access-Control-Allow-Origin: '*' }, \
+             '/sw.js': { Service-Worker-Allowed: /, Access-Control-Max-Age: 600 } }",
+        )
+        .unwrap();
+        let rules: Vec<_> = dev
+            .headers
+            .0
+            .iter()
+            .map(|rule| (rule.pattern.as_str(), rule.headers.len()))
+            .collect();
+        assert_eq!(rules, [("/**", 1), ("/sw.js", 2)]);
+        assert_eq!(
+            dev.headers.0[1].headers[1],
+            ("Access-Control-Max-Age".to_string(), "600".to_string())
+        );
+        assert!(serde_yaml::from_str::<DevConfig>("headers: { '/**': nosniff }").is_err());
+    }
+
+    #[test]
+    fn test_validate_url_prefixes() {
+        let root = |sources: &str| -> RootConfig {
+            serde_yaml::from_str(&format!("site: {{ name: Docs }}\nsources: {}", sources)).unwrap()
+        };
+
+        let ok = root(
+            "[{ name: guide, url_prefix: /, local: { path: . } }, \
+             { name: api, url_prefix: api/, local: { path: . } }, \
+             { name: apis, local: { path: . } }]",
+        );
+        assert!(ok.validate().is_ok());
+        assert_eq!(ok.sources[1].url_prefix(), "/api");
+
+        let err = root(
+            "[{ name: guide, url_prefix: /, local: { path: . } }, \
+             { name: blog, url_prefix: '', local: { path: . } }]",
+        )
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "sources 'guide' and 'blog' both have the url_prefix '/'"
+        );
+
+        let err = root(
+            "[{ name: v2, url_prefix: /api/v2, local: { path: . } }, \
+             { name: api, url_prefix: /api, local: { path: . } }]",
+        )
+        .validate()
+        .unwrap_err();
+        assert!(err.starts_with(
+            "source 'v2' (url_prefix '/api/v2') is inside source 'api' (url_prefix '/api')"
+        ));
+    }
+
+    #[test]
+    fn test_versions() {
+        let root = |sources: &str| -> RootConfig {
+            serde_yaml::from_str(&format!("site: {{ name: Docs }}\nsources: {}", sources)).unwrap()
+        };
+
+        let config = root(
+            "[{ name: api, location: { git: 'https://example.com/api' }, \
+             versions: [{ name: v2, ref: v2.0.0 }, { name: v1, ref: v1.4.0 }] }]",
+        );
+        assert!(config.validate().is_ok());
+        let sources = config.expanded_sources();
+        let names: Vec<_> = sources.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["api", "api@v1"]);
+        assert_eq!(sources[0].url_prefix(), "/api/v2");
+        assert_eq!(sources[1].url_prefix(), "/api/v1");
+        let git = sources[1].location.location().as_git().unwrap();
+        assert_eq!(git.git_ref.as_deref(), Some("v1.4.0"));
+
+        let err = root("[{ name: api, local: { path: . }, versions: [{ name: v2, ref: main }] }]")
+            .validate()
+            .unwrap_err();
+        assert_eq!(err, "source 'api' has versions, which need a git location");
+
+        let err = root(
+            "[{ name: api, location: { git: 'https://example.com/api' }, \
+             versions: [{ name: v2, ref: main }, { name: v2, ref: v2.0.0 }] }]",
+        )
+        .validate()
+        .unwrap_err();
+        assert_eq!(err, "source 'api' has the version 'v2' twice");
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let yaml = r#"
+site: { name: Docs, url: "https://staging.example.com" }
+sources: []
+profiles:
+  production:
+    site: { url: "https://example.com" }
+"#;
+        let path = Path::new("/nonexistent/undox.yaml");
+
+        let mut value: Value = serde_yaml::from_str(yaml).unwrap();
+        apply_profile(&mut value, path, Some("production"), false).unwrap();
+        assert_eq!(value["site"]["url"], "https://example.com");
+        assert_eq!(value["site"]["name"], "Docs");
+        assert!(value.get("profiles").is_none());
+
+        // Without a profile the section is just dropped
+        let mut value: Value = serde_yaml::from_str(yaml).unwrap();
+        apply_profile(&mut value, path, None, false).unwrap();
+        assert_eq!(value["site"]["url"], "https://staging.example.com");
+        assert!(value.get("profiles").is_none());
+
+        let mut value: Value = serde_yaml::from_str(yaml).unwrap();
+        let err = apply_profile(&mut value, path, Some("prod"), false).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("unknown profile 'prod'"));
+        assert!(msg.contains("available profiles: production"));
+    }
+
+    #[test]
+    fn test_rebase_paths() {
+        let mut value: Value = serde_yaml::from_str(
+            r#"
+sources:
+  - { name: sdk, local: { path: ./sdk } }
+  - { name: api, location: { path: /srv/api } }
+  - { name: cli, location: { git: "https://example.com/cli" } }
+theme: { location: { path: theme } }
+"#,
+        )
+        .unwrap();
+        rebase_paths(&mut value, Path::new("/shared"));
+
+        assert_eq!(value["sources"][0]["local"]["path"], "/shared/./sdk");
+        assert_eq!(value["sources"][1]["location"]["path"], "/srv/api");
+        assert_eq!(
+            value["sources"][2]["location"]["git"],
+            "https://example.com/cli"
+        );
+        assert_eq!(value["theme"]["location"]["path"], "/shared/theme");
+    }
+
+    #[test]
+    fn test_extends() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("shared")).unwrap();
+        std::fs::write(
+            dir.join("shared/base.yaml"),
+            "site: { name: Shared, url: https://example.com }\nsources: [{ name: sdk, local: { path: sdk } }]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("undox.yaml"),
+            "extends: shared/base.yaml\nsite: { name: Docs }\n",
+        )
+        .unwrap();
 
-    /// Load the config from a file path
-    pub(crate) async fn load_from_file(path: &Path) -> Result<Self, ConfigError> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| ConfigError::Validation(format!("failed to read config file: {}", e)))?;
+        let mut unknown = Vec::new();
+        let value = read_with_extends(
+            &dir.join("undox.yaml"),
+            &dir.join("cache"),
+            &mut Vec::new(),
+            &mut unknown,
+        )
+        .unwrap();
+        assert_eq!(value["site"]["name"], "Docs");
+        assert_eq!(value["site"]["url"], "https://example.com");
+        let sdk_path = dir.join("shared/sdk");
+        assert_eq!(
+            value["sources"][0]["local"]["path"],
+            sdk_path.to_str().unwrap()
+        );
+        assert_eq!(unknown.len(), 2);
 
-        serde_yaml::from_str(&content)
-            .map_err(|e| ConfigError::Validation(format!("failed to parse config: {}", e)))
+        // A file that extends itself, through another, is an error
+        std::fs::write(dir.join("shared/base.yaml"), "extends: ../undox.yaml\n").unwrap();
+        let err = read_with_extends(
+            &dir.join("undox.yaml"),
+            &dir.join("cache"),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("config extends itself"));
     }
 }
@@ -0,0 +1,73 @@
+//! Merging raw config values.
+//!
+//! Profiles are layered over the base config before it is parsed, so a
+//! profile only needs the settings it changes.
+
+use serde_yaml::Value;
+
+/// Merge `overlay` into `base`.
+///
+/// Maps merge key by key, recursively, so an overlay can change a single
+/// nested setting. Anything else, including lists, replaces the base value
+/// outright: a profile's `sources` list is the whole list, not additions.
+pub fn deep_merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(s: &str) -> Value {
+        serde_yaml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_deep_merge() {
+        let mut base = yaml(
+            r#"
+site: { name: Docs, url: "https://staging.example.com" }
+theme: { settings: { banner: true, logo: { light: a.png, dark: b.png } } }
+markdown: { extensions: [tables, footnotes] }
+"#,
+        );
+        let overlay = yaml(
+            r#"
+site: { url: "https://example.com" }
+theme: { settings: { logo: { dark: c.png } } }
+markdown: { extensions: [tables] }
+dev: { live_reload: false }
+"#,
+        );
+        deep_merge(&mut base, overlay);
+
+        let expected = yaml(
+            r#"
+site: { name: Docs, url: "https://example.com" }
+theme: { settings: { banner: true, logo: { light: a.png, dark: c.png } } }
+markdown: { extensions: [tables] }
+dev: { live_reload: false }
+"#,
+        );
+        assert_eq!(base, expected);
+    }
+
+    #[test]
+    fn test_deep_merge_replaces_mismatched_types() {
+        let mut base = yaml("nav: [index.md, guide/]\nurl_prefix: /cli");
+        deep_merge(&mut base, yaml("nav: { section: Guide }\nurl_prefix: null"));
+        assert_eq!(base, yaml("nav: { section: Guide }\nurl_prefix: null"));
+    }
+}
@@ -3,18 +3,30 @@
 //! This module handles all aspects of configuration:
 //! - Type definitions for config structures (`types`)
 //! - Loading configs from files (`load`)
+//! - Merging profiles over the base config (`merge`)
+//! - Reporting keys no setting reads (`schema`)
 //! - Resolving child configs to synthetic root configs (`resolve`)
 
 mod load;
+mod merge;
 mod resolve;
+mod schema;
 mod types;
 
 use serde::{Deserialize, Deserializer, Serialize};
 
+pub(crate) use load::read_config_file;
+pub use load::{CACHE_DIR_ENV, cache_dir, default_config_file, find_config_file, git_cache_dir};
+pub use schema::{UnknownKey, unknown_keys};
+
 // Re-export all types for convenient access
 pub use types::{
-    ChildConfig, DevConfig, GitLocation, GitValue, Location, MarkdownConfig, NavConfig, NavItem,
-    RootConfig, SiteConfig, SourceConfig, SourceLocation, ThemeConfig, WatchConfig,
+    AnchorPosition, AnnouncementConfig, AssetsConfig, BudgetConfig, BuildConfig, ChildConfig,
+    CleanConfig, DevConfig, DevHeaders, FeedConfig, FollowSymlinks, FooterConfig,
+    FrontMatterConfig, GitLocation, GitValue, HeadingsConfig, HtmlPolicy, HtmlRule, ImagesConfig,
+    InjectPosition, Languages, Location, MarkdownConfig, NavConfig, NavItem, NavMode,
+    OrphanSeverity, PdfConfig, PdfConverter, RedirectsFormat, RootConfig, SiteConfig, SourceConfig,
+    SourceLocation, StaticConfig, ThemeConfig, UrlRewrite, UrlStyle, WatchConfig, is_url,
 };
 
 // =============================================================================
@@ -52,6 +64,7 @@ pub enum ConfigError {
 /// The top-level configuration, which can be either a root site config
 /// or a child config that points to a parent site.
 #[derive(Debug, Clone, Serialize)]
+#[allow(clippy::large_enum_variant)] // Loaded once, so not worth boxing
 pub enum Config {
     Root(RootConfig),
     Child(ChildConfig),
@@ -112,9 +125,16 @@ fn format_root_error(e: serde_yaml::Error) -> String {
         // Could be site.name or source[].name - provide both possibilities
         return "invalid config: missing required 'name' field (check 'site.name' and each source's 'name')".to_string();
     }
-    if msg.contains("location must have either") {
+    if msg.contains("location must have either") || msg.contains("untagged enum SourceLocation") {
         return "invalid config: each source must have either 'local: { path: ... }' for inline content or 'location: { path/git: ... }' for external sources".to_string();
     }
+    if msg.contains("untagged enum Location") {
+        return "invalid config: a location must be either '{ path: ... }' or '{ git: ... }'"
+            .to_string();
+    }
+    if msg.contains("untagged enum NavItem") {
+        return "invalid config: each nav entry must be a path ('page.md'), a titled path ('Title: page.md'), or a map with 'section', 'path', 'label', or 'divider'".to_string();
+    }
 
     format!("invalid config: {msg}")
 }
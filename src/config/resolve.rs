@@ -5,9 +5,12 @@
 
 use std::path::{Path, PathBuf};
 
+use tracing::info;
+
 use crate::git::GitFetcher;
 
-use super::types::{ChildConfig, Location, RootConfig, SourceLocation};
+use super::load::{default_config_file, read_config};
+use super::types::{ChildConfig, CleanConfig, Location, RootConfig, SourceLocation};
 use super::{Config, ConfigError};
 
 /// Result of resolving a child config, containing the synthetic root config
@@ -40,14 +43,17 @@ impl ChildConfig {
         let parent_path = resolve_location(parent_location, child_base_path, cache_dir)?;
 
         // Load parent config
-        let parent_config_path = parent_path.join("undox.yaml");
-        let parent_config_str =
-            std::fs::read_to_string(&parent_config_path).map_err(ConfigError::ReadParent)?;
+        let parent_config_path = default_config_file(&parent_path);
+        if !parent_config_path.is_file() {
+            return Err(ConfigError::ReadParent(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no undox config file in {}", parent_path.display()),
+            )));
+        }
 
         // Parse parent config - it must be a root config
-        let parent_config: Config = serde_yaml::from_str(&parent_config_str).map_err(|e| {
-            ConfigError::Validation(format!("failed to parse parent config: {}", e))
-        })?;
+        let parent_config = read_config(&parent_config_path, cache_dir, false, None)
+            .map_err(|e| ConfigError::Validation(format!("failed to load parent config: {}", e)))?;
 
         let mut parent_root = match parent_config {
             Config::Root(root) => root,
@@ -79,12 +85,14 @@ impl ChildConfig {
 
         for (i, source) in sources.iter_mut().enumerate() {
             if i == source_index {
-                // This is the child's source - point to local content
+                // This is the child's source - point to local content, as
+                // one version when the parent builds several
                 source.location = SourceLocation::Local {
                     local: Location::Path {
                         path: resolved_content_path.clone(),
                     },
                 };
+                source.versions.clear();
 
                 // Apply nav from child config
                 if let Some(ref nav) = self.nav {
@@ -129,10 +137,18 @@ impl ChildConfig {
                 if let Some(ref edit_path) = site_overrides.edit_path {
                     parent_root.site.edit_path = Some(edit_path.clone());
                 }
+                if let Some(ref template) = site_overrides.edit_url_template {
+                    parent_root.site.edit_url_template = Some(template.clone());
+                }
             }
             if let Some(ref theme_override) = overrides.theme {
                 theme = theme_override.clone();
             }
+            if let Some(ref extra) = overrides.extra {
+                for (key, value) in extra {
+                    parent_root.extra.insert(key.clone(), value.clone());
+                }
+            }
         }
 
         // Create root config with all sources (our source now points to local content)
@@ -142,6 +158,20 @@ impl ChildConfig {
             theme,
             markdown: parent_root.markdown,
             dev: parent_root.dev,
+            extra: parent_root.extra,
+            // The cache and clean paths belong to the repo being built, not
+            // the parent
+            cache_dir: None,
+            clean: CleanConfig::default(),
+            feed: parent_root.feed,
+            assets: parent_root.assets,
+            build: parent_root.build,
+            static_files: parent_root.static_files,
+            pdf: parent_root.pdf,
+            html: parent_root.html,
+            nav: parent_root.nav,
+            budget: parent_root.budget,
+            languages: parent_root.languages,
         };
 
         Ok(ResolvedChildConfig {
@@ -169,7 +199,7 @@ fn resolve_location(
         }
         Location::Git { git } => {
             let git_loc = git.to_location();
-            eprintln!("Fetching parent config from {}...", git_loc.url);
+            info!("Fetching parent config from {}...", git_loc.url);
             let fetcher = GitFetcher::new(cache_dir.to_path_buf());
             let repo_path = fetcher.fetch_location(&git_loc)?;
 
@@ -0,0 +1,514 @@
+//! Unknown key detection.
+//!
+//! Config structs accept keys they don't know, since `deny_unknown_fields`
+//! doesn't work with the untagged and flattened enums. A misspelled key is
+//! then silently ignored, so after parsing, the raw value is walked again
+//! against the known keys to report the ones nothing reads.
+
+use std::fmt;
+
+use serde_yaml::{Mapping, Value};
+
+const ROOT_KEYS: &[&str] = &[
+    "site",
+    "sources",
+    "theme",
+    "markdown",
+    "dev",
+    "extra",
+    "cache_dir",
+    "clean",
+    "feed",
+    "assets",
+    "build",
+    "static",
+    "pdf",
+    "html",
+    "nav",
+    "budget",
+    "languages",
+    "profiles",
+    "extends",
+];
+/// A profile can set anything the root config can, except more profiles
+const PROFILE_KEYS: &[&str] = &[
+    "site",
+    "sources",
+    "theme",
+    "markdown",
+    "dev",
+    "extra",
+    "cache_dir",
+    "clean",
+    "feed",
+    "assets",
+    "build",
+    "static",
+    "pdf",
+    "html",
+    "nav",
+    "budget",
+    "languages",
+];
+const CHILD_KEYS: &[&str] = &["name", "parent", "content", "nav", "overrides", "dev"];
+const SITE_KEYS: &[&str] = &[
+    "name",
+    "url",
+    "output",
+    "favicon",
+    "description",
+    "language",
+    "author",
+    "logo",
+    "social_image",
+    "repository",
+    "edit_path",
+    "edit_url_template",
+    "nav_mode",
+    "nav_max_depth",
+    "base_path",
+    "url_style",
+    "redirects",
+    "redirects_format",
+    "robots",
+    "llms",
+    "print",
+    "git_dates",
+    "git_contributors",
+    "git_contributors_limit",
+    "social_cards",
+    "announcement",
+    "footer",
+];
+const BUDGET_KEYS: &[&str] = &["max_page_kb"];
+const ANNOUNCEMENT_KEYS: &[&str] = &["text", "id"];
+const FOOTER_KEYS: &[&str] = &["copyright", "links"];
+const FOOTER_LINK_KEYS: &[&str] = &["title", "url"];
+const SOURCE_KEYS: &[&str] = &[
+    "name",
+    "title",
+    "url_prefix",
+    "nav",
+    "html_policy",
+    "strip_numeric_prefix",
+    "weight",
+    "group",
+    "nav_max_depth",
+    "repository",
+    "edit_path",
+    "edit_url_template",
+    "url_rewrite",
+    "flatten",
+    "assets",
+    "follow_symlinks",
+    "versions",
+    "language",
+    "location",
+    "local",
+];
+const FOLLOW_SYMLINKS_KEYS: &[&str] = &["files", "directories"];
+const URL_REWRITE_KEYS: &[&str] = &["from", "to"];
+const VERSION_KEYS: &[&str] = &["name", "ref"];
+const LOCATION_KEYS: &[&str] = &["path", "git"];
+const GIT_KEYS: &[&str] = &["url", "ref", "path"];
+const THEME_KEYS: &[&str] = &["name", "location", "settings"];
+const MARKDOWN_KEYS: &[&str] = &[
+    "extensions",
+    "headings",
+    "images",
+    "html_policy",
+    "front_matter",
+    "highlight_theme",
+];
+const HEADINGS_KEYS: &[&str] = &[
+    "anchors",
+    "symbol",
+    "class",
+    "position",
+    "levels",
+    "transliterate",
+    "offset",
+];
+const IMAGES_KEYS: &[&str] = &["lazy"];
+const FRONT_MATTER_KEYS: &[&str] = &["strict", "fields"];
+const DEV_KEYS: &[&str] = &["parent", "watch", "live_reload", "headers"];
+const WATCH_KEYS: &[&str] = &["poll", "poll_interval_ms", "debounce_ms"];
+const CLEAN_KEYS: &[&str] = &["extra_paths"];
+const BUILD_KEYS: &[&str] = &["strict"];
+const ASSETS_KEYS: &[&str] = &["fingerprint", "extra"];
+const STATIC_KEYS: &[&str] = &["max_file_size", "link"];
+const PDF_KEYS: &[&str] = &["enabled", "converter", "command"];
+const HTML_KEYS: &[&str] = &["rules"];
+const NAV_KEYS: &[&str] = &["orphans"];
+const FEED_KEYS: &[&str] = &["source", "title", "output", "limit", "full_content"];
+const OVERRIDES_KEYS: &[&str] = &["site", "theme", "extra"];
+const SITE_OVERRIDES_KEYS: &[&str] = &["repository", "edit_path", "edit_url_template"];
+const NAV_SECTION_KEYS: &[&str] = &["section", "items", "icon"];
+const NAV_LINK_KEYS: &[&str] = &["path", "title", "icon", "children"];
+const NAV_DIVIDER_KEYS: &[&str] = &["divider"];
+const NAV_LABEL_KEYS: &[&str] = &["label", "icon"];
+
+/// A config key that no setting reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownKey {
+    /// Where the key is, like `sources[1].url_prefx`
+    pub path: String,
+    /// A known key with a similar name
+    pub suggestion: Option<&'static str>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown key `{}`", self.path)?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, " (did you mean `{}`?)", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the keys in a raw root or child config that no setting reads.
+///
+/// Free-form sections (`extra`, `theme.settings`, `site.redirects`) and
+/// titled nav entries accept any key.
+pub fn unknown_keys(value: &Value) -> Vec<UnknownKey> {
+    let mut walker = Walker::default();
+    let is_child = value.get("parent").is_some() && value.get("site").is_none();
+    if is_child {
+        walker.child(value);
+    } else {
+        walker.root(value, "", ROOT_KEYS);
+        if let Some(Value::Mapping(profiles)) = value.get("profiles") {
+            for (name, profile) in profiles {
+                if let Some(name) = name.as_str() {
+                    walker.root(profile, &format!("profiles.{}", name), PROFILE_KEYS);
+                }
+            }
+        }
+    }
+    walker.found
+}
+
+#[derive(Default)]
+struct Walker {
+    found: Vec<UnknownKey>,
+}
+
+impl Walker {
+    fn root(&mut self, value: &Value, path: &str, known: &[&'static str]) {
+        let Some(map) = self.keys(value, path, known) else {
+            return;
+        };
+        if let Some(site) = map.get("site") {
+            self.site(site, &join(path, "site"));
+        }
+        if let Some(Value::Sequence(sources)) = map.get("sources") {
+            for (i, source) in sources.iter().enumerate() {
+                self.source(source, &format!("{}[{}]", join(path, "sources"), i));
+            }
+        }
+        if let Some(theme) = map.get("theme") {
+            self.theme(theme, &join(path, "theme"));
+        }
+        if let Some(markdown) = map.get("markdown") {
+            self.markdown(markdown, &join(path, "markdown"));
+        }
+        if let Some(dev) = map.get("dev") {
+            self.dev(dev, &join(path, "dev"));
+        }
+        if let Some(clean) = map.get("clean") {
+            self.keys(clean, &join(path, "clean"), CLEAN_KEYS);
+        }
+        if let Some(feed) = map.get("feed") {
+            self.keys(feed, &join(path, "feed"), FEED_KEYS);
+        }
+        if let Some(assets) = map.get("assets") {
+            self.keys(assets, &join(path, "assets"), ASSETS_KEYS);
+        }
+        if let Some(build) = map.get("build") {
+            self.keys(build, &join(path, "build"), BUILD_KEYS);
+        }
+        if let Some(static_files) = map.get("static") {
+            self.keys(static_files, &join(path, "static"), STATIC_KEYS);
+        }
+        if let Some(pdf) = map.get("pdf") {
+            self.keys(pdf, &join(path, "pdf"), PDF_KEYS);
+        }
+        if let Some(html) = map.get("html") {
+            self.keys(html, &join(path, "html"), HTML_KEYS);
+        }
+        if let Some(nav) = map.get("nav") {
+            self.keys(nav, &join(path, "nav"), NAV_KEYS);
+        }
+        if let Some(budget) = map.get("budget") {
+            self.keys(budget, &join(path, "budget"), BUDGET_KEYS);
+        }
+    }
+
+    fn site(&mut self, value: &Value, path: &str) {
+        let Some(map) = self.keys(value, path, SITE_KEYS) else {
+            return;
+        };
+        if let Some(announcement) = map.get("announcement") {
+            self.keys(announcement, &join(path, "announcement"), ANNOUNCEMENT_KEYS);
+        }
+        if let Some(footer) = map.get("footer")
+            && let Some(footer) = self.keys(footer, &join(path, "footer"), FOOTER_KEYS)
+            && let Some(Value::Sequence(links)) = footer.get("links")
+        {
+            for (i, link) in links.iter().enumerate() {
+                let link_path = format!("{}[{}]", join(path, "footer.links"), i);
+                self.keys(link, &link_path, FOOTER_LINK_KEYS);
+            }
+        }
+    }
+
+    fn child(&mut self, value: &Value) {
+        let Some(map) = self.keys(value, "", CHILD_KEYS) else {
+            return;
+        };
+        for key in ["parent", "content"] {
+            if let Some(location) = map.get(key) {
+                self.location(location, key);
+            }
+        }
+        if let Some(nav) = map.get("nav") {
+            self.nav(nav, "nav");
+        }
+        if let Some(overrides) = map.get("overrides")
+            && let Some(overrides) = self.keys(overrides, "overrides", OVERRIDES_KEYS)
+        {
+            if let Some(site) = overrides.get("site") {
+                self.keys(site, "overrides.site", SITE_OVERRIDES_KEYS);
+            }
+            if let Some(theme) = overrides.get("theme") {
+                self.theme(theme, "overrides.theme");
+            }
+        }
+        if let Some(dev) = map.get("dev") {
+            self.dev(dev, "dev");
+        }
+    }
+
+    fn source(&mut self, value: &Value, path: &str) {
+        let Some(map) = self.keys(value, path, SOURCE_KEYS) else {
+            return;
+        };
+        for key in ["location", "local"] {
+            if let Some(location) = map.get(key) {
+                self.location(location, &join(path, key));
+            }
+        }
+        if let Some(nav) = map.get("nav") {
+            self.nav(nav, &join(path, "nav"));
+        }
+        if let Some(follow) = map.get("follow_symlinks") {
+            self.keys(follow, &join(path, "follow_symlinks"), FOLLOW_SYMLINKS_KEYS);
+        }
+        if let Some(Value::Sequence(rewrites)) = map.get("url_rewrite") {
+            for (i, rewrite) in rewrites.iter().enumerate() {
+                let rewrite_path = format!("{}[{}]", join(path, "url_rewrite"), i);
+                self.keys(rewrite, &rewrite_path, URL_REWRITE_KEYS);
+            }
+        }
+        if let Some(Value::Sequence(versions)) = map.get("versions") {
+            for (i, version) in versions.iter().enumerate() {
+                let version_path = format!("{}[{}]", join(path, "versions"), i);
+                self.keys(version, &version_path, VERSION_KEYS);
+            }
+        }
+    }
+
+    fn location(&mut self, value: &Value, path: &str) {
+        let Some(map) = self.keys(value, path, LOCATION_KEYS) else {
+            return;
+        };
+        if let Some(git) = map.get("git") {
+            self.keys(git, &join(path, "git"), GIT_KEYS);
+        }
+    }
+
+    fn theme(&mut self, value: &Value, path: &str) {
+        let Some(map) = self.keys(value, path, THEME_KEYS) else {
+            return;
+        };
+        if let Some(location) = map.get("location") {
+            self.location(location, &join(path, "location"));
+        }
+    }
+
+    fn markdown(&mut self, value: &Value, path: &str) {
+        let Some(map) = self.keys(value, path, MARKDOWN_KEYS) else {
+            return;
+        };
+        let sections = [
+            ("headings", HEADINGS_KEYS),
+            ("images", IMAGES_KEYS),
+            ("front_matter", FRONT_MATTER_KEYS),
+        ];
+        for (key, known) in sections {
+            if let Some(section) = map.get(key) {
+                self.keys(section, &join(path, key), known);
+            }
+        }
+    }
+
+    fn dev(&mut self, value: &Value, path: &str) {
+        let Some(map) = self.keys(value, path, DEV_KEYS) else {
+            return;
+        };
+        if let Some(parent) = map.get("parent") {
+            self.location(parent, &join(path, "parent"));
+        }
+        if let Some(watch) = map.get("watch") {
+            self.keys(watch, &join(path, "watch"), WATCH_KEYS);
+        }
+    }
+
+    fn nav(&mut self, value: &Value, path: &str) {
+        let Value::Sequence(items) = value else {
+            return;
+        };
+        for (i, item) in items.iter().enumerate() {
+            let item_path = format!("{}[{}]", path, i);
+            // The key that picks the kind of entry, as serde does
+            if item.get("section").is_some() {
+                if let Some(map) = self.keys(item, &item_path, NAV_SECTION_KEYS)
+                    && let Some(items) = map.get("items")
+                {
+                    self.nav(items, &join(&item_path, "items"));
+                }
+            } else if item.get("path").is_some() {
+                if let Some(map) = self.keys(item, &item_path, NAV_LINK_KEYS)
+                    && let Some(children) = map.get("children")
+                {
+                    self.nav(children, &join(&item_path, "children"));
+                }
+            } else if item.get("divider").is_some() {
+                self.keys(item, &item_path, NAV_DIVIDER_KEYS);
+            } else if item.get("label").is_some() {
+                self.keys(item, &item_path, NAV_LABEL_KEYS);
+            }
+            // Anything else is a `Title: path` entry, where any key goes
+        }
+    }
+
+    /// Report the keys of `value` not in `known`, returning it if it's a map.
+    fn keys<'v>(
+        &mut self,
+        value: &'v Value,
+        path: &str,
+        known: &[&'static str],
+    ) -> Option<&'v Mapping> {
+        let map = value.as_mapping()?;
+        for key in map.keys().filter_map(Value::as_str) {
+            if !known.contains(&key) {
+                self.found.push(UnknownKey {
+                    path: join(path, key),
+                    suggestion: closest(key, known),
+                });
+            }
+        }
+        Some(map)
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// The known key closest to `key`, if it's only a typo or two away.
+fn closest(key: &str, known: &[&'static str]) -> Option<&'static str> {
+    known
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(yaml: &str) -> Vec<String> {
+        let value: Value = serde_yaml::from_str(yaml).unwrap();
+        unknown_keys(&value).into_iter().map(|k| k.path).collect()
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let yaml = r#"
+site:
+  name: Docs
+  redirects: { /old: /new }
+theme:
+  settings: { anything: true }
+extra:
+  anything: true
+sources:
+  - name: guide
+    local: { path: ./guide }
+  - naame: cli
+    url_prefx: /cli
+    location: { git: { url: https://example.com/cli, branch: main } }
+    nav:
+      - Getting Started: start.md
+      - section: Commands
+        items:
+          - path: sync.md
+            titel: Sync
+markdown:
+  headings: { anchor: true }
+"#;
+        assert_eq!(
+            paths(yaml),
+            vec![
+                "sources[1].naame",
+                "sources[1].url_prefx",
+                "sources[1].location.git.branch",
+                "sources[1].nav[1].items[0].titel",
+                "markdown.headings.anchor",
+            ]
+        );
+
+        let profiles = "site: { name: Docs }\nsources: []\nprofiles: { prod: { site: { ur: x } } }";
+        assert_eq!(paths(profiles), vec!["profiles.prod.site.ur"]);
+
+        let child = "name: cli\nparent: { path: .. }\ncontent: { path: . }\noverides: {}\n";
+        assert_eq!(paths(child), vec!["overides"]);
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let value: Value = serde_yaml::from_str("site: { name: Docs, nav_mod: tabs }").unwrap();
+        let keys = unknown_keys(&value);
+        assert_eq!(keys[0].suggestion, Some("nav_mode"));
+        assert_eq!(
+            keys[0].to_string(),
+            "unknown key `site.nav_mod` (did you mean `nav_mode`?)"
+        );
+
+        let value: Value = serde_yaml::from_str("site: { name: Docs, colour: red }").unwrap();
+        assert_eq!(unknown_keys(&value)[0].suggestion, None);
+    }
+}
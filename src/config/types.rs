@@ -3,7 +3,8 @@
 //! This module contains all the data structures used in undox configuration files.
 //! These types are pure data - no I/O or complex logic.
 
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -159,6 +160,465 @@ pub struct RootConfig {
     /// Development-specific settings (watch mode, etc.)
     #[serde(default)]
     pub dev: DevConfig,
+    /// Site-wide values passed to templates and content as `extra.*`
+    #[serde(default)]
+    pub extra: serde_yaml::Mapping,
+    /// Where git clones and other cached data are kept (default:
+    /// `.undox/cache`), overridden by `UNDOX_CACHE_DIR`
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    /// Settings for `undox clean`
+    #[serde(default)]
+    pub clean: CleanConfig,
+    /// Atom feed of one source's pages
+    #[serde(default)]
+    pub feed: Option<FeedConfig>,
+    /// Cache busting for theme and other static files
+    #[serde(default)]
+    pub assets: AssetsConfig,
+    /// Settings for `undox build`
+    #[serde(default)]
+    pub build: BuildConfig,
+    /// How static files are published
+    #[serde(default, rename = "static")]
+    pub static_files: StaticConfig,
+    /// PDF export of each source's print view
+    #[serde(default)]
+    pub pdf: PdfConfig,
+    /// Rewriting of every page's final HTML
+    #[serde(default)]
+    pub html: HtmlConfig,
+    /// Checks of configured navigation
+    #[serde(default)]
+    pub nav: NavSettings,
+    /// Size limits that generated pages are checked against
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    /// Languages the site is written in, by code, for sources' `language`
+    #[serde(default, skip_serializing_if = "Languages::is_empty")]
+    pub languages: Languages,
+}
+
+/// Settings for `undox build`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildConfig {
+    /// Fail the build if there are any warnings, like `--strict`
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Size limits that generated pages are checked against.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Warn about pages whose HTML is larger than this many kilobytes
+    pub max_page_kb: Option<u64>,
+}
+
+/// Checks of sources' configured navigation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NavSettings {
+    /// What to do about pages left out of their source's `nav`
+    #[serde(default)]
+    pub orphans: OrphanSeverity,
+}
+
+/// How pages left out of configured navigation are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrphanSeverity {
+    /// Don't report them
+    Ignore,
+    /// List them as a warning
+    #[default]
+    Warn,
+    /// List them and fail the build
+    Error,
+}
+
+/// How static files are published.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StaticConfig {
+    /// Warn about static files larger than this
+    pub max_file_size: Option<FileSize>,
+    /// Hard-link static files into the output instead of copying them,
+    /// where the file system allows
+    #[serde(default)]
+    pub link: bool,
+}
+
+/// PDF export of each source's print view. `pdf: true` turns it on with the
+/// default converter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "PdfValue")]
+pub struct PdfConfig {
+    /// Write a PDF per source (not in dev mode)
+    pub enabled: bool,
+    /// Program that turns the print view into a PDF
+    pub converter: PdfConverter,
+    /// Name or path of the converter's binary (default: `chromium` or
+    /// `weasyprint`)
+    pub command: Option<String>,
+}
+
+impl PdfConfig {
+    /// The binary to run for the converter.
+    pub fn command(&self) -> &str {
+        self.command.as_deref().unwrap_or(match self.converter {
+            PdfConverter::Chromium => "chromium",
+            PdfConverter::Weasyprint => "weasyprint",
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PdfValue {
+    Enabled(bool),
+    Settings {
+        #[serde(default = "default_pdf_enabled")]
+        enabled: bool,
+        #[serde(default)]
+        converter: PdfConverter,
+        command: Option<String>,
+    },
+}
+
+fn default_pdf_enabled() -> bool {
+    true
+}
+
+impl From<PdfValue> for PdfConfig {
+    fn from(value: PdfValue) -> Self {
+        match value {
+            PdfValue::Enabled(enabled) => Self {
+                enabled,
+                ..Self::default()
+            },
+            PdfValue::Settings {
+                enabled,
+                converter,
+                command,
+            } => Self {
+                enabled,
+                converter,
+                command,
+            },
+        }
+    }
+}
+
+/// Program used to write PDFs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfConverter {
+    /// Headless Chromium or Chrome
+    #[default]
+    Chromium,
+    /// WeasyPrint
+    Weasyprint,
+}
+
+/// Rewriting of every page's final HTML.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlConfig {
+    /// Rules applied to each page, in order
+    #[serde(default, deserialize_with = "deserialize_html_rules")]
+    pub rules: Vec<HtmlRule>,
+}
+
+/// A rule rewriting pages' HTML. Rules without required settings can be
+/// given by name alone, like `- wrap_tables`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HtmlRule {
+    /// Wrap each `<table>` in a `<div>`, so wide tables can scroll
+    WrapTables(WrapTablesRule),
+    /// Add `rel` and `target` to links that leave the site
+    ExternalLinks(ExternalLinksRule),
+    /// Add a snippet of HTML, such as analytics, to every page
+    Inject(InjectRule),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WrapTablesRule {
+    /// Class of the wrapping `<div>`
+    #[serde(default = "default_table_wrapper_class")]
+    pub class: String,
+}
+
+fn default_table_wrapper_class() -> String {
+    "table-wrapper".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalLinksRule {
+    /// `rel` for links that don't have one (none if empty)
+    #[serde(default = "default_external_rel")]
+    pub rel: String,
+    /// `target` for links that don't have one (none if empty)
+    #[serde(default = "default_external_target")]
+    pub target: String,
+}
+
+fn default_external_rel() -> String {
+    "noopener noreferrer".to_string()
+}
+
+fn default_external_target() -> String {
+    "_blank".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InjectRule {
+    /// Element the snippet is added to the end of
+    #[serde(default)]
+    pub position: InjectPosition,
+    /// The snippet
+    pub html: String,
+}
+
+/// Where an [`InjectRule`] adds its snippet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectPosition {
+    /// Before `</head>`
+    Head,
+    /// Before `</body>`
+    #[default]
+    Body,
+}
+
+/// Deserialize HTML rules, where a bare name stands for the rule with its
+/// default settings.
+fn deserialize_html_rules<'de, D>(deserializer: D) -> Result<Vec<HtmlRule>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde_yaml::Value;
+    use serde_yaml::value::{Tag, TaggedValue};
+
+    let values = Vec::<Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .map(|value| {
+            let (name, settings) = match value {
+                Value::String(name) => (name, Value::Mapping(Default::default())),
+                Value::Mapping(rule) if rule.len() == 1 => {
+                    let (name, settings) = rule.into_iter().next().unwrap();
+                    match name {
+                        Value::String(name) => (name, settings),
+                        _ => return Err(serde::de::Error::custom("HTML rule names are strings")),
+                    }
+                }
+                _ => {
+                    return Err(serde::de::Error::custom(
+                        "an HTML rule is a rule name, or a map from one rule name to its settings",
+                    ));
+                }
+            };
+            // serde_yaml reads enums from tagged values, like `!inject {...}`
+            let rule = Value::Tagged(Box::new(TaggedValue {
+                tag: Tag::new(name),
+                value: settings,
+            }));
+            serde_yaml::from_value(rule).map_err(serde::de::Error::custom)
+        })
+        .collect()
+}
+
+/// A file size: a number of bytes, or a string with a `KB`, `MB`, or `GB`
+/// unit (powers of 1024), like `"50MB"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "FileSizeValue", into = "u64")]
+pub struct FileSize(pub u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FileSizeValue {
+    Bytes(u64),
+    Text(String),
+}
+
+impl TryFrom<FileSizeValue> for FileSize {
+    type Error = String;
+
+    fn try_from(value: FileSizeValue) -> Result<Self, Self::Error> {
+        let text = match value {
+            FileSizeValue::Bytes(bytes) => return Ok(FileSize(bytes)),
+            FileSizeValue::Text(text) => text,
+        };
+        let upper = text.trim().to_ascii_uppercase();
+        let (number, unit) = upper.split_at(
+            upper
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(upper.len()),
+        );
+        let multiplier: u64 = match unit.trim() {
+            "" | "B" => 1,
+            "KB" => 1 << 10,
+            "MB" => 1 << 20,
+            "GB" => 1 << 30,
+            _ => return Err(format!("'{}' isn't a file size like \"50MB\"", text)),
+        };
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("'{}' isn't a file size like \"50MB\"", text))?;
+        Ok(FileSize((number * multiplier as f64) as u64))
+    }
+}
+
+impl From<FileSize> for u64 {
+    fn from(size: FileSize) -> Self {
+        size.0
+    }
+}
+
+/// The site's languages, written as a map from language code to display
+/// name, like `{ en: English, de: Deutsch }`, in switcher order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "serde_yaml::Mapping", into = "serde_yaml::Mapping")]
+pub struct Languages(pub Vec<Language>);
+
+/// A language of a multilingual site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Language {
+    /// BCP-47 language tag, like `en` or `pt-BR`
+    pub code: String,
+    /// Name shown in language switchers, like "Deutsch"
+    pub name: String,
+}
+
+impl Languages {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get a language by its code.
+    pub fn get(&self, code: &str) -> Option<&Language> {
+        self.0.iter().find(|language| language.code == code)
+    }
+}
+
+impl TryFrom<serde_yaml::Mapping> for Languages {
+    type Error = String;
+
+    fn try_from(mapping: serde_yaml::Mapping) -> Result<Self, Self::Error> {
+        mapping
+            .into_iter()
+            .map(|(code, name)| match (code, name) {
+                (serde_yaml::Value::String(code), serde_yaml::Value::String(name)) => {
+                    Ok(Language { code, name })
+                }
+                _ => Err("languages maps language codes to names, like `en: English`".to_string()),
+            })
+            .collect::<Result<_, _>>()
+            .map(Languages)
+    }
+}
+
+impl From<Languages> for serde_yaml::Mapping {
+    fn from(languages: Languages) -> Self {
+        languages
+            .0
+            .into_iter()
+            .map(|language| (language.code.into(), language.name.into()))
+            .collect()
+    }
+}
+
+/// Settings for `undox clean`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanConfig {
+    /// More files or directories to delete, relative to the config file
+    #[serde(default)]
+    pub extra_paths: Vec<PathBuf>,
+}
+
+/// Settings for the files templates link with `asset()`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssetsConfig {
+    /// Also write each theme file under a name with a hash of its content,
+    /// which `asset()` links to (not in dev mode)
+    #[serde(default)]
+    pub fingerprint: bool,
+    /// More output files to fingerprint, relative to the output directory,
+    /// such as `images/logo.svg`
+    #[serde(default)]
+    pub extra: Vec<String>,
+}
+
+/// An Atom feed of one source's pages, newest first by their `date`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedConfig {
+    /// Name of the source the feed lists
+    pub source: String,
+    /// Feed title (default: the site name)
+    pub title: Option<String>,
+    /// Where the feed is written, relative to the output directory
+    #[serde(default = "default_feed_output")]
+    pub output: String,
+    /// Number of pages the feed lists
+    #[serde(default = "default_feed_limit")]
+    pub limit: usize,
+    /// Include each page's full HTML rather than its summary
+    #[serde(default)]
+    pub full_content: bool,
+}
+
+fn default_feed_output() -> String {
+    "atom.xml".to_string()
+}
+
+fn default_feed_limit() -> usize {
+    20
+}
+
+impl RootConfig {
+    /// The sources to build, with each versioned source expanded into a
+    /// source per version.
+    pub fn expanded_sources(&self) -> Vec<SourceConfig> {
+        self.sources
+            .iter()
+            .flat_map(SourceConfig::expand_versions)
+            .collect()
+    }
+
+    /// Check the settings that can't be checked while parsing.
+    pub fn validate(&self) -> Result<(), String> {
+        self.site.validate()?;
+        for source in &self.sources {
+            source.validate_versions()?;
+        }
+        validate_url_prefixes(&self.expanded_sources())?;
+        for source in &self.sources {
+            if let Some(language) = &source.language
+                && self.languages.get(language).is_none()
+            {
+                return Err(format!(
+                    "source '{}' has the language '{}', which isn't in languages",
+                    source.name, language
+                ));
+            }
+        }
+        if let Some(feed) = &self.feed {
+            if !self.sources.iter().any(|source| source.name == feed.source) {
+                return Err(format!("feed.source '{}' isn't a source", feed.source));
+            }
+            let output = Path::new(&feed.output);
+            if feed.output.is_empty()
+                || output.is_absolute()
+                || output
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+            {
+                return Err(format!(
+                    "feed.output '{}' must be a path inside the output directory",
+                    feed.output
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Child configuration - used in source repos to point back to the parent site.
@@ -189,6 +649,9 @@ pub struct RootConfigOverrides {
     /// Theme override
     #[serde(default)]
     pub theme: Option<ThemeConfig>,
+    /// `extra` values to add or replace, key by key
+    #[serde(default)]
+    pub extra: Option<serde_yaml::Mapping>,
 }
 
 /// Partial site config for overrides
@@ -196,6 +659,7 @@ pub struct RootConfigOverrides {
 pub struct SiteConfigOverrides {
     pub repository: Option<String>,
     pub edit_path: Option<String>,
+    pub edit_url_template: Option<String>,
 }
 
 // =============================================================================
@@ -208,18 +672,208 @@ pub struct SiteConfig {
     pub url: Option<String>,
     #[serde(default = "default_output")]
     pub output: PathBuf,
-    /// Path to the site favicon (relative to config file)
+    /// Path to the site favicon (relative to config file), or a URL
     pub favicon: Option<String>,
+    /// Short description of the site, for `<meta name="description">`
+    pub description: Option<String>,
+    /// BCP-47 language tag of the content (e.g. "en", "pt-BR"), for
+    /// `<html lang>` and the search index
+    pub language: Option<String>,
+    /// Author or organization behind the site
+    pub author: Option<String>,
+    /// Path or URL of the logo shown in the site header
+    pub logo: Option<String>,
+    /// Path or URL of the image shown when a page without its own `image`
+    /// is shared on social media
+    pub social_image: Option<String>,
     /// Repository URL for "edit on GitHub" links
     pub repository: Option<String>,
     /// Path within the repo where docs live (for edit links)
     pub edit_path: Option<String>,
+    /// Edit link URL with `{repository}`, `{ref}`, and `{path}`
+    /// placeholders, for hosts other than GitHub and GitLab
+    pub edit_url_template: Option<String>,
+    /// Whether sources get their own tabs or share one sidebar
+    #[serde(default)]
+    pub nav_mode: NavMode,
+    /// How many levels of navigation the sidebar shows (all if not set)
+    pub nav_max_depth: Option<usize>,
+    /// URL path the site is served under (e.g. "/docs"), prefixed onto every
+    /// generated URL
+    pub base_path: Option<String>,
+    /// How page URLs are written, and so which files pages are written to
+    #[serde(default)]
+    pub url_style: UrlStyle,
+    /// Redirects from old URLs to new ones. A trailing `/*` on the old URL
+    /// matches everything under it.
+    #[serde(default)]
+    pub redirects: BTreeMap<String, String>,
+    /// Host config file to write redirects to, needed for wildcard redirects
+    pub redirects_format: Option<RedirectsFormat>,
+    /// Contents of `robots.txt` (default: allow everything)
+    pub robots: Option<String>,
+    /// Write `llms.txt` and `llms-full.txt` for AI tools
+    #[serde(default)]
+    pub llms: bool,
+    /// Write a page per source with all of its pages, for printing
+    #[serde(default)]
+    pub print: bool,
+    /// Give pages a `last_updated` date from their last git commit
+    #[serde(default)]
+    pub git_dates: bool,
+    /// Give pages a list of the people who committed to them
+    #[serde(default)]
+    pub git_contributors: bool,
+    /// How many contributors to list per page, most recent first
+    #[serde(default = "default_git_contributors_limit")]
+    pub git_contributors_limit: usize,
+    /// Generate a social media card image for each page without an `image`
+    #[serde(default)]
+    pub social_cards: bool,
+    /// Banner shown at the top of every page
+    pub announcement: Option<AnnouncementConfig>,
+    /// Copyright notice and links for the page footer
+    pub footer: Option<FooterConfig>,
+}
+
+/// A banner shown at the top of every page, written in markdown.
+/// `announcement: "..."` sets just the text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "AnnouncementValue")]
+pub struct AnnouncementConfig {
+    /// The banner's markdown, rendered as inline HTML
+    pub text: String,
+    /// Identifies the announcement, so themes can remember that it was
+    /// dismissed (default: a hash of the text, so new text shows again)
+    pub id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnnouncementValue {
+    Text(String),
+    Settings { text: String, id: Option<String> },
+}
+
+impl From<AnnouncementValue> for AnnouncementConfig {
+    fn from(value: AnnouncementValue) -> Self {
+        match value {
+            AnnouncementValue::Text(text) => Self { text, id: None },
+            AnnouncementValue::Settings { text, id } => Self { text, id },
+        }
+    }
+}
+
+/// The page footer's copyright notice and links.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FooterConfig {
+    /// Copyright notice, as plain text
+    pub copyright: Option<String>,
+    /// Links listed in the footer, in order
+    #[serde(default)]
+    pub links: Vec<FooterLink>,
+}
+
+/// A link in the page footer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FooterLink {
+    pub title: String,
+    pub url: String,
+}
+
+impl SiteConfig {
+    /// Check the site settings that can't be checked while parsing.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(favicon) = &self.favicon
+            && !is_url(favicon)
+        {
+            let extension = Path::new(favicon)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase());
+            if !matches!(extension.as_deref(), Some("ico" | "png" | "svg")) {
+                return Err(format!(
+                    "site.favicon '{}' must be an .ico, .png, or .svg file",
+                    favicon
+                ));
+            }
+        }
+        for (from, to) in &self.redirects {
+            if !from.starts_with('/') {
+                return Err(format!("redirect '{}' must start with '/'", from));
+            }
+            let wildcard = from.ends_with("/*");
+            if from.trim_end_matches("/*").contains('*') {
+                return Err(format!(
+                    "redirect '{}' can only have a wildcard as its last segment ('/*')",
+                    from
+                ));
+            }
+            if wildcard && self.redirects_format.is_none() {
+                return Err(format!(
+                    "wildcard redirect '{}' needs site.redirects_format to be set",
+                    from
+                ));
+            }
+            if !to.starts_with('/') && !to.contains("://") {
+                return Err(format!(
+                    "redirect target '{}' must be a path starting with '/' or a full URL",
+                    to
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a `favicon`, `logo`, or `social_image` setting is a URL, to use as it is, rather
+/// than a file to publish.
+pub fn is_url(value: &str) -> bool {
+    value.starts_with('/') || value.contains("://") || value.starts_with("data:")
+}
+
+/// Host config file format for redirects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedirectsFormat {
+    /// A Netlify (and Cloudflare Pages) `_redirects` file
+    Netlify,
+    /// A `vercel.json` file
+    Vercel,
+}
+
+/// How navigation is split across sources.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NavMode {
+    /// A tab per source, each with its own sidebar
+    #[default]
+    Tabs,
+    /// One sidebar with a section per source, and no tabs
+    Combined,
+}
+
+/// How page URLs are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlStyle {
+    /// `/installation`, written to `installation/index.html`
+    #[default]
+    Directory,
+    /// `/installation.html`, written to `installation.html`, for hosts that
+    /// don't serve a directory's `index.html`
+    Html,
+    /// `/installation/`, written to `installation/index.html`
+    TrailingSlash,
 }
 
 fn default_output() -> PathBuf {
     PathBuf::from("_site")
 }
 
+fn default_git_contributors_limit() -> usize {
+    10
+}
+
 // =============================================================================
 // Theme configuration
 // =============================================================================
@@ -280,11 +934,218 @@ pub struct SourceConfig {
     pub url_prefix: Option<String>,
     /// Navigation structure (auto-generated if omitted)
     pub nav: Option<NavConfig>,
+    /// Raw HTML policy for this source (overrides `markdown.html_policy`)
+    pub html_policy: Option<HtmlPolicy>,
+    /// Drop `01-` style ordering prefixes from file and directory names in
+    /// URLs and titles, and order navigation by them
+    #[serde(default)]
+    pub strip_numeric_prefix: bool,
+    /// Position of the source's tab (lower comes first)
+    pub weight: Option<i64>,
+    /// Group of related sources, shown together (e.g. as a dropdown)
+    pub group: Option<String>,
+    /// How many levels of navigation the sidebar shows, overriding
+    /// `site.nav_max_depth`
+    pub nav_max_depth: Option<usize>,
+    /// Repository the source's files live in, for edit links (overrides
+    /// `site.repository`)
+    pub repository: Option<String>,
+    /// Path added to the repository URL for edit links, overriding
+    /// `site.edit_path`
+    pub edit_path: Option<String>,
+    /// Edit link URL with `{repository}`, `{ref}`, and `{path}`
+    /// placeholders, overriding `site.edit_url_template`
+    pub edit_url_template: Option<String>,
+    /// Directory prefixes replaced in URLs; the first match applies
+    #[serde(default)]
+    pub url_rewrite: Vec<UrlRewrite>,
+    /// Number of leading directories dropped from URLs that no
+    /// `url_rewrite` entry matches
+    #[serde(default)]
+    pub flatten: usize,
+    /// More directories of static files, relative to the config file,
+    /// published under the source's URL prefix
+    #[serde(default)]
+    pub assets: Vec<PathBuf>,
+    /// Which symbolic links content discovery follows
+    #[serde(default)]
+    pub follow_symlinks: FollowSymlinks,
+    /// Git refs to build the source from, each under
+    /// `{url_prefix}/{version name}`; the first is the latest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<SourceVersion>,
+    /// Language the source is written in, one of the site's `languages`
+    pub language: Option<String>,
     /// Where the content comes from
     #[serde(flatten)]
     pub location: SourceLocation,
 }
 
+/// A version of a git source, built from one of its refs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceVersion {
+    /// Name of the version, used in its URLs (e.g. "v2")
+    pub name: String,
+    /// Branch, tag, or commit to build the version from
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+}
+
+impl SourceConfig {
+    /// The source's URL prefix, defaulting to `/{name}`, normalized to start
+    /// with `/` and not end with one (unless it's the root, `/`).
+    pub fn url_prefix(&self) -> String {
+        let prefix = match &self.url_prefix {
+            Some(prefix) => prefix.trim_matches('/').to_string(),
+            None => self.name.clone(),
+        };
+        format!("/{}", prefix)
+    }
+
+    /// The sources this one is built as: itself, or with `versions`, one
+    /// per version, fetched at the version's ref and under
+    /// `{url_prefix}/{version}`. The latest version keeps the source's name,
+    /// and the others are named `{name}@{version}`.
+    pub fn expand_versions(&self) -> Vec<SourceConfig> {
+        if self.versions.is_empty() {
+            return vec![self.clone()];
+        }
+        let prefix = self.url_prefix();
+        self.versions
+            .iter()
+            .enumerate()
+            .map(|(i, version)| {
+                let mut source = self.clone();
+                source.versions = Vec::new();
+                if i > 0 {
+                    source.name = format!("{}@{}", self.name, version.name);
+                }
+                source.url_prefix =
+                    Some(format!("{}/{}", prefix.trim_end_matches('/'), version.name));
+                if let SourceLocation::Remote {
+                    location: Location::Git { git },
+                } = &mut source.location
+                {
+                    let mut location = git.to_location();
+                    location.git_ref = Some(version.git_ref.clone());
+                    *git = GitValue::Expanded(location);
+                }
+                source
+            })
+            .collect()
+    }
+
+    /// Check `versions`: only git sources can have them, and their names
+    /// must be unique and usable in URLs.
+    fn validate_versions(&self) -> Result<(), String> {
+        if self.versions.is_empty() {
+            return Ok(());
+        }
+        if !matches!(
+            self.location,
+            SourceLocation::Remote {
+                location: Location::Git { .. }
+            }
+        ) {
+            return Err(format!(
+                "source '{}' has versions, which need a git location",
+                self.name
+            ));
+        }
+        for (i, version) in self.versions.iter().enumerate() {
+            let name = &version.name;
+            if name.is_empty() || name.contains(['/', '@', '?', '#']) {
+                return Err(format!(
+                    "source '{}' has an invalid version name '{}'",
+                    self.name, name
+                ));
+            }
+            if self.versions[..i].iter().any(|other| &other.name == name) {
+                return Err(format!(
+                    "source '{}' has the version '{}' twice",
+                    self.name, name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which symbolic links content discovery follows. Links that aren't
+/// followed are skipped with a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowSymlinks {
+    /// Follow links to files
+    #[serde(default = "default_follow_file_symlinks")]
+    pub files: bool,
+    /// Follow links to directories, which can pull in whole trees (and
+    /// loops, which are skipped)
+    #[serde(default)]
+    pub directories: bool,
+}
+
+fn default_follow_file_symlinks() -> bool {
+    true
+}
+
+impl Default for FollowSymlinks {
+    fn default() -> Self {
+        Self {
+            files: default_follow_file_symlinks(),
+            directories: false,
+        }
+    }
+}
+
+/// Check that no two sources share a URL prefix, and that no source's
+/// prefix is inside another's, except for the root (`/`), which can hold
+/// other sources.
+fn validate_url_prefixes(sources: &[SourceConfig]) -> Result<(), String> {
+    let prefixes: Vec<(String, &str)> = sources
+        .iter()
+        .map(|source| (source.url_prefix(), source.name.as_str()))
+        .collect();
+    for (i, (prefix, name)) in prefixes.iter().enumerate() {
+        for (other_prefix, other_name) in &prefixes[i + 1..] {
+            if prefix == other_prefix {
+                return Err(format!(
+                    "sources '{}' and '{}' both have the url_prefix '{}'",
+                    name, other_name, prefix
+                ));
+            }
+            let nested = [(prefix, other_prefix), (other_prefix, prefix)]
+                .into_iter()
+                .find(|(outer, inner)| {
+                    outer.as_str() != "/" && inner.starts_with(&format!("{}/", outer))
+                });
+            if let Some((outer, inner)) = nested {
+                let (outer_name, inner_name) = if outer == prefix {
+                    (name, other_name)
+                } else {
+                    (other_name, name)
+                };
+                return Err(format!(
+                    "source '{}' (url_prefix '{}') is inside source '{}' (url_prefix '{}'); \
+                     only the root prefix '/' can contain other sources",
+                    inner_name, inner, outer_name, outer
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A directory prefix replaced in a source's URLs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlRewrite {
+    /// Directory within the source, like `reference/api/v2`
+    pub from: PathBuf,
+    /// Directory to use in its place; empty moves the contents up to the
+    /// source's root
+    #[serde(default)]
+    pub to: PathBuf,
+}
+
 /// Where a source's content is located.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -325,6 +1186,25 @@ pub struct MarkdownConfig {
     /// Extensions to enable for markdown processing
     #[serde(default = "default_markdown_extensions")]
     pub extensions: Vec<String>,
+    /// Heading permalink anchor settings
+    #[serde(default)]
+    pub headings: HeadingsConfig,
+    /// Image rendering settings
+    #[serde(default)]
+    pub images: ImagesConfig,
+    /// How raw HTML in markdown is handled
+    #[serde(default)]
+    pub html_policy: HtmlPolicy,
+    /// Front matter validation settings
+    #[serde(default)]
+    pub front_matter: FrontMatterConfig,
+    /// Color theme for syntax highlighting, by name
+    #[serde(default = "default_highlight_theme")]
+    pub highlight_theme: String,
+}
+
+fn default_highlight_theme() -> String {
+    "github-dark".to_string()
 }
 
 fn default_markdown_extensions() -> Vec<String> {
@@ -343,10 +1223,114 @@ impl Default for MarkdownConfig {
     fn default() -> Self {
         Self {
             extensions: default_markdown_extensions(),
+            headings: HeadingsConfig::default(),
+            images: ImagesConfig::default(),
+            html_policy: HtmlPolicy::default(),
+            front_matter: FrontMatterConfig::default(),
+            highlight_theme: default_highlight_theme(),
+        }
+    }
+}
+
+/// How raw HTML written in markdown is handled.
+///
+/// HTML inside code blocks and inline code is never affected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HtmlPolicy {
+    /// Pass raw HTML through unchanged
+    #[default]
+    Allow,
+    /// Render raw HTML as visible text
+    Escape,
+    /// Keep safe tags and attributes, dropping scripts, styles, and event handlers
+    Sanitize,
+}
+
+/// Configuration for heading permalink anchors.
+///
+/// ```yaml
+/// markdown:
+///   headings:
+///     symbol: "¶"
+///     position: before
+///     levels: [2, 3, 4]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeadingsConfig {
+    /// Whether to emit permalink anchors at all (default: true)
+    pub anchors: bool,
+    /// Anchor content, inserted as HTML (default: "#")
+    pub symbol: String,
+    /// Extra CSS class added alongside `header-anchor` (e.g. an icon class)
+    pub class: Option<String>,
+    /// Whether the anchor goes before or after the heading text
+    pub position: AnchorPosition,
+    /// Heading levels that get anchors (default: 1-6)
+    pub levels: Vec<u8>,
+    /// Transliterate heading ids to ASCII, e.g. "Überblick" -> "uberblick"
+    /// (default: false, keeping letters from any script)
+    pub transliterate: bool,
+    /// Shift heading levels down by this many, capped at h6 (default: 0).
+    /// With 1, `#` renders as `<h2>`, leaving `<h1>` to the theme's page title.
+    pub offset: u8,
+}
+
+impl Default for HeadingsConfig {
+    fn default() -> Self {
+        Self {
+            anchors: true,
+            symbol: "#".to_string(),
+            class: None,
+            position: AnchorPosition::After,
+            levels: vec![1, 2, 3, 4, 5, 6],
+            transliterate: false,
+            offset: 0,
         }
     }
 }
 
+/// Where the heading anchor is placed relative to the heading text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorPosition {
+    Before,
+    #[default]
+    After,
+}
+
+/// Configuration for images in markdown content.
+///
+/// ```yaml
+/// markdown:
+///   images:
+///     lazy: true
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImagesConfig {
+    /// Add `loading="lazy"` and `decoding="async"` to images (default: false)
+    pub lazy: bool,
+}
+
+/// Configuration for front matter validation.
+///
+/// ```yaml
+/// markdown:
+///   front_matter:
+///     strict: true
+///     fields: [author, version]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrontMatterConfig {
+    /// Fail the build when front matter can't be parsed (default: warn and continue)
+    pub strict: bool,
+    /// Custom fields pages may use; other fields produce a warning (default: any)
+    pub fields: Option<Vec<String>>,
+}
+
 // =============================================================================
 // Navigation configuration
 // =============================================================================
@@ -362,6 +1346,7 @@ pub type NavConfig = Vec<NavItem>;
 ///   - installation.md                    # Simple path, title from filename
 ///   - Getting Started: getting-started.md  # Explicit title
 ///   - section: Commands                  # Section with nested items
+///     icon: terminal                     # Optional icon for themes
 ///     items:
 ///       - sync.md
 ///       - Search: search.md
@@ -370,6 +1355,11 @@ pub type NavConfig = Vec<NavItem>;
 ///     children:
 ///       - configuration/root.md
 ///       - configuration/sub.md
+///   - path: cli.md                       # Link with a title and icon
+///     title: CLI Reference
+///     icon: terminal
+///   - divider: true                      # Horizontal rule
+///   - label: Advanced topics             # Unlinked heading
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -379,15 +1369,32 @@ pub enum NavItem {
     Section {
         section: String,
         items: Vec<NavItem>,
+        /// Icon name for themes to render with `icon()`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
     },
-    /// A link with nested children
+    /// A link with a custom title, icon, or nested children
     /// Use this when a page has sub-pages underneath it
     LinkWithChildren {
         path: String,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         title: Option<String>,
+        /// Icon name for themes to render with `icon()`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
         children: Vec<NavItem>,
     },
+    /// A horizontal rule between items
+    Divider { divider: bool },
+    /// A heading without a link or nested items
+    /// Must come before `Titled`, which would also accept it
+    Label {
+        label: String,
+        /// Icon name for themes to render with `icon()`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+    },
     /// A titled page: { "Display Title": "path/to/file.md" }
     Titled(std::collections::HashMap<String, String>),
     /// A simple path: "file.md" or "dir/" for auto-expand
@@ -408,6 +1415,9 @@ pub struct DevConfig {
     /// Enable live reload in the browser when files change (default: true)
     #[serde(default = "default_live_reload")]
     pub live_reload: bool,
+    /// Response headers the dev server adds, by path
+    #[serde(default)]
+    pub headers: DevHeaders,
 }
 
 impl Default for DevConfig {
@@ -416,6 +1426,7 @@ impl Default for DevConfig {
             parent: None,
             watch: WatchConfig::default(),
             live_reload: true,
+            headers: DevHeaders::default(),
         }
     }
 }
@@ -424,6 +1435,78 @@ fn default_live_reload() -> bool {
     true
 }
 
+/// Response headers for `undox serve`, written as a map from path pattern to
+/// headers, like `{ "/api/**": { Access-Control-Allow-Origin: "*" } }`. When
+/// patterns overlap, the later one's headers win.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "serde_yaml::Mapping", into = "serde_yaml::Mapping")]
+pub struct DevHeaders(pub Vec<HeaderRule>);
+
+/// Headers for the paths matching a pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderRule {
+    /// Path under the site's base path, where `*` matches within a segment
+    /// and `**` across segments
+    pub pattern: String,
+    /// Header names and values
+    pub headers: Vec<(String, String)>,
+}
+
+impl TryFrom<serde_yaml::Mapping> for DevHeaders {
+    type Error = String;
+
+    fn try_from(mapping: serde_yaml::Mapping) -> Result<Self, Self::Error> {
+        let error = || {
+            "dev.headers maps path patterns to headers, like `\"/**\": { X-Frame-Options: DENY }`"
+                .to_string()
+        };
+        mapping
+            .into_iter()
+            .map(|(pattern, headers)| {
+                let (serde_yaml::Value::String(pattern), serde_yaml::Value::Mapping(headers)) =
+                    (pattern, headers)
+                else {
+                    return Err(error());
+                };
+                let headers = headers
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let value = match value {
+                            serde_yaml::Value::String(value) => value,
+                            serde_yaml::Value::Number(value) => value.to_string(),
+                            serde_yaml::Value::Bool(value) => value.to_string(),
+                            _ => return Err(error()),
+                        };
+                        match name {
+                            serde_yaml::Value::String(name) => Ok((name, value)),
+                            _ => Err(error()),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(HeaderRule { pattern, headers })
+            })
+            .collect::<Result<_, _>>()
+            .map(DevHeaders)
+    }
+}
+
+impl From<DevHeaders> for serde_yaml::Mapping {
+    fn from(headers: DevHeaders) -> Self {
+        headers
+            .0
+            .into_iter()
+            .map(|rule| {
+                let headers: serde_yaml::Mapping = rule
+                    .headers
+                    .into_iter()
+                    .map(|(name, value)| (name.into(), value.into()))
+                    .collect();
+                (rule.pattern.into(), headers.into())
+            })
+            .collect()
+    }
+}
+
 /// Configuration for file watching during development.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WatchConfig {
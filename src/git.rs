@@ -1,10 +1,14 @@
 //! Git repository fetching for remote documentation sources.
 
+use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use git2::{FetchOptions, Repository};
+use tracing::info;
 
 use crate::config::GitLocation;
 
@@ -35,6 +39,30 @@ pub enum GitError {
 
     #[error("failed to open cached repository: {0}")]
     OpenRepo(git2::Error),
+
+    #[error("offline and no cached copy of {url}")]
+    Offline { url: String },
+}
+
+// =============================================================================
+// Offline mode
+// =============================================================================
+
+/// Environment variable that turns on offline mode when set to `1` or `true`.
+pub const OFFLINE_ENV: &str = "UNDOX_OFFLINE";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn on offline mode for every fetch in this process, as `--offline` does.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether git fetches should use cached repositories without updating
+/// them: set by [`set_offline`] or [`OFFLINE_ENV`].
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+        || std::env::var(OFFLINE_ENV).is_ok_and(|value| value == "1" || value == "true")
 }
 
 // =============================================================================
@@ -42,52 +70,104 @@ pub enum GitError {
 // =============================================================================
 
 /// Fetches and caches git repositories for use as documentation sources.
+///
+/// A fetcher can be shared between threads. Each repository it fetches is
+/// fetched once, even when several sources ask for it at the same time.
 pub struct GitFetcher {
     cache_dir: PathBuf,
+    /// Use cached repositories as they are instead of fetching
+    offline: bool,
+    /// A lock per cached repository, holding whether it has been fetched
+    fetches: Mutex<HashMap<PathBuf, Arc<Mutex<bool>>>>,
 }
 
 impl GitFetcher {
     /// Create a new GitFetcher that caches repositories in the given directory.
+    ///
+    /// The fetcher is offline if [`is_offline`] says so.
     pub fn new(cache_dir: PathBuf) -> Self {
-        Self { cache_dir }
+        Self {
+            cache_dir,
+            offline: is_offline(),
+            fetches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use cached repositories without fetching, and fail for repositories
+    /// that aren't cached.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
     }
 
     /// Fetch a git repository from a GitLocation and return the local path to the clone.
     ///
     /// If the repository is already cached, it will be updated (fetch + checkout).
-    /// Otherwise, a fresh clone will be performed.
+    /// Otherwise, a fresh clone will be performed. Offline, a cached repository
+    /// is used as it is, and one that isn't cached is an error.
+    ///
+    /// Callers asking for a repository that's being fetched wait for that
+    /// fetch rather than starting another in the same directory, and later
+    /// calls reuse it.
     ///
     /// Note: This returns the repository root. Use `git.path` separately to
     /// navigate to a subdirectory within the repo.
     pub fn fetch_location(&self, git: &GitLocation) -> Result<PathBuf, GitError> {
+        let repo_cache_dir = self.cache_dir.join(self.cache_key(git));
+
+        let fetch = self
+            .fetches
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(repo_cache_dir.clone())
+            .or_default()
+            .clone();
+        let mut fetched = fetch.lock().unwrap_or_else(|e| e.into_inner());
+        if !*fetched {
+            self.fetch_into(git, &repo_cache_dir)?;
+            *fetched = true;
+        }
+        Ok(repo_cache_dir)
+    }
+
+    /// Clone or update a repository in its cache directory.
+    fn fetch_into(&self, git: &GitLocation, repo_cache_dir: &Path) -> Result<(), GitError> {
+        if self.offline {
+            if !repo_cache_dir.exists() {
+                return Err(GitError::Offline {
+                    url: git.url.clone(),
+                });
+            }
+            info!(
+                "Updating cached repository for {}... (offline, using cache)",
+                git.url
+            );
+            return Ok(());
+        }
+
         // Ensure cache directory exists
         std::fs::create_dir_all(&self.cache_dir).map_err(GitError::CacheDir)?;
 
-        let repo_cache_dir = self.cache_dir.join(self.cache_key(git));
-
         if repo_cache_dir.exists() {
             // Update existing clone
-            self.update_repo(&repo_cache_dir, &git.url, git.git_ref.as_deref())?;
+            self.update_repo(repo_cache_dir, &git.url, git.git_ref.as_deref())
         } else {
             // Fresh clone
-            self.clone_repo(&repo_cache_dir, &git.url, git.git_ref.as_deref())?;
+            self.clone_repo(repo_cache_dir, &git.url, git.git_ref.as_deref())
         }
-
-        Ok(repo_cache_dir)
     }
 
     /// Generate a cache key (directory name) from a URL.
     ///
-    /// Uses a hash of the URL, git_ref, and path to create a short, filesystem-safe name.
+    /// Uses a hash of the URL and git_ref to create a short, filesystem-safe
+    /// name. The path isn't part of it, so sources reading different
+    /// directories of the same repository and ref share one clone.
     fn cache_key(&self, location: &GitLocation) -> String {
         let mut hasher = DefaultHasher::new();
         location.url.hash(&mut hasher);
         if let Some(git_ref) = &location.git_ref {
             git_ref.hash(&mut hasher);
         }
-        if let Some(path) = &location.path {
-            path.to_string_lossy().hash(&mut hasher);
-        }
         format!("{:016x}", hasher.finish())
     }
 
@@ -98,7 +178,7 @@ impl GitFetcher {
         url: &str,
         git_ref: Option<&str>,
     ) -> Result<(), GitError> {
-        eprintln!("Cloning {}...", url);
+        info!("Cloning {}...", url);
 
         // Clone the repository
         let repo = Repository::clone(url, target_dir).map_err(|e| GitError::CloneFailed {
@@ -121,7 +201,7 @@ impl GitFetcher {
         url: &str,
         git_ref: Option<&str>,
     ) -> Result<(), GitError> {
-        eprintln!("Updating cached repository for {}...", url);
+        info!("Updating cached repository for {}...", url);
 
         let repo = Repository::open(repo_dir).map_err(GitError::OpenRepo)?;
 
@@ -198,6 +278,18 @@ impl GitFetcher {
         Ok(())
     }
 
+    /// The branch checked out in a cached repository, or the commit when
+    /// HEAD is detached.
+    pub fn checked_out_ref(repo_path: &Path) -> Option<String> {
+        let repo = Repository::open(repo_path).ok()?;
+        let head = repo.head().ok()?;
+        if head.is_branch() {
+            head.shorthand().map(str::to_string)
+        } else {
+            head.target().map(|oid| oid.to_string())
+        }
+    }
+
     /// Resolve a ref string to a git object.
     ///
     /// Tries the following in order:
@@ -246,3 +338,64 @@ impl GitFetcher {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_uses_cache() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache_dir = temp.path();
+        let fetcher = GitFetcher::new(cache_dir.to_path_buf()).with_offline(true);
+        let git = GitLocation {
+            url: "https://example.com/docs.git".to_string(),
+            git_ref: None,
+            path: None,
+        };
+
+        // Nothing cached yet, and no network to clone from
+        assert!(matches!(
+            fetcher.fetch_location(&git),
+            Err(GitError::Offline { url }) if url == git.url
+        ));
+
+        let cached = cache_dir.join(fetcher.cache_key(&git));
+        std::fs::create_dir_all(&cached).unwrap();
+        assert_eq!(fetcher.fetch_location(&git).unwrap(), cached);
+    }
+
+    #[test]
+    fn test_shared_fetch() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let origin = dir.join("origin");
+        std::fs::create_dir_all(&origin).unwrap();
+        let repo = Repository::init(&origin).unwrap();
+        std::fs::write(origin.join("intro.md"), "# Intro").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("intro.md")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Ada", "ada@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        let fetcher = GitFetcher::new(dir.join("cache")).with_offline(false);
+        let location = |path: &str| GitLocation {
+            url: origin.to_string_lossy().into_owned(),
+            git_ref: None,
+            path: Some(PathBuf::from(path)),
+        };
+        let (guide, api) = std::thread::scope(|scope| {
+            let guide = scope.spawn(|| fetcher.fetch_location(&location("guide")));
+            let api = scope.spawn(|| fetcher.fetch_location(&location("api")));
+            (guide.join().unwrap().unwrap(), api.join().unwrap().unwrap())
+        });
+        assert_eq!(guide, api);
+        assert!(guide.join("intro.md").exists());
+
+        // Later calls reuse the fetch rather than going back to the origin
+        std::fs::remove_dir_all(&origin).unwrap();
+        assert_eq!(fetcher.fetch_location(&location("guide")).unwrap(), guide);
+    }
+}
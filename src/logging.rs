@@ -0,0 +1,42 @@
+//! Log output.
+//!
+//! Messages are logged through `tracing` and print as plain lines on stdout,
+//! like the rest of undox's output. The dev server's requests and live reload
+//! clients are logged under [`REQUESTS`], which is off unless asked for.
+
+use std::io::IsTerminal;
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
+
+/// Target of the dev server's request log.
+pub const REQUESTS: &str = "undox::requests";
+
+/// Start printing undox's logs: everything down to info, or only warnings
+/// when `quiet`, and the dev server's requests when `requests`.
+pub fn init(quiet: bool, requests: bool) {
+    let level = if quiet {
+        LevelFilter::WARN
+    } else {
+        LevelFilter::INFO
+    };
+    let requests = if requests {
+        LevelFilter::INFO
+    } else {
+        LevelFilter::OFF
+    };
+    let filter = Targets::new()
+        .with_target("undox", level)
+        .with_target(REQUESTS, requests);
+    let format = tracing_subscriber::fmt::layer()
+        .without_time()
+        .with_target(false)
+        .with_level(false)
+        .with_ansi(std::io::stdout().is_terminal());
+
+    // Only fails if a subscriber is already set
+    let _ = tracing_subscriber::registry()
+        .with(format.with_filter(filter))
+        .try_init();
+}
@@ -6,6 +6,7 @@ pub mod build;
 pub mod commands;
 pub mod config;
 pub mod git;
+pub mod logging;
 pub mod theme;
 pub mod util;
 
@@ -28,47 +29,167 @@ pub struct InitArgs {
     /// Whether to overwrite files in the directory if they exist
     #[arg(short, long, default_value = "false")]
     force: bool,
+
+    /// Create the config from this mkdocs.yml, using its docs directory
+    /// as the content
+    #[arg(long, value_name = "MKDOCS_YML")]
+    from_mkdocs: Option<PathBuf>,
 }
 
 #[derive(Parser)]
 pub struct BuildArgs {
-    /// The path to the configuration file
-    #[arg(short, long, alias = "config", default_value = "undox.yaml")]
+    /// The path to the configuration file (default: the first of undox.yaml,
+    /// undox.yml, undox.toml, and undox.json)
+    #[arg(short, long, alias = "config")]
+    config_file: Option<PathBuf>,
+
+    /// Fail the build if any internal link is broken
+    #[arg(long, default_value = "false")]
+    strict_links: bool,
+
+    /// Fail the build if a configured nav entry doesn't match any page
+    #[arg(long, default_value = "false")]
+    strict_nav: bool,
+
+    /// Fail the build if there are any warnings, including broken links
+    #[arg(long, default_value = "false")]
+    strict: bool,
+
+    /// Print how long each step of the build took
+    #[arg(long, default_value = "false")]
+    timing: bool,
+
+    /// Print the largest pages and how much each source wrote
+    #[arg(long, default_value = "false")]
+    sizes: bool,
+
+    /// Don't show build progress or each step's output, e.g. in CI logs
+    #[arg(short, long, default_value = "false")]
+    quiet: bool,
+
+    /// Use cached git repositories without fetching (also set by
+    /// UNDOX_OFFLINE=1)
+    #[arg(long, default_value = "false")]
+    offline: bool,
+
+    /// Fail the build if the config file has keys no setting reads
+    #[arg(long, default_value = "false")]
+    strict_config: bool,
+
+    /// Include pages marked `draft: true`
+    #[arg(long, default_value = "false")]
+    drafts: bool,
+
+    /// Write a PDF of each source, like `pdf: true`
+    #[arg(long, default_value = "false")]
+    pdf: bool,
+
+    /// Serve the site under this URL or path (e.g. https://example.com/docs/
+    /// or /docs), overriding `site.url` and `site.base_path`
+    #[arg(long)]
+    base_url: Option<String>,
+
+    /// Merge this profile from the config (or its `undox.<profile>.yaml`
+    /// file) over the base config
+    #[arg(long)]
+    profile: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct CheckArgs {
+    /// The path to the configuration file (default: the first of undox.yaml,
+    /// undox.yml, undox.toml, and undox.json)
+    #[arg(short, long, alias = "config")]
     config_file: Option<PathBuf>,
+
+    /// Treat config keys no setting reads as problems
+    #[arg(long, default_value = "false")]
+    strict_config: bool,
 }
 
 #[derive(Parser)]
 pub struct ServeArgs {
-    /// The address to bind to
+    /// The address to bind to: an IPv4 or IPv6 address, or a hostname; pass
+    /// it more than once to listen on several
     #[arg(short, long, default_value = "0.0.0.0")]
-    bind: String,
+    bind: Vec<String>,
 
-    /// The port to bind to
-    #[arg(short, long, default_value = "3000")]
-    port: u16,
+    /// The port to bind to (default: 3000, or the next free port if it's
+    /// taken)
+    #[arg(short, long)]
+    port: Option<u16>,
 
-    /// Open the project in the default browser
-    #[arg(short, long, default_value = "false")]
-    open: bool,
+    /// Open the site in the default browser, at this path if given (default:
+    /// the first source's home page)
+    #[arg(short, long, num_args = 0..=1, value_name = "PATH")]
+    open: Option<Option<String>>,
 
-    /// The path to the configuration file
-    #[arg(short, long, alias = "config", default_value = "undox.yaml")]
+    /// The path to the configuration file (default: the first of undox.yaml,
+    /// undox.yml, undox.toml, and undox.json)
+    #[arg(short, long, alias = "config")]
     config_file: Option<PathBuf>,
 
     /// Whether to watch for changes and rebuild automatically (default: true)
     #[arg(short, long, default_value = "true")]
     watch: bool,
+
+    /// Merge this profile from the config (or its `undox.<profile>.yaml`
+    /// file) over the base config
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Print how long each step of each build took
+    #[arg(long, default_value = "false")]
+    timing: bool,
+
+    /// Use cached git repositories without fetching (also set by
+    /// UNDOX_OFFLINE=1)
+    #[arg(long, default_value = "false")]
+    offline: bool,
+
+    /// Serve the existing output directory as it is, without building or
+    /// watching
+    #[arg(long, default_value = "false")]
+    no_build: bool,
+
+    /// Skip building the search index
+    #[arg(long, default_value = "false")]
+    no_index: bool,
+
+    /// Serve this directory as it is instead of the site's output, without
+    /// loading the config
+    #[arg(long, conflicts_with_all = ["config_file", "profile"])]
+    dir: Option<PathBuf>,
+
+    /// Log each request, and live reload clients connecting and
+    /// disconnecting
+    #[arg(short = 'v', long, visible_alias = "verbose", default_value = "false")]
+    log: bool,
+
+    /// Only print the server's address, warnings, and errors, not each
+    /// build's output
+    #[arg(short, long, default_value = "false")]
+    quiet: bool,
 }
 
 #[derive(Parser)]
 pub struct CleanArgs {
-    /// The path to the configuration file
-    #[arg(short, long, alias = "config", default_value = "undox.yaml")]
+    /// The path to the configuration file (default: the first of undox.yaml,
+    /// undox.yml, undox.toml, and undox.json)
+    #[arg(short, long, alias = "config")]
     config_file: Option<PathBuf>,
 
     /// Show what will be deleted, but don't delete anything
     #[arg(short, long, default_value = "false")]
     dry_run: bool,
+
+    /// Keep the cache of fetched git repositories
+    #[arg(long, conflicts_with = "cache_only")]
+    keep_cache: bool,
+
+    /// Only delete the cache, keeping the built site
+    #[arg(long)]
+    cache_only: bool,
 }
 
 #[derive(Subcommand)]
@@ -79,6 +200,9 @@ enum UndoxCommand {
     /// Build the undox project
     Build(BuildArgs),
 
+    /// Check the config, sources, navigation, and theme without building
+    Check(CheckArgs),
+
     /// Serve the undox project on a local port
     Serve(ServeArgs),
 
@@ -90,6 +214,12 @@ enum UndoxCommand {
 async fn main() -> Result<(), anyhow::Error> {
     let args = Args::parse();
 
+    match &args.command {
+        UndoxCommand::Build(args) => logging::init(args.quiet, false),
+        UndoxCommand::Serve(args) => logging::init(args.quiet, args.log),
+        _ => logging::init(false, false),
+    }
+
     match args.command {
         UndoxCommand::Init(args) => {
             commands::init::run(&args).await?;
@@ -97,6 +227,9 @@ async fn main() -> Result<(), anyhow::Error> {
         UndoxCommand::Build(args) => {
             commands::build::run(&args).await?;
         }
+        UndoxCommand::Check(args) => {
+            commands::check::run(&args).await?;
+        }
         UndoxCommand::Serve(args) => {
             commands::serve::run(&args).await?;
         }
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// Theme configuration loaded from undox-theme.yaml
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -12,6 +14,151 @@ pub struct ThemeConfig {
     /// Pagefind search configuration
     #[serde(default)]
     pub pagefind: PagefindConfig,
+
+    /// Reading time estimate; `page.word_count` and `page.reading_time` are
+    /// only computed when this is set
+    #[serde(default)]
+    pub reading_time: Option<ReadingTimeConfig>,
+
+    /// Colors of the generated social cards (`site.social_cards`)
+    #[serde(default)]
+    pub social_cards: SocialCardsConfig,
+
+    /// Settings the theme reads from `theme.settings`, by name
+    #[serde(default)]
+    pub settings_schema: BTreeMap<String, SettingSchema>,
+}
+
+/// A setting declared by a theme.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingSchema {
+    /// Type the value must have (default: any)
+    #[serde(default, rename = "type")]
+    pub kind: SettingType,
+    /// Whether the site must set it
+    #[serde(default)]
+    pub required: bool,
+    /// Value used when the site doesn't set it
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// What the setting does, for people configuring the theme
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Type of a theme setting's value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingType {
+    #[default]
+    Any,
+    String,
+    Number,
+    Integer,
+    Boolean,
+    List,
+    Map,
+}
+
+impl SettingType {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            SettingType::Any => true,
+            SettingType::String => value.is_string(),
+            SettingType::Number => value.is_number(),
+            SettingType::Integer => value.is_i64() || value.is_u64(),
+            SettingType::Boolean => value.is_boolean(),
+            SettingType::List => value.is_array(),
+            SettingType::Map => value.is_object(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            SettingType::Any => "any",
+            SettingType::String => "string",
+            SettingType::Number => "number",
+            SettingType::Integer => "integer",
+            SettingType::Boolean => "boolean",
+            SettingType::List => "list",
+            SettingType::Map => "map",
+        }
+    }
+}
+
+/// Name of the type of a settings value, for errors.
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "map",
+    }
+}
+
+/// Reading time configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingTimeConfig {
+    /// Reading speed used for the estimate (default: 200)
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: usize,
+}
+
+fn default_words_per_minute() -> usize {
+    200
+}
+
+impl Default for ReadingTimeConfig {
+    fn default() -> Self {
+        Self {
+            words_per_minute: default_words_per_minute(),
+        }
+    }
+}
+
+impl ReadingTimeConfig {
+    /// Minutes needed to read `words` words, rounded up (at least one).
+    pub fn minutes(&self, words: usize) -> usize {
+        words.div_ceil(self.words_per_minute.max(1)).max(1)
+    }
+}
+
+/// Social card colors, as `#rrggbb` hex
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocialCardsConfig {
+    #[serde(default = "default_card_background")]
+    pub background: String,
+    /// Color of the page title
+    #[serde(default = "default_card_foreground")]
+    pub foreground: String,
+    /// Color of the site name and the bar along the left edge
+    #[serde(default = "default_card_accent")]
+    pub accent: String,
+}
+
+fn default_card_background() -> String {
+    "#0f172a".to_string()
+}
+
+fn default_card_foreground() -> String {
+    "#f8fafc".to_string()
+}
+
+fn default_card_accent() -> String {
+    "#38bdf8".to_string()
+}
+
+impl Default for SocialCardsConfig {
+    fn default() -> Self {
+        Self {
+            background: default_card_background(),
+            foreground: default_card_foreground(),
+            accent: default_card_accent(),
+        }
+    }
 }
 
 /// Pagefind-specific configuration
@@ -28,6 +175,10 @@ pub struct PagefindConfig {
     /// Force a specific language for indexing (ISO 639-1 code)
     #[serde(default)]
     pub force_language: Option<String>,
+
+    /// Front matter fields exposed as search filters and metadata
+    #[serde(default)]
+    pub filters: Vec<String>,
 }
 
 fn default_root_selector() -> String {
@@ -48,6 +199,7 @@ impl Default for PagefindConfig {
             root_selector: default_root_selector(),
             exclude_selectors: default_exclude_selectors(),
             force_language: None,
+            filters: Vec::new(),
         }
     }
 }
@@ -70,6 +222,62 @@ impl ThemeConfig {
 
         Ok(config)
     }
+
+    /// Names of the site's settings that the theme's `settings_schema`
+    /// doesn't declare, for warnings. Empty when the theme has no schema.
+    pub fn undeclared_settings<'a>(&self, settings: &'a Value) -> Vec<&'a str> {
+        match settings {
+            Value::Object(map) if !self.settings_schema.is_empty() => map
+                .keys()
+                .filter(|name| !self.settings_schema.contains_key(*name))
+                .map(String::as_str)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Check the site's `theme.settings` against the theme's
+    /// `settings_schema`, and fill in the declared defaults.
+    ///
+    /// Settings the schema doesn't declare are passed through (see
+    /// [`ThemeConfig::undeclared_settings`]). Without a schema, the settings
+    /// are returned as they are.
+    pub fn resolve_settings(&self, settings: &Value) -> Result<Value, ThemeConfigError> {
+        if self.settings_schema.is_empty() {
+            return Ok(settings.clone());
+        }
+
+        let mut resolved = match settings {
+            Value::Null => Map::new(),
+            Value::Object(map) => map.clone(),
+            other => {
+                return Err(ThemeConfigError::SettingsNotMap(value_type(other)));
+            }
+        };
+
+        for (name, schema) in &self.settings_schema {
+            match resolved.get(name) {
+                Some(value) if !schema.kind.matches(value) => {
+                    return Err(ThemeConfigError::SettingType {
+                        name: name.clone(),
+                        expected: schema.kind.name(),
+                        found: value_type(value),
+                    });
+                }
+                Some(_) => {}
+                None if schema.required => {
+                    return Err(ThemeConfigError::MissingSetting(name.clone()));
+                }
+                None => {
+                    if let Some(default) = &schema.default {
+                        resolved.insert(name.clone(), default.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(Value::Object(resolved))
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -79,4 +287,84 @@ pub enum ThemeConfigError {
 
     #[error("failed to parse theme config {0}: {1}")]
     Parse(std::path::PathBuf, serde_yaml::Error),
+
+    #[error("theme.settings must be a map, found {0}")]
+    SettingsNotMap(&'static str),
+
+    #[error("missing required theme setting '{0}'")]
+    MissingSetting(String),
+
+    #[error("theme setting '{name}' must be of type {expected}, found {found}")]
+    SettingType {
+        name: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_time_minutes() {
+        let config = ReadingTimeConfig::default();
+        assert_eq!(config.minutes(0), 1);
+        assert_eq!(config.minutes(200), 1);
+        assert_eq!(config.minutes(201), 2);
+
+        let slow = ReadingTimeConfig {
+            words_per_minute: 100,
+        };
+        assert_eq!(slow.minutes(1000), 10);
+    }
+
+    fn theme(yaml: &str) -> ThemeConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_settings_schema() {
+        let config = theme(
+            r##"
+settings_schema:
+  accent_color:
+    type: string
+    default: "#0af"
+  repo_url:
+    type: string
+    required: true
+  sidebar_width:
+    type: integer
+"##,
+        );
+        assert_eq!(config.settings_schema["repo_url"].kind, SettingType::String);
+        assert!(config.settings_schema["repo_url"].required);
+
+        let settings = serde_json::json!({ "repo_url": "https://example.com" });
+        assert_eq!(
+            config.resolve_settings(&settings).unwrap(),
+            serde_json::json!({ "repo_url": "https://example.com", "accent_color": "#0af" })
+        );
+
+        let settings = serde_json::json!({ "repo_url": "x", "banner": true });
+        assert_eq!(config.undeclared_settings(&settings), vec!["banner"]);
+
+        let err = config.resolve_settings(&Value::Null).unwrap_err();
+        assert_eq!(err.to_string(), "missing required theme setting 'repo_url'");
+
+        let settings = serde_json::json!({ "repo_url": "x", "sidebar_width": "wide" });
+        let err = config.resolve_settings(&settings).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "theme setting 'sidebar_width' must be of type integer, found string"
+        );
+    }
+
+    #[test]
+    fn test_no_settings_schema() {
+        let config = theme("name: plain");
+        let settings = serde_json::json!({ "anything": [1, 2] });
+        assert_eq!(config.resolve_settings(&settings).unwrap(), settings);
+    }
 }
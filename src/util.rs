@@ -1,5 +1,7 @@
 //! Shared utility functions.
 
+use std::path::{Component, Path, PathBuf};
+
 /// Convert a slug to title case.
 ///
 /// Splits on `-` and `_`, capitalizes each word.
@@ -18,6 +20,146 @@ pub fn title_case(s: &str) -> String {
         .join(" ")
 }
 
+/// Split a numeric ordering prefix (`01-`, `10_`) off a file or directory
+/// name.
+///
+/// "01-introduction.md" -> (Some(1), "introduction.md")
+/// "introduction.md" -> (None, "introduction.md")
+pub fn split_numeric_prefix(name: &str) -> (Option<u64>, &str) {
+    let digits = name.len() - name.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = &name[digits..];
+    match rest.strip_prefix(['-', '_']) {
+        Some(rest) if digits > 0 && !rest.is_empty() => (name[..digits].parse().ok(), rest),
+        _ => (None, name),
+    }
+}
+
+/// 32-bit FNV-1a hash; unlike `DefaultHasher`, stable across Rust releases.
+pub fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+/// Escape HTML special characters.
+pub fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Decode the entities that pulldown-cmark emits (the reverse of `html_escape`).
+pub fn decode_entities(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Convert an HTML fragment to plain text.
+///
+/// Tags are removed, entities decoded, and runs of whitespace collapsed to
+/// a single space.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Count the words of prose in an HTML fragment.
+///
+/// Code blocks (`<pre>` elements) are left out, since they aren't read like
+/// prose; inline code counts.
+pub fn word_count(html: &str) -> usize {
+    let mut prose = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<pre") {
+        prose.push_str(&rest[..start]);
+        rest = match rest[start..].find("</pre>") {
+            Some(end) => &rest[start + end + "</pre>".len()..],
+            None => "",
+        };
+    }
+    prose.push_str(rest);
+    html_to_text(&prose).split_whitespace().count()
+}
+
+/// Shorten text to at most `max_chars` characters.
+///
+/// Longer text is cut at the last word boundary that fits and ends with `…`.
+pub fn truncate_text(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    // Leave room for the ellipsis
+    let cut = text
+        .char_indices()
+        .nth(max_chars.saturating_sub(1))
+        .map_or(text.len(), |(i, _)| i);
+    let shortened = match text[..cut].rfind(' ') {
+        Some(space) if space > 0 => &text[..space],
+        _ => &text[..cut],
+    };
+    format!(
+        "{}…",
+        shortened.trim_end_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+    )
+}
+
+/// Resolve `.` and `..` components of a relative path.
+///
+/// Returns `None` for absolute paths and paths that escape their root
+/// (e.g. `../outside`).
+pub fn normalize_relative_path(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(out)
+}
+
+/// Match a URL path against a glob, where `*` matches within a segment and
+/// `**` matches across segments.
+///
+/// "/guide/*.html" matches "/guide/intro.html" but not "/guide/a/b.html"
+/// "/**" matches every path
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[u8], path: &[u8]) -> bool {
+        match pattern {
+            [] => path.is_empty(),
+            [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| matches(rest, &path[i..])),
+            [b'*', rest @ ..] => {
+                let segment = path.iter().position(|&b| b == b'/').unwrap_or(path.len());
+                (0..=segment).any(|i| matches(rest, &path[i..]))
+            }
+            [first, rest @ ..] => path.first() == Some(first) && matches(rest, &path[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), path.as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,4 +172,69 @@ mod tests {
         assert_eq!(title_case("README"), "README");
         assert_eq!(title_case("my-cool-feature"), "My Cool Feature");
     }
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(html_escape("<div>&</div>"), "&lt;div&gt;&amp;&lt;/div&gt;");
+    }
+
+    #[test]
+    fn test_split_numeric_prefix() {
+        assert_eq!(split_numeric_prefix("01-intro.md"), (Some(1), "intro.md"));
+        assert_eq!(split_numeric_prefix("10_guides"), (Some(10), "guides"));
+        assert_eq!(split_numeric_prefix("intro.md"), (None, "intro.md"));
+        assert_eq!(split_numeric_prefix("2024.md"), (None, "2024.md"));
+        assert_eq!(split_numeric_prefix("01-"), (None, "01-"));
+        assert_eq!(split_numeric_prefix("-intro"), (None, "-intro"));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text("<p>Use <code>cargo\n build</code> &amp; <a href=\"/x\">go</a></p>"),
+            "Use cargo build & go"
+        );
+    }
+
+    #[test]
+    fn test_word_count() {
+        assert_eq!(word_count("<p>Run <code>undox build</code> now.</p>"), 4);
+
+        // Mostly code: only the prose around the blocks counts
+        let html = "<p>Example:</p>\n\
+                    <pre><code class=\"language-rust\">fn main() {\n    println!(\"hi\");\n}\n</code></pre>\n\
+                    <p>And the output:</p>\n\
+                    <figure class=\"code-block\"><pre><code>hi there\n</code></pre></figure>";
+        assert_eq!(word_count(html), 4);
+    }
+
+    #[test]
+    fn test_truncate_text() {
+        assert_eq!(truncate_text("short", 10), "short");
+        assert_eq!(truncate_text("one two three four", 12), "one two…");
+        assert_eq!(truncate_text("one, two three", 9), "one…");
+        assert_eq!(truncate_text("unbreakable", 5), "unbr…");
+    }
+
+    #[test]
+    fn test_normalize_relative_path() {
+        assert_eq!(
+            normalize_relative_path(Path::new("guide/./images/../shot.png")),
+            Some(PathBuf::from("guide/shot.png"))
+        );
+        assert_eq!(normalize_relative_path(Path::new("../outside")), None);
+        assert_eq!(normalize_relative_path(Path::new("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("/sw.js", "/sw.js"));
+        assert!(!glob_match("/sw.js", "/sw.json"));
+        assert!(glob_match("/guide/*.html", "/guide/intro.html"));
+        assert!(!glob_match("/guide/*.html", "/guide/a/b.html"));
+        assert!(glob_match("/guide/**", "/guide/a/b.html"));
+        assert!(glob_match("/**", "/"));
+        assert!(glob_match("/**/*.js", "/_theme/app.js"));
+        assert!(!glob_match("/*", "/a/b"));
+    }
 }